@@ -1,17 +1,22 @@
+use crate::agent::budget::{BudgetLimit, LoopDetector, LoopVerdict, TurnBudget, TurnBudgets};
 use crate::agent::dispatcher::{
     NativeToolDispatcher, ParsedToolCall, ToolDispatcher, ToolExecutionResult, XmlToolDispatcher,
 };
+use crate::agent::loop_::{parallel_batch_len, stop_on_budget};
 use crate::agent::memory_loader::{DefaultMemoryLoader, MemoryLoader};
 use crate::agent::prompt::{PromptContext, SystemPromptBuilder};
+use crate::config::schema::DEFAULT_MODEL;
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::observability::{self, Observer, ObserverEvent};
+use crate::providers::capabilities::CapabilityRegistry;
 use crate::providers::{self, ChatMessage, ChatRequest, ConversationMessage, Provider};
 use crate::runtime;
 use crate::security::SecurityPolicy;
 use crate::tools::{self, Tool, ToolSpec};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use futures_util::StreamExt;
 use std::io::Write as IoWrite;
 use std::sync::Arc;
 use std::time::Instant;
@@ -26,6 +31,7 @@ pub struct Agent {
     tool_dispatcher: Box<dyn ToolDispatcher>,
     memory_loader: Box<dyn MemoryLoader>,
     config: crate::config::AgentConfig,
+    turn_budget: TurnBudget,
     model_name: String,
     temperature: f64,
     workspace_dir: std::path::PathBuf,
@@ -44,6 +50,7 @@ pub struct AgentBuilder {
     tool_dispatcher: Option<Box<dyn ToolDispatcher>>,
     memory_loader: Option<Box<dyn MemoryLoader>>,
     config: Option<crate::config::AgentConfig>,
+    turn_budget: Option<TurnBudget>,
     model_name: Option<String>,
     temperature: Option<f64>,
     workspace_dir: Option<std::path::PathBuf>,
@@ -63,6 +70,7 @@ impl AgentBuilder {
             tool_dispatcher: None,
             memory_loader: None,
             config: None,
+            turn_budget: None,
             model_name: None,
             temperature: None,
             workspace_dir: None,
@@ -112,6 +120,12 @@ impl AgentBuilder {
         self
     }
 
+    /// Limits on each turn; defaults to the agent config's `[agent.budget]`.
+    pub fn turn_budget(mut self, turn_budget: TurnBudget) -> Self {
+        self.turn_budget = Some(turn_budget);
+        self
+    }
+
     pub fn model_name(mut self, model_name: String) -> Self {
         self.model_name = Some(model_name);
         self
@@ -147,6 +161,10 @@ impl AgentBuilder {
             .tools
             .ok_or_else(|| anyhow::anyhow!("tools are required"))?;
         let tool_specs = tools.iter().map(|tool| tool.spec()).collect();
+        let config = self.config.unwrap_or_default();
+        let turn_budget = self
+            .turn_budget
+            .unwrap_or_else(|| TurnBudget::from_config(&config.turn_budget()));
 
         Ok(Agent {
             provider: self
@@ -169,10 +187,9 @@ impl AgentBuilder {
             memory_loader: self
                 .memory_loader
                 .unwrap_or_else(|| Box::new(DefaultMemoryLoader::default())),
-            config: self.config.unwrap_or_default(),
-            model_name: self
-                .model_name
-                .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into()),
+            config,
+            turn_budget,
+            model_name: self.model_name.unwrap_or_else(|| DEFAULT_MODEL.into()),
             temperature: self.temperature.unwrap_or(0.7),
             workspace_dir: self
                 .workspace_dir
@@ -244,7 +261,7 @@ impl Agent {
         let model_name = config
             .default_model
             .as_deref()
-            .unwrap_or("anthropic/claude-sonnet-4-20250514")
+            .unwrap_or(DEFAULT_MODEL)
             .to_string();
 
         let provider: Box<dyn Provider> = providers::create_routed_provider(
@@ -253,7 +270,10 @@ impl Agent {
             &config.reliability,
             &config.model_routes,
             &model_name,
+            Arc::new(CapabilityRegistry::from_config(config)),
         )?;
+        let provider = providers::with_replay(provider, &config.replay, &config.workspace_dir)?;
+        let provider = providers::with_redaction(provider, config, Arc::clone(&observer))?;
 
         let dispatcher_choice = config.agent.tool_dispatcher.as_str();
         let tool_dispatcher: Box<dyn ToolDispatcher> = match dispatcher_choice {
@@ -272,6 +292,7 @@ impl Agent {
             .memory_loader(Box::new(DefaultMemoryLoader::default()))
             .prompt_builder(SystemPromptBuilder::with_defaults())
             .config(config.agent.clone())
+            .turn_budget(TurnBudgets::from_config(config).for_channel("cli").clone())
             .model_name(model_name)
             .temperature(config.default_temperature)
             .workspace_dir(config.workspace_dir.clone())
@@ -368,9 +389,29 @@ impl Agent {
             return results;
         }
 
+        // Runs of parallel-safe calls execute together, up to the cap;
+        // anything else runs alone. Results stay in call order.
+        let max_parallel = self.config.tool_parallelism();
         let mut results = Vec::with_capacity(calls.len());
-        for call in calls {
-            results.push(self.execute_tool_call(call).await);
+        let mut next = 0;
+        while next < calls.len() {
+            let batch_len = parallel_batch_len(
+                &self.tools,
+                calls[next..]
+                    .iter()
+                    .map(|call| (call.name.as_str(), &call.arguments)),
+                max_parallel,
+            );
+            let mut executions = Vec::with_capacity(batch_len);
+            for call in &calls[next..next + batch_len] {
+                executions.push(self.execute_tool_call(call));
+            }
+            next += batch_len;
+            let batch: Vec<_> = futures_util::stream::iter(executions)
+                .buffered(batch_len)
+                .collect()
+                .await;
+            results.extend(batch);
         }
         results
     }
@@ -406,7 +447,30 @@ impl Agent {
         self.history
             .push(ConversationMessage::Chat(ChatMessage::user(enriched)));
 
-        for _ in 0..self.config.max_tool_iterations {
+        let budget = self.turn_budget.clone();
+        let started_at = Instant::now();
+        let mut tokens_used: u64 = 0;
+        let mut tool_calls_used: usize = 0;
+        let mut loop_detector = LoopDetector::new(budget.max_repeated_calls);
+        let mut last_text = String::new();
+
+        for _ in 0..budget.max_iterations {
+            if let Some(max) = budget.max_duration {
+                if started_at.elapsed() >= max {
+                    let reason = format!("this request hit its {}s time limit", max.as_secs());
+                    return Ok(self.stop_turn(
+                        BudgetLimit::Duration,
+                        max.as_secs(),
+                        &reason,
+                        &last_text,
+                    ));
+                }
+            }
+            if let Some(max) = budget.max_tokens.filter(|max| tokens_used >= *max) {
+                let reason = format!("this request used up its {max}-token budget");
+                return Ok(self.stop_turn(BudgetLimit::Tokens, max, &reason, &last_text));
+            }
+
             let messages = self.tool_dispatcher.to_provider_messages(&self.history);
             let response = match self
                 .provider
@@ -418,6 +482,7 @@ impl Agent {
                         } else {
                             None
                         },
+                        reasoning: None,
                     },
                     &self.model_name,
                     self.temperature,
@@ -427,6 +492,9 @@ impl Agent {
                 Ok(resp) => resp,
                 Err(err) => return Err(err),
             };
+            if let Some(usage) = response.usage {
+                tokens_used += usage.input_tokens + usage.output_tokens;
+            }
 
             let (text, calls) = self.tool_dispatcher.parse_response(&response);
             if calls.is_empty() {
@@ -454,6 +522,7 @@ impl Agent {
             }
 
             if !text.is_empty() {
+                last_text.clone_from(&text);
                 self.history
                     .push(ConversationMessage::Chat(ChatMessage::assistant(
                         text.clone(),
@@ -462,21 +531,88 @@ impl Agent {
                 let _ = std::io::stdout().flush();
             }
 
+            let allowed = budget.max_tool_calls.map_or(calls.len(), |max| {
+                max.saturating_sub(tool_calls_used).min(calls.len())
+            });
+            if allowed == 0 {
+                let max = budget.max_tool_calls.unwrap_or_default();
+                let reason = format!("this request used all {max} tool calls it is allowed");
+                return Ok(self.stop_turn(
+                    BudgetLimit::ToolCalls,
+                    u64::try_from(max).unwrap_or(u64::MAX),
+                    &reason,
+                    &last_text,
+                ));
+            }
+            tool_calls_used += allowed;
+
+            for call in &calls[..allowed] {
+                loop_detector.record(&call.name, &call.arguments);
+            }
+            let note = match loop_detector.check() {
+                LoopVerdict::Clear => None,
+                LoopVerdict::Warn(pattern) => Some(format!(
+                    "[Loop detected] You have called {pattern}. Repeating it will not give a \
+                     different result. Change approach, or answer the user with what you have; \
+                     repeating it again ends this turn."
+                )),
+                LoopVerdict::Stop(pattern) => {
+                    let reason = format!("I kept calling {pattern} without making progress");
+                    return Ok(self.stop_turn(
+                        BudgetLimit::ToolLoop,
+                        u64::try_from(budget.max_repeated_calls).unwrap_or(u64::MAX),
+                        &reason,
+                        &last_text,
+                    ));
+                }
+            };
+
             self.history.push(ConversationMessage::AssistantToolCalls {
                 text: response.text.clone(),
                 tool_calls: response.tool_calls.clone(),
+                reasoning: response.reasoning.clone(),
             });
 
-            let results = self.execute_tools(&calls).await;
+            let mut results = self.execute_tools(&calls[..allowed]).await;
+            results.extend(calls[allowed..].iter().map(|call| ToolExecutionResult {
+                name: call.name.clone(),
+                output: "Error: skipped, this turn's tool-call budget is used up.".into(),
+                success: false,
+                tool_call_id: call.tool_call_id.clone(),
+            }));
             let formatted = self.tool_dispatcher.format_results(&results);
             self.history.push(formatted);
+            if let Some(note) = note {
+                self.history
+                    .push(ConversationMessage::Chat(ChatMessage::user(note)));
+            }
             self.trim_history();
         }
 
-        anyhow::bail!(
-            "Agent exceeded maximum tool iterations ({})",
-            self.config.max_tool_iterations
-        )
+        let max = budget.max_iterations;
+        Ok(self.stop_turn(
+            BudgetLimit::Iterations,
+            u64::try_from(max).unwrap_or(u64::MAX),
+            &format!("this request reached its {max}-step limit"),
+            &last_text,
+        ))
+    }
+
+    /// End a turn on `limit`, keeping the explanation in the history.
+    fn stop_turn(
+        &mut self,
+        limit: BudgetLimit,
+        value: u64,
+        reason: &str,
+        last_text: &str,
+    ) -> String {
+        let reply = stop_on_budget(self.observer.as_ref(), limit, value, reason, last_text);
+        self.history
+            .push(ConversationMessage::Chat(ChatMessage::assistant(
+                reply.clone(),
+            )));
+        self.trim_history();
+        reply
     }
 
     pub async fn run_single(&mut self, message: &str) -> Result<String> {
@@ -538,7 +674,7 @@ pub async fn run(
     let model_name = effective_config
         .default_model
         .as_deref()
-        .unwrap_or("anthropic/claude-sonnet-4-20250514")
+        .unwrap_or(DEFAULT_MODEL)
         .to_string();
 
     agent.observer.record_event(&ObserverEvent::AgentStart {
@@ -594,6 +730,8 @@ mod tests {
                 return Ok(crate::providers::ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
+                    usage: None,
+                    reasoning: None,
                 });
             }
             Ok(guard.remove(0))
@@ -632,6 +770,8 @@ mod tests {
             responses: Mutex::new(vec![crate::providers::ChatResponse {
                 text: Some("hello".into()),
                 tool_calls: vec![],
+                usage: None,
+                reasoning: None,
             }]),
         });
 
@@ -669,10 +809,14 @@ mod tests {
                         name: "echo".into(),
                         arguments: "{}".into(),
                     }],
+                    usage: None,
+                    reasoning: None,
                 },
                 crate::providers::ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
+                    usage: None,
+                    reasoning: None,
                 },
             ]),
         });
@@ -703,4 +847,47 @@ mod tests {
             .iter()
             .any(|msg| matches!(msg, ConversationMessage::ToolResults(_))));
     }
+
+    #[tokio::test]
+    async fn turn_stops_a_model_stuck_repeating_a_tool_call() {
+        let repeat = || crate::providers::ChatResponse {
+            text: Some(String::new()),
+            tool_calls: vec![crate::providers::ToolCall {
+                id: "tc".into(),
+                name: "echo".into(),
+                arguments: r#"{"text":"again"}"#.into(),
+            }],
+            usage: None,
+            reasoning: None,
+        };
+        let provider = Box::new(MockProvider {
+            responses: Mutex::new((0..10).map(|_| repeat()).collect()),
+        });
+
+        let memory_cfg = crate::config::MemoryConfig {
+            backend: "none".into(),
+            ..crate::config::MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> = Arc::from(
+            crate::memory::create_memory(&memory_cfg, std::path::Path::new("/tmp"), None).unwrap(),
+        );
+
+        let observer: Arc<dyn Observer> = Arc::from(crate::observability::NoopObserver {});
+        let mut agent = Agent::builder()
+            .provider(provider)
+            .tools(vec![Box::new(MockTool)])
+            .memory(mem)
+            .observer(observer)
+            .tool_dispatcher(Box::new(NativeToolDispatcher))
+            .workspace_dir(std::path::PathBuf::from("/tmp"))
+            .build()
+            .unwrap();
+
+        let response = agent.turn("hi").await.unwrap();
+        assert!(response.contains("without making progress"));
+        assert!(agent.history().iter().any(|msg| matches!(
+            msg,
+            ConversationMessage::Chat(m) if m.content.starts_with("[Loop detected]")
+        )));
+    }
 }
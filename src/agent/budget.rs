@@ -0,0 +1,283 @@
+//! Per-turn limits for the tool-call loop.
+//!
+//! A [`TurnBudget`] caps how many LLM round-trips, tool calls, tokens and
+//! seconds one user message may consume. [`LoopDetector`] watches the tool
+//! calls themselves and flags a model that keeps making the same call, or
+//! keeps alternating between two, without getting anywhere.
+//!
+//! Budgets come from `[agent.budget]`, overridden per channel by
+//! `[channels_config.budgets.<channel>]`.
+
+use super::loop_::MAX_TOOL_ITERATIONS;
+use crate::config::{Config, TurnBudgetConfig};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Repeats of one call (or of a two-call cycle) before the model is warned.
+const DEFAULT_MAX_REPEATED_CALLS: usize = 3;
+
+/// The limit that ended a turn early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Iterations,
+    Duration,
+    ToolCalls,
+    Tokens,
+    ToolLoop,
+}
+
+impl BudgetLimit {
+    /// Name used in observer events and metrics labels.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Iterations => "iterations",
+            Self::Duration => "duration_secs",
+            Self::ToolCalls => "tool_calls",
+            Self::Tokens => "tokens",
+            Self::ToolLoop => "tool_loop",
+        }
+    }
+}
+
+/// Resolved limits for one turn. `None` means unlimited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnBudget {
+    pub max_iterations: usize,
+    pub max_duration: Option<Duration>,
+    pub max_tool_calls: Option<usize>,
+    pub max_tokens: Option<u64>,
+    /// 0 disables loop detection.
+    pub max_repeated_calls: usize,
+}
+
+impl Default for TurnBudget {
+    fn default() -> Self {
+        Self::from_config(&TurnBudgetConfig::default())
+    }
+}
+
+impl TurnBudget {
+    pub fn from_config(config: &TurnBudgetConfig) -> Self {
+        Self {
+            max_iterations: config.max_iterations.unwrap_or(MAX_TOOL_ITERATIONS).max(1),
+            max_duration: config.max_duration_secs.map(Duration::from_secs),
+            max_tool_calls: config.max_tool_calls,
+            max_tokens: config.max_tokens,
+            max_repeated_calls: config
+                .max_repeated_calls
+                .unwrap_or(DEFAULT_MAX_REPEATED_CALLS),
+        }
+    }
+}
+
+/// The agent budget plus any per-channel overrides, resolved once at startup.
+#[derive(Debug, Clone, Default)]
+pub struct TurnBudgets {
+    agent: TurnBudget,
+    channels: HashMap<String, TurnBudget>,
+}
+
+impl TurnBudgets {
+    pub fn from_config(config: &Config) -> Self {
+        let agent = config.agent.turn_budget();
+        Self {
+            agent: TurnBudget::from_config(&agent),
+            channels: config
+                .channels_config
+                .budgets
+                .iter()
+                .map(|(channel, budget)| {
+                    (channel.clone(), TurnBudget::from_config(&budget.or(&agent)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Budget for turns on `channel`, falling back to the agent budget.
+    pub fn for_channel(&self, channel: &str) -> &TurnBudget {
+        self.channels.get(channel).unwrap_or(&self.agent)
+    }
+}
+
+/// What the loop should do about the calls seen so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopVerdict {
+    Clear,
+    /// The model just started looping; tell it so and let it change course.
+    Warn(String),
+    /// It kept looping after the warning; end the turn.
+    Stop(String),
+}
+
+/// Spots a model stuck repeating one tool call, or bouncing between two.
+///
+/// Calls are compared by name and arguments. After `threshold` identical
+/// calls in a row (or `threshold` cycles of an A/B pair) the detector warns;
+/// if the very next call continues the pattern it says stop.
+#[derive(Debug)]
+pub struct LoopDetector {
+    threshold: usize,
+    calls: Vec<(String, String)>,
+    warned: bool,
+}
+
+impl LoopDetector {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            calls: Vec::new(),
+            warned: false,
+        }
+    }
+
+    /// Note a call the model asked for.
+    pub fn record(&mut self, name: &str, arguments: &serde_json::Value) {
+        if self.threshold > 0 {
+            self.calls.push((name.to_string(), arguments.to_string()));
+        }
+    }
+
+    /// Check the calls recorded so far for a repeat or an oscillation.
+    pub fn check(&mut self) -> LoopVerdict {
+        let Some(pattern) = self.pattern() else {
+            self.warned = false;
+            return LoopVerdict::Clear;
+        };
+        if self.warned {
+            LoopVerdict::Stop(pattern)
+        } else {
+            self.warned = true;
+            LoopVerdict::Warn(pattern)
+        }
+    }
+
+    /// Describe the pattern the latest calls form, if any.
+    fn pattern(&self) -> Option<String> {
+        if self.repeats_with_period(1) {
+            let (name, _) = &self.calls[self.calls.len() - 1];
+            return Some(format!(
+                "`{name}` with the same arguments {} times in a row",
+                self.threshold
+            ));
+        }
+        if self.repeats_with_period(2) {
+            let (first, _) = &self.calls[self.calls.len() - 2];
+            let (second, _) = &self.calls[self.calls.len() - 1];
+            return Some(format!(
+                "`{first}` and `{second}` back and forth with the same arguments {} times",
+                self.threshold
+            ));
+        }
+        None
+    }
+
+    /// Whether the last `period * threshold` calls repeat every `period`.
+    fn repeats_with_period(&self, period: usize) -> bool {
+        let needed = period * self.threshold;
+        if self.threshold == 0 || self.calls.len() < needed {
+            return false;
+        }
+        let tail = &self.calls[self.calls.len() - needed..];
+        // A period-2 "cycle" of one call is already a period-1 repeat.
+        (period == 1 || tail[0] != tail[1])
+            && tail
+                .iter()
+                .skip(period)
+                .zip(tail)
+                .all(|(later, earlier)| later == earlier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn channel_budgets_fall_back_to_agent_budget() {
+        let mut config = Config::default();
+        config.agent.budget.max_tool_calls = Some(40);
+        config.agent.budget.max_duration_secs = Some(120);
+        config.channels_config.budgets.insert(
+            "telegram".into(),
+            TurnBudgetConfig {
+                max_tool_calls: Some(5),
+                max_repeated_calls: Some(0),
+                ..TurnBudgetConfig::default()
+            },
+        );
+
+        let budgets = TurnBudgets::from_config(&config);
+        let telegram = budgets.for_channel("telegram");
+        assert_eq!(telegram.max_tool_calls, Some(5));
+        assert_eq!(telegram.max_duration, Some(Duration::from_secs(120)));
+        assert_eq!(telegram.max_iterations, MAX_TOOL_ITERATIONS);
+        assert_eq!(telegram.max_repeated_calls, 0);
+
+        let discord = budgets.for_channel("discord");
+        assert_eq!(discord.max_tool_calls, Some(40));
+        assert_eq!(discord.max_repeated_calls, DEFAULT_MAX_REPEATED_CALLS);
+    }
+
+    #[test]
+    fn legacy_max_tool_iterations_applies_unless_the_budget_sets_it() {
+        let mut config = Config::default();
+        config.agent.max_tool_iterations = Some(20);
+        let budgets = TurnBudgets::from_config(&config);
+        assert_eq!(budgets.for_channel("cli").max_iterations, 20);
+
+        config.agent.budget.max_iterations = Some(50);
+        let budgets = TurnBudgets::from_config(&config);
+        assert_eq!(budgets.for_channel("cli").max_iterations, 50);
+    }
+
+    #[test]
+    fn repeated_call_warns_then_stops() {
+        let mut detector = LoopDetector::new(3);
+        let args = json!({"command": "ls"});
+
+        detector.record("shell", &args);
+        detector.record("shell", &args);
+        assert_eq!(detector.check(), LoopVerdict::Clear);
+
+        detector.record("shell", &args);
+        assert!(matches!(detector.check(), LoopVerdict::Warn(p) if p.contains("`shell`")));
+
+        detector.record("shell", &args);
+        assert!(matches!(detector.check(), LoopVerdict::Stop(_)));
+    }
+
+    #[test]
+    fn oscillation_is_detected_and_progress_clears_the_warning() {
+        let mut detector = LoopDetector::new(2);
+        let read = json!({"path": "a.txt"});
+        let write = json!({"path": "a.txt", "content": "x"});
+
+        for _ in 0..2 {
+            detector.record("file_read", &read);
+            detector.record("file_write", &write);
+        }
+        assert!(matches!(
+            detector.check(),
+            LoopVerdict::Warn(p) if p.contains("`file_read` and `file_write`")
+        ));
+
+        detector.record("shell", &json!({"command": "cargo test"}));
+        assert_eq!(detector.check(), LoopVerdict::Clear);
+    }
+
+    #[test]
+    fn different_arguments_are_not_a_loop() {
+        let mut detector = LoopDetector::new(2);
+        detector.record("file_read", &json!({"path": "a.txt"}));
+        detector.record("file_read", &json!({"path": "b.txt"}));
+        detector.record("file_read", &json!({"path": "c.txt"}));
+        assert_eq!(detector.check(), LoopVerdict::Clear);
+
+        let mut disabled = LoopDetector::new(0);
+        for _ in 0..5 {
+            disabled.record("shell", &json!({}));
+        }
+        assert_eq!(disabled.check(), LoopVerdict::Clear);
+    }
+}
@@ -1,21 +1,34 @@
-use crate::config::Config;
+use super::budget::{BudgetLimit, LoopDetector, LoopVerdict, TurnBudget, TurnBudgets};
+use crate::agent::steering::SteeringInbox;
+use crate::config::schema::DEFAULT_MODEL;
+use crate::config::{Config, ModelCapabilities};
+use crate::cost::{BudgetCheck, CostTracker, UsagePeriod, UsageScope};
 use crate::memory::{self, Memory, MemoryCategory};
-use crate::observability::{self, Observer, ObserverEvent};
-use crate::providers::{self, ChatMessage, Provider, ToolCall};
+use crate::observability::{self, Observer, ObserverEvent, ObserverMetric};
+use crate::providers::capabilities::CapabilityRegistry;
+use crate::providers::streaming::StreamAccumulator;
+use crate::providers::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::providers::traits::ContentPartType;
+use crate::providers::{
+    self, ChatMessage, ChatRequest, ChatResponse, ChatUsage, Provider, Reasoning, ResponseSchema,
+    ToolCall,
+};
 use crate::runtime;
-use crate::security::SecurityPolicy;
-use crate::tools::{self, Tool};
+use crate::security::{ApprovalScope, SecurityPolicy};
+use crate::tools::{self, Tool, ToolResult, ToolSpec};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use futures_util::StreamExt;
 use std::collections::HashSet;
 use std::fmt::Write;
 use std::io::Write as _;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 /// Maximum agentic tool-use iterations per user message to prevent runaway loops.
 /// GUI automation tasks (screenshot→analyze→click→verify) typically need ~5 iterations
 /// per action, so this must be high enough for multi-step workflows.
+/// Overridden by `max_iterations` in `[agent.budget]`.
 pub const MAX_TOOL_ITERATIONS: usize = 200;
 
 /// Maximum characters retained per tool result to prevent a single output
@@ -24,10 +37,21 @@ pub const MAX_TOOL_RESULT_CHARS: usize = 16_000;
 
 /// Trigger auto-compaction when non-system message count exceeds this threshold.
 /// When exceeded, the oldest messages are dropped (system prompt is always preserved).
+/// Used when the model's context window is unknown.
 pub const MAX_HISTORY_MESSAGES: usize = 50;
 
-/// Keep this many most-recent non-system messages after compaction.
-const COMPACTION_KEEP_RECENT_MESSAGES: usize = 20;
+/// Context token budget when the model's context window is unknown
+/// (~100K characters of English).
+pub const MAX_HISTORY_TOKENS: usize = 25_000;
+
+/// Per-message framing overhead (role markers, separators).
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Approximate cost of one attached image across vision providers.
+const IMAGE_TOKENS: usize = 1_500;
+
+/// Memory context may use at most 1/N of the context budget per message.
+const MEMORY_CONTEXT_SHARE: usize = 8;
 
 /// Safety cap for compaction source transcript passed to the summarizer.
 const COMPACTION_MAX_SOURCE_CHARS: usize = 12_000;
@@ -39,9 +63,152 @@ fn autosave_memory_key(prefix: &str) -> String {
     format!("{prefix}_{}", Uuid::new_v4())
 }
 
+/// How much context a model can take, counted with its tokenizer. Unknown
+/// models get the conservative [`MAX_HISTORY_MESSAGES`] /
+/// [`MAX_HISTORY_TOKENS`] limits.
+#[derive(Clone)]
+pub struct ContextBudget {
+    pub max_messages: usize,
+    /// Tokens left for the system prompt and history, memory context included.
+    pub max_tokens: usize,
+    /// Tokens set aside with [`reserve`](Self::reserve), e.g. for tool specs.
+    pub reserved_tokens: usize,
+    pub tokenizer: Arc<dyn Tokenizer>,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self {
+            max_messages: MAX_HISTORY_MESSAGES,
+            max_tokens: MAX_HISTORY_TOKENS,
+            reserved_tokens: 0,
+            tokenizer: Arc::new(HeuristicTokenizer::GENERIC),
+        }
+    }
+}
+
+impl ContextBudget {
+    /// Budget for a model: the context window minus room for the reply, with
+    /// a 10% margin for estimation error and provider-side framing.
+    pub fn for_capabilities(caps: &ModelCapabilities, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        let Some(window) = caps.context_window.filter(|w| *w > 0) else {
+            return Self {
+                tokenizer,
+                ..Self::default()
+            };
+        };
+        let reply = caps.max_output_tokens.unwrap_or(4_096).min(window / 4);
+        let usable = window.saturating_sub(reply) / 10 * 9;
+        let max_tokens = usize::try_from(usable).unwrap_or(usize::MAX);
+        Self {
+            max_messages: (max_tokens / 500).clamp(10, 500),
+            max_tokens,
+            reserved_tokens: 0,
+            tokenizer,
+        }
+    }
+
+    /// Budget for `model` (a model name or `hint:<name>`).
+    pub fn for_model(capabilities: &CapabilityRegistry, model: &str) -> Self {
+        Self::for_capabilities(&capabilities.lookup(model), capabilities.tokenizer(model))
+    }
+
+    /// Set aside `tokens` (tool specs) that every request carries.
+    pub fn reserve(&mut self, tokens: usize) {
+        self.max_tokens = self.max_tokens.saturating_sub(tokens);
+        self.reserved_tokens += tokens;
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.tokenizer.count(text)
+    }
+
+    /// Tokens one message costs, including framing and attached images.
+    pub fn message_tokens(&self, message: &ChatMessage) -> usize {
+        let images = message
+            .parts
+            .iter()
+            .flatten()
+            .filter(|p| p.content_type == ContentPartType::Image)
+            .count();
+        self.count(&message.content) + MESSAGE_OVERHEAD_TOKENS + images * IMAGE_TOKENS
+    }
+
+    pub fn history_tokens(&self, history: &[ChatMessage]) -> usize {
+        history.iter().map(|m| self.message_tokens(m)).sum()
+    }
+
+    /// Cap a `[Memory context]` block to an eighth of the budget, dropping
+    /// the lowest-ranked entries (recall returns the best matches first).
+    pub fn fit_memory_context(&self, context: String) -> String {
+        let limit = self.max_tokens / MEMORY_CONTEXT_SHARE;
+        if self.count(&context) <= limit {
+            return context;
+        }
+
+        let mut lines = context.lines();
+        let mut fitted = lines.next().unwrap_or_default().to_string();
+        let mut used = self.count(&fitted);
+        let mut entries = 0;
+        for line in lines.filter(|l| !l.is_empty()) {
+            let cost = self.count(line) + 1;
+            if used + cost > limit {
+                break;
+            }
+            fitted.push('\n');
+            fitted.push_str(line);
+            used += cost;
+            entries += 1;
+        }
+        if entries == 0 {
+            return String::new();
+        }
+        fitted.push_str("\n\n");
+        fitted
+    }
+
+    /// Where the request's tokens go, for verbose diagnostics. `tokens`
+    /// holds each message's [`message_tokens`](Self::message_tokens).
+    fn usage_event(&self, model: &str, history: &[ChatMessage], tokens: &[usize]) -> ObserverEvent {
+        let (mut system, mut memory, mut conversation) = (0, 0, 0);
+        for (message, &tokens) in history.iter().zip(tokens) {
+            if message.role == "system" {
+                system += tokens;
+                continue;
+            }
+            let memory_tokens = memory_context_block(&message.content)
+                .map_or(0, |block| self.count(block))
+                .min(tokens);
+            memory += memory_tokens;
+            conversation += tokens - memory_tokens;
+        }
+        ObserverEvent::ContextUsage {
+            model: model.to_string(),
+            tokenizer: self.tokenizer.name().to_string(),
+            system_tokens: system,
+            memory_tokens: memory,
+            tool_tokens: self.reserved_tokens,
+            history_tokens: conversation,
+            limit_tokens: self.max_tokens + self.reserved_tokens,
+        }
+    }
+
+    /// Most-recent non-system messages kept verbatim after compaction.
+    fn keep_recent(&self) -> usize {
+        self.max_messages * 2 / 5
+    }
+}
+
+/// The `[Memory context]` block injected ahead of a user message, if any.
+fn memory_context_block(content: &str) -> Option<&str> {
+    let start = content.find("[Memory context]\n")?;
+    let block = &content[start..];
+    Some(block.find("\n\n").map_or(block, |end| &block[..end + 2]))
+}
+
 /// Trim conversation history to prevent unbounded growth.
 /// Preserves the system prompt (first message if role=system) and the most recent messages.
-pub fn trim_history(history: &mut Vec<ChatMessage>) {
+pub fn trim_history(history: &mut Vec<ChatMessage>, budget: &ContextBudget) {
     // Nothing to trim if within limit
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
@@ -50,38 +217,58 @@ pub fn trim_history(history: &mut Vec<ChatMessage>) {
         history.len()
     };
 
-    if non_system_count <= MAX_HISTORY_MESSAGES {
+    if non_system_count <= budget.max_messages {
         return;
     }
 
     let start = if has_system { 1 } else { 0 };
-    let to_remove = non_system_count - MAX_HISTORY_MESSAGES;
+    let to_remove = non_system_count - budget.max_messages;
     history.drain(start..start + to_remove);
+    drop_orphan_tool_results(history, start);
+}
+
+/// Remove native tool results left at `index` after the assistant message
+/// that requested them was trimmed away; providers reject unmatched results.
+fn drop_orphan_tool_results(history: &mut Vec<ChatMessage>, index: usize) {
+    while history.get(index).is_some_and(|m| m.role == "tool") {
+        history.remove(index);
+    }
 }
 
-/// Character-budget trim: drop oldest non-system messages until total content
-/// size fits within a safe limit (~100K chars ≈ ~25K tokens).
-/// This catches cases where message count is within bounds but individual
-/// messages contain huge payloads (e.g. screenshot descriptions, shell output).
-const MAX_HISTORY_CHARS: usize = 100_000;
+/// Token-budget trim: drop oldest non-system messages until the history fits
+/// the model's context window. This catches cases where message count is
+/// within bounds but individual messages contain huge payloads (e.g.
+/// screenshot descriptions, shell output).
+pub fn trim_history_by_tokens(history: &mut Vec<ChatMessage>, budget: &ContextBudget) {
+    trim_history_by_tokens_counted(history, budget);
+}
 
-pub fn trim_history_by_size(history: &mut Vec<ChatMessage>) {
-    let total: usize = history.iter().map(|m| m.content.len()).sum();
-    if total <= MAX_HISTORY_CHARS {
-        return;
+/// [`trim_history_by_tokens`], returning the token count of each message
+/// kept so callers don't count the history again.
+fn trim_history_by_tokens_counted(
+    history: &mut Vec<ChatMessage>,
+    budget: &ContextBudget,
+) -> Vec<usize> {
+    let mut tokens: Vec<usize> = history.iter().map(|m| budget.message_tokens(m)).collect();
+    let mut total: usize = tokens.iter().sum();
+    if total <= budget.max_tokens {
+        return tokens;
     }
 
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let start = if has_system { 1 } else { 0 };
 
     // Drop oldest non-system messages until under budget (keep at least 4 recent)
-    while history.len() > start + 4 {
-        let total: usize = history.iter().map(|m| m.content.len()).sum();
-        if total <= MAX_HISTORY_CHARS {
-            break;
-        }
+    while history.len() > start + 4 && total > budget.max_tokens {
+        total -= tokens.remove(start);
         history.remove(start);
     }
+    // Same as drop_orphan_tool_results, keeping `tokens` in step.
+    while history.get(start).is_some_and(|m| m.role == "tool") {
+        tokens.remove(start);
+        history.remove(start);
+    }
+    tokens
 }
 
 fn build_compaction_transcript(messages: &[ChatMessage]) -> String {
@@ -106,12 +293,35 @@ fn apply_compaction_summary(
 ) {
     let summary_msg = ChatMessage::assistant(format!("[Compaction summary]\n{}", summary.trim()));
     history.splice(start..compact_end, std::iter::once(summary_msg));
+    drop_orphan_tool_results(history, start + 1);
+}
+
+/// Shape of a compaction summary: up to 12 short points.
+fn compaction_schema() -> ResponseSchema {
+    ResponseSchema::new(
+        "conversation_summary",
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "points": {
+                    "type": "array",
+                    "items": {"type": "string", "minLength": 1},
+                    "minItems": 1,
+                    "maxItems": 12
+                }
+            },
+            "required": ["points"],
+            "additionalProperties": false
+        }),
+    )
+    .with_description("Key facts, preferences, decisions and open tasks from older messages")
 }
 
 pub async fn auto_compact_history(
     history: &mut Vec<ChatMessage>,
     provider: &dyn Provider,
     model: &str,
+    budget: &ContextBudget,
 ) -> Result<bool> {
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
@@ -120,12 +330,12 @@ pub async fn auto_compact_history(
         history.len()
     };
 
-    if non_system_count <= MAX_HISTORY_MESSAGES {
+    if non_system_count <= budget.max_messages {
         return Ok(false);
     }
 
     let start = if has_system { 1 } else { 0 };
-    let keep_recent = COMPACTION_KEEP_RECENT_MESSAGES.min(non_system_count);
+    let keep_recent = budget.keep_recent().min(non_system_count);
     let compact_count = non_system_count.saturating_sub(keep_recent);
     if compact_count == 0 {
         return Ok(false);
@@ -135,20 +345,37 @@ pub async fn auto_compact_history(
     let to_compact: Vec<ChatMessage> = history[start..compact_end].to_vec();
     let transcript = build_compaction_transcript(&to_compact);
 
-    let summarizer_system = "You are a conversation compaction engine. Summarize older chat history into concise context for future turns. Preserve: user preferences, commitments, decisions, unresolved tasks, key facts. Omit: filler, repeated chit-chat, verbose tool logs. Output plain text bullet points only.";
+    let summarizer_system = "You are a conversation compaction engine. Summarize older chat history into concise context for future turns. Preserve: user preferences, commitments, decisions, unresolved tasks, key facts. Omit: filler, repeated chit-chat, verbose tool logs.";
 
     let summarizer_user = format!(
-        "Summarize the following conversation history for context preservation. Keep it short (max 12 bullet points).\n\n{}",
+        "Summarize the following conversation history for context preservation. Keep it short (max 12 points).\n\n{}",
         transcript
     );
+    let messages = [
+        ChatMessage::system(summarizer_system),
+        ChatMessage::user(summarizer_user),
+    ];
+    let request = ChatRequest {
+        messages: &messages,
+        tools: None,
+        reasoning: None,
+    };
 
-    let summary_raw = provider
-        .chat_with_system(Some(summarizer_system), &summarizer_user, model, 0.2)
+    let summary_raw = match provider
+        .chat_structured(request, &compaction_schema(), model, 0.2)
         .await
-        .unwrap_or_else(|_| {
-            // Fallback to deterministic local truncation when summarization fails.
-            truncate_with_ellipsis(&transcript, COMPACTION_MAX_SUMMARY_CHARS)
-        });
+    {
+        Ok(value) => value["points"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .map(|point| format!("- {}", point.trim()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        // Fallback to deterministic local truncation when summarization fails.
+        Err(_) => truncate_with_ellipsis(&transcript, COMPACTION_MAX_SUMMARY_CHARS),
+    };
 
     let summary = truncate_with_ellipsis(&summary_raw, COMPACTION_MAX_SUMMARY_CHARS);
     apply_compaction_summary(history, start, compact_end, &summary);
@@ -256,7 +483,11 @@ fn parse_tool_call_value(value: &serde_json::Value) -> Option<ParsedToolCall> {
             .to_string();
         if !name.is_empty() {
             let arguments = parse_arguments_value(function.get("arguments"));
-            return Some(ParsedToolCall { name, arguments });
+            return Some(ParsedToolCall {
+                name,
+                arguments,
+                tool_call_id: None,
+            });
         }
     }
 
@@ -272,7 +503,11 @@ fn parse_tool_call_value(value: &serde_json::Value) -> Option<ParsedToolCall> {
     }
 
     let arguments = parse_arguments_value(value.get("arguments"));
-    Some(ParsedToolCall { name, arguments })
+    Some(ParsedToolCall {
+        name,
+        arguments,
+        tool_call_id: None,
+    })
 }
 
 fn parse_tool_calls_from_json_value(value: &serde_json::Value) -> Vec<ParsedToolCall> {
@@ -436,10 +671,144 @@ fn parse_structured_tool_calls(tool_calls: &[ToolCall]) -> Vec<ParsedToolCall> {
             name: call.name.clone(),
             arguments: serde_json::from_str::<serde_json::Value>(&call.arguments)
                 .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new())),
+            tool_call_id: Some(call.id.clone()),
         })
         .collect()
 }
 
+/// Encode an assistant turn with native tool calls the way provider
+/// `chat()` implementations read it back from history. Provider reasoning
+/// blocks are kept so signed thinking survives into the next iteration.
+fn build_native_assistant_history(
+    text: &str,
+    tool_calls: &[ToolCall],
+    reasoning: Option<&Reasoning>,
+) -> String {
+    let content = (!text.trim().is_empty()).then_some(text);
+    let mut payload = serde_json::json!({
+        "content": content,
+        "tool_calls": tool_calls,
+    });
+    if let Some(reasoning) = reasoning.filter(|r| !r.blocks.is_empty()) {
+        payload["reasoning"] = serde_json::json!(reasoning.blocks);
+    }
+    payload.to_string()
+}
+
+/// Whether the model asked to run this call with `approved: true`.
+fn requests_approval(arguments: &serde_json::Value) -> bool {
+    arguments
+        .get("approved")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// How many of `calls` (name and arguments, in order) run together: a run
+/// of up to `max_parallel` parallel-safe calls, otherwise just the first.
+/// Calls claiming approval always run alone, so an `APPROVAL_REQUIRED`
+/// answer earlier in the response is known before they are sanitized.
+pub(crate) fn parallel_batch_len<'a>(
+    tools: &[Box<dyn Tool>],
+    calls: impl IntoIterator<Item = (&'a str, &'a serde_json::Value)>,
+    max_parallel: usize,
+) -> usize {
+    calls
+        .into_iter()
+        .take(max_parallel)
+        .take_while(|(name, arguments)| {
+            !requests_approval(arguments)
+                && find_tool(tools, name).is_some_and(|tool| tool.parallel_safe(arguments))
+        })
+        .count()
+        .max(1)
+}
+
+/// Execute one tool call and time it. The result is `None` when no tool
+/// has the call's name.
+async fn execute_tool_call(
+    tools: &[Box<dyn Tool>],
+    observer: &dyn Observer,
+    name: &str,
+    args: serde_json::Value,
+) -> (Option<Result<ToolResult>>, Duration) {
+    observer.record_event(&ObserverEvent::ToolCallStart {
+        tool: name.to_string(),
+    });
+    let start = Instant::now();
+    let result = match find_tool(tools, name) {
+        Some(tool) => Some(tool.execute(args).await),
+        None => None,
+    };
+    (result, start.elapsed())
+}
+
+/// Append one call's result, capped at [`MAX_TOOL_RESULT_CHARS`], to the
+/// results being collected for the next request.
+fn push_tool_result(
+    use_native_tools: bool,
+    call: &ParsedToolCall,
+    result: &str,
+    tool_results: &mut String,
+    native_results: &mut Vec<ChatMessage>,
+) {
+    let capped = if result.len() > MAX_TOOL_RESULT_CHARS {
+        truncate_with_ellipsis(result, MAX_TOOL_RESULT_CHARS)
+    } else {
+        result.to_string()
+    };
+    if use_native_tools {
+        native_results.push(build_native_tool_result(
+            call.tool_call_id.as_deref(),
+            &capped,
+        ));
+    } else {
+        let _ = writeln!(
+            tool_results,
+            "<tool_result name=\"{}\">\n{}\n</tool_result>",
+            call.name, capped
+        );
+    }
+}
+
+/// End a turn that ran into `limit`: report it and tell the user why the
+/// answer is incomplete.
+pub(crate) fn stop_on_budget(
+    observer: &dyn Observer,
+    limit: BudgetLimit,
+    value: u64,
+    reason: &str,
+    last_text: &str,
+) -> String {
+    tracing::warn!(
+        budget = limit.as_str(),
+        limit = value,
+        "Agent turn stopped: {reason}"
+    );
+    observer.record_event(&ObserverEvent::BudgetExceeded {
+        budget: limit.as_str().to_string(),
+        limit: value,
+    });
+    if last_text.is_empty() {
+        format!(
+            "I stopped before finishing because {reason}. The work may be partially done — \
+             ask me to continue or narrow the request."
+        )
+    } else {
+        format!("{last_text}\n\n(Note: I stopped before finishing because {reason}.)")
+    }
+}
+
+/// Encode one native tool result as a `tool` history message.
+fn build_native_tool_result(tool_call_id: Option<&str>, content: &str) -> ChatMessage {
+    ChatMessage::tool(
+        serde_json::json!({
+            "tool_call_id": tool_call_id.unwrap_or("unknown"),
+            "content": content,
+        })
+        .to_string(),
+    )
+}
+
 fn build_assistant_history_with_tool_calls(text: &str, tool_calls: &[ToolCall]) -> String {
     let mut parts = Vec::new();
 
@@ -465,12 +834,15 @@ fn build_assistant_history_with_tool_calls(text: &str, tool_calls: &[ToolCall])
 pub struct ParsedToolCall {
     pub name: String,
     pub arguments: serde_json::Value,
+    /// Provider-assigned id, set only for native tool calls.
+    pub tool_call_id: Option<String>,
 }
 
 /// Execute a single turn of the agent loop: send messages, parse tool calls,
 /// execute tools, and loop until the LLM produces a final text response.
 /// When `silent` is true, suppresses stdout (for channel use).
 // --- ZeroClaw fork: pub visibility for gateway usage ---
+#[allow(clippy::too_many_arguments)]
 pub async fn agent_turn(
     provider: &dyn Provider,
     history: &mut Vec<ChatMessage>,
@@ -480,6 +852,10 @@ pub async fn agent_turn(
     model: &str,
     temperature: f64,
     silent: bool,
+    capabilities: &CapabilityRegistry,
+    max_parallel_tools: usize,
+    turn_budget: &TurnBudget,
+    hooks: TurnHooks<'_, '_>,
 ) -> Result<String> {
     run_tool_call_loop(
         provider,
@@ -490,12 +866,203 @@ pub async fn agent_turn(
         model,
         temperature,
         silent,
+        capabilities,
+        max_parallel_tools,
+        turn_budget,
+        hooks,
     )
     .await
 }
 
+/// Open the cost tracker for `config`. Failures are logged rather than
+/// fatal so a broken cost store never blocks the agent.
+pub(crate) fn create_cost_tracker(config: &Config) -> Option<CostTracker> {
+    CostTracker::from_config(config)
+        .map_err(|e| tracing::warn!("Cost tracking unavailable: {e}"))
+        .ok()
+}
+
+/// Portion of streamed text that is safe to show the user: everything before
+/// the first `<tool_call>` tag, holding back a trailing fragment that could
+/// still turn into one.
+fn visible_stream_prefix(text: &str) -> &str {
+    const TOOL_CALL_TAG: &str = "<tool_call";
+    if let Some(pos) = text.find(TOOL_CALL_TAG) {
+        return &text[..pos];
+    }
+    if let Some(pos) = text.rfind('<') {
+        if TOOL_CALL_TAG.starts_with(&text[pos..]) {
+            return &text[..pos];
+        }
+    }
+    text
+}
+
+/// Stream one LLM call, forwarding user-visible text to `on_delta` as it
+/// arrives, and return the assembled response.
+async fn stream_chat_response(
+    provider: &dyn Provider,
+    history: &[ChatMessage],
+    tools: Option<&[ToolSpec]>,
+    model: &str,
+    temperature: f64,
+    on_delta: &tokio::sync::mpsc::UnboundedSender<String>,
+    forwarded_any: &mut bool,
+) -> Result<ChatResponse> {
+    let mut stream = provider
+        .stream_chat(
+            ChatRequest {
+                messages: history,
+                tools,
+                reasoning: None,
+            },
+            model,
+            temperature,
+        )
+        .await?;
+
+    let mut accumulator = StreamAccumulator::new();
+    let mut forwarded = 0;
+    while let Some(event) = stream.next().await {
+        accumulator.push(&event?);
+        let visible = visible_stream_prefix(accumulator.text());
+        if visible.len() > forwarded {
+            // Separate narration from earlier iterations of the same turn.
+            if forwarded == 0 && *forwarded_any {
+                let _ = on_delta.send("\n\n".to_string());
+            }
+            let _ = on_delta.send(visible[forwarded..].to_string());
+            forwarded = visible.len();
+            *forwarded_any = true;
+        }
+    }
+
+    Ok(accumulator.finish())
+}
+
+/// Flatten a structured response into the text protocol `parse_tool_calls`
+/// understands.
+fn response_to_history_text(response: ChatResponse) -> String {
+    let text = response.text.unwrap_or_default();
+    if response.tool_calls.is_empty() {
+        text
+    } else {
+        build_assistant_history_with_tool_calls(&text, &response.tool_calls)
+    }
+}
+
+fn token_count(tokens: usize) -> u64 {
+    u64::try_from(tokens).unwrap_or(u64::MAX)
+}
+
+/// Refuse the next LLM call once the spending limit is reached. When spending
+/// crosses the warning threshold and a cheaper model is configured, `model`
+/// is switched to it for the rest of the turn.
+fn enforce_budget(tracker: &CostTracker, input_tokens: u64, model: &mut String) -> Result<()> {
+    let estimate = tracker.usage_for(model, input_tokens, 0).cost_usd;
+
+    match tracker.check_budget(estimate)? {
+        BudgetCheck::Allowed => {}
+        BudgetCheck::Warning {
+            current_usd,
+            limit_usd,
+            period,
+        } => {
+            if let Some(cheaper) = tracker.downgrade_model().filter(|m| *m != model.as_str()) {
+                tracing::warn!(
+                    current_usd,
+                    limit_usd,
+                    ?period,
+                    from = model.as_str(),
+                    to = cheaper,
+                    "Approaching spending limit; downgrading model"
+                );
+                *model = cheaper.to_string();
+            }
+        }
+        BudgetCheck::Exceeded {
+            current_usd,
+            limit_usd,
+            period,
+        } => {
+            anyhow::bail!(
+                "Spending limit reached: ${current_usd:.2} of ${limit_usd:.2} {} budget used. \
+                 Raise the limit in config or try again later.",
+                match period {
+                    UsagePeriod::Session => "session",
+                    UsagePeriod::Day => "daily",
+                    UsagePeriod::Month => "monthly",
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Token usage of one LLM call: as reported by the provider, otherwise
+/// estimated from the request's `input_tokens` and the response.
+fn response_usage(input_tokens: u64, budget: &ContextBudget, response: &ChatResponse) -> ChatUsage {
+    response.usage.unwrap_or_else(|| ChatUsage {
+        input_tokens,
+        output_tokens: token_count(
+            budget.count(response.text_or_empty())
+                + response
+                    .tool_calls
+                    .iter()
+                    .map(|c| budget.count(&c.arguments))
+                    .sum::<usize>(),
+        ),
+        cached_input_tokens: 0,
+        cache_write_input_tokens: 0,
+    })
+}
+
+/// Charge one LLM call's usage to `tracker`.
+fn record_llm_usage(tracker: &CostTracker, model: &str, usage: &ChatUsage) {
+    if let Err(e) = tracker.record_chat_usage(model, usage) {
+        tracing::warn!("Failed to record LLM usage: {e}");
+    }
+}
+
+/// Optional per-turn hooks for [`run_tool_call_loop`]. The default runs a
+/// plain turn with none of them.
+#[derive(Default)]
+pub struct TurnHooks<'a, 's> {
+    /// When the provider streams, user-visible text is forwarded here as it
+    /// is generated so channels can show a live draft.
+    pub on_delta: Option<&'a tokio::sync::mpsc::UnboundedSender<String>>,
+    /// Every LLM call is budget-checked first and its token usage recorded
+    /// afterwards.
+    pub cost_tracker: Option<&'a CostTracker>,
+    /// Reasoning text from each LLM call of the turn is appended here.
+    pub reasoning_out: Option<&'a mut String>,
+    /// Images returned by tools that deliver them to the user (see
+    /// [`Tool::delivers_images`]) are collected as `(base64, mime)` pairs.
+    pub images_out: Option<&'a mut Vec<(String, String)>>,
+    /// Approval is handled by this scope's broker instead of the model:
+    /// `approved` flags from the model are always dropped, and calls that
+    /// answer `APPROVAL_REQUIRED` are parked for the user to decide.
+    pub approvals: Option<&'a mut ApprovalScope<'s>>,
+    /// Messages the user sends while the turn runs are taken from here after
+    /// each round of tool calls and shown to the model with the results.
+    pub steering: Option<&'a SteeringInbox>,
+}
+
 /// Execute a single turn of the agent loop: send messages, parse tool calls,
 /// execute tools, and loop until the LLM produces a final text response.
+///
+/// Providers that support native tool calling receive the tool specs in the
+/// request and their structured tool calls/results are kept in history;
+/// other providers use the `<tool_call>` text protocol.
+///
+/// Up to `max_parallel_tools` consecutive calls to tools that are
+/// [`Tool::parallel_safe`] run concurrently; 1 runs every call in turn.
+///
+/// `turn_budget` bounds the turn. Hitting a limit, or a model that keeps
+/// looping on the same tool calls after being told so, ends the turn with
+/// an explanation in place of the answer and a
+/// [`ObserverEvent::BudgetExceeded`] event.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn run_tool_call_loop(
     provider: &dyn Provider,
     history: &mut Vec<ChatMessage>,
@@ -505,7 +1072,19 @@ pub(crate) async fn run_tool_call_loop(
     model: &str,
     temperature: f64,
     silent: bool,
+    capabilities: &CapabilityRegistry,
+    max_parallel_tools: usize,
+    turn_budget: &TurnBudget,
+    hooks: TurnHooks<'_, '_>,
 ) -> Result<String> {
+    let TurnHooks {
+        on_delta,
+        cost_tracker,
+        mut reasoning_out,
+        mut images_out,
+        mut approvals,
+        steering,
+    } = hooks;
     // Self-approval guard: track tools that returned APPROVAL_REQUIRED in this
     // turn so the LLM cannot self-approve by retrying with approved=true.
     // A new turn (new webhook call) starts with an empty set, allowing
@@ -513,37 +1092,129 @@ pub(crate) async fn run_tool_call_loop(
     let mut denied_tools: HashSet<String> = HashSet::new();
 
     let mut last_text = String::new();
+    let mut streamed_any = false;
+    let mut model = model.to_string();
+    let started_at = Instant::now();
+    let mut tokens_used: u64 = 0;
+    let mut tool_calls_used: usize = 0;
+    let mut loop_detector = LoopDetector::new(turn_budget.max_repeated_calls);
+
+    let use_native_tools = provider.supports_native_tools() && !tools_registry.is_empty();
+    let tool_specs: Vec<ToolSpec> = if use_native_tools {
+        tools_registry.iter().map(|tool| tool.spec()).collect()
+    } else {
+        Vec::new()
+    };
+    let request_tools = use_native_tools.then_some(tool_specs.as_slice());
+    let tool_specs_json = if use_native_tools {
+        serde_json::to_string(&tool_specs).unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    for _iteration in 0..turn_budget.max_iterations {
+        if let Some(max) = turn_budget.max_duration {
+            if started_at.elapsed() >= max {
+                let reason = format!("this request hit its {}s time limit", max.as_secs());
+                return Ok(stop_on_budget(
+                    observer,
+                    BudgetLimit::Duration,
+                    max.as_secs(),
+                    &reason,
+                    &last_text,
+                ));
+            }
+        }
+        if let Some(max) = turn_budget.max_tokens.filter(|max| tokens_used >= *max) {
+            let reason = format!("this request used up its {max}-token budget");
+            return Ok(stop_on_budget(
+                observer,
+                BudgetLimit::Tokens,
+                max,
+                &reason,
+                &last_text,
+            ));
+        }
 
-    for _iteration in 0..MAX_TOOL_ITERATIONS {
         // --- ZeroClaw fork: Mid-turn trim ---
-        trim_history(history);
-        trim_history_by_size(history);
+        // Trim a copy for this request only: the routed model may have a
+        // smaller window than the one the caller persists history with.
+        let mut budget = ContextBudget::for_model(capabilities, &model);
+        budget.reserve(budget.count(&tool_specs_json));
+        let mut request_history = history.clone();
+        trim_history(&mut request_history, &budget);
+        // Count once; the budget check, diagnostics and usage estimate share it.
+        let message_tokens = trim_history_by_tokens_counted(&mut request_history, &budget);
+        let input_tokens =
+            token_count(message_tokens.iter().sum::<usize>() + budget.reserved_tokens);
+
+        if let Some(tracker) = cost_tracker {
+            enforce_budget(tracker, input_tokens, &mut model)?;
+        }
+
+        observer.record_event(&budget.usage_event(&model, &request_history, &message_tokens));
 
         observer.record_event(&ObserverEvent::LlmRequest {
             provider: provider_name.to_string(),
-            model: model.to_string(),
-            messages_count: history.len(),
+            model: model.clone(),
+            messages_count: request_history.len(),
         });
 
         let llm_started_at = Instant::now();
-        let response = match provider
-            .chat_with_history(history, model, temperature)
-            .await
-        {
+        let llm_result = match on_delta {
+            Some(tx) if provider.supports_streaming() => {
+                stream_chat_response(
+                    provider,
+                    &request_history,
+                    request_tools,
+                    &model,
+                    temperature,
+                    tx,
+                    &mut streamed_any,
+                )
+                .await
+            }
+            _ => {
+                provider
+                    .chat(
+                        ChatRequest {
+                            messages: &request_history,
+                            tools: request_tools,
+                            reasoning: None,
+                        },
+                        &model,
+                        temperature,
+                    )
+                    .await
+            }
+        };
+        let response = match llm_result {
             Ok(resp) => {
                 observer.record_event(&ObserverEvent::LlmResponse {
                     provider: provider_name.to_string(),
-                    model: model.to_string(),
+                    model: model.clone(),
                     duration: llm_started_at.elapsed(),
                     success: true,
                     error_message: None,
                 });
+                if let Some(usage) = resp.usage {
+                    observer.record_metric(&ObserverMetric::TokensUsed(
+                        usage.input_tokens + usage.output_tokens,
+                    ));
+                }
+                if cost_tracker.is_some() || turn_budget.max_tokens.is_some() {
+                    let usage = response_usage(input_tokens, &budget, &resp);
+                    tokens_used += usage.input_tokens + usage.output_tokens;
+                    if let Some(tracker) = cost_tracker {
+                        record_llm_usage(tracker, &model, &usage);
+                    }
+                }
                 resp
             }
             Err(e) => {
                 observer.record_event(&ObserverEvent::LlmResponse {
                     provider: provider_name.to_string(),
-                    model: model.to_string(),
+                    model: model.clone(),
                     duration: llm_started_at.elapsed(),
                     success: false,
                     error_message: Some(crate::providers::sanitize_api_error(&e.to_string())),
@@ -552,17 +1223,43 @@ pub(crate) async fn run_tool_call_loop(
             }
         };
 
-        let response_text = response;
-        let mut assistant_history_content = response_text.clone();
-        let (parsed_text, tool_calls) = parse_tool_calls(&response_text);
-        let mut parsed_text = parsed_text;
-        let mut tool_calls = tool_calls;
+        if let (Some(out), Some(reasoning)) = (reasoning_out.as_deref_mut(), &response.reasoning) {
+            let text = reasoning.text.trim();
+            if !text.is_empty() {
+                if !out.is_empty() {
+                    out.push_str("\n\n");
+                }
+                out.push_str(text);
+            }
+        }
+
+        let (parsed_text, tool_calls, assistant_history_content) = if use_native_tools {
+            let text = response.text.unwrap_or_default();
+            let history_content = if response.tool_calls.is_empty() {
+                text.clone()
+            } else {
+                build_native_assistant_history(
+                    &text,
+                    &response.tool_calls,
+                    response.reasoning.as_ref(),
+                )
+            };
+            (
+                text,
+                parse_structured_tool_calls(&response.tool_calls),
+                history_content,
+            )
+        } else {
+            let response_text = response_to_history_text(response);
+            let (parsed_text, tool_calls) = parse_tool_calls(&response_text);
+            (parsed_text, tool_calls, response_text)
+        };
 
         if tool_calls.is_empty() {
             // No tool calls — this is the final response
-            history.push(ChatMessage::assistant(response_text.clone()));
+            history.push(ChatMessage::assistant(assistant_history_content.clone()));
             return Ok(if parsed_text.is_empty() {
-                response_text
+                assistant_history_content
             } else {
                 parsed_text
             });
@@ -578,100 +1275,229 @@ pub(crate) async fn run_tool_call_loop(
             }
         }
 
-        // Execute each tool call and build results
+        // Calls past the tool-call budget are skipped; once it is spent, a
+        // response that still wants tools ends the turn.
+        let allowed = turn_budget.max_tool_calls.map_or(tool_calls.len(), |max| {
+            max.saturating_sub(tool_calls_used).min(tool_calls.len())
+        });
+        if allowed == 0 {
+            let max = turn_budget.max_tool_calls.unwrap_or_default();
+            let reason = format!("this request used all {max} tool calls it is allowed");
+            return Ok(stop_on_budget(
+                observer,
+                BudgetLimit::ToolCalls,
+                u64::try_from(max).unwrap_or(u64::MAX),
+                &reason,
+                &last_text,
+            ));
+        }
+        tool_calls_used += allowed;
+
+        for call in &tool_calls[..allowed] {
+            loop_detector.record(&call.name, &call.arguments);
+        }
+        // Notes for the model, sent along with this step's tool results.
+        let mut notes = Vec::new();
+        match loop_detector.check() {
+            LoopVerdict::Clear => {}
+            LoopVerdict::Warn(pattern) => notes.push(format!(
+                "[Loop detected] You have called {pattern}. Repeating it will not give a \
+                 different result. Change approach, or answer the user with what you have; \
+                 repeating it again ends this turn."
+            )),
+            LoopVerdict::Stop(pattern) => {
+                let reason = format!("I kept calling {pattern} without making progress");
+                return Ok(stop_on_budget(
+                    observer,
+                    BudgetLimit::ToolLoop,
+                    u64::try_from(turn_budget.max_repeated_calls).unwrap_or(u64::MAX),
+                    &reason,
+                    &last_text,
+                ));
+            }
+        }
+
+        // Execute the tool calls and build results. Runs of parallel-safe
+        // calls execute concurrently; results keep the original call order.
         let mut tool_results = String::new();
+        let mut native_results = Vec::new();
         // Capture at most one image from tool results to send to the agent LLM
         let mut result_image: Option<(String, String)> = None; // (base64, mime)
-        for call in &tool_calls {
-            observer.record_event(&ObserverEvent::ToolCallStart {
-                tool: call.name.clone(),
-            });
-            let start = Instant::now();
+        let mut next_call = 0;
+        while next_call < allowed {
+            let batch_len = parallel_batch_len(
+                tools_registry,
+                tool_calls[next_call..allowed]
+                    .iter()
+                    .map(|call| (call.name.as_str(), &call.arguments)),
+                max_parallel_tools,
+            );
+            let batch = &tool_calls[next_call..next_call + batch_len];
+            next_call += batch_len;
+
             // Self-approval guard: strip approved=true if this tool was
-            // denied earlier in this turn to prevent LLM self-approval.
-            let sanitized_args = {
+            // denied earlier in this turn to prevent LLM self-approval. With
+            // an approval broker only the user can approve, so always strip.
+            let mut executions = Vec::with_capacity(batch.len());
+            for call in batch {
                 let mut args = call.arguments.clone();
-                if denied_tools.contains(&call.name) {
+                if approvals.is_some() || denied_tools.contains(&call.name) {
                     if let Some(obj) = args.as_object_mut() {
-                        if obj.get("approved").and_then(|v| v.as_bool()).unwrap_or(false) {
-                            obj.insert(
-                                "approved".into(),
-                                serde_json::Value::Bool(false),
-                            );
+                        if obj
+                            .get("approved")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false)
+                        {
+                            obj.insert("approved".into(), serde_json::Value::Bool(false));
                         }
                     }
                 }
-                args
-            };
+                executions.push(execute_tool_call(
+                    tools_registry,
+                    observer,
+                    &call.name,
+                    args,
+                ));
+            }
+            // `buffered` polls the whole batch at once but yields in order.
+            let outcomes: Vec<_> = futures_util::stream::iter(executions)
+                .buffered(batch_len)
+                .collect()
+                .await;
 
-            let result = if let Some(tool) = find_tool(tools_registry, &call.name) {
-                match tool.execute(sanitized_args).await {
-                    Ok(r) => {
+            for (call, (outcome, duration)) in batch.iter().zip(outcomes) {
+                let result = match outcome {
+                    Some(Ok(r)) => {
                         observer.record_event(&ObserverEvent::ToolCall {
                             tool: call.name.clone(),
-                            duration: start.elapsed(),
+                            duration,
                             success: r.success,
                         });
                         // Track APPROVAL_REQUIRED denials for the self-approval guard
-                        if !r.success {
-                            if let Some(ref err) = r.error {
-                                if err.contains("APPROVAL_REQUIRED") {
-                                    denied_tools.insert(call.name.clone());
-                                }
+                        let mut parked_id = None;
+                        if let Some(reason) = r
+                            .error
+                            .as_deref()
+                            .filter(|err| !r.success && err.contains("APPROVAL_REQUIRED"))
+                        {
+                            denied_tools.insert(call.name.clone());
+                            if let Some(scope) = approvals.as_deref_mut() {
+                                let request = scope.broker.park(
+                                    scope.conversation,
+                                    &call.name,
+                                    call.arguments.clone(),
+                                    reason,
+                                );
+                                parked_id = Some(request.id.clone());
+                                scope.parked.push(request);
+                            }
+                        }
+                        if find_tool(tools_registry, &call.name)
+                            .is_some_and(|t| t.delivers_images())
+                        {
+                            if let (Some(images), Some(b64), Some(mime)) =
+                                (images_out.as_deref_mut(), &r.image_base64, &r.image_mime)
+                            {
+                                images.push((b64.clone(), mime.clone()));
                             }
                         }
                         // Capture image from tool result (first one only to limit context)
                         if result_image.is_none() {
-                            if let (Some(b64), Some(mime)) = (r.image_base64.as_ref(), r.image_mime.as_ref()) {
+                            if let (Some(b64), Some(mime)) =
+                                (r.image_base64.as_ref(), r.image_mime.as_ref())
+                            {
                                 result_image = Some((b64.clone(), mime.clone()));
                             }
                         }
-                        if r.success {
+                        if let Some(id) = parked_id {
+                            format!(
+                                "APPROVAL_REQUIRED: this call is parked as approval request {id} and \
+                                 the user has been asked to approve it. Do not retry it or ask for \
+                                 approval yourself. Briefly tell the user what you want to do and \
+                                 why; if they approve, it runs and the result is added to the \
+                                 conversation."
+                            )
+                        } else if r.success {
                             r.output
                         } else {
                             format!("Error: {}", r.error.unwrap_or_else(|| r.output))
                         }
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         observer.record_event(&ObserverEvent::ToolCall {
                             tool: call.name.clone(),
-                            duration: start.elapsed(),
+                            duration,
                             success: false,
                         });
                         format!("Error executing {}: {e}", call.name)
                     }
-                }
-            } else {
-                format!("Unknown tool: {}", call.name)
-            };
-
-            let capped = if result.len() > MAX_TOOL_RESULT_CHARS {
-                truncate_with_ellipsis(&result, MAX_TOOL_RESULT_CHARS)
-            } else {
-                result
-            };
-            let _ = writeln!(
-                tool_results,
-                "<tool_result name=\"{}\">\n{}\n</tool_result>",
-                call.name, capped
+                    None => format!("Unknown tool: {}", call.name),
+                };
+
+                push_tool_result(
+                    use_native_tools,
+                    call,
+                    &result,
+                    &mut tool_results,
+                    &mut native_results,
+                );
+            }
+        }
+        for call in &tool_calls[allowed..] {
+            push_tool_result(
+                use_native_tools,
+                call,
+                "Error: skipped, this turn's tool-call budget is used up.",
+                &mut tool_results,
+                &mut native_results,
             );
         }
 
+        for message in steering.map(SteeringInbox::drain).unwrap_or_default() {
+            notes.push(format!(
+                "[New message from the user while you were working]\n{message}\n\
+                 Take it into account from here on."
+            ));
+        }
+
         // Add assistant message with tool calls + tool results to history.
         // --- ZeroClaw fork: multimodal image support ---
         // If a tool returned an image (e.g. screenshot), send it as a multimodal
         // message so the agent LLM can see the actual screen.
-        history.push(ChatMessage::assistant(assistant_history_content.clone()));
-        let results_text = format!("[Tool results]\n{tool_results}");
-        if let Some((b64, mime)) = result_image {
-            history.push(ChatMessage::with_image(results_text, b64, mime));
+        history.push(ChatMessage::assistant(assistant_history_content));
+        if use_native_tools {
+            history.extend(native_results);
+            if let Some((b64, mime)) = result_image {
+                history.push(ChatMessage::with_image(
+                    "[Image from tool result]",
+                    b64,
+                    mime,
+                ));
+            }
+            if !notes.is_empty() {
+                history.push(ChatMessage::user(notes.join("\n\n")));
+            }
         } else {
-            history.push(ChatMessage::user(results_text));
+            let mut results_text = format!("[Tool results]\n{tool_results}");
+            for note in &notes {
+                results_text.push('\n');
+                results_text.push_str(note);
+            }
+            if let Some((b64, mime)) = result_image {
+                history.push(ChatMessage::with_image(results_text, b64, mime));
+            } else {
+                history.push(ChatMessage::user(results_text));
+            }
         }
     }
 
     // Exhausted iterations — return partial text instead of hard failure
-    tracing::warn!("Agent reached max tool iterations ({MAX_TOOL_ITERATIONS})");
+    let max = turn_budget.max_iterations;
+    tracing::warn!("Agent reached max tool iterations ({max})");
+    observer.record_event(&ObserverEvent::BudgetExceeded {
+        budget: BudgetLimit::Iterations.as_str().to_string(),
+        limit: u64::try_from(max).unwrap_or(u64::MAX),
+    });
     if last_text.is_empty() {
         Ok("I ran out of steps while working on that task. The work is partially done — please try a simpler request or break it into smaller steps.".to_string())
     } else {
@@ -785,19 +1611,28 @@ pub async fn run(
         .or(config.default_model.as_deref())
         .unwrap_or("anthropic/claude-sonnet-4");
 
+    let capabilities = Arc::new(CapabilityRegistry::from_config(&config));
     let provider: Box<dyn Provider> = providers::create_routed_provider(
         provider_name,
         config.api_key.as_deref(),
         &config.reliability,
         &config.model_routes,
         model_name,
+        Arc::clone(&capabilities),
     )?;
+    let provider = providers::with_replay(provider, &config.replay, &config.workspace_dir)?;
+    let provider = providers::with_redaction(provider, &config, Arc::clone(&observer))?;
+    let context_budget = ContextBudget::for_model(&capabilities, model_name);
 
     observer.record_event(&ObserverEvent::AgentStart {
         provider: provider_name.to_string(),
         model: model_name.to_string(),
     });
 
+    let cost_tracker =
+        create_cost_tracker(&config).map(|t| t.scoped(UsageScope::channel("cli", "local")));
+    let turn_budget = TurnBudgets::from_config(&config).for_channel("cli").clone();
+
     // ── Hardware RAG (datasheet retrieval when peripherals + datasheet_dir) ──
     let hardware_rag: Option<crate::rag::HardwareRag> = config
         .peripherals
@@ -924,8 +1759,11 @@ pub async fn run(
         bootstrap_max_chars,
     );
 
-    // Append structured tool-use instructions with schemas
-    system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    // Append structured tool-use instructions with schemas (text protocol only;
+    // native tool calling receives the specs with each request)
+    if !provider.supports_native_tools() {
+        system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    }
 
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
@@ -940,7 +1778,8 @@ pub async fn run(
         }
 
         // Inject memory + hardware RAG context into user message
-        let mem_context = build_context(mem.as_ref(), &msg).await;
+        let mem_context =
+            context_budget.fit_memory_context(build_context(mem.as_ref(), &msg).await);
         let rag_limit = if config.agent.compact_context { 2 } else { 5 };
         let hw_context = hardware_rag
             .as_ref()
@@ -967,6 +1806,13 @@ pub async fn run(
             model_name,
             temperature,
             false,
+            &capabilities,
+            config.agent.tool_parallelism(),
+            &turn_budget,
+            TurnHooks {
+                cost_tracker: cost_tracker.as_ref(),
+                ..TurnHooks::default()
+            },
         )
         .await?;
         println!("{response}");
@@ -1005,7 +1851,8 @@ pub async fn run(
             }
 
             // Inject memory + hardware RAG context into user message
-            let mem_context = build_context(mem.as_ref(), &msg.content).await;
+            let mem_context =
+                context_budget.fit_memory_context(build_context(mem.as_ref(), &msg.content).await);
             let rag_limit = if config.agent.compact_context { 2 } else { 5 };
             let hw_context = hardware_rag
                 .as_ref()
@@ -1029,6 +1876,13 @@ pub async fn run(
                 model_name,
                 temperature,
                 false,
+                &capabilities,
+                config.agent.tool_parallelism(),
+                &turn_budget,
+                TurnHooks {
+                    cost_tracker: cost_tracker.as_ref(),
+                    ..TurnHooks::default()
+                },
             )
             .await
             {
@@ -1043,7 +1897,8 @@ pub async fn run(
 
             // Auto-compaction before hard trimming to preserve long-context signal.
             if let Ok(compacted) =
-                auto_compact_history(&mut history, provider.as_ref(), model_name).await
+                auto_compact_history(&mut history, provider.as_ref(), model_name, &context_budget)
+                    .await
             {
                 if compacted {
                     println!("🧹 Auto-compaction complete");
@@ -1051,7 +1906,7 @@ pub async fn run(
             }
 
             // Hard cap as a safety net.
-            trim_history(&mut history);
+            trim_history(&mut history, &context_budget);
 
             if config.memory.auto_save {
                 let summary = truncate_with_ellipsis(&response, 100);
@@ -1120,14 +1975,19 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
     let model_name = config
         .default_model
         .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+        .unwrap_or_else(|| DEFAULT_MODEL.into());
+    let capabilities = Arc::new(CapabilityRegistry::from_config(&config));
     let provider: Box<dyn Provider> = providers::create_routed_provider(
         provider_name,
         config.api_key.as_deref(),
         &config.reliability,
         &config.model_routes,
         &model_name,
+        Arc::clone(&capabilities),
     )?;
+    let provider = providers::with_replay(provider, &config.replay, &config.workspace_dir)?;
+    let provider = providers::with_redaction(provider, &config, Arc::clone(&observer))?;
+    let context_budget = ContextBudget::for_model(&capabilities, &model_name);
 
     let hardware_rag: Option<crate::rag::HardwareRag> = config
         .peripherals
@@ -1205,9 +2065,11 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         // --- upstream: bootstrap compaction ---
         bootstrap_max_chars,
     );
-    system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    if !provider.supports_native_tools() {
+        system_prompt.push_str(&build_tool_instructions(&tools_registry));
+    }
 
-    let mem_context = build_context(mem.as_ref(), message).await;
+    let mem_context = context_budget.fit_memory_context(build_context(mem.as_ref(), message).await);
     let rag_limit = if config.agent.compact_context { 2 } else { 5 };
     let hw_context = hardware_rag
         .as_ref()
@@ -1225,6 +2087,8 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         ChatMessage::user(&enriched),
     ];
 
+    let cost_tracker = create_cost_tracker(&config);
+
     agent_turn(
         provider.as_ref(),
         &mut history,
@@ -1234,6 +2098,13 @@ pub async fn process_message(config: Config, message: &str) -> Result<String> {
         &model_name,
         config.default_temperature,
         true,
+        &capabilities,
+        config.agent.tool_parallelism(),
+        TurnBudgets::from_config(&config).for_channel("cli"),
+        TurnHooks {
+            cost_tracker: cost_tracker.as_ref(),
+            ..TurnHooks::default()
+        },
     )
     .await
 }
@@ -1420,7 +2291,7 @@ I will now call the tool with this payload:
         let original_len = history.len();
         assert!(original_len > MAX_HISTORY_MESSAGES + 1);
 
-        trim_history(&mut history);
+        trim_history(&mut history, &ContextBudget::default());
 
         // System prompt preserved
         assert_eq!(history[0].role, "system");
@@ -1439,7 +2310,7 @@ I will now call the tool with this payload:
             ChatMessage::user("hello"),
             ChatMessage::assistant("hi"),
         ];
-        trim_history(&mut history);
+        trim_history(&mut history, &ContextBudget::default());
         assert_eq!(history.len(), 3);
     }
 
@@ -1472,6 +2343,44 @@ I will now call the tool with this payload:
         assert!(history[3].content.contains("recent 2"));
     }
 
+    struct SummaryProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(r#"{"points": ["user prefers tea", "deploy to staging is pending"]}"#.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_compaction_renders_structured_summary_points() {
+        let mut history = vec![ChatMessage::system("sys")];
+        for i in 0..20 {
+            history.push(ChatMessage::user(format!("msg {i}")));
+        }
+        let budget = ContextBudget {
+            max_messages: 10,
+            ..ContextBudget::default()
+        };
+
+        let compacted = auto_compact_history(&mut history, &SummaryProvider, "m", &budget)
+            .await
+            .unwrap();
+
+        assert!(compacted);
+        assert_eq!(
+            history[1].content,
+            "[Compaction summary]\n- user prefers tea\n- deploy to staging is pending"
+        );
+        assert_eq!(history.last().unwrap().content, "msg 19");
+    }
+
     #[test]
     fn autosave_memory_key_has_prefix_and_uniqueness() {
         let key1 = autosave_memory_key("user_msg");
@@ -1503,6 +2412,43 @@ I will now call the tool with this payload:
         assert!(recalled.iter().any(|entry| entry.content.contains("45")));
     }
 
+    fn budget_tracker(tmp: &TempDir, downgrade_model: Option<&str>) -> CostTracker {
+        let config = crate::config::CostConfig {
+            enabled: true,
+            daily_limit_usd: 1.0,
+            warn_at_percent: 50,
+            downgrade_model: downgrade_model.map(str::to_string),
+            ..Default::default()
+        };
+        CostTracker::new(config, tmp.path()).unwrap()
+    }
+
+    #[test]
+    fn enforce_budget_refuses_when_limit_exceeded() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = budget_tracker(&tmp, None);
+        tracker
+            .record_usage(crate::cost::TokenUsage::new("m", 1_000_000, 0, 2.0, 0.0))
+            .unwrap();
+
+        let mut model = "m".to_string();
+        let err = enforce_budget(&tracker, 10, &mut model).unwrap_err();
+        assert!(err.to_string().contains("daily budget"));
+    }
+
+    #[test]
+    fn enforce_budget_downgrades_model_near_limit() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = budget_tracker(&tmp, Some("cheap-model"));
+        tracker
+            .record_usage(crate::cost::TokenUsage::new("m", 1_000_000, 0, 0.6, 0.0))
+            .unwrap();
+
+        let mut model = "m".to_string();
+        enforce_budget(&tracker, 10, &mut model).unwrap();
+        assert_eq!(model, "cheap-model");
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Recovery Tests - Tool Call Parsing Edge Cases
     // ═══════════════════════════════════════════════════════════════════════
@@ -1566,7 +2512,7 @@ Done."#;
         for i in 0..MAX_HISTORY_MESSAGES + 20 {
             history.push(ChatMessage::user(format!("msg {i}")));
         }
-        trim_history(&mut history);
+        trim_history(&mut history, &ContextBudget::default());
         assert_eq!(history.len(), MAX_HISTORY_MESSAGES);
     }
 
@@ -1578,19 +2524,206 @@ Done."#;
             history.push(ChatMessage::user(format!("user {i}")));
             history.push(ChatMessage::assistant(format!("assistant {i}")));
         }
-        trim_history(&mut history);
+        trim_history(&mut history, &ContextBudget::default());
         assert_eq!(history[0].role, "system");
         assert_eq!(history[history.len() - 1].role, "assistant");
     }
 
+    #[test]
+    fn trim_history_drops_orphaned_tool_results() {
+        let mut history = vec![
+            ChatMessage::system("system"),
+            ChatMessage::assistant(build_native_assistant_history(
+                "",
+                &[ToolCall {
+                    id: "call_1".into(),
+                    name: "shell".into(),
+                    arguments: "{}".into(),
+                }],
+                None,
+            )),
+            build_native_tool_result(Some("call_1"), "a"),
+            build_native_tool_result(Some("call_1"), "b"),
+        ];
+        for i in 0..MAX_HISTORY_MESSAGES - 1 {
+            history.push(ChatMessage::user(format!("user {i}")));
+        }
+
+        trim_history(&mut history, &ContextBudget::default());
+        assert_eq!(history[0].role, "system");
+        assert_eq!(history[1].role, "user");
+        assert!(history.iter().all(|m| m.role != "tool"));
+    }
+
+    #[test]
+    fn native_tool_history_uses_provider_format() {
+        let calls = vec![ToolCall {
+            id: "call_1".into(),
+            name: "shell".into(),
+            arguments: r#"{"command":"ls"}"#.into(),
+        }];
+        let assistant: serde_json::Value =
+            serde_json::from_str(&build_native_assistant_history("  ", &calls, None)).unwrap();
+        assert!(assistant["content"].is_null());
+        assert_eq!(assistant["tool_calls"][0]["id"], "call_1");
+        assert_eq!(assistant["tool_calls"][0]["name"], "shell");
+        assert!(assistant.get("reasoning").is_none());
+
+        let reasoning = Reasoning {
+            text: "Listing first.".into(),
+            blocks: vec![serde_json::json!({
+                "type": "thinking",
+                "thinking": "Listing first.",
+                "signature": "sig",
+            })],
+        };
+        let assistant: serde_json::Value = serde_json::from_str(&build_native_assistant_history(
+            "",
+            &calls,
+            Some(&reasoning),
+        ))
+        .unwrap();
+        assert_eq!(assistant["reasoning"][0]["signature"], "sig");
+
+        let result = build_native_tool_result(Some("call_1"), "done");
+        assert_eq!(result.role, "tool");
+        let value: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(value["tool_call_id"], "call_1");
+        assert_eq!(value["content"], "done");
+    }
+
     #[test]
     fn trim_history_with_only_system_prompt() {
         // Recovery: Only system prompt should not be trimmed
         let mut history = vec![ChatMessage::system("system prompt")];
-        trim_history(&mut history);
+        trim_history(&mut history, &ContextBudget::default());
         assert_eq!(history.len(), 1);
     }
 
+    fn budget(context_window: u64) -> ContextBudget {
+        ContextBudget::for_capabilities(
+            &ModelCapabilities {
+                context_window: Some(context_window),
+                ..ModelCapabilities::default()
+            },
+            Arc::new(HeuristicTokenizer::GENERIC),
+        )
+    }
+
+    #[test]
+    fn context_budget_scales_with_context_window() {
+        let unknown = ContextBudget::for_capabilities(
+            &ModelCapabilities::default(),
+            Arc::new(HeuristicTokenizer::GENERIC),
+        );
+        assert_eq!(unknown.max_tokens, MAX_HISTORY_TOKENS);
+        assert_eq!(unknown.max_messages, MAX_HISTORY_MESSAGES);
+
+        // 8K window: 2K reserved for the reply, 10% margin on the rest.
+        let small = budget(8_192);
+        assert_eq!(small.max_tokens, 5_526);
+        assert_eq!(small.max_messages, 11);
+
+        let large = ContextBudget::for_capabilities(
+            &ModelCapabilities {
+                context_window: Some(200_000),
+                max_output_tokens: Some(8_192),
+                ..ModelCapabilities::default()
+            },
+            Arc::new(HeuristicTokenizer::GENERIC),
+        );
+        assert!(large.max_tokens > MAX_HISTORY_TOKENS);
+        assert!(large.max_messages > MAX_HISTORY_MESSAGES);
+
+        let mut history = vec![ChatMessage::system("system prompt")];
+        for i in 0..30 {
+            history.push(ChatMessage::user(format!("msg {i}")));
+        }
+        trim_history(&mut history, &small);
+        assert_eq!(history.len(), 12);
+        assert_eq!(history[1].content, "msg 19");
+    }
+
+    #[test]
+    fn trim_history_by_tokens_counts_cjk_per_character() {
+        let budget = budget(8_192);
+        // 1,500 CJK characters is ~1,950 tokens but only 375 under chars/4.
+        let cjk = "中".repeat(1_500);
+        let english = "word ".repeat(400);
+        let mut history = vec![ChatMessage::system("system prompt")];
+        for _ in 0..3 {
+            history.push(ChatMessage::user(&cjk));
+            history.push(ChatMessage::assistant(&english));
+        }
+        assert!(budget.history_tokens(&history) > budget.max_tokens);
+
+        trim_history_by_tokens(&mut history, &budget);
+        assert_eq!(history.len(), 5);
+        assert!(budget.history_tokens(&history) <= budget.max_tokens);
+        assert_eq!(history[0].role, "system");
+    }
+
+    #[test]
+    fn reserved_tokens_shrink_the_history_budget() {
+        let mut budget = budget(8_192);
+        let full = budget.max_tokens;
+        budget.reserve(1_000);
+        assert_eq!(budget.max_tokens, full - 1_000);
+        assert_eq!(budget.reserved_tokens, 1_000);
+    }
+
+    #[test]
+    fn memory_context_is_capped_to_its_share() {
+        let budget = budget(8_192);
+        let mut context = String::from("[Memory context]\n");
+        for i in 0..50 {
+            let _ = writeln!(context, "- fact_{i}: {}", "detail ".repeat(20));
+        }
+        context.push('\n');
+
+        let fitted = budget.fit_memory_context(context.clone());
+        assert!(fitted.starts_with("[Memory context]\n- fact_0:"));
+        assert!(fitted.ends_with("\n\n"));
+        assert!(budget.count(&fitted) <= budget.max_tokens / MEMORY_CONTEXT_SHARE);
+
+        let short = "[Memory context]\n- name: Ada\n\n".to_string();
+        assert_eq!(budget.fit_memory_context(short.clone()), short);
+    }
+
+    #[test]
+    fn context_usage_separates_memory_from_history() {
+        let mut budget = budget(8_192);
+        budget.reserve(300);
+        let history = vec![
+            ChatMessage::system("You are helpful."),
+            ChatMessage::user("[Memory context]\n- name: Ada\n\nWhat is my name?"),
+            ChatMessage::assistant("Ada."),
+        ];
+        let tokens: Vec<usize> = history.iter().map(|m| budget.message_tokens(m)).collect();
+        let ObserverEvent::ContextUsage {
+            system_tokens,
+            memory_tokens,
+            tool_tokens,
+            history_tokens,
+            limit_tokens,
+            ..
+        } = budget.usage_event("model", &history, &tokens)
+        else {
+            panic!("expected a context usage event");
+        };
+        assert_eq!(system_tokens, budget.message_tokens(&history[0]));
+        assert_eq!(
+            memory_tokens,
+            budget.count("[Memory context]\n- name: Ada\n\n")
+        );
+        assert_eq!(tool_tokens, 300);
+        assert_eq!(
+            system_tokens + memory_tokens + history_tokens,
+            budget.history_tokens(&history)
+        );
+        assert_eq!(limit_tokens, 5_526);
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Recovery Tests - Arguments Parsing
     // ═══════════════════════════════════════════════════════════════════════
@@ -1710,4 +2843,395 @@ Done."#;
         let result = parse_tool_calls_from_json_value(&value);
         assert_eq!(result.len(), 2);
     }
+
+    #[test]
+    fn visible_stream_prefix_hides_tool_call_markup() {
+        assert_eq!(visible_stream_prefix("Checking now"), "Checking now");
+        assert_eq!(
+            visible_stream_prefix("Checking <tool_call>{\"name\":\"shell\"}"),
+            "Checking "
+        );
+        // A trailing partial tag is held back until it can be classified.
+        assert_eq!(visible_stream_prefix("Checking <tool_"), "Checking ");
+        assert_eq!(visible_stream_prefix("a < b"), "a < b");
+    }
+
+    struct SlowTool {
+        name: &'static str,
+        parallel: bool,
+        running: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "Sleep briefly and echo the tag argument"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": { "tag": { "type": "string" } }
+            })
+        }
+
+        fn parallel_safe(&self, _args: &serde_json::Value) -> bool {
+            self.parallel
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            use std::sync::atomic::Ordering;
+
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolResult {
+                success: true,
+                output: format!("tag-{}", args["tag"].as_str().unwrap_or_default()),
+                error: None,
+                image_base64: None,
+                image_mime: None,
+            })
+        }
+    }
+
+    struct ThreeCallProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ThreeCallProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("done".to_string())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            if messages
+                .iter()
+                .any(|msg| msg.content.contains("[Tool results]"))
+            {
+                return Ok("done".to_string());
+            }
+            Ok(r#"<tool_call>
+{"name":"fast_read","arguments":{"tag":"a"}}
+</tool_call>
+<tool_call>
+{"name":"fast_read","arguments":{"tag":"b"}}
+</tool_call>
+<tool_call>
+{"name":"slow_write","arguments":{"tag":"c"}}
+</tool_call>"#
+                .to_string())
+        }
+    }
+
+    async fn run_three_calls(max_parallel_tools: usize) -> (String, usize) {
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(SlowTool {
+                name: "fast_read",
+                parallel: true,
+                running: Arc::clone(&running),
+                peak: Arc::clone(&peak),
+            }),
+            Box::new(SlowTool {
+                name: "slow_write",
+                parallel: false,
+                running,
+                peak: Arc::clone(&peak),
+            }),
+        ];
+        let mut history = vec![ChatMessage::user("go")];
+
+        let reply = run_tool_call_loop(
+            &ThreeCallProvider,
+            &mut history,
+            &tools,
+            &crate::observability::NoopObserver,
+            "test",
+            "test-model",
+            0.0,
+            true,
+            &CapabilityRegistry::default(),
+            max_parallel_tools,
+            &TurnBudget::default(),
+            TurnHooks::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply, "done");
+
+        let results = history
+            .iter()
+            .find(|msg| msg.content.contains("[Tool results]"))
+            .map(|msg| msg.content.clone())
+            .unwrap();
+        (results, peak.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn parallel_safe_calls_run_together_and_keep_call_order() {
+        let (results, peak) = run_three_calls(4).await;
+        // The two reads overlap; the write is not parallel-safe and runs alone.
+        assert_eq!(peak, 2);
+        let a = results.find("tag-a").unwrap();
+        let b = results.find("tag-b").unwrap();
+        let c = results.find("tag-c").unwrap();
+        assert!(a < b && b < c);
+
+        let (_, peak) = run_three_calls(1).await;
+        assert_eq!(peak, 1);
+    }
+
+    struct RepeatingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for RepeatingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("done".to_string())
+        }
+
+        async fn chat_with_history(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(r#"<tool_call>
+{"name":"fast_read","arguments":{"tag":"same"}}
+</tool_call>"#
+                .to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_tool_calls_are_flagged_then_stop_the_turn() {
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SlowTool {
+            name: "fast_read",
+            parallel: true,
+            running: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            peak: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+        })];
+        let mut history = vec![ChatMessage::user("go")];
+
+        let reply = run_tool_call_loop(
+            &RepeatingProvider,
+            &mut history,
+            &tools,
+            &crate::observability::NoopObserver,
+            "test",
+            "test-model",
+            0.0,
+            true,
+            &CapabilityRegistry::default(),
+            1,
+            &TurnBudget::default(),
+            TurnHooks::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(reply.contains("I kept calling `fast_read`"), "{reply}");
+        // Three calls ran, the third with a warning; the fourth ended the turn.
+        let results: Vec<_> = history
+            .iter()
+            .filter(|msg| msg.content.contains("[Tool results]"))
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].content.contains("[Loop detected]"));
+    }
+
+    #[tokio::test]
+    async fn tool_call_budget_skips_extra_calls_then_stops() {
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(SlowTool {
+                name: "fast_read",
+                parallel: true,
+                running: Arc::clone(&running),
+                peak: Arc::clone(&peak),
+            }),
+            Box::new(SlowTool {
+                name: "slow_write",
+                parallel: false,
+                running,
+                peak,
+            }),
+        ];
+        let budget = TurnBudget {
+            max_tool_calls: Some(2),
+            ..TurnBudget::default()
+        };
+        let mut history = vec![ChatMessage::user("go")];
+
+        let reply = run_tool_call_loop(
+            &ThreeCallProvider,
+            &mut history,
+            &tools,
+            &crate::observability::NoopObserver,
+            "test",
+            "test-model",
+            0.0,
+            true,
+            &CapabilityRegistry::default(),
+            1,
+            &budget,
+            TurnHooks::default(),
+        )
+        .await
+        .unwrap();
+
+        // The provider answers once it sees results, so the turn completes.
+        assert_eq!(reply, "done");
+        let results = history
+            .iter()
+            .find(|msg| msg.content.contains("[Tool results]"))
+            .unwrap();
+        assert!(results.content.contains("tag-b"));
+        assert!(!results.content.contains("tag-c"));
+        assert!(results.content.contains("tool-call budget is used up"));
+    }
+
+    #[tokio::test]
+    async fn steering_messages_reach_the_model_with_tool_results() {
+        use crate::channels::turns::ActiveTurns;
+        use crate::config::TurnInterruptMode;
+
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SlowTool {
+            name: "fast_read",
+            parallel: true,
+            running: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            peak: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+        })];
+        let turns = Arc::new(ActiveTurns::new(TurnInterruptMode::Steer));
+        let turn = turns.begin("test_alice").await;
+        turns
+            .steer("test_alice", "actually use staging".into())
+            .unwrap();
+        let mut history = vec![ChatMessage::user("go")];
+
+        run_tool_call_loop(
+            &ThreeCallProvider,
+            &mut history,
+            &tools,
+            &crate::observability::NoopObserver,
+            "test",
+            "test-model",
+            0.0,
+            true,
+            &CapabilityRegistry::default(),
+            1,
+            &TurnBudget::default(),
+            TurnHooks {
+                steering: Some(turn.inbox()),
+                ..TurnHooks::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let results = history
+            .iter()
+            .find(|msg| msg.content.contains("[Tool results]"))
+            .unwrap();
+        assert!(results.content.contains("actually use staging"));
+        assert!(turn.finish().is_empty());
+    }
+
+    struct CountingProvider {
+        seen: std::sync::Mutex<Vec<usize>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for CountingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("done".to_string())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.seen.lock().unwrap().push(messages.len());
+            Ok("done".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn small_window_model_trims_the_request_not_the_history() {
+        let mut overrides = std::collections::HashMap::new();
+        overrides.insert(
+            "small-model".to_string(),
+            ModelCapabilities {
+                context_window: Some(2_000),
+                ..ModelCapabilities::default()
+            },
+        );
+        let capabilities = CapabilityRegistry::new(
+            overrides,
+            std::collections::HashMap::new(),
+            Vec::new(),
+            "test",
+        );
+        let provider = CountingProvider {
+            seen: std::sync::Mutex::new(Vec::new()),
+        };
+        let mut history = vec![ChatMessage::system("system prompt")];
+        for i in 0..30 {
+            history.push(ChatMessage::user(format!("msg {i}")));
+        }
+
+        run_tool_call_loop(
+            &provider,
+            &mut history,
+            &[],
+            &crate::observability::NoopObserver,
+            "test",
+            "small-model",
+            0.0,
+            true,
+            &capabilities,
+            1,
+            &TurnBudget::default(),
+            TurnHooks::default(),
+        )
+        .await
+        .unwrap();
+
+        // The routed model saw a trimmed request; the caller keeps everything.
+        assert_eq!(*provider.seen.lock().unwrap(), vec![11]);
+        assert_eq!(history.len(), 32);
+        assert_eq!(history[1].content, "msg 0");
+    }
 }
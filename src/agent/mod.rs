@@ -1,7 +1,9 @@
 #[allow(clippy::module_inception)]
 pub mod agent;
+pub mod budget;
 pub mod dispatcher;
 pub mod loop_;
+pub mod steering;
 // --- ZeroClaw fork: model routing ---
 pub mod routing;
 // --- upstream additions ---
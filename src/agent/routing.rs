@@ -1,267 +1,916 @@
-//! Automatic model routing based on task domain.
+//! Config-driven model routing.
 //!
-//! Classifies incoming messages as **technical** (coding, programming, DevOps,
-//! system administration) or **general** (conversation, Q&A, creative, casual).
+//! Each incoming message is checked against the `[[routing.rules]]` from
+//! config — keywords, regexes, attachment kinds, channel, sender, message
+//! length — and the first matching rule picks a `[[model_routes]]` hint.
+//! When no rule matches, an optional LLM or embedding classifier chooses among
+//! described routes before `default_hint` applies.
 //!
-//! Technical tasks → Claude Opus (primary model) — best-in-class for coding,
-//! tool use, agentic reasoning, and long-context code analysis.
+//! Without a `[routing]` section, [`builtin_routing`] keeps technical and
+//! tool-driven messages on the default model and sends the rest to
+//! `hint:fast`, provided a `fast` model route exists.
 //!
-//! General tasks → Gemini (fast model) — fast, cheap, great for conversation,
-//! Q&A, creative writing, summarization, and general knowledge.
+//! `zeroclaw route explain "<msg>"` prints the decision and the rule that fired.
+
+use crate::channels::traits::MediaType;
+use crate::config::schema::DEFAULT_MODEL;
+use crate::config::{
+    Config, ModelRouteConfig, RoutingClassifierConfig, RoutingClassifierKind, RoutingConfig,
+    RoutingRuleConfig,
+};
+use crate::cost::{CostTracker, UsageScope};
+use crate::memory::embeddings::{self, EmbeddingProvider};
+use crate::providers::capabilities::CapabilityRegistry;
+use crate::providers::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::providers::{self, ChatMessage, ChatRequest, ChatUsage, Provider, ResponseSchema};
+use anyhow::Context;
+use regex::Regex;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+/// Classifier answer meaning "none of the described routes".
+const DEFAULT_ROUTE: &str = "default";
+
+/// Hint the built-in rules send general chat to.
+const BUILTIN_FAST_HINT: &str = "fast";
+
+/// Short replies to a tool-approval prompt the primary model just issued.
+const APPROVAL_REPLY_PATTERN: &str = r"(?i)^\s*(yes|y|no|n|ok|okay|approved?|denied|deny|go ahead|go for it|do it|cancel|stop|abort|nope|yes, go ahead|yes please|yes, approved|no, don't|no, cancel)\s*$";
+
+/// Code blocks, file paths and source-file extensions.
+const CODE_PATTERNS: &[&str] = &[
+    "```|` ",
+    r"~/|/usr/|/etc/|/var/|/tmp/|C:\\",
+    r"\.(rs|py|ts|js|go|sol|toml|yaml|yml|json|env|sh|tf)",
+    r"(?i)(\(\)|\{\}|\[\]).*\b(fn|def|func|class|const|let|var) |\b(fn|def|func|class|const|let|var) .*(\(\)|\{\}|\[\])",
+];
+
+/// Error messages and stack traces.
+const ERROR_KEYWORDS: &[&str] = &[
+    "error:",
+    "traceback",
+    "panic at",
+    "stack trace",
+    "segfault",
+    "exception",
+    "compile error",
+    "build failed",
+    "syntax error",
+    "type error",
+];
+
+/// Apps, desktop actions and memory requests that need tool use.
+const TOOL_KEYWORDS: &[&str] = &[
+    "kakaotalk",
+    "kakao talk",
+    "카카오톡",
+    "카톡",
+    "imessage",
+    "messages app",
+    "메시지",
+    "telegram",
+    "텔레그램",
+    "discord",
+    "디스코드",
+    "slack",
+    "슬랙",
+    "mail app",
+    "mail.app",
+    "safari",
+    "chrome",
+    "brave",
+    "firefox",
+    "finder",
+    "terminal",
+    "system preferences",
+    "system settings",
+    "notes app",
+    "reminders",
+    "spotify",
+    "music app",
+    "zoom",
+    "facetime",
+    "send a message",
+    "send the message",
+    "send message",
+    "send a mail",
+    "send the mail",
+    "send an email",
+    "send email",
+    "send it to",
+    "send this to",
+    "send to my",
+    "take a screenshot",
+    "take screenshot",
+    "capture screen",
+    "click on",
+    "click the",
+    "right click",
+    "double click",
+    "type in",
+    "type into",
+    "check my email",
+    "check email",
+    "check my mail",
+    "read my email",
+    "play music",
+    "play song",
+    "play the ",
+    "메일 보내",
+    "메시지 보내",
+    "문자 보내",
+    "앱 열어",
+    "앱 실행",
+    "스크린샷",
+    "open the ",
+    "open a ",
+    "open my ",
+    "launch the ",
+    "launch a ",
+    "launch my ",
+    "close the ",
+    "close a ",
+    "close my ",
+    "quit the ",
+    "quit a ",
+    "quit my ",
+    "remember this",
+    "memorize",
+    "recall what",
+    "기억해",
+    "저장해",
+];
+
+/// Shell commands and programming actions.
+const SHELL_KEYWORDS: &[&str] = &[
+    "ssh ",
+    "scp ",
+    "curl ",
+    "wget ",
+    "docker ",
+    "kubectl ",
+    "helm ",
+    "terraform ",
+    "cargo ",
+    "rustc ",
+    "npm ",
+    "pnpm ",
+    "yarn ",
+    "pip ",
+    "pip3 ",
+    "python3 ",
+    "node ",
+    "git ",
+    "make ",
+    "cmake ",
+    "gcc ",
+    "g++ ",
+    "chmod ",
+    "chown ",
+    "sudo ",
+    "systemctl ",
+    "launchctl ",
+    "brew ",
+    "debug ",
+    "deploy ",
+    "compile ",
+    "refactor ",
+    "implement ",
+    "migrate ",
+    "rebase ",
+    "commit ",
+    "push ",
+    "merge ",
+    "fix the bug",
+    "fix this bug",
+    "fix the error",
+    "fix this error",
+    "fix the code",
+    "fix this code",
+    "read the file",
+    "edit the file",
+    "open the file",
+    "create a file",
+    "create the file",
+    "delete the file",
+    "list the files",
+    "check the logs",
+    "run the test",
+    "run tests",
+    "run cargo",
+    "run npm",
+    "build the",
+    "build this",
+];
+
+/// Programming, infrastructure and security vocabulary.
+const TECH_KEYWORDS: &[&str] = &[
+    "function",
+    "variable",
+    "class ",
+    "struct ",
+    "enum ",
+    "interface ",
+    "trait ",
+    "generic",
+    "async ",
+    "await ",
+    "callback",
+    "closure",
+    "iterator",
+    "pointer",
+    "reference",
+    "mutex",
+    "semaphore",
+    "thread",
+    "goroutine",
+    "coroutine",
+    "architecture",
+    "microservice",
+    "monolith",
+    "api endpoint",
+    "middleware",
+    "load balancer",
+    "reverse proxy",
+    "database",
+    "schema",
+    "migration",
+    "query",
+    "index ",
+    "cache",
+    "redis",
+    "postgres",
+    "mysql",
+    "mongodb",
+    "kubernetes",
+    "k8s",
+    "docker",
+    "container",
+    "terraform",
+    "ansible",
+    "helm",
+    "argocd",
+    "ci/cd",
+    "cicd",
+    "pipeline",
+    "github actions",
+    "aws ",
+    "gcp ",
+    "azure ",
+    "vulnerability",
+    "authentication",
+    "authorization",
+    "ssl",
+    "tls",
+    "certificate",
+    "firewall",
+    "race condition",
+    "deadlock",
+    "memory leak",
+    "buffer overflow",
+    "injection",
+    "refactor",
+    "optimize",
+    "performance",
+    "benchmark",
+    "test coverage",
+    "unit test",
+    "integration test",
+    "linter",
+    "clippy",
+    "eslint",
+    "prettier",
+    "react",
+    "nextjs",
+    "next.js",
+    "vue",
+    "angular",
+    "express",
+    "fastapi",
+    "django",
+    "flask",
+    "rust ",
+    "golang",
+    "typescript",
+    "solidity",
+    "smart contract",
+    "blockchain",
+    "web3",
+    "oracle",
+    "scribe",
+    "chronicle",
+    "코드",
+    "프로그",
+    "개발",
+    "コード",
+    "プログラム",
+    "代码",
+    "程序",
+    "编程",
+];
+
+/// Routing used when config has no `[routing]` section: the technical/general
+/// split zeroclaw always shipped. Empty unless `model_routes` has a `fast` hint.
+pub fn builtin_routing(model_routes: &[ModelRouteConfig]) -> RoutingConfig {
+    if !model_routes
+        .iter()
+        .any(|route| route.hint == BUILTIN_FAST_HINT)
+    {
+        return RoutingConfig::default();
+    }
+    let strings = |items: &[&str]| items.iter().map(|s| (*s).to_string()).collect();
+    let rule = |name: &str| RoutingRuleConfig {
+        name: Some(name.into()),
+        ..RoutingRuleConfig::default()
+    };
+    RoutingConfig {
+        rules: vec![
+            RoutingRuleConfig {
+                patterns: vec![APPROVAL_REPLY_PATTERN.into()],
+                follow_up: Some(true),
+                ..rule("approval reply")
+            },
+            RoutingRuleConfig {
+                patterns: strings(CODE_PATTERNS),
+                ..rule("code")
+            },
+            RoutingRuleConfig {
+                keywords: strings(ERROR_KEYWORDS),
+                ..rule("errors")
+            },
+            RoutingRuleConfig {
+                keywords: strings(TOOL_KEYWORDS),
+                ..rule("tool use")
+            },
+            RoutingRuleConfig {
+                keywords: strings(SHELL_KEYWORDS),
+                ..rule("shell")
+            },
+            RoutingRuleConfig {
+                keywords: strings(TECH_KEYWORDS),
+                ..rule("technical")
+            },
+        ],
+        default_hint: Some(BUILTIN_FAST_HINT.into()),
+        classifier: None,
+    }
+}
 
-/// Decide which model hint to use for the given user message.
-///
-/// Returns `Some("hint:fast")` for general/non-technical tasks (→ Gemini),
-/// or `None` for technical tasks (→ Claude Opus, the default primary model).
-///
-/// Each message is classified independently so that switching between general
-/// chat and technical work within the same conversation routes to the right
-/// model. The shared conversation history provides context continuity
-/// regardless of which model handles a particular turn.
-///
-/// **Exception**: Short approval/denial responses ("yes", "ok", "go ahead")
-/// in active conversations always go to the primary model, since they may
-/// be answering a tool-approval prompt from Claude.
-pub fn select_model_hint(message: &str, has_prior_exchange: bool) -> Option<&'static str> {
-    let lower = message.to_ascii_lowercase();
-
-    // Short approval/denial responses in active conversations stay on primary
-    // model — they're likely answering a tool-approval or action-confirmation
-    // prompt that Claude issued. Without conversation tracking we can't know
-    // which model asked, so we play it safe.
-    if has_prior_exchange && is_approval_response(&lower) {
-        return None;
+/// The message attributes routing rules can match on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouteInput<'a> {
+    pub message: &'a str,
+    pub channel: Option<&'a str>,
+    pub sender: Option<&'a str>,
+    pub attachments: &'a [MediaType],
+    /// Whether the conversation already has an earlier exchange.
+    pub follow_up: bool,
+}
+
+/// Why a route was chosen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteReason {
+    /// A configured rule matched; `matched` lists the conditions that fired.
+    Rule { name: String, matched: Vec<String> },
+    /// The fallback classifier picked the route.
+    Classifier {
+        kind: RoutingClassifierKind,
+        detail: String,
+    },
+    /// Nothing claimed the message.
+    Default,
+}
+
+impl fmt::Display for RouteReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rule { name, matched } if matched.is_empty() => {
+                write!(f, "rule \"{name}\" (no conditions)")
+            }
+            Self::Rule { name, matched } => write!(f, "rule \"{name}\" ({})", matched.join(", ")),
+            Self::Classifier { kind, detail } => {
+                let kind = match kind {
+                    RoutingClassifierKind::Llm => "llm",
+                    RoutingClassifierKind::Embedding => "embedding",
+                };
+                write!(f, "{kind} classifier ({detail})")
+            }
+            Self::Default => write!(f, "no rule matched"),
+        }
     }
+}
 
-    // Technical / coding / programming / DevOps → Claude Opus
-    if is_technical_task(message) {
-        return None;
+/// Outcome of routing one message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteDecision {
+    /// Route hint to use; `None` keeps the default model.
+    pub hint: Option<String>,
+    pub reason: RouteReason,
+}
+
+impl RouteDecision {
+    /// Model string to send to the provider: `hint:<name>` or `default_model`.
+    pub fn model(&self, default_model: &str) -> String {
+        self.hint
+            .as_ref()
+            .map_or_else(|| default_model.to_string(), |hint| format!("hint:{hint}"))
     }
+}
 
-    // Everything else → Gemini (general conversation, Q&A, creative, etc.)
-    Some("hint:fast")
+/// Parse a media type name as used in config and on the command line
+/// (`photo`, `voice`, `video_note`, ...).
+pub fn parse_media_type(name: &str) -> Option<MediaType> {
+    let normalized = name.trim().to_ascii_lowercase().replace(['_', '-'], "");
+    Some(match normalized.as_str() {
+        "photo" => MediaType::Photo,
+        "document" => MediaType::Document,
+        "video" => MediaType::Video,
+        "audio" => MediaType::Audio,
+        "voice" => MediaType::Voice,
+        "videonote" => MediaType::VideoNote,
+        "animation" => MediaType::Animation,
+        "sticker" => MediaType::Sticker,
+        "location" => MediaType::Location,
+        "contact" => MediaType::Contact,
+        "poll" => MediaType::Poll,
+        "venue" => MediaType::Venue,
+        _ => return None,
+    })
 }
 
-/// Returns `true` if the message is about coding, programming, technical
-/// operations, or requires tool use / system interaction.
-///
-/// This is intentionally strict: only clearly technical messages go to
-/// Claude Opus. General-purpose "write", "create", "summarize" requests
-/// that aren't about code go to Gemini since it handles them well and
-/// is much cheaper.
-fn is_technical_task(message: &str) -> bool {
-    let lower = message.to_ascii_lowercase();
-
-    // ── Direct code indicators ──
-
-    // Code blocks or inline code
-    if message.contains("```") || message.contains("` ") {
-        return true;
-    }
-
-    // File paths
-    if message.contains("~/") || message.contains("/usr/") || message.contains("/etc/")
-        || message.contains("/var/") || message.contains("/tmp/")
-        || message.contains("C:\\") || message.contains(".rs")
-        || message.contains(".py") || message.contains(".ts")
-        || message.contains(".js") || message.contains(".go")
-        || message.contains(".sol") || message.contains(".toml")
-        || message.contains(".yaml") || message.contains(".yml")
-        || message.contains(".json") || message.contains(".env")
-        || message.contains(".sh") || message.contains(".tf")
-    {
-        return true;
+fn attachment_matches(kind: &str, media: &MediaType) -> bool {
+    match kind {
+        "any" => true,
+        "image" => media.is_image(),
+        "file" => media.is_file(),
+        other => parse_media_type(other).as_ref() == Some(media),
     }
+}
 
-    // Error messages / stack traces
-    if lower.contains("error:") || lower.contains("traceback")
-        || lower.contains("panic at") || lower.contains("stack trace")
-        || lower.contains("segfault") || lower.contains("exception")
-        || lower.contains("compile error") || lower.contains("build failed")
-        || lower.contains("syntax error") || lower.contains("type error")
-    {
-        return true;
-    }
-
-    // ── macOS app / system interaction (requires tool use) ──
-    // Any request to interact with applications needs Claude for proper tool use.
-    // App names are safe (no false positives). Action verbs use multi-word
-    // phrases to avoid matching common English ("send me a summary", "open question").
-    const APP_NAMES: &[&str] = &[
-        "kakaotalk", "kakao talk", "카카오톡", "카톡",
-        "imessage", "messages app", "메시지",
-        "telegram", "텔레그램",
-        "discord", "디스코드",
-        "slack", "슬랙",
-        "mail app", "mail.app",
-        "safari", "chrome", "brave", "firefox",
-        "finder", "terminal",
-        "system preferences", "system settings",
-        "notes app", "reminders",
-        "spotify", "music app",
-        "zoom", "facetime",
-    ];
-    for app in APP_NAMES {
-        if lower.contains(app) {
-            return true;
+struct Rule {
+    name: String,
+    hint: Option<String>,
+    keywords: Vec<String>,
+    patterns: Vec<Regex>,
+    attachments: Vec<String>,
+    channels: Vec<String>,
+    senders: Vec<String>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    follow_up: Option<bool>,
+}
+
+impl Rule {
+    fn compile(index: usize, config: &RoutingRuleConfig) -> anyhow::Result<Self> {
+        let name = config
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", index + 1));
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| {
+                    format!("Invalid pattern {pattern:?} in routing rule \"{name}\"")
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let attachments: Vec<String> = config
+            .attachments
+            .iter()
+            .map(|kind| kind.trim().to_ascii_lowercase())
+            .collect();
+        if let Some(kind) = attachments.iter().find(|kind| {
+            !matches!(kind.as_str(), "any" | "image" | "file") && parse_media_type(kind).is_none()
+        }) {
+            anyhow::bail!("Unknown attachment kind {kind:?} in routing rule \"{name}\"");
         }
+        Ok(Self {
+            hint: config.hint.clone(),
+            keywords: config
+                .keywords
+                .iter()
+                .filter(|k| !k.is_empty())
+                .map(|k| k.to_lowercase())
+                .collect(),
+            patterns,
+            attachments,
+            channels: config.channels.clone(),
+            senders: config.senders.clone(),
+            min_length: config.min_length,
+            max_length: config.max_length,
+            follow_up: config.follow_up,
+            name,
+        })
     }
-    // Action phrases that imply system interaction (multi-word to avoid false positives)
-    const SYSTEM_ACTIONS: &[&str] = &[
-        "send a message", "send the message", "send message",
-        "send a mail", "send the mail", "send an email", "send email",
-        "send it to", "send this to", "send to my",
-        "take a screenshot", "take screenshot", "capture screen",
-        "click on", "click the", "right click", "double click",
-        "type in", "type into",
-        "check my email", "check email", "check my mail", "read my email",
-        "play music", "play song", "play the ",
-        "메일 보내", "메시지 보내", "문자 보내",
-        "앱 열어", "앱 실행", "스크린샷",
-    ];
-    for action in SYSTEM_ACTIONS {
-        if lower.contains(action) {
-            return true;
+
+    /// Conditions that fired, or `None` when any configured condition fails.
+    fn matches(&self, input: &RouteInput<'_>) -> Option<Vec<String>> {
+        let mut matched = Vec::new();
+        if !self.keywords.is_empty() {
+            let lower = input.message.to_lowercase();
+            let keyword = self.keywords.iter().find(|k| lower.contains(k.as_str()))?;
+            matched.push(format!("keyword {keyword:?}"));
         }
+        if !self.patterns.is_empty() {
+            let pattern = self.patterns.iter().find(|p| p.is_match(input.message))?;
+            matched.push(format!("pattern {:?}", pattern.as_str()));
+        }
+        if !self.attachments.is_empty() {
+            let kind = self.attachments.iter().find(|kind| {
+                input
+                    .attachments
+                    .iter()
+                    .any(|media| attachment_matches(kind, media))
+            })?;
+            matched.push(format!("attachment {kind:?}"));
+        }
+        if !self.channels.is_empty() {
+            let channel = input
+                .channel
+                .filter(|c| self.channels.iter().any(|x| x.eq_ignore_ascii_case(c)))?;
+            matched.push(format!("channel {channel:?}"));
+        }
+        if !self.senders.is_empty() {
+            let sender = input
+                .sender
+                .filter(|s| self.senders.iter().any(|x| x == s))?;
+            matched.push(format!("sender {sender:?}"));
+        }
+        let length = input.message.chars().count();
+        if let Some(min) = self.min_length {
+            if length < min {
+                return None;
+            }
+            matched.push(format!("length {length} >= {min}"));
+        }
+        if let Some(max) = self.max_length {
+            if length > max {
+                return None;
+            }
+            matched.push(format!("length {length} <= {max}"));
+        }
+        if let Some(follow_up) = self.follow_up {
+            if input.follow_up != follow_up {
+                return None;
+            }
+            matched.push(
+                if follow_up {
+                    "follow-up"
+                } else {
+                    "opening message"
+                }
+                .to_string(),
+            );
+        }
+        Some(matched)
     }
+}
 
-    // Desktop app interaction verbs — "open/launch/close/quit" followed by anything
-    // implies tool use (computer tool). Match the verb prefix generously since
-    // the object can be any app name ("open the elgato stream deck application").
-    const APP_VERBS: &[&str] = &[
-        "open the ", "open a ", "open my ",
-        "launch the ", "launch a ", "launch my ",
-        "close the ", "close a ", "close my ",
-        "quit the ", "quit a ", "quit my ",
-    ];
-    for verb in APP_VERBS {
-        if lower.contains(verb) {
-            return true;
+struct Classifier {
+    config: RoutingClassifierConfig,
+    embedder: Option<Box<dyn EmbeddingProvider>>,
+    route_vectors: tokio::sync::OnceCell<Vec<(String, Vec<f32>)>>,
+}
+
+impl Classifier {
+    /// Hint picked by the classifier plus a short explanation; `None` when
+    /// no described route fits.
+    async fn classify(
+        &self,
+        message: &str,
+        provider: &dyn Provider,
+        default_model: &str,
+        cost_tracker: Option<&CostTracker>,
+    ) -> anyhow::Result<Option<(String, String)>> {
+        match self.config.kind {
+            RoutingClassifierKind::Llm => {
+                self.classify_llm(message, provider, default_model, cost_tracker)
+                    .await
+            }
+            RoutingClassifierKind::Embedding => self.classify_embedding(message).await,
         }
     }
-    // Memory operations need tool use → Claude
-    if lower.contains("remember this") || lower.contains("memorize")
-        || lower.contains("recall what") || lower.contains("기억해")
-        || lower.contains("저장해")
-    {
-        return true;
-    }
-
-    // ── Shell / DevOps commands ──
-    const SHELL_SIGNALS: &[&str] = &[
-        "ssh ", "scp ", "curl ", "wget ", "docker ",
-        "kubectl ", "helm ", "terraform ",
-        "cargo ", "rustc ", "npm ", "pnpm ", "yarn ",
-        "pip ", "pip3 ", "python3 ", "node ",
-        "git ", "make ", "cmake ", "gcc ", "g++ ",
-        "chmod ", "chown ", "sudo ", "systemctl ",
-        "launchctl ", "brew ",
-    ];
-    for cmd in SHELL_SIGNALS {
-        if lower.contains(cmd) {
-            return true;
+
+    async fn classify_llm(
+        &self,
+        message: &str,
+        provider: &dyn Provider,
+        default_model: &str,
+        cost_tracker: Option<&CostTracker>,
+    ) -> anyhow::Result<Option<(String, String)>> {
+        let mut catalogue = String::new();
+        let mut names = Vec::with_capacity(self.config.routes.len() + 1);
+        for (hint, description) in &self.config.routes {
+            let _ = writeln!(catalogue, "- {hint}: {description}");
+            names.push(hint.as_str());
         }
+        let _ = writeln!(catalogue, "- {DEFAULT_ROUTE}: anything else");
+        names.push(DEFAULT_ROUTE);
+
+        let messages = [
+            ChatMessage::system(format!(
+                "Choose the route best suited to handle the user's message. \
+                 Do not answer the message itself.\n\nRoutes:\n{catalogue}"
+            )),
+            ChatMessage::user(message),
+        ];
+        let schema = ResponseSchema::new(
+            "route",
+            serde_json::json!({
+                "type": "object",
+                "properties": { "route": { "type": "string", "enum": names } },
+                "required": ["route"],
+                "additionalProperties": false
+            }),
+        )
+        .with_description("Route chosen for the message");
+        let model = self.config.model.as_deref().unwrap_or(default_model);
+        let value = provider
+            .chat_structured(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                &schema,
+                model,
+                0.0,
+            )
+            .await?;
+        if let Some(tracker) = cost_tracker {
+            // `chat_structured` reports no usage, so estimate it.
+            let tokenizer = HeuristicTokenizer::for_model(model);
+            let input: usize = messages.iter().map(|m| tokenizer.count(&m.content)).sum();
+            let usage = ChatUsage {
+                input_tokens: (input + tokenizer.count(&schema.schema.to_string())) as u64,
+                output_tokens: tokenizer.count(&value.to_string()) as u64,
+                ..ChatUsage::default()
+            };
+            if let Err(e) = tracker.record_chat_usage(model, &usage) {
+                tracing::warn!("Failed to record routing classifier usage: {e}");
+            }
+        }
+        let route = value["route"].as_str().unwrap_or(DEFAULT_ROUTE);
+        Ok((route != DEFAULT_ROUTE)
+            .then(|| (route.to_string(), format!("{model} chose {route:?}"))))
     }
 
-    // ── Programming / technical action verbs ──
-    // These specifically relate to code/system operations, NOT general actions
-    const TECH_ACTIONS: &[&str] = &[
-        "debug ", "deploy ", "compile ", "refactor ",
-        "implement ", "migrate ", "rebase ",
-        "commit ", "push ", "merge ",
-        "fix the bug", "fix this bug", "fix the error", "fix this error",
-        "fix the code", "fix this code",
-        "read the file", "edit the file", "open the file",
-        "create a file", "create the file", "delete the file",
-        "list the files", "check the logs",
-        "run the test", "run tests", "run cargo", "run npm",
-        "build the", "build this",
-    ];
-    for action in TECH_ACTIONS {
-        if lower.contains(action) {
-            return true;
-        }
+    async fn classify_embedding(&self, message: &str) -> anyhow::Result<Option<(String, String)>> {
+        let embedder = self
+            .embedder
+            .as_deref()
+            .context("Embedding classifier has no embedding provider")?;
+        let route_vectors = self
+            .route_vectors
+            .get_or_try_init(|| async {
+                let descriptions: Vec<&str> =
+                    self.config.routes.values().map(String::as_str).collect();
+                let vectors = embedder.embed(&descriptions).await?;
+                anyhow::Ok(self.config.routes.keys().cloned().zip(vectors).collect())
+            })
+            .await?;
+        let query = embedder.embed_one(message).await?;
+
+        let best = route_vectors
+            .iter()
+            .map(|(hint, vector)| {
+                (
+                    hint,
+                    crate::memory::vector::cosine_similarity(&query, vector),
+                )
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        Ok(best
+            .filter(|(_, score)| *score >= self.config.min_similarity)
+            .map(|(hint, score)| (hint.clone(), format!("similarity {score:.2}"))))
     }
+}
 
-    // ── Technical domain keywords ──
-    const TECH_KEYWORDS: &[&str] = &[
-        // Programming concepts
-        "function", "variable", "class ", "struct ", "enum ",
-        "interface ", "trait ", "generic", "async ", "await ",
-        "callback", "closure", "iterator", "pointer", "reference",
-        "mutex", "semaphore", "thread", "goroutine", "coroutine",
-        // Architecture & systems
-        "architecture", "microservice", "monolith", "api endpoint",
-        "middleware", "load balancer", "reverse proxy",
-        "database", "schema", "migration", "query", "index ",
-        "cache", "redis", "postgres", "mysql", "mongodb",
-        // DevOps & infrastructure
-        "kubernetes", "k8s", "docker", "container",
-        "terraform", "ansible", "helm", "argocd",
-        "ci/cd", "cicd", "pipeline", "github actions",
-        "aws ", "gcp ", "azure ",
-        // Security & networking
-        "vulnerability", "authentication", "authorization",
-        "ssl", "tls", "certificate", "firewall",
-        "race condition", "deadlock", "memory leak",
-        "buffer overflow", "injection",
-        // Code quality
-        "refactor", "optimize", "performance", "benchmark",
-        "test coverage", "unit test", "integration test",
-        "linter", "clippy", "eslint", "prettier",
-        // Specific technologies
-        "react", "nextjs", "next.js", "vue", "angular",
-        "express", "fastapi", "django", "flask",
-        "rust ", "golang", "typescript", "solidity",
-        "smart contract", "blockchain", "web3",
-        "oracle", "scribe", "chronicle",
-    ];
-    for keyword in TECH_KEYWORDS {
-        if lower.contains(keyword) {
-            return true;
+/// Routes incoming messages to model hints according to `[routing]` config.
+///
+/// The default router has no rules and always keeps the default model.
+#[derive(Default)]
+pub struct ModelRouter {
+    rules: Vec<Rule>,
+    default_hint: Option<String>,
+    classifier: Option<Classifier>,
+}
+
+impl ModelRouter {
+    /// Compile `routing`, checking every hint against `model_routes`.
+    /// `embedder` is required by the embedding classifier only.
+    pub fn new(
+        routing: &RoutingConfig,
+        model_routes: &[ModelRouteConfig],
+        embedder: Option<Box<dyn EmbeddingProvider>>,
+    ) -> anyhow::Result<Self> {
+        let check_hint = |hint: &str, owner: &str| {
+            if model_routes.iter().any(|route| route.hint == hint) {
+                Ok(())
+            } else {
+                anyhow::bail!(
+                    "{owner} targets unknown hint \"{hint}\"; add a [[model_routes]] entry with hint = \"{hint}\""
+                )
+            }
+        };
+
+        let rules = routing
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| Rule::compile(index, rule))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for rule in &rules {
+            if let Some(hint) = &rule.hint {
+                check_hint(hint, &format!("Routing rule \"{}\"", rule.name))?;
+            }
+        }
+        if let Some(hint) = &routing.default_hint {
+            check_hint(hint, "[routing] default_hint")?;
         }
+
+        let classifier = match &routing.classifier {
+            Some(config) => {
+                for hint in config.routes.keys() {
+                    check_hint(hint, "[routing.classifier]")?;
+                }
+                if config.kind == RoutingClassifierKind::Embedding && embedder.is_none() {
+                    anyhow::bail!("The embedding routing classifier needs an embedding provider");
+                }
+                Some(Classifier {
+                    config: config.clone(),
+                    embedder,
+                    route_vectors: tokio::sync::OnceCell::new(),
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            rules,
+            default_hint: routing.default_hint.clone(),
+            classifier,
+        })
     }
 
-    // ── Code-like patterns ──
+    /// Build the router from `config`; the embedding classifier reuses the
+    /// `[memory]` embedding provider. Without a `[routing]` section the
+    /// [`builtin_routing`] rules apply.
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let routing = config
+            .routing
+            .clone()
+            .unwrap_or_else(|| builtin_routing(&config.model_routes));
+        let needs_embedder = routing
+            .classifier
+            .as_ref()
+            .is_some_and(|c| c.kind == RoutingClassifierKind::Embedding);
+        let embedder = if needs_embedder {
+            if config.memory.embedding_provider == "none" {
+                anyhow::bail!(
+                    "The embedding routing classifier needs [memory] embedding_provider to be set"
+                );
+            }
+            Some(embeddings::create_embedding_provider(
+                &config.memory.embedding_provider,
+                config.api_key.as_deref(),
+                &config.memory.embedding_model,
+                config.memory.embedding_dimensions,
+            ))
+        } else {
+            None
+        };
+        Self::new(&routing, &config.model_routes, embedder)
+    }
 
-    // Contains typical code symbols in combination
-    if (lower.contains("()") || lower.contains("{}") || lower.contains("[]"))
-        && (lower.contains("fn ") || lower.contains("def ") || lower.contains("func ")
-            || lower.contains("class ") || lower.contains("const ")
-            || lower.contains("let ") || lower.contains("var "))
-    {
-        return true;
+    /// Whether a classifier is configured as the fallback.
+    pub fn has_classifier(&self) -> bool {
+        self.classifier.is_some()
     }
 
-    // CJK technical action words
-    if lower.contains("코드") || lower.contains("프로그") || lower.contains("개발")  // Korean: code, program, develop
-        || lower.contains("コード") || lower.contains("プログラム") // Japanese: code, program
-        || lower.contains("代码") || lower.contains("程序") || lower.contains("编程") // Chinese: code, program, programming
-    {
-        return true;
+    /// First matching rule, if any. Never touches the network.
+    pub fn match_rules(&self, input: &RouteInput<'_>) -> Option<RouteDecision> {
+        self.rules.iter().find_map(|rule| {
+            rule.matches(input).map(|matched| RouteDecision {
+                hint: rule.hint.clone(),
+                reason: RouteReason::Rule {
+                    name: rule.name.clone(),
+                    matched,
+                },
+            })
+        })
+    }
+
+    /// Route `input`: rules first, then the classifier, then `default_hint`.
+    /// Classifier failures are logged and fall through to the default. LLM
+    /// classifier calls are charged to `cost_tracker`.
+    pub async fn route(
+        &self,
+        input: &RouteInput<'_>,
+        provider: &dyn Provider,
+        default_model: &str,
+        cost_tracker: Option<&CostTracker>,
+    ) -> RouteDecision {
+        if let Some(decision) = self.match_rules(input) {
+            return decision;
+        }
+        if let Some(classifier) = &self.classifier {
+            match classifier
+                .classify(input.message, provider, default_model, cost_tracker)
+                .await
+            {
+                Ok(Some((hint, detail))) => {
+                    return RouteDecision {
+                        hint: Some(hint),
+                        reason: RouteReason::Classifier {
+                            kind: classifier.config.kind,
+                            detail,
+                        },
+                    };
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Routing classifier failed: {e}"),
+            }
+        }
+        self.fallback()
     }
 
-    false
+    fn fallback(&self) -> RouteDecision {
+        RouteDecision {
+            hint: self.default_hint.clone(),
+            reason: RouteReason::Default,
+        }
+    }
 }
 
-/// Check if the message is an approval/denial response to a permission request.
-fn is_approval_response(lower: &str) -> bool {
-    let trimmed = lower.trim();
-    matches!(
-        trimmed,
-        "yes" | "y" | "no" | "n" | "ok" | "okay"
-            | "approved" | "approve" | "denied" | "deny"
-            | "go ahead" | "go for it" | "do it"
-            | "cancel" | "stop" | "abort" | "nope"
-            | "yes, go ahead" | "yes please" | "yes, approved"
-            | "no, don't" | "no, cancel"
-    )
+/// `zeroclaw route explain`: show which rule (or classifier) routes `input`
+/// and the model it resolves to. The classifier only runs with `classify`,
+/// since it calls the provider.
+pub async fn explain(
+    config: &Config,
+    input: &RouteInput<'_>,
+    classify: bool,
+) -> anyhow::Result<()> {
+    let router = ModelRouter::from_config(config)?;
+    let default_model = config.default_model.as_deref().unwrap_or(DEFAULT_MODEL);
+
+    let decision = match router.match_rules(input) {
+        Some(decision) => decision,
+        None if classify && router.has_classifier() => {
+            let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+            let provider = providers::with_replay(
+                providers::create_routed_provider(
+                    provider_name,
+                    config.api_key.as_deref(),
+                    &config.reliability,
+                    &config.model_routes,
+                    default_model,
+                    Arc::new(CapabilityRegistry::from_config(config)),
+                )?,
+                &config.replay,
+                &config.workspace_dir,
+            )?;
+            let observer = Arc::from(crate::observability::create_observer(&config.observability));
+            let provider = providers::with_redaction(provider, config, observer)?;
+            let cost_tracker = crate::agent::loop_::create_cost_tracker(config)
+                .map(|t| t.scoped(UsageScope::channel("cli", "local")));
+            router
+                .route(
+                    input,
+                    provider.as_ref(),
+                    default_model,
+                    cost_tracker.as_ref(),
+                )
+                .await
+        }
+        None => router.fallback(),
+    };
+
+    println!("Rule:   {}", decision.reason);
+    match &decision.hint {
+        Some(hint) => {
+            let target = config
+                .model_routes
+                .iter()
+                .find(|route| &route.hint == hint)
+                .map(|route| format!("{} via {}", route.model, route.provider))
+                .unwrap_or_default();
+            println!("Route:  hint:{hint} → {target}");
+        }
+        None => println!("Route:  default model → {default_model}"),
+    }
+    if decision.reason == RouteReason::Default && router.has_classifier() && !classify {
+        println!("Note:   the fallback classifier was not run; pass --classify to include it");
+    }
+    Ok(())
 }
 
 /// Extract a short subject from conversation messages.
 ///
 /// Looks at the first user message and truncates to ~100 chars at a sentence
-/// or word boundary. Used to label persistent conversations.
+/// or word boundary. Used to label persistent conversations. This runs on
+/// every saved turn, so it stays local rather than asking a model.
 pub fn extract_subject(messages: &[crate::providers::ChatMessage]) -> Option<String> {
     const MAX_SUBJECT_LEN: usize = 100;
 
@@ -329,292 +978,313 @@ pub fn extract_subject(messages: &[crate::providers::ChatMessage]) -> Option<Str
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    // ── General tasks → hint:fast (Gemini) ──
-
-    #[test]
-    fn general_greeting() {
-        assert_eq!(select_model_hint("hello", false), Some("hint:fast"));
-        assert_eq!(select_model_hint("hi there", false), Some("hint:fast"));
-        assert_eq!(select_model_hint("good morning", false), Some("hint:fast"));
-    }
-
-    #[test]
-    fn general_factual_question() {
-        assert_eq!(
-            select_model_hint("what is the capital of France?", false),
-            Some("hint:fast")
-        );
-        assert_eq!(
-            select_model_hint("who invented the telephone?", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_casual_chat() {
-        assert_eq!(
-            select_model_hint("how are you?", false),
-            Some("hint:fast")
-        );
-        assert_eq!(
-            select_model_hint("thanks!", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_creative_writing() {
-        // "write me a poem" is creative, NOT technical → Gemini
-        assert_eq!(
-            select_model_hint("write me a haiku about autumn", false),
-            Some("hint:fast")
-        );
-        assert_eq!(
-            select_model_hint("tell me a joke", false),
-            Some("hint:fast")
-        );
-        assert_eq!(
-            select_model_hint("write a short story about a cat", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_summarization() {
-        assert_eq!(
-            select_model_hint("summarize the French Revolution", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_translation() {
-        assert_eq!(
-            select_model_hint("translate hello world to Korean", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_recommendation() {
-        assert_eq!(
-            select_model_hint("recommend a good book about history", false),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_long_non_technical() {
-        // Long but clearly non-technical → Gemini
-        assert_eq!(
-            select_model_hint(
-                "I'm planning a trip to Japan next month and wondering about the best places to visit in Tokyo. What are some must-see attractions?",
-                false
-            ),
-            Some("hint:fast")
-        );
-    }
-
-    #[test]
-    fn general_empty_message() {
-        assert_eq!(select_model_hint("", false), Some("hint:fast"));
-    }
-
-    // ── Technical tasks → None (Claude Opus) ──
-
-    #[test]
-    fn technical_code_block() {
-        assert_eq!(
-            select_model_hint("fix this code:\n```rust\nfn main() { panic!() }\n```", false),
-            None
-        );
-    }
-
-    #[test]
-    fn technical_file_path() {
-        assert_eq!(
-            select_model_hint("check ~/Development/zeroclaw/src/main.rs", false),
-            None
-        );
+    use async_trait::async_trait;
+
+    fn route(hint: &str) -> ModelRouteConfig {
+        ModelRouteConfig {
+            hint: hint.into(),
+            provider: "openrouter".into(),
+            model: format!("{hint}-model"),
+            api_key: None,
+            thinking_budget: None,
+            reasoning_effort: None,
+        }
     }
 
-    #[test]
-    fn technical_error_message() {
-        assert_eq!(
-            select_model_hint("I got this error: thread 'main' panicked at 'index out of bounds'", false),
-            None
-        );
+    fn router(routing: &RoutingConfig) -> ModelRouter {
+        ModelRouter::new(routing, &[route("fast"), route("vision")], None).unwrap()
     }
 
-    #[test]
-    fn technical_shell_command() {
-        assert_eq!(
-            select_model_hint("run cargo test to check if everything passes", false),
-            None
-        );
+    fn input(message: &str) -> RouteInput<'_> {
+        RouteInput {
+            message,
+            ..RouteInput::default()
+        }
     }
 
-    #[test]
-    fn technical_deployment() {
-        assert_eq!(
-            select_model_hint("deploy the latest build to the staging kubernetes cluster", false),
-            None
-        );
+    fn sample_rules() -> RoutingConfig {
+        RoutingConfig {
+            rules: vec![
+                RoutingRuleConfig {
+                    name: Some("approvals".into()),
+                    keywords: vec!["yes".into(), "go ahead".into()],
+                    max_length: Some(12),
+                    follow_up: Some(true),
+                    ..RoutingRuleConfig::default()
+                },
+                RoutingRuleConfig {
+                    name: Some("code".into()),
+                    keywords: vec!["cargo ".into(), "Docker".into()],
+                    patterns: vec![r"```|\.(rs|py|toml)\b".into()],
+                    ..RoutingRuleConfig::default()
+                },
+                RoutingRuleConfig {
+                    name: Some("photos".into()),
+                    hint: Some("vision".into()),
+                    attachments: vec!["image".into()],
+                    ..RoutingRuleConfig::default()
+                },
+                RoutingRuleConfig {
+                    name: Some("telegram chatter".into()),
+                    hint: Some("fast".into()),
+                    channels: vec!["telegram".into()],
+                    max_length: Some(40),
+                    ..RoutingRuleConfig::default()
+                },
+            ],
+            default_hint: Some("fast".into()),
+            classifier: None,
+        }
     }
 
     #[test]
-    fn technical_debugging() {
+    fn first_matching_rule_wins() {
+        let router = router(&sample_rules());
+        let decision = router
+            .match_rules(&input("please edit main.rs and run cargo test"))
+            .unwrap();
+        assert_eq!(decision.hint, None);
+        assert_eq!(decision.model("primary"), "primary");
         assert_eq!(
-            select_model_hint("debug why the API endpoint returns 500", false),
-            None
+            decision.reason.to_string(),
+            r#"rule "code" (keyword "cargo ", pattern "```|\\.(rs|py|toml)\\b")"#
         );
     }
 
     #[test]
-    fn technical_file_operations() {
-        assert_eq!(
-            select_model_hint("create a file called utils.rs with helper functions", false),
-            None
+    fn every_condition_in_a_rule_must_match() {
+        let router = router(&sample_rules());
+        // Keyword alone is not enough: the code rule also needs a pattern.
+        assert!(router
+            .match_rules(&input("set up docker compose"))
+            .is_none());
+        // Approval rule needs a follow-up.
+        assert!(router.match_rules(&input("yes")).is_none());
+        let follow_up = RouteInput {
+            follow_up: true,
+            ..input("yes")
+        };
+        let decision = router.match_rules(&follow_up).unwrap();
+        assert!(
+            matches!(decision.reason, RouteReason::Rule { ref name, .. } if name == "approvals")
         );
     }
 
     #[test]
-    fn technical_docker() {
-        assert_eq!(
-            select_model_hint("set up docker compose for the project", false),
-            None
-        );
-    }
+    fn attachment_channel_and_length_conditions() {
+        let router = router(&sample_rules());
+        let photos = [MediaType::Photo];
+        let decision = router
+            .match_rules(&RouteInput {
+                attachments: &photos,
+                ..input("what is this?")
+            })
+            .unwrap();
+        assert_eq!(decision.model("primary"), "hint:vision");
 
-    #[test]
-    fn technical_git_operations() {
+        let short = RouteInput {
+            channel: Some("telegram"),
+            ..input("hi there")
+        };
         assert_eq!(
-            select_model_hint("commit and push these changes", false),
-            None
+            router.match_rules(&short).unwrap().hint.as_deref(),
+            Some("fast")
         );
+        let long = RouteInput {
+            channel: Some("telegram"),
+            ..input("tell me everything you know about the history of Rome")
+        };
+        assert!(router.match_rules(&long).is_none());
     }
 
-    #[test]
-    fn technical_code_concept() {
+    #[tokio::test]
+    async fn unmatched_messages_use_default_hint() {
+        let router = router(&sample_rules());
+        let decision = router
+            .route(&input("good morning"), &NoProvider, "primary", None)
+            .await;
+        assert_eq!(decision.reason, RouteReason::Default);
+        assert_eq!(decision.model("primary"), "hint:fast");
         assert_eq!(
-            select_model_hint("explain how async functions work in Rust", false),
-            None
+            ModelRouter::default()
+                .route(&input("good morning"), &NoProvider, "primary", None)
+                .await
+                .model("primary"),
+            "primary"
         );
     }
 
     #[test]
-    fn technical_database() {
-        assert_eq!(
-            select_model_hint("how do I add an index to the users table?", false),
-            None
-        );
+    fn invalid_config_is_rejected() {
+        let unknown_hint = RoutingConfig {
+            default_hint: Some("missing".into()),
+            ..RoutingConfig::default()
+        };
+        let err = ModelRouter::new(&unknown_hint, &[route("fast")], None)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("unknown hint \"missing\""));
+
+        let bad_pattern = RoutingConfig {
+            rules: vec![RoutingRuleConfig {
+                patterns: vec!["(".into()],
+                ..RoutingRuleConfig::default()
+            }],
+            ..RoutingConfig::default()
+        };
+        let err = ModelRouter::new(&bad_pattern, &[], None).err().unwrap();
+        assert!(err.to_string().contains("routing rule \"#1\""));
+
+        let bad_attachment = RoutingConfig {
+            rules: vec![RoutingRuleConfig {
+                attachments: vec!["hologram".into()],
+                ..RoutingRuleConfig::default()
+            }],
+            ..RoutingConfig::default()
+        };
+        assert!(ModelRouter::new(&bad_attachment, &[], None).is_err());
     }
 
     #[test]
-    fn technical_security() {
-        assert_eq!(
-            select_model_hint("check for vulnerability in the auth module", false),
-            None
-        );
-    }
+    fn builtin_rules_split_technical_and_general_messages() {
+        assert!(builtin_routing(&[route("vision")]).rules.is_empty());
+
+        let router = router(&builtin_routing(&[route("fast")]));
+        let model = |input: &RouteInput<'_>| {
+            router
+                .match_rules(input)
+                .map_or_else(|| "hint:fast".to_string(), |d| d.model("primary"))
+        };
+        for message in [
+            "fix this code:\n```rust\nfn main() {}\n```",
+            "check ~/Development/zeroclaw/src/main.rs",
+            "I got this error: index out of bounds",
+            "run cargo test to check everything",
+            "open the elgato stream deck application",
+            "explain how async functions work in Rust",
+            "코드 리뷰 해줘",
+        ] {
+            assert_eq!(model(&input(message)), "primary", "{message}");
+        }
+        for message in ["hello", "what is the capital of France?", "tell me a joke"] {
+            assert_eq!(model(&input(message)), "hint:fast", "{message}");
+        }
 
-    #[test]
-    fn technical_file_extension() {
-        assert_eq!(
-            select_model_hint("read config.toml and update the port", false),
-            None
-        );
+        let reply = RouteInput {
+            follow_up: true,
+            ..input("Go ahead")
+        };
+        assert_eq!(router.match_rules(&reply).unwrap().hint, None);
+        assert!(router.match_rules(&input("go ahead")).is_none());
     }
 
     #[test]
-    fn technical_cjk_code() {
-        // Korean: "코드를 수정해주세요" = "Please fix the code"
-        assert_eq!(
-            select_model_hint("코드를 수정해주세요", false),
-            None
-        );
+    fn parses_media_type_names() {
+        assert_eq!(parse_media_type("Voice"), Some(MediaType::Voice));
+        assert_eq!(parse_media_type("video_note"), Some(MediaType::VideoNote));
+        assert_eq!(parse_media_type("hologram"), None);
     }
 
-    // ── Approval responses ──
-
-    #[test]
-    fn approval_in_conversation_stays_primary() {
-        // In active conversation, approval goes to Claude (may be tool approval)
-        assert_eq!(select_model_hint("yes", true), None);
-        assert_eq!(select_model_hint("go ahead", true), None);
-        assert_eq!(select_model_hint("cancel", true), None);
+    struct NoProvider;
+
+    #[async_trait]
+    impl Provider for NoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("unexpected provider call")
+        }
     }
 
-    #[test]
-    fn approval_first_message_goes_to_gemini() {
-        // First message "yes" with no context → just a word, route to Gemini
-        assert_eq!(select_model_hint("yes", false), Some("hint:fast"));
-        assert_eq!(select_model_hint("ok", false), Some("hint:fast"));
+    struct ClassifyingProvider(&'static str);
+
+    #[async_trait]
+    impl Provider for ClassifyingProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            _message: &str,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            assert_eq!(model, "hint:fast");
+            assert!(system_prompt.unwrap().contains("- vision: pictures"));
+            Ok(format!(r#"{{"route": "{}"}}"#, self.0))
+        }
     }
 
-    // ── Follow-up routing (per-message, not locked) ──
-
-    #[test]
-    fn followup_general_goes_to_gemini() {
-        // In active conversation, general questions still go to Gemini
-        assert_eq!(
-            select_model_hint("what's the weather like?", true),
-            Some("hint:fast")
-        );
-        assert_eq!(
-            select_model_hint("tell me about cats", true),
-            Some("hint:fast")
-        );
+    fn llm_classifier_routing() -> RoutingConfig {
+        RoutingConfig {
+            classifier: Some(RoutingClassifierConfig {
+                kind: RoutingClassifierKind::Llm,
+                routes: [("vision".to_string(), "pictures and diagrams".to_string())]
+                    .into_iter()
+                    .collect(),
+                model: Some("hint:fast".into()),
+                min_similarity: 0.3,
+            }),
+            ..RoutingConfig::default()
+        }
     }
 
-    #[test]
-    fn followup_technical_goes_to_claude() {
-        // In active conversation, technical still goes to Claude
-        assert_eq!(
-            select_model_hint("now deploy it to kubernetes", true),
-            None
-        );
-        assert_eq!(
-            select_model_hint("fix the bug in main.rs", true),
-            None
-        );
+    #[tokio::test]
+    async fn llm_classifier_runs_when_no_rule_matches() {
+        let router = router(&llm_classifier_routing());
+        let decision = router
+            .route(
+                &input("draw me a diagram"),
+                &ClassifyingProvider("vision"),
+                "primary",
+                None,
+            )
+            .await;
+        assert_eq!(decision.hint.as_deref(), Some("vision"));
+        assert!(decision.reason.to_string().starts_with("llm classifier"));
+
+        let decision = router
+            .route(
+                &input("hello"),
+                &ClassifyingProvider("default"),
+                "primary",
+                None,
+            )
+            .await;
+        assert_eq!(decision.reason, RouteReason::Default);
     }
 
-    // ── App interaction → Claude (requires computer tool) ──
-
-    #[test]
-    fn technical_open_app_by_name() {
-        // "open the <app name>" must route to Claude — requires computer tool
-        assert_eq!(
-            select_model_hint("Open the elgato stream deck application", false),
-            None
-        );
-        assert_eq!(
-            select_model_hint("open the settings", false),
-            None
-        );
-        assert_eq!(
-            select_model_hint("launch the terminal", false),
-            None
-        );
-        assert_eq!(
-            select_model_hint("close the browser", false),
-            None
-        );
-        assert_eq!(
-            select_model_hint("quit the music app", false),
-            None
-        );
+    #[tokio::test]
+    async fn llm_classifier_usage_is_recorded() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cost = crate::config::CostConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let tracker = CostTracker::new(cost, tmp.path()).unwrap();
+        router(&llm_classifier_routing())
+            .route(
+                &input("draw me a diagram"),
+                &ClassifyingProvider("vision"),
+                "primary",
+                Some(&tracker),
+            )
+            .await;
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.request_count, 1);
+        assert!(summary.by_model.contains_key("hint:fast"));
+        assert!(summary.total_tokens > 0);
     }
 
-    #[test]
-    fn general_open_question_not_misrouted() {
-        // "open" as adjective/noun should NOT trigger app detection
-        assert_eq!(
-            select_model_hint("what are some open problems in physics?", false),
-            Some("hint:fast")
-        );
+    #[tokio::test]
+    async fn classifier_failure_falls_back_to_default() {
+        let router = router(&llm_classifier_routing());
+        let decision = router
+            .route(&input("hello"), &NoProvider, "primary", None)
+            .await;
+        assert_eq!(decision, router.fallback());
     }
 
     // ── Subject extraction ──
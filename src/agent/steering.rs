@@ -0,0 +1,42 @@
+//! Messages for a turn that is already running.
+//!
+//! The channel layer queues follow-ups in a [`SteeringInbox`] while a turn is
+//! in flight; the tool loop drains it between steps.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Messages sent to a running turn, read by the tool loop between steps.
+#[derive(Debug, Clone)]
+pub struct SteeringInbox(Arc<Mutex<Option<Vec<String>>>>);
+
+impl SteeringInbox {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(Some(Vec::new()))))
+    }
+
+    /// Queue a message for the turn; hands it back once the turn is over.
+    pub fn push(&self, message: String) -> Result<(), String> {
+        match self.0.lock().as_mut() {
+            Some(queue) => {
+                queue.push(message);
+                Ok(())
+            }
+            None => Err(message),
+        }
+    }
+
+    /// Take every message queued so far.
+    pub fn drain(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Stop accepting messages and return those the turn never read.
+    pub(crate) fn close(&self) -> Vec<String> {
+        self.0.lock().take().unwrap_or_default()
+    }
+}
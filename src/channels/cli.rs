@@ -46,7 +46,7 @@ impl Channel for CliChannel {
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
                     .as_secs(),
-                attachments: vec![],
+                ..Default::default()
             };
 
             if tx.send(msg).await.is_err() {
@@ -94,7 +94,7 @@ mod tests {
             content: "hello".into(),
             channel: "cli".into(),
             timestamp: 1_234_567_890,
-            attachments: vec![],
+            ..Default::default()
         };
         assert_eq!(msg.id, "test-id");
         assert_eq!(msg.sender, "user");
@@ -111,7 +111,7 @@ mod tests {
             content: "c".into(),
             channel: "ch".into(),
             timestamp: 0,
-            attachments: vec![],
+            ..Default::default()
         };
         let cloned = msg.clone();
         assert_eq!(cloned.id, msg.id);
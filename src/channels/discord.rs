@@ -1,4 +1,5 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Button, Channel, ChannelMessage, MessageKind};
+use crate::util::truncate_with_ellipsis;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
@@ -39,6 +40,128 @@ impl DiscordChannel {
         self.allowed_users.iter().any(|u| u == "*" || u == user_id)
     }
 
+    /// Post a single message and return its ID.
+    async fn post_message(&self, channel_id: &str, content: &str) -> anyhow::Result<String> {
+        let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&json!({ "content": content }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord send message failed ({status}): {err}");
+        }
+
+        let data: serde_json::Value = resp.json().await?;
+        data.get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Discord send message response missing message id"))
+    }
+
+    /// Replace the content of a message previously sent by the bot.
+    async fn edit_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        let url =
+            format!("https://discord.com/api/v10/channels/{channel_id}/messages/{message_id}");
+        let resp = self
+            .client
+            .patch(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&json!({ "content": content }))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord edit message failed ({status}): {err}");
+        }
+
+        Ok(())
+    }
+
+    /// Turn a button press (a component interaction) into a callback
+    /// message. Returns `None` for other interactions, users outside the
+    /// allowlist and guilds other than the configured one.
+    fn parse_interaction(&self, d: &serde_json::Value) -> Option<ChannelMessage> {
+        // Type 3 is MESSAGE_COMPONENT
+        if d.get("type").and_then(serde_json::Value::as_u64) != Some(3) {
+            return None;
+        }
+        let user_id = d
+            .pointer("/member/user/id")
+            .or_else(|| d.pointer("/user/id"))
+            .and_then(serde_json::Value::as_str)?;
+        if !self.is_user_allowed(user_id) {
+            tracing::warn!("Discord: ignoring button press from unauthorized user: {user_id}");
+            return None;
+        }
+        if let (Some(gid), Some(g)) = (
+            &self.guild_id,
+            d.get("guild_id").and_then(serde_json::Value::as_str),
+        ) {
+            if g != gid {
+                return None;
+            }
+        }
+
+        let custom_id = d
+            .pointer("/data/custom_id")
+            .and_then(serde_json::Value::as_str)
+            .filter(|id| !id.is_empty())?;
+        let channel_id = d.get("channel_id").and_then(serde_json::Value::as_str)?;
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: channel_id.to_string(),
+            content: custom_id.to_string(),
+            channel: "discord".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            kind: MessageKind::Callback,
+            ..Default::default()
+        })
+    }
+
+    /// Acknowledge an interaction without changing the message, so Discord
+    /// does not report it as failed.
+    async fn ack_interaction(&self, d: &serde_json::Value) {
+        let (Some(id), Some(token)) = (
+            d.get("id").and_then(serde_json::Value::as_str),
+            d.get("token").and_then(serde_json::Value::as_str),
+        ) else {
+            return;
+        };
+        let url = format!("https://discord.com/api/v10/interactions/{id}/{token}/callback");
+        // Type 6 is DEFERRED_UPDATE_MESSAGE
+        if let Err(e) = self
+            .client
+            .post(&url)
+            .json(&json!({ "type": 6 }))
+            .send()
+            .await
+        {
+            tracing::debug!("Discord interaction ack failed: {e}");
+        }
+    }
+
     fn bot_user_id_from_token(token: &str) -> Option<String> {
         // Discord bot tokens are base64(bot_user_id).timestamp.hmac
         let part = token.split('.').next()?;
@@ -52,6 +175,9 @@ const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstu
 ///
 /// Discord rejects longer payloads with `50035 Invalid Form Body`.
 const DISCORD_MAX_MESSAGE_LENGTH: usize = 2000;
+/// Reasoning shown ahead of a reply is cut to this many characters so the
+/// spoiler is not split across messages.
+const DISCORD_MAX_REASONING_CHARS: usize = 1000;
 
 /// Split a message into chunks that respect Discord's 2000-character limit.
 /// Tries to split at word boundaries when possible.
@@ -101,6 +227,32 @@ fn split_message_for_discord(message: &str) -> Vec<String> {
     chunks
 }
 
+/// Action rows of up to five buttons each; Discord allows five rows. The
+/// first button is styled as primary, the rest as secondary.
+fn message_components(buttons: &[Button]) -> serde_json::Value {
+    let rows: Vec<serde_json::Value> = buttons
+        .chunks(5)
+        .take(5)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let components: Vec<serde_json::Value> = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    json!({
+                        "type": 2,
+                        "style": if row == 0 && i == 0 { 1 } else { 2 },
+                        "label": truncate_with_ellipsis(&b.label, 77),
+                        "custom_id": b.callback_id,
+                    })
+                })
+                .collect();
+            json!({ "type": 1, "components": components })
+        })
+        .collect();
+    json!(rows)
+}
+
 /// Minimal base64 decode (no extra dep) — only needs to decode the user ID portion
 #[allow(clippy::cast_possible_truncation)]
 fn base64_decode(input: &str) -> Option<String> {
@@ -301,8 +453,23 @@ impl Channel for DiscordChannel {
                         _ => {}
                     }
 
-                    // Only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
                     let event_type = event.get("t").and_then(|t| t.as_str()).unwrap_or("");
+
+                    // Button presses arrive as INTERACTION_CREATE
+                    if event_type == "INTERACTION_CREATE" {
+                        let Some(d) = event.get("d") else {
+                            continue;
+                        };
+                        self.ack_interaction(d).await;
+                        if let Some(channel_msg) = self.parse_interaction(d) {
+                            if tx.send(channel_msg).await.is_err() {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Otherwise only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
                     if event_type != "MESSAGE_CREATE" {
                         continue;
                     }
@@ -355,7 +522,7 @@ impl Channel for DiscordChannel {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_secs(),
-                        attachments: vec![],
+                        ..Default::default()
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -412,6 +579,87 @@ impl Channel for DiscordChannel {
         }
         Ok(())
     }
+
+    fn format_reasoning(&self, reasoning: &str) -> Option<String> {
+        Some(super::formatting::reasoning_to_discord(
+            reasoning,
+            DISCORD_MAX_REASONING_CHARS,
+        ))
+    }
+
+    fn supports_draft_updates(&self) -> bool {
+        true
+    }
+
+    async fn send_draft(&self, message: &str, channel_id: &str) -> anyhow::Result<String> {
+        let content = truncate_with_ellipsis(message, DISCORD_MAX_MESSAGE_LENGTH - 3);
+        self.post_message(channel_id, &content).await
+    }
+
+    async fn update_draft(
+        &self,
+        message: &str,
+        channel_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<()> {
+        let content = truncate_with_ellipsis(message, DISCORD_MAX_MESSAGE_LENGTH - 3);
+        self.edit_message(channel_id, message_id, &content).await
+    }
+
+    async fn finalize_draft(
+        &self,
+        message: &str,
+        channel_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<()> {
+        let formatted = super::formatting::markdown_to_discord(message);
+        let chunks = split_message_for_discord(&formatted);
+        let Some((first, rest)) = chunks.split_first() else {
+            return Ok(());
+        };
+
+        self.edit_message(channel_id, message_id, first).await?;
+        for chunk in rest {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            self.post_message(channel_id, chunk).await?;
+        }
+        Ok(())
+    }
+
+    fn supports_buttons(&self) -> bool {
+        true
+    }
+
+    async fn send_interactive(
+        &self,
+        message: &str,
+        buttons: &[Button],
+        channel_id: &str,
+    ) -> anyhow::Result<()> {
+        let formatted = super::formatting::markdown_to_discord(message);
+        let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
+        let body = json!({
+            "content": truncate_with_ellipsis(&formatted, DISCORD_MAX_MESSAGE_LENGTH - 3),
+            "components": message_components(buttons),
+        });
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord send message with buttons failed ({status}): {err}");
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -424,6 +672,39 @@ mod tests {
         assert_eq!(ch.name(), "discord");
     }
 
+    #[test]
+    fn discord_component_interaction_becomes_callback_message() {
+        let ch = DiscordChannel::new("fake".into(), Some("g1".into()), vec!["111".into()], false);
+        let d = json!({
+            "id": "i1",
+            "token": "tok",
+            "type": 3,
+            "guild_id": "g1",
+            "channel_id": "c1",
+            "member": { "user": { "id": "111" } },
+            "data": { "custom_id": "deny ab12cd", "component_type": 2 }
+        });
+        let msg = ch.parse_interaction(&d).unwrap();
+        assert_eq!(msg.kind, MessageKind::Callback);
+        assert_eq!(msg.sender, "c1");
+        assert_eq!(msg.content, "deny ab12cd");
+
+        let mut other_guild = d.clone();
+        other_guild["guild_id"] = json!("g2");
+        assert!(ch.parse_interaction(&other_guild).is_none());
+        let mut stranger = d.clone();
+        stranger["member"] = json!({ "user": { "id": "222" } });
+        assert!(ch.parse_interaction(&stranger).is_none());
+        let mut command = d;
+        command["type"] = json!(2);
+        assert!(ch.parse_interaction(&command).is_none());
+
+        let rows = message_components(&[Button::new("Approve", "a"), Button::new("Deny", "d")]);
+        assert_eq!(rows[0]["components"][0]["style"], 1);
+        assert_eq!(rows[0]["components"][1]["style"], 2);
+        assert_eq!(rows[0]["components"][1]["custom_id"], "d");
+    }
+
     #[test]
     fn base64_decode_bot_id() {
         // "MTIzNDU2" decodes to "123456"
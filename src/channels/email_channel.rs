@@ -470,7 +470,7 @@ impl Channel for EmailChannel {
                             content,
                             channel: "email".to_string(),
                             timestamp: ts,
-                            attachments: vec![],
+                            ..Default::default()
                         };
                         if tx.send(msg).await.is_err() {
                             return Ok(());
@@ -143,6 +143,76 @@ pub fn markdown_to_discord(input: &str) -> String {
     result
 }
 
+/// Render model reasoning as a Telegram expandable blockquote, which shows
+/// collapsed until tapped. Long reasoning is cut to `max_chars`.
+pub fn reasoning_to_telegram_html(reasoning: &str, max_chars: usize) -> String {
+    let text = crate::util::truncate_with_ellipsis(reasoning.trim(), max_chars);
+    format!(
+        "<blockquote expandable>💭 <i>Reasoning</i>\n{}</blockquote>",
+        escape_html(&text)
+    )
+}
+
+/// Render model reasoning as a Discord spoiler, hidden until clicked. Long
+/// reasoning is cut to `max_chars`.
+pub fn reasoning_to_discord(reasoning: &str, max_chars: usize) -> String {
+    let text = crate::util::truncate_with_ellipsis(reasoning.trim(), max_chars);
+    // A `||` inside the text would end the spoiler early.
+    format!("-# 💭 Reasoning\n||{}||", text.replace("||", "| |"))
+}
+
+/// Telegram tags that `telegram_html_to_plain` removes. Anything else that
+/// looks like a tag is left as written.
+const TELEGRAM_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "code",
+    "del",
+    "em",
+    "i",
+    "ins",
+    "pre",
+    "s",
+    "span",
+    "strike",
+    "strong",
+    "tg-spoiler",
+    "u",
+];
+
+/// Turn Telegram HTML back into readable text, for when Telegram rejects the
+/// markup and the message is resent without `parse_mode`.
+pub fn telegram_html_to_plain(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let tag_len = tail.find('>').map(|end| end + 1);
+        let is_telegram_tag = tag_len.is_some_and(|len| {
+            let name = tail[1..len - 1]
+                .trim_start_matches('/')
+                .split(|c: char| c.is_whitespace())
+                .next()
+                .unwrap_or_default();
+            TELEGRAM_TAGS.contains(&name)
+        });
+        match tag_len {
+            Some(len) if is_telegram_tag => rest = &tail[len..],
+            _ => {
+                out.push('<');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
 /// Escape HTML entities in text content.
 fn escape_html(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
@@ -599,4 +669,34 @@ mod tests {
         assert!(output.contains("<pre>block2</pre>"));
         assert!(output.contains("text"));
     }
+
+    // ── Reasoning ───────────────────────────────────────────────
+
+    #[test]
+    fn telegram_reasoning_is_escaped_and_expandable() {
+        assert_eq!(
+            reasoning_to_telegram_html("  x < 5, so <b>no</b>  ", 100),
+            "<blockquote expandable>💭 <i>Reasoning</i>\nx &lt; 5, so &lt;b&gt;no&lt;/b&gt;</blockquote>"
+        );
+        let long = reasoning_to_telegram_html(&"a".repeat(50), 10);
+        assert!(long.contains("aaaaaaaaaa...</blockquote>"));
+    }
+
+    #[test]
+    fn telegram_plain_fallback_strips_markup() {
+        let html = reasoning_to_telegram_html("x < 5", 100);
+        assert_eq!(telegram_html_to_plain(&html), "💭 Reasoning\nx < 5");
+        assert_eq!(
+            telegram_html_to_plain("<b>a</b> <a href=\"https://x.y\">link</a> <3 <div>"),
+            "a link <3 <div>"
+        );
+    }
+
+    #[test]
+    fn discord_reasoning_stays_inside_spoiler() {
+        assert_eq!(
+            reasoning_to_discord("a || b", 100),
+            "-# 💭 Reasoning\n||a | | b||"
+        );
+    }
 }
@@ -178,7 +178,7 @@ end tell"#
                                 .duration_since(std::time::UNIX_EPOCH)
                                 .unwrap_or_default()
                                 .as_secs(),
-                            attachments: vec![],
+                            ..Default::default()
                         };
 
                         if tx.send(msg).await.is_err() {
@@ -561,7 +561,7 @@ impl Channel for IrcChannel {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_secs(),
-                        attachments: vec![],
+                        ..Default::default()
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -1,4 +1,4 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Button, Channel, ChannelMessage, MessageKind};
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -6,6 +6,31 @@ use uuid::Uuid;
 
 const FEISHU_BASE_URL: &str = "https://open.feishu.cn/open-apis";
 
+/// Interactive card with the message and one button per callback ID. The
+/// callback ID travels in the button's `value` and comes back in
+/// `card.action.trigger` events.
+fn interactive_card(message: &str, buttons: &[Button]) -> serde_json::Value {
+    let actions: Vec<serde_json::Value> = buttons
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            serde_json::json!({
+                "tag": "button",
+                "text": { "tag": "plain_text", "content": b.label },
+                "type": if i == 0 { "primary" } else { "default" },
+                "value": { "callback_id": b.callback_id },
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "config": { "wide_screen_mode": true },
+        "elements": [
+            { "tag": "div", "text": { "tag": "plain_text", "content": message } },
+            { "tag": "action", "actions": actions },
+        ],
+    })
+}
+
 /// Lark/Feishu channel — receives events via HTTP callback, sends via Open API
 pub struct LarkChannel {
     app_id: String,
@@ -85,13 +110,111 @@ impl LarkChannel {
         Ok(token)
     }
 
+    /// Send a message of `msg_type` with its JSON-encoded `content`,
+    /// refreshing the tenant token once on 401.
+    async fn send_content(
+        &self,
+        recipient: &str,
+        msg_type: &str,
+        content: String,
+    ) -> anyhow::Result<()> {
+        let token = self.get_tenant_access_token().await?;
+        let url = format!("{FEISHU_BASE_URL}/im/v1/messages?receive_id_type=chat_id");
+
+        let body = serde_json::json!({
+            "receive_id": recipient,
+            "msg_type": msg_type,
+            "content": content,
+        });
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Content-Type", "application/json; charset=utf-8")
+            .json(&body)
+            .send()
+            .await?;
+
+        if resp.status().as_u16() == 401 {
+            // Token expired, invalidate and retry once
+            self.invalidate_token().await;
+            let new_token = self.get_tenant_access_token().await?;
+            let retry_resp = self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bearer {new_token}"))
+                .header("Content-Type", "application/json; charset=utf-8")
+                .json(&body)
+                .send()
+                .await?;
+
+            if !retry_resp.status().is_success() {
+                let err = retry_resp.text().await.unwrap_or_default();
+                anyhow::bail!("Lark send failed after token refresh: {err}");
+            }
+            return Ok(());
+        }
+
+        if !resp.status().is_success() {
+            let err = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Lark send failed: {err}");
+        }
+
+        Ok(())
+    }
+
     /// Invalidate cached token (called on 401)
     async fn invalidate_token(&self) {
         let mut cached = self.tenant_token.write().await;
         *cached = None;
     }
 
-    /// Parse an event callback payload and extract text messages
+    /// Parse a `card.action.trigger` event (a card button press) into a
+    /// callback message. The event must carry the verification token, since
+    /// a press may approve a tool call.
+    fn parse_card_action(&self, payload: &serde_json::Value) -> Option<ChannelMessage> {
+        let token = payload.pointer("/header/token").and_then(|t| t.as_str());
+        if token != Some(self.verification_token.as_str()) {
+            tracing::warn!("Lark: ignoring card action with invalid verification token");
+            return None;
+        }
+
+        let event = payload.get("event")?;
+        let open_id = event
+            .pointer("/operator/open_id")
+            .and_then(|s| s.as_str())
+            .unwrap_or("");
+        if !self.is_user_allowed(open_id) {
+            tracing::warn!("Lark: ignoring card action from unauthorized user: {open_id}");
+            return None;
+        }
+
+        let callback_id = event
+            .pointer("/action/value/callback_id")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())?;
+        let chat_id = event
+            .pointer("/context/open_chat_id")
+            .and_then(|c| c.as_str())
+            .unwrap_or(open_id);
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: chat_id.to_string(),
+            content: callback_id.to_string(),
+            channel: "lark".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            kind: MessageKind::Callback,
+            ..Default::default()
+        })
+    }
+
+    /// Parse an event callback payload and extract text messages and card
+    /// button presses
     pub fn parse_event_payload(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
         let mut messages = Vec::new();
 
@@ -102,6 +225,11 @@ impl LarkChannel {
             .and_then(|e| e.as_str())
             .unwrap_or("");
 
+        if event_type == "card.action.trigger" {
+            messages.extend(self.parse_card_action(payload));
+            return messages;
+        }
+
         if event_type != "im.message.receive_v1" {
             return messages;
         }
@@ -191,51 +319,8 @@ impl Channel for LarkChannel {
     }
 
     async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
-        let token = self.get_tenant_access_token().await?;
-        let url = format!("{FEISHU_BASE_URL}/im/v1/messages?receive_id_type=chat_id");
-
         let content = serde_json::json!({ "text": message }).to_string();
-        let body = serde_json::json!({
-            "receive_id": recipient,
-            "msg_type": "text",
-            "content": content,
-        });
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {token}"))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .json(&body)
-            .send()
-            .await?;
-
-        if resp.status().as_u16() == 401 {
-            // Token expired, invalidate and retry once
-            self.invalidate_token().await;
-            let new_token = self.get_tenant_access_token().await?;
-            let retry_resp = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bearer {new_token}"))
-                .header("Content-Type", "application/json; charset=utf-8")
-                .json(&body)
-                .send()
-                .await?;
-
-            if !retry_resp.status().is_success() {
-                let err = retry_resp.text().await.unwrap_or_default();
-                anyhow::bail!("Lark send failed after token refresh: {err}");
-            }
-            return Ok(());
-        }
-
-        if !resp.status().is_success() {
-            let err = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Lark send failed: {err}");
-        }
-
-        Ok(())
+        self.send_content(recipient, "text", content).await
     }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
@@ -280,7 +365,8 @@ impl Channel for LarkChannel {
                 }
             }
 
-            (StatusCode::OK, "ok").into_response()
+            // Card actions expect a JSON body; an empty object leaves the card as is.
+            (StatusCode::OK, Json(serde_json::json!({}))).into_response()
         }
 
         let state = AppState {
@@ -311,6 +397,20 @@ impl Channel for LarkChannel {
     async fn health_check(&self) -> bool {
         self.get_tenant_access_token().await.is_ok()
     }
+
+    fn supports_buttons(&self) -> bool {
+        true
+    }
+
+    async fn send_interactive(
+        &self,
+        message: &str,
+        buttons: &[Button],
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        let card = interactive_card(message, buttons).to_string();
+        self.send_content(recipient, "interactive", card).await
+    }
 }
 
 #[cfg(test)]
@@ -471,6 +571,38 @@ mod tests {
         assert!(msgs.is_empty());
     }
 
+    #[test]
+    fn lark_parse_card_action_requires_token_and_allowlist() {
+        let ch = make_channel();
+        let payload = serde_json::json!({
+            "schema": "2.0",
+            "header": { "event_type": "card.action.trigger", "token": "test_verification_token" },
+            "event": {
+                "operator": { "open_id": "ou_testuser123" },
+                "action": { "tag": "button", "value": { "callback_id": "approve ab12cd" } },
+                "context": { "open_chat_id": "oc_chat123" }
+            }
+        });
+        let msgs = ch.parse_event_payload(&payload);
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].kind, MessageKind::Callback);
+        assert_eq!(msgs[0].sender, "oc_chat123");
+        assert_eq!(msgs[0].content, "approve ab12cd");
+
+        let mut forged = payload.clone();
+        forged["header"]["token"] = "wrong".into();
+        assert!(ch.parse_event_payload(&forged).is_empty());
+        let mut stranger = payload;
+        stranger["event"]["operator"]["open_id"] = "ou_other".into();
+        assert!(ch.parse_event_payload(&stranger).is_empty());
+
+        let card = interactive_card("Run it?", &[Button::new("Approve", "approve ab12cd")]);
+        assert_eq!(
+            card["elements"][1]["actions"][0]["value"]["callback_id"],
+            "approve ab12cd"
+        );
+    }
+
     #[test]
     fn lark_parse_wrong_event_type() {
         let ch = make_channel();
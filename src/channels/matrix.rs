@@ -236,7 +236,7 @@ impl Channel for MatrixChannel {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_secs(),
-                        attachments: vec![],
+                        ..Default::default()
                     };
 
                     if tx.send(msg).await.is_err() {
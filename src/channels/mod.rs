@@ -8,8 +8,11 @@ pub mod irc;
 pub mod lark;
 pub mod matrix;
 pub mod slack;
+pub mod speech;
 pub mod telegram;
 pub mod traits;
+pub mod transcription;
+pub mod turns;
 pub mod whatsapp;
 
 pub use cli::CliChannel;
@@ -21,22 +24,34 @@ pub use irc::IrcChannel;
 pub use lark::LarkChannel;
 pub use matrix::MatrixChannel;
 pub use slack::SlackChannel;
+pub use speech::SpeechSynthesizer;
 pub use telegram::TelegramChannel;
 pub use traits::Channel;
+use traits::{Button, MessageKind};
+pub use transcription::Transcriber;
+use turns::{is_stop_command, ActiveTurns};
 pub use whatsapp::WhatsAppChannel;
 
+use crate::agent::budget::TurnBudgets;
 // --- ZeroClaw fork: extended imports for per-user conversations ---
 use crate::agent::loop_::{
-    agent_turn, auto_compact_history, build_tool_instructions, run_tool_call_loop, trim_history,
-    trim_history_by_size,
+    agent_turn, auto_compact_history, build_tool_instructions, create_cost_tracker,
+    run_tool_call_loop, trim_history, trim_history_by_tokens, ContextBudget, TurnHooks,
+    MAX_TOOL_RESULT_CHARS,
 };
-use crate::config::Config;
+use crate::agent::routing::{ModelRouter, RouteInput};
+use crate::config::schema::DEFAULT_MODEL;
+use crate::config::{Config, TurnInterruptMode};
+use crate::cost::{CostTracker, UsageScope};
 use crate::identity;
 use crate::memory::{self, Memory};
-use crate::observability::{self, Observer};
+use crate::observability::{self, Observer, ObserverEvent, ObserverMetric};
+use crate::providers::capabilities::CapabilityRegistry;
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
-use crate::security::SecurityPolicy;
+use crate::security::{
+    ApprovalBroker, ApprovalDecision, ApprovalScope, PendingApproval, SecurityPolicy,
+};
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
@@ -57,6 +72,11 @@ const CHANNEL_MESSAGE_TIMEOUT_SECS: u64 = 3600;
 const CHANNEL_PARALLELISM_PER_CHANNEL: usize = 4;
 const CHANNEL_MIN_IN_FLIGHT_MESSAGES: usize = 8;
 const CHANNEL_MAX_IN_FLIGHT_MESSAGES: usize = 64;
+/// Longest tool output echoed back to the user after an approval.
+const APPROVAL_REPLY_MAX_CHARS: usize = 3000;
+/// Minimum interval between streamed draft edits, to stay under platform
+/// message-edit rate limits.
+const STREAM_DRAFT_UPDATE_INTERVAL_MS: u64 = 1000;
 
 #[derive(Clone)]
 struct ChannelRuntimeContext {
@@ -72,12 +92,30 @@ struct ChannelRuntimeContext {
     // --- ZeroClaw fork: per-user conversation history for multi-turn context ---
     conversations: Arc<DashMap<String, Vec<ChatMessage>>>,
     // --- end ZeroClaw fork ---
+    cost_tracker: Option<CostTracker>,
+    model_router: Arc<ModelRouter>,
+    capabilities: Arc<CapabilityRegistry>,
+    show_reasoning: bool,
+    transcriber: Option<Arc<dyn Transcriber>>,
+    synthesizer: Option<Arc<dyn SpeechSynthesizer>>,
+    speech: Arc<crate::config::SpeechConfig>,
+    approvals: Arc<ApprovalBroker>,
+    /// Most tool calls run at once in a turn (1 = one after another).
+    max_parallel_tools: usize,
+    turn_budgets: Arc<TurnBudgets>,
+    /// Turns in flight per conversation, for stopping and steering them.
+    turns: Arc<ActiveTurns>,
 }
 
 fn conversation_memory_key(msg: &traits::ChannelMessage) -> String {
     format!("{}_{}_{}", msg.channel, msg.sender, msg.id)
 }
 
+/// Key of the conversation a message belongs to: one per channel user.
+fn sender_key(msg: &traits::ChannelMessage) -> String {
+    format!("{}_{}", msg.channel, msg.sender)
+}
+
 async fn build_memory_context(mem: &dyn Memory, user_msg: &str) -> String {
     let mut context = String::new();
 
@@ -100,6 +138,7 @@ async fn build_memory_context(mem: &dyn Memory, user_msg: &str) -> String {
 ///
 /// - Image attachments (Photo, Sticker, Animation): reads the downloaded file,
 ///   base64-encodes it, and returns `ChatMessage::with_image()` for vision models.
+///   When `vision` is false the image is described like any other file instead.
 /// - File-based media (Voice, Audio, Video, Document, VideoNote): appends a
 ///   bracketed description to the text so the agent knows a file is available.
 /// - Structured data (Location, Contact, Poll, Venue): already described in
@@ -107,10 +146,11 @@ async fn build_memory_context(mem: &dyn Memory, user_msg: &str) -> String {
 fn build_user_message_from_attachments(
     text: &str,
     attachments: &[traits::MediaAttachment],
+    vision: bool,
 ) -> ChatMessage {
     // Find the first image attachment with a downloaded file
     let image_attachment = attachments.iter().find(|a| {
-        a.media_type.is_image() && a.file_path.is_some()
+        vision && a.media_type.is_image() && a.file_path.is_some()
     });
 
     if let Some(img) = image_attachment {
@@ -154,6 +194,257 @@ fn build_user_message_from_attachments(
 
 // --- end ZeroClaw fork ---
 
+/// Speak `reply` as a voice message. History keeps the text either way.
+pub(crate) async fn speak_reply(
+    synthesizer: &dyn SpeechSynthesizer,
+    channel: &dyn Channel,
+    reply: &str,
+    recipient: &str,
+) -> anyhow::Result<()> {
+    let text = speech::speakable_text(reply);
+    if text.is_empty() {
+        anyhow::bail!("reply has no speakable text");
+    }
+    let audio = synthesizer.synthesize(&text).await?;
+    channel.send_voice_reply(&audio, recipient).await
+}
+
+/// Deliver images produced by tools during the turn, after the reply text.
+/// Failures are logged; the reply has already gone out.
+async fn send_tool_images(channel: &dyn Channel, images: Vec<(String, String)>, recipient: &str) {
+    use base64::Engine;
+
+    for (b64, mime_type) in images {
+        let bytes = match base64::engine::general_purpose::STANDARD.decode(&b64) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Skipping undecodable tool image: {e}");
+                continue;
+            }
+        };
+        if let Err(e) = channel.send_image(bytes, &mime_type, recipient).await {
+            tracing::warn!("Failed to send image on {}: {e}", channel.name());
+        }
+    }
+}
+
+/// Approve/deny buttons whose callback IDs are the replies that
+/// [`ApprovalDecision::parse`] accepts, so typed and pressed answers match.
+pub(crate) fn approval_buttons(request: &PendingApproval) -> [Button; 2] {
+    [
+        Button::new("✅ Approve", format!("approve {}", request.id)),
+        Button::new("❎ Deny", format!("deny {}", request.id)),
+    ]
+}
+
+/// Ask the user about each tool call parked for approval during the turn.
+async fn send_approval_prompts(channel: &dyn Channel, parked: &[PendingApproval], recipient: &str) {
+    for request in parked {
+        let buttons = approval_buttons(request);
+        if let Err(e) = channel
+            .send_interactive(&request.prompt(), &buttons, recipient)
+            .await
+        {
+            tracing::warn!("Failed to send approval request on {}: {e}", channel.name());
+        }
+    }
+}
+
+/// What deciding a parked call needs. Channels and the gateway each build
+/// one over their own state.
+pub(crate) struct ApprovalReplyContext<'a> {
+    pub approvals: &'a ApprovalBroker,
+    pub tools_registry: &'a [Box<dyn Tool>],
+    pub observer: &'a dyn Observer,
+    pub conversations: &'a DashMap<String, Vec<ChatMessage>>,
+    pub memory: &'a dyn Memory,
+    pub system_prompt: &'a str,
+}
+
+/// Run a parked call exactly as it was parked, with `approved: true`.
+/// Returns whether it succeeded, the text to report, and how long it took.
+async fn run_approved_call(
+    ctx: &ApprovalReplyContext<'_>,
+    request: &PendingApproval,
+) -> (bool, String, Duration) {
+    let started = Instant::now();
+    let Some(tool) = ctx.tools_registry.iter().find(|t| t.name() == request.tool) else {
+        return (
+            false,
+            format!("Unknown tool: {}", request.tool),
+            started.elapsed(),
+        );
+    };
+    let mut args = request.arguments.clone();
+    if let Some(obj) = args.as_object_mut() {
+        obj.insert("approved".into(), serde_json::Value::Bool(true));
+    }
+
+    ctx.observer.record_event(&ObserverEvent::ToolCallStart {
+        tool: request.tool.clone(),
+    });
+    let (success, output) = match tool.execute(args).await {
+        Ok(r) if r.success => (true, r.output),
+        Ok(r) => (false, format!("Error: {}", r.error.unwrap_or(r.output))),
+        Err(e) => (false, format!("Error executing {}: {e}", request.tool)),
+    };
+    ctx.observer.record_event(&ObserverEvent::ToolCall {
+        tool: request.tool.clone(),
+        duration: started.elapsed(),
+        success,
+    });
+    (success, output, started.elapsed())
+}
+
+/// Append a note to the sender's conversation so the model sees it next turn.
+async fn note_in_conversation(ctx: &ApprovalReplyContext<'_>, sender_key: &str, note: String) {
+    let (history_json, subject) = {
+        let mut history = ctx
+            .conversations
+            .entry(sender_key.to_string())
+            .or_insert_with(|| vec![ChatMessage::system(ctx.system_prompt)]);
+        history.push(ChatMessage::user(note));
+        (
+            serde_json::to_string(history.value()).unwrap_or_default(),
+            crate::agent::routing::extract_subject(history.value()),
+        )
+    };
+    let _ = ctx
+        .memory
+        .save_conversation(sender_key, &history_json, subject.as_deref())
+        .await;
+}
+
+/// Decide a parked tool call from an `approve <id>` / `deny <id>` reply in
+/// `sender_key`'s conversation. Approved calls run exactly as parked,
+/// without asking the model; the outcome is noted in the conversation
+/// history and returned as the reply for the user.
+pub(crate) async fn decide_approval(
+    ctx: &ApprovalReplyContext<'_>,
+    sender_key: &str,
+    decision: ApprovalDecision,
+    id: &str,
+) -> String {
+    match ctx.approvals.take(sender_key, id) {
+        Err(e) => format!("⚠️ {e}"),
+        Ok(request) => {
+            let (reply, note) = match decision {
+                ApprovalDecision::Deny => {
+                    ctx.approvals
+                        .audit_decision(&request, "denied", false, None);
+                    (
+                        format!("❎ Denied {}: {}", request.id, request.action()),
+                        format!(
+                            "[Approval] The user denied request {} ({}: {}); it was not run.",
+                            request.id,
+                            request.tool,
+                            request.action()
+                        ),
+                    )
+                }
+                ApprovalDecision::Approve => {
+                    let (success, output, elapsed) = run_approved_call(ctx, &request).await;
+                    ctx.approvals.audit_decision(
+                        &request,
+                        "approved",
+                        true,
+                        Some((success, elapsed)),
+                    );
+                    let output = truncate_with_ellipsis(&output, MAX_TOOL_RESULT_CHARS);
+                    (
+                        format!(
+                            "{} Approved {}, {} ran:\n{}",
+                            if success { "✅" } else { "⚠️" },
+                            request.id,
+                            request.tool,
+                            truncate_with_ellipsis(&output, APPROVAL_REPLY_MAX_CHARS)
+                        ),
+                        format!(
+                            "[Approval] The user approved request {} ({}: {}). Result:\n{output}",
+                            request.id,
+                            request.tool,
+                            request.action()
+                        ),
+                    )
+                }
+            };
+            note_in_conversation(ctx, sender_key, note).await;
+            reply
+        }
+    }
+}
+
+/// Answer an `approve <id>` / `deny <id>` reply on the channel it came from.
+async fn handle_approval_reply(
+    ctx: &ChannelRuntimeContext,
+    msg: &traits::ChannelMessage,
+    decision: ApprovalDecision,
+    id: &str,
+) {
+    let approval_ctx = ApprovalReplyContext {
+        approvals: &ctx.approvals,
+        tools_registry: &ctx.tools_registry,
+        observer: ctx.observer.as_ref(),
+        conversations: &ctx.conversations,
+        memory: ctx.memory.as_ref(),
+        system_prompt: &ctx.system_prompt,
+    };
+    let reply = decide_approval(&approval_ctx, &sender_key(msg), decision, id).await;
+
+    let Some(channel) = ctx.channels_by_name.get(&msg.channel) else {
+        return;
+    };
+    match channel.send(&reply, &msg.sender).await {
+        Ok(()) => ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+            channel: msg.channel.clone(),
+            direction: "outbound".to_string(),
+        }),
+        Err(e) => tracing::warn!("Failed to send approval result on {}: {e}", channel.name()),
+    }
+}
+
+/// Transcribe voice, audio and video-note attachments into the message text
+/// so memory recall, routing and the model all see what was said. The
+/// attachments stay on the message; failures leave the text untouched.
+pub(crate) async fn transcribe_attachments(
+    transcriber: Option<&dyn Transcriber>,
+    mut msg: traits::ChannelMessage,
+) -> traits::ChannelMessage {
+    let Some(transcriber) = transcriber else {
+        return msg;
+    };
+
+    let mut transcripts = Vec::new();
+    for att in &mut msg.attachments {
+        if !att.media_type.is_audio() {
+            continue;
+        }
+        let Some(path) = att.file_path.as_deref() else {
+            continue;
+        };
+        match transcriber
+            .transcribe(std::path::Path::new(path), att.mime_type.as_deref())
+            .await
+        {
+            Ok(text) if !text.is_empty() => {
+                transcripts.push(format!("[{} transcript] {text}", att.media_type));
+                att.metadata.insert("transcript".into(), text);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                backend = transcriber.name(),
+                "Failed to transcribe {} attachment: {e}",
+                att.media_type
+            ),
+        }
+    }
+
+    if !transcripts.is_empty() {
+        msg.content = format!("{}\n{}", msg.content, transcripts.join("\n"));
+    }
+    msg
+}
+
 fn spawn_supervised_listener(
     ch: Arc<dyn Channel>,
     tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
@@ -209,13 +500,99 @@ fn log_worker_join_result(result: Result<(), tokio::task::JoinError>) {
     }
 }
 
+/// Accumulate streamed deltas into a draft message on `channel`, editing it at
+/// most once per `STREAM_DRAFT_UPDATE_INTERVAL_MS`. Resolves to the draft's
+/// message ID once the sender is dropped, or `None` if no draft was sent.
+fn spawn_draft_updater(
+    channel: Arc<dyn Channel>,
+    recipient: String,
+    mut deltas: tokio::sync::mpsc::UnboundedReceiver<String>,
+) -> tokio::task::JoinHandle<Option<String>> {
+    tokio::spawn(async move {
+        let interval = Duration::from_millis(STREAM_DRAFT_UPDATE_INTERVAL_MS);
+        let mut text = String::new();
+        let mut draft_id: Option<String> = None;
+        let mut last_update: Option<Instant> = None;
+
+        while let Some(delta) = deltas.recv().await {
+            text.push_str(&delta);
+            if text.trim().is_empty() || last_update.is_some_and(|t| t.elapsed() < interval) {
+                continue;
+            }
+
+            match draft_id.as_deref() {
+                None => match channel.send_draft(&text, &recipient).await {
+                    Ok(id) => draft_id = Some(id),
+                    Err(e) => {
+                        tracing::warn!(channel = channel.name(), "Failed to send draft: {e}");
+                        return None;
+                    }
+                },
+                Some(id) => {
+                    if let Err(e) = channel.update_draft(&text, &recipient, id).await {
+                        tracing::debug!(channel = channel.name(), "Failed to update draft: {e}");
+                    }
+                }
+            }
+            last_update = Some(Instant::now());
+        }
+
+        draft_id
+    })
+}
+
+/// Deliver `message`, replacing the streamed draft when one exists. Falls
+/// back to a fresh `send` if the draft cannot be finalized.
+async fn send_or_finalize_draft(
+    channel: &dyn Channel,
+    message: &str,
+    recipient: &str,
+    draft_id: Option<&str>,
+) -> anyhow::Result<()> {
+    if let Some(id) = draft_id {
+        match channel.finalize_draft(message, recipient, id).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                tracing::warn!(channel = channel.name(), "Failed to finalize draft: {e}");
+            }
+        }
+    }
+    channel.send(message, recipient).await
+}
+
+/// Put the channel's rendering of `reasoning` ahead of `reply`. Channels
+/// that cannot collapse reasoning get the reply unchanged.
+fn with_reasoning(channel: &dyn Channel, reasoning: &str, reply: &str) -> String {
+    if reasoning.trim().is_empty() {
+        return reply.to_string();
+    }
+    match channel.format_reasoning(reasoning) {
+        Some(block) => format!("{block}\n\n{reply}"),
+        None => reply.to_string(),
+    }
+}
+
 async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::ChannelMessage) {
+    let mut msg = transcribe_attachments(ctx.transcriber.as_deref(), msg).await;
     println!(
         "  💬 [{}] from {}: {}",
         msg.channel,
         msg.sender,
         truncate_with_ellipsis(&msg.content, 80)
     );
+    ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+        channel: msg.channel.clone(),
+        direction: "inbound".to_string(),
+    });
+
+    if let Some((decision, id)) = ApprovalDecision::parse(&msg.content) {
+        handle_approval_reply(&ctx, &msg, decision, &id).await;
+        return;
+    }
+    // Other button presses reach the model as the user's choice.
+    if msg.kind == MessageKind::Callback {
+        msg.content = format!("[Button pressed] {}", msg.content);
+    }
 
     let memory_context = build_memory_context(ctx.memory.as_ref(), &msg.content).await;
 
@@ -237,7 +614,6 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
         "discord" => "[Platform: Discord] The user is chatting with you via Discord. You ARE the Discord bot.\n\n",
         _ => "",
     };
-    let enriched_message = format!("{channel_hint}{memory_context}{}", msg.content);
     // --- end ZeroClaw fork ---
 
     let target_channel = ctx.channels_by_name.get(&msg.channel).cloned();
@@ -247,7 +623,16 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
 
     // --- ZeroClaw fork: persistent per-user conversation history ---
     // Sender key combines channel + user so each channel user has their own history.
-    let sender_key = format!("{}_{}", msg.channel, msg.sender);
+    let sender_key = sender_key(&msg);
+
+    // Track the turn so follow-ups can stop or steer it. Waits for a turn
+    // that is being cancelled to save its history first.
+    let turn = match ctx.turns.mode() {
+        TurnInterruptMode::Off => None,
+        TurnInterruptMode::Cancel | TurnInterruptMode::Steer => {
+            Some(ctx.turns.begin(&sender_key).await)
+        }
+    };
 
     let mut history = ctx
         .conversations
@@ -256,8 +641,49 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
         .value()
         .clone();
 
+    let follow_up = history.len() > 1;
+
+    let cost_tracker = ctx
+        .cost_tracker
+        .as_ref()
+        .map(|t| t.scoped(UsageScope::channel(&msg.channel, &msg.sender)));
+
+    let attachment_types: Vec<_> = msg
+        .attachments
+        .iter()
+        .map(|a| a.media_type.clone())
+        .collect();
+    let route = ctx
+        .model_router
+        .route(
+            &RouteInput {
+                message: &msg.content,
+                channel: Some(&msg.channel),
+                sender: Some(&msg.sender),
+                attachments: &attachment_types,
+                follow_up,
+            },
+            ctx.provider.as_ref(),
+            ctx.model.as_str(),
+            cost_tracker.as_ref(),
+        )
+        .await;
+    let model = route.model(ctx.model.as_str());
+    tracing::debug!(
+        model = model.as_str(),
+        reason = %route.reason,
+        "Channel routing decision"
+    );
+    let budget = ContextBudget::for_model(&ctx.capabilities, &model);
+    // Stored history follows the default model's window, not the routed one.
+    let history_budget = ContextBudget::for_model(&ctx.capabilities, &ctx.model);
+    let vision = ctx.capabilities.lookup(&model).vision != Some(false);
+    let memory_context = budget.fit_memory_context(memory_context);
+    let enriched_message = format!("{channel_hint}{memory_context}{}", msg.content);
+
     // Build multimodal ChatMessage for image attachments
-    let user_message = build_user_message_from_attachments(&enriched_message, &msg.attachments);
+    let user_message =
+        build_user_message_from_attachments(&enriched_message, &msg.attachments, vision);
     history.push(user_message);
     // --- end ZeroClaw fork ---
 
@@ -279,7 +705,45 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
         });
     }
 
-    let llm_result = tokio::time::timeout(
+    // Answer in kind when the user wants voice and the channel can send it.
+    let sent_voice = msg
+        .attachments
+        .iter()
+        .any(|a| a.media_type == traits::MediaType::Voice);
+    let voice_reply = match (target_channel.as_ref(), ctx.synthesizer.as_ref()) {
+        (Some(channel), Some(synthesizer))
+            if channel.supports_voice_replies()
+                && ctx
+                    .speech
+                    .reply_mode_for(&msg.channel, &msg.sender)
+                    .wants_voice(sent_voice) =>
+        {
+            Some(Arc::clone(synthesizer))
+        }
+        _ => None,
+    };
+
+    // Stream the reply into an editable draft when both sides support it.
+    let (delta_tx, draft_task) = match target_channel.as_ref() {
+        Some(channel)
+            if voice_reply.is_none()
+                && channel.supports_draft_updates()
+                && ctx.provider.supports_streaming() =>
+        {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let task = spawn_draft_updater(Arc::clone(channel), msg.sender.clone(), rx);
+            (Some(tx), Some(task))
+        }
+        _ => (None, None),
+    };
+
+    let mut reasoning = String::new();
+    let mut images = Vec::new();
+    let mut approval_scope = ApprovalScope::new(&ctx.approvals, &sender_key);
+    let wants_images = target_channel
+        .as_ref()
+        .is_some_and(|channel| channel.supports_image_replies());
+    let turn_loop = tokio::time::timeout(
         Duration::from_secs(CHANNEL_MESSAGE_TIMEOUT_SECS),
         run_tool_call_loop(
             ctx.provider.as_ref(),
@@ -287,20 +751,45 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
             ctx.tools_registry.as_ref(),
             ctx.observer.as_ref(),
             "channel-runtime",
-            ctx.model.as_str(),
+            model.as_str(),
             ctx.temperature,
             true, // silent — channels don't write to stdout
+            &ctx.capabilities,
+            ctx.max_parallel_tools,
+            ctx.turn_budgets.for_channel(&msg.channel),
+            TurnHooks {
+                on_delta: delta_tx.as_ref(),
+                cost_tracker: cost_tracker.as_ref(),
+                reasoning_out: ctx.show_reasoning.then_some(&mut reasoning),
+                images_out: wants_images.then_some(&mut images),
+                approvals: Some(&mut approval_scope),
+                steering: turn.as_ref().map(|turn| turn.inbox()),
+            },
         ),
-    )
-    .await;
+    );
+    // Dropping the loop on cancellation also drops its running tool calls,
+    // which kills any shell commands they started.
+    let llm_result = match turn.as_ref() {
+        Some(turn) => tokio::select! {
+            result = turn_loop => Some(result),
+            () = turn.cancelled() => None,
+        },
+        None => Some(turn_loop.await),
+    };
 
     // Stop the typing indicator
     let _ = typing_stop_tx.send(true);
 
+    drop(delta_tx);
+    let draft_id = match draft_task {
+        Some(task) => task.await.ok().flatten(),
+        None => None,
+    };
+
     // --- ZeroClaw fork: persist history after agent turn, with trimming ---
     let save_history = |history: &mut Vec<ChatMessage>, ctx: &ChannelRuntimeContext, sender_key: &str| {
-        trim_history(history);
-        trim_history_by_size(history);
+        trim_history(history, &history_budget);
+        trim_history_by_tokens(history, &history_budget);
         let subject = crate::agent::routing::extract_subject(history);
         let history_json = serde_json::to_string(&history).unwrap_or_default();
         ctx.conversations.insert(sender_key.to_string(), history.clone());
@@ -309,7 +798,31 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
     // --- end ZeroClaw fork ---
 
     match llm_result {
-        Ok(Ok(response)) => {
+        None => {
+            println!(
+                "  ⏹️ Turn stopped by the user after {}ms",
+                started_at.elapsed().as_millis()
+            );
+            history.push(ChatMessage::assistant(
+                "[Stopped by the user before finishing]",
+            ));
+            let (history_json, subject) = save_history(&mut history, &ctx, &sender_key);
+            let _ = ctx
+                .memory
+                .save_conversation(&sender_key, &history_json, subject.as_deref())
+                .await;
+
+            if let Some(channel) = target_channel.as_ref() {
+                let _ = send_or_finalize_draft(
+                    channel.as_ref(),
+                    "⏹️ Stopped.",
+                    &msg.sender,
+                    draft_id.as_deref(),
+                )
+                .await;
+            }
+        }
+        Some(Ok(Ok(response))) => {
             println!(
                 "  🤖 Reply ({}ms): {}",
                 started_at.elapsed().as_millis(),
@@ -321,6 +834,7 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
                 &mut history,
                 ctx.provider.as_ref(),
                 ctx.model.as_str(),
+                &history_budget,
             )
             .await;
             let (history_json, subject) = save_history(&mut history, &ctx, &sender_key);
@@ -334,13 +848,48 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
             // (e.g. Telegram's send() calls markdown_to_telegram_html internally).
             // Do NOT convert here — that would double-convert and escape HTML tags.
 
-            if let Some(channel) = target_channel.as_ref() {
-                if let Err(e) = channel.send(&response, &msg.sender).await {
+            let spoken = match (target_channel.as_ref(), voice_reply.as_deref()) {
+                (Some(channel), Some(synthesizer)) => {
+                    match speak_reply(synthesizer, channel.as_ref(), &response, &msg.sender).await {
+                        Ok(()) => true,
+                        Err(e) => {
+                            tracing::warn!("Voice reply failed, sending text instead: {e}");
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            };
+
+            if spoken {
+                ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+                    channel: msg.channel.clone(),
+                    direction: "outbound".to_string(),
+                });
+            } else if let Some(channel) = target_channel.as_ref() {
+                let reply = with_reasoning(channel.as_ref(), &reasoning, &response);
+                if let Err(e) = send_or_finalize_draft(
+                    channel.as_ref(),
+                    &reply,
+                    &msg.sender,
+                    draft_id.as_deref(),
+                )
+                .await
+                {
                     eprintln!("  ❌ Failed to reply on {}: {e}", channel.name());
+                } else {
+                    ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+                        channel: msg.channel.clone(),
+                        direction: "outbound".to_string(),
+                    });
                 }
             }
+
+            if let Some(channel) = target_channel.as_ref() {
+                send_tool_images(channel.as_ref(), images, &msg.sender).await;
+            }
         }
-        Ok(Err(e)) => {
+        Some(Ok(Err(e))) => {
             let err_str = e.to_string();
             eprintln!(
                 "  ❌ LLM error after {}ms: {err_str}",
@@ -362,10 +911,16 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
                 .await;
 
             if let Some(channel) = target_channel.as_ref() {
-                let _ = channel.send(&format!("⚠️ Error: {e}"), &msg.sender).await;
+                let _ = send_or_finalize_draft(
+                    channel.as_ref(),
+                    &format!("⚠️ Error: {e}"),
+                    &msg.sender,
+                    draft_id.as_deref(),
+                )
+                .await;
             }
         }
-        Err(_) => {
+        Some(Err(_)) => {
             let timeout_msg = format!(
                 "LLM response timed out after {}s",
                 CHANNEL_MESSAGE_TIMEOUT_SECS
@@ -383,15 +938,82 @@ async fn process_channel_message(ctx: Arc<ChannelRuntimeContext>, msg: traits::C
                 .await;
 
             if let Some(channel) = target_channel.as_ref() {
+                let _ = send_or_finalize_draft(
+                    channel.as_ref(),
+                    "⚠️ Request timed out while waiting for the model. Please try again.",
+                    &msg.sender,
+                    draft_id.as_deref(),
+                )
+                .await;
+            }
+        }
+    }
+
+    // Ask about calls parked for approval during the turn, after the reply.
+    if let Some(channel) = target_channel.as_ref() {
+        send_approval_prompts(channel.as_ref(), &approval_scope.parked, &msg.sender).await;
+    }
+
+    // Steering that arrived too late for the loop to read gets its own turn.
+    let leftovers = turn.map(turns::TurnGuard::finish).unwrap_or_default();
+    if !leftovers.is_empty() {
+        let follow_up = traits::ChannelMessage {
+            content: leftovers.join("\n\n"),
+            attachments: Vec::new(),
+            kind: MessageKind::Text,
+            ..msg
+        };
+        Box::pin(process_channel_message(ctx, follow_up)).await;
+    }
+}
+
+/// Deliver `msg` to the sender's running turn when it stops or steers it,
+/// so it gets there without waiting for a free worker. Returns the message
+/// when it should be processed as a turn of its own.
+fn route_to_running_turn(
+    ctx: &Arc<ChannelRuntimeContext>,
+    msg: traits::ChannelMessage,
+) -> Option<traits::ChannelMessage> {
+    let mode = ctx.turns.mode();
+    // Approvals, button presses and media always take the normal path.
+    if mode == TurnInterruptMode::Off
+        || msg.kind == MessageKind::Callback
+        || !msg.attachments.is_empty()
+        || ApprovalDecision::parse(&msg.content).is_some()
+    {
+        return Some(msg);
+    }
+
+    let key = sender_key(&msg);
+    if is_stop_command(&msg.content) {
+        if !ctx.turns.cancel(&key) {
+            return Some(msg);
+        }
+    } else if mode == TurnInterruptMode::Cancel {
+        // The new turn waits for the cancelled one to wind down.
+        ctx.turns.cancel(&key);
+        return Some(msg);
+    } else {
+        if let Err(content) = ctx.turns.steer(&key, msg.content) {
+            return Some(traits::ChannelMessage { content, ..msg });
+        }
+        if let Some(channel) = ctx.channels_by_name.get(&msg.channel).cloned() {
+            tokio::spawn(async move {
                 let _ = channel
                     .send(
-                        "⚠️ Request timed out while waiting for the model. Please try again.",
+                        "📝 Noted — I'll work that into what I'm doing.",
                         &msg.sender,
                     )
                     .await;
-            }
+            });
         }
     }
+
+    ctx.observer.record_event(&ObserverEvent::ChannelMessage {
+        channel: msg.channel,
+        direction: "inbound".to_string(),
+    });
+    None
 }
 
 async fn run_message_dispatch_loop(
@@ -403,6 +1025,12 @@ async fn run_message_dispatch_loop(
     let mut workers = tokio::task::JoinSet::new();
 
     while let Some(msg) = rx.recv().await {
+        ctx.observer
+            .record_metric(&ObserverMetric::QueueDepth(rx.len() as u64));
+        let Some(msg) = route_to_running_turn(&ctx, msg) else {
+            continue;
+        };
+
         let permit = match Arc::clone(&semaphore).acquire_owned().await {
             Ok(permit) => permit,
             Err(_) => break,
@@ -484,6 +1112,55 @@ pub fn build_system_prompt(
     autonomy_config: Option<&crate::config::AutonomyConfig>,
     // --- upstream: bootstrap compaction ---
     bootstrap_max_chars: Option<usize>,
+) -> String {
+    system_prompt_with_approvals(
+        workspace_dir,
+        model_name,
+        tools,
+        skills,
+        identity_config,
+        model_routes,
+        autonomy_config,
+        bootstrap_max_chars,
+        false,
+    )
+}
+
+/// Approval broker for `config`, auditing decisions when the audit log can
+/// be opened.
+pub(crate) fn approval_broker(config: &Config, security: Arc<SecurityPolicy>) -> ApprovalBroker {
+    let zeroclaw_dir = config
+        .config_path
+        .parent()
+        .unwrap_or(&config.workspace_dir)
+        .to_path_buf();
+    let broker = ApprovalBroker::new(
+        security,
+        Duration::from_secs(config.autonomy.approval_timeout_secs),
+    );
+    match crate::security::AuditLogger::new(config.security.audit.clone(), zeroclaw_dir) {
+        Ok(audit) => broker.with_audit(audit),
+        Err(e) => {
+            tracing::warn!("Approval audit logging unavailable: {e}");
+            broker
+        }
+    }
+}
+
+/// [`build_system_prompt`], optionally for channels whose approval broker
+/// parks `APPROVAL_REQUIRED` calls and asks the user itself. The model is
+/// then told to wait for the user instead of retrying with `approved: true`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn system_prompt_with_approvals(
+    workspace_dir: &std::path::Path,
+    model_name: &str,
+    tools: &[(&str, &str)],
+    skills: &[crate::skills::Skill],
+    identity_config: Option<&crate::config::IdentityConfig>,
+    model_routes: &[crate::config::ModelRouteConfig],
+    autonomy_config: Option<&crate::config::AutonomyConfig>,
+    bootstrap_max_chars: Option<usize>,
+    brokered_approvals: bool,
 ) -> String {
     use std::fmt::Write;
     let mut prompt = String::with_capacity(8192);
@@ -586,34 +1263,49 @@ pub fn build_system_prompt(
     );
 
     // ── 2c. Approval Protocol ─────────────────────────────────────
-    prompt.push_str("## Approval Protocol\n\n");
-    prompt.push_str(
-        "When a tool returns an APPROVAL_REQUIRED error:\n\
-         1. **Do NOT** retry with `approved=true` — you cannot self-approve.\n\
-         2. Present a clear, formatted approval request:\n\n\
-         ```\n\
-         \u{1F510} **Permission Required**\n\n\
-         **Action**: [exact command or file operation]\n\
-         **Risk Level**: [Medium / High]\n\
-         **What it does**: [clear explanation of the effect]\n\
-         **Why**: [reason you need to do this]\n\n\
-         Reply **yes** to approve or **no** to cancel.\n\
-         ```\n\n\
-         3. Stop and wait for the user's next message.\n\
-         4. Only after the user explicitly approves (\"yes\", \"approved\", \"go ahead\"),\n\
-            retry the operation with `approved: true`.\n\
-         5. If the user says no/cancel/stop, acknowledge and suggest alternatives.\n\n",
-    );
+    if brokered_approvals {
+        prompt.push_str("## Approval Requests\n\n");
+        prompt.push_str(
+            "On chat channels approval is handled by the system, not by you. When a tool \
+             call is parked for approval, the user is sent an approval request with its own ID.\n\
+             - Do NOT retry the call or add `approved: true`; it will be stripped.\n\
+             - Briefly tell the user what you want to do and why, then wait.\n\
+             - Results of approved calls appear in the conversation as `[Approval]` notes.\n\n",
+        );
+    } else {
+        prompt.push_str("## Approval Protocol\n\n");
+        prompt.push_str(
+            "When a tool returns an APPROVAL_REQUIRED error:\n\
+             1. **Do NOT** retry with `approved=true` — you cannot self-approve.\n\
+             2. Present a clear, formatted approval request:\n\n\
+             ```\n\
+             \u{1F510} **Permission Required**\n\n\
+             **Action**: [exact command or file operation]\n\
+             **Risk Level**: [Medium / High]\n\
+             **What it does**: [clear explanation of the effect]\n\
+             **Why**: [reason you need to do this]\n\n\
+             Reply **yes** to approve or **no** to cancel.\n\
+             ```\n\n\
+             3. Stop and wait for the user's next message.\n\
+             4. Only after the user explicitly approves (\"yes\", \"approved\", \"go ahead\"),\n\
+                retry the operation with `approved: true`.\n\
+             5. If the user says no/cancel/stop, acknowledge and suggest alternatives.\n\n",
+        );
+    }
 
     // ── 2e. Shell Command Access ─────────────────────────────────
     if let Some(autonomy) = autonomy_config {
         if !autonomy.allowed_commands.is_empty() {
             prompt.push_str("## Shell Command Access\n\n");
-            prompt.push_str(
+            prompt.push_str(if brokered_approvals {
+                "The following commands are AVAILABLE via the `shell` tool. \
+                 \"Approval needed\" means the system asks the user before running it \
+                 — it does NOT mean the command is blocked.\n\n"
+            } else {
                 "The following commands are AVAILABLE via the `shell` tool. \
                  \"Approval needed\" means you must ask the user first, then retry with \
-                 approved=true — it does NOT mean the command is blocked.\n\n",
-            );
+                 approved=true — it does NOT mean the command is blocked.\n\n"
+            });
 
             let high_risk: &[&str] = &[
                 "rm", "dd", "mkfs", "nc", "ncat", "netcat", "iptables", "ufw",
@@ -628,17 +1320,27 @@ pub fn build_system_prompt(
                 };
                 let _ = writeln!(prompt, "- `{cmd}` ({note})");
             }
-            prompt.push_str(
+            prompt.push_str(if brokered_approvals {
+                "\n**IMPORTANT**: APPROVAL_REQUIRED means \"the call is waiting for the user's \
+                 approval.\" It does NOT mean blocked or unavailable. \
+                 You CAN execute every command listed above.\n\n"
+            } else {
                 "\n**IMPORTANT**: APPROVAL_REQUIRED means \"ask the user, then retry with \
                  approved=true after they say yes.\" It does NOT mean blocked or unavailable. \
-                 You CAN execute every command listed above.\n\n",
-            );
+                 You CAN execute every command listed above.\n\n"
+            });
         }
     }
 
     // ── 2f. Self-Healing Protocol ──────────────────────────────
     prompt.push_str("## Self-Healing Protocol\n\n");
-    prompt.push_str(
+    let redeploy_call = if brokered_approvals {
+        "`self_upgrade` with `check_only=false` (the user is asked to approve).\n"
+    } else {
+        "`self_upgrade` with `check_only=false, approved=true`.\n"
+    };
+    let _ = write!(
+        prompt,
         "You are a **self-healing, self-modifying** agent. When something fails:\n\n\
          1. **Diagnose**: Read logs, check process status, identify root cause.\n\
          2. **Fix**: Apply the minimal change to restore functionality.\n\
@@ -653,7 +1355,7 @@ pub fn build_system_prompt(
          ### Self-Modification & Redeployment\n\n\
          **CRITICAL — READ THIS CAREFULLY**:\n\
          To redeploy yourself, use the `self_upgrade` tool:\n\
-         `self_upgrade` with `check_only=false, approved=true`.\n\
+         {redeploy_call}\
          It handles git pull → cargo build → binary copy → codesign → safe restart.\n\n\
          **DO NOT** try to deploy manually via shell commands. These are \
          BLOCKED by the security policy and will fail:\n\
@@ -923,11 +1625,14 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     if let Some(ref sl) = config.channels_config.slack {
         channels.push((
             "Slack",
-            Arc::new(SlackChannel::new(
-                sl.bot_token.clone(),
-                sl.channel_id.clone(),
-                sl.allowed_users.clone(),
-            )),
+            Arc::new(
+                SlackChannel::new(
+                    sl.bot_token.clone(),
+                    sl.channel_id.clone(),
+                    sl.allowed_users.clone(),
+                )
+                .with_app_token(sl.app_token.clone()),
+            ),
         ));
     }
 
@@ -1056,11 +1761,31 @@ pub async fn start_channels(config: Config) -> Result<()> {
         .default_provider
         .clone()
         .unwrap_or_else(|| "openrouter".into());
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
-        &provider_name,
-        config.api_key.as_deref(),
-        &config.reliability,
+    let model = config
+        .default_model
+        .clone()
+        .unwrap_or_else(|| DEFAULT_MODEL.into());
+    let capabilities = Arc::new(CapabilityRegistry::from_config(&config));
+    let observer: Arc<dyn Observer> =
+        Arc::from(observability::create_observer(&config.observability));
+    let provider = providers::with_replay(
+        providers::create_routed_provider(
+            &provider_name,
+            config.api_key.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &model,
+            Arc::clone(&capabilities),
+        )?,
+        &config.replay,
+        &config.workspace_dir,
+    )?;
+    let provider: Arc<dyn Provider> = Arc::from(providers::with_redaction(
+        provider,
+        &config,
+        Arc::clone(&observer),
     )?);
+    let model_router = Arc::new(ModelRouter::from_config(&config)?);
 
     // Warm up the provider connection pool (TLS handshake, DNS, HTTP/2 setup)
     // so the first real message doesn't hit a cold-start timeout.
@@ -1068,18 +1793,12 @@ pub async fn start_channels(config: Config) -> Result<()> {
         tracing::warn!("Provider warmup failed (non-fatal): {e}");
     }
 
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
     let runtime: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let security = Arc::new(SecurityPolicy::from_config(
         &config.autonomy,
         &config.workspace_dir,
     ));
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
@@ -1140,7 +1859,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
         ),
         (
             "self_upgrade",
-            "Check for and apply ZeroClaw updates. Use check_only=true to see pending changes; set check_only=false with approved=true to pull and rebuild.",
+            "Check for and apply ZeroClaw updates. Use check_only=true to see pending changes; set check_only=false to pull and rebuild once the user approves.",
         ),
         (
             "computer",
@@ -1176,7 +1895,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
     } else {
         None
     };
-    let mut system_prompt = build_system_prompt(
+    // Channels park APPROVAL_REQUIRED calls and ask the user themselves, so
+    // the model must not negotiate approval in chat.
+    let mut system_prompt = system_prompt_with_approvals(
         &workspace,
         &model,
         &tool_descs,
@@ -1187,8 +1908,13 @@ pub async fn start_channels(config: Config) -> Result<()> {
         Some(&config.autonomy),
         // --- upstream: bootstrap compaction ---
         bootstrap_max_chars,
+        true,
     );
-    system_prompt.push_str(&build_tool_instructions(tools_registry.as_ref()));
+    if !provider.supports_native_tools() {
+        system_prompt.push_str(&build_tool_instructions(tools_registry.as_ref()));
+    }
+
+    let approvals = approval_broker(&config, Arc::clone(&security));
 
     if !skills.is_empty() {
         println!(
@@ -1357,8 +2083,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
                     if hist.first().map_or(false, |m| m.role == "system") {
                         hist[0] = ChatMessage::system(&system_prompt);
                     }
-                    trim_history(&mut hist);
-                    trim_history_by_size(&mut hist);
+                    let budget = ContextBudget::for_model(&capabilities, &model);
+                    trim_history(&mut hist, &budget);
+                    trim_history_by_tokens(&mut hist, &budget);
                     conversations.insert(sender_id, hist);
                 }
             }
@@ -1386,6 +2113,17 @@ pub async fn start_channels(config: Config) -> Result<()> {
         temperature,
         auto_save_memory: config.memory.auto_save,
         conversations,
+        cost_tracker: create_cost_tracker(&config),
+        model_router,
+        capabilities,
+        show_reasoning: config.channels_config.show_reasoning,
+        transcriber: transcription::create_transcriber(&config.transcription)?.map(Arc::from),
+        synthesizer: speech::create_speech_synthesizer(&config.speech)?.map(Arc::from),
+        speech: Arc::new(config.speech.clone()),
+        approvals: Arc::new(approvals),
+        max_parallel_tools: config.agent.tool_parallelism(),
+        turn_budgets: Arc::new(TurnBudgets::from_config(&config)),
+        turns: Arc::new(ActiveTurns::new(config.channels_config.on_new_message)),
     });
 
     run_message_dispatch_loop(rx, runtime_ctx, max_in_flight_messages).await;
@@ -1403,8 +2141,11 @@ mod tests {
     use super::*;
     use crate::memory::{Memory, MemoryCategory, SqliteMemory};
     use crate::observability::NoopObserver;
-    use crate::providers::{ChatMessage, ChatResponse, Provider, ToolCall};
+    use crate::providers::{
+        ChatMessage, ChatRequest, ChatResponse, ChatStream, Provider, StreamEvent, ToolCall,
+    };
     use crate::tools::{Tool, ToolResult};
+    use futures_util::StreamExt;
     use std::collections::HashMap;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
@@ -1577,6 +2318,20 @@ mod tests {
             temperature: 0.0,
             auto_save_memory: false,
             conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            show_reasoning: false,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(crate::config::SpeechConfig::default()),
+            approvals: Arc::new(ApprovalBroker::new(
+                Arc::new(SecurityPolicy::default()),
+                Duration::from_secs(600),
+            )),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            turns: Arc::new(ActiveTurns::new(TurnInterruptMode::default())),
         });
 
         process_channel_message(
@@ -1587,7 +2342,7 @@ mod tests {
                 content: "What is the BTC price now?".to_string(),
                 channel: "test-channel".to_string(),
                 timestamp: 1,
-                attachments: vec![],
+                ..Default::default()
             },
         )
         .await;
@@ -1599,40 +2354,556 @@ mod tests {
         assert!(!sent_messages[0].contains("mock_price"));
     }
 
-    struct NoopMemory;
+    /// Provider that only answers through native (structured) tool calls.
+    struct NativeToolCallingProvider;
 
     #[async_trait::async_trait]
-    impl Memory for NoopMemory {
-        fn name(&self) -> &str {
-            "noop"
-        }
-
-        async fn store(
+    impl Provider for NativeToolCallingProvider {
+        async fn chat_with_system(
             &self,
-            _key: &str,
-            _content: &str,
-            _category: crate::memory::MemoryCategory,
-        ) -> anyhow::Result<()> {
-            Ok(())
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("text protocol should not be used")
         }
 
-        async fn recall(
+        async fn chat(
             &self,
-            _query: &str,
-            _limit: usize,
-        ) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
-            Ok(Vec::new())
-        }
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let tools = request.tools.unwrap_or_default();
+            assert!(tools.iter().any(|spec| spec.name == "mock_price"));
+
+            let tool_result = request.messages.iter().find(|m| m.role == "tool");
+            if let Some(result) = tool_result {
+                let value: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+                assert_eq!(value["tool_call_id"], "call_1");
+                assert!(value["content"].as_str().unwrap().contains("65000"));
+                return Ok(ChatResponse {
+                    text: Some("BTC is at $65,000.".into()),
+                    tool_calls: vec![],
+                    usage: None,
+                    reasoning: None,
+                });
+            }
 
-        async fn get(&self, _key: &str) -> anyhow::Result<Option<crate::memory::MemoryEntry>> {
-            Ok(None)
+            Ok(ChatResponse {
+                text: Some(r#"Checking {"symbol": "BTC"} now."#.into()),
+                tool_calls: vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "mock_price".into(),
+                    arguments: r#"{"symbol":"BTC"}"#.into(),
+                }],
+                usage: None,
+                reasoning: None,
+            })
         }
 
-        async fn list(
-            &self,
-            _category: Option<&crate::memory::MemoryCategory>,
-        ) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
-            Ok(Vec::new())
+        fn supports_native_tools(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_uses_native_tool_calls_when_supported() {
+        let channel_impl = Arc::new(RecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let conversations = Arc::new(DashMap::new());
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::new(NativeToolCallingProvider),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            conversations: Arc::clone(&conversations),
+            cost_tracker: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            show_reasoning: false,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(crate::config::SpeechConfig::default()),
+            approvals: Arc::new(ApprovalBroker::new(
+                Arc::new(SecurityPolicy::default()),
+                Duration::from_secs(600),
+            )),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            turns: Arc::new(ActiveTurns::new(TurnInterruptMode::default())),
+        });
+
+        process_channel_message(
+            runtime_ctx,
+            traits::ChannelMessage {
+                id: "msg-1".to_string(),
+                sender: "alice".to_string(),
+                content: "What is the BTC price now?".to_string(),
+                channel: "test-channel".to_string(),
+                timestamp: 1,
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let sent_messages = channel_impl.sent_messages.lock().await;
+        assert_eq!(sent_messages.len(), 1);
+        assert_eq!(sent_messages[0], "alice:BTC is at $65,000.");
+
+        let history = conversations.iter().next().unwrap().value().clone();
+        let roles: Vec<&str> = history.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "tool", "assistant"]);
+        let assistant: serde_json::Value = serde_json::from_str(&history[2].content).unwrap();
+        assert_eq!(assistant["tool_calls"][0]["id"], "call_1");
+    }
+
+    #[derive(Default)]
+    struct DraftRecordingChannel {
+        events: tokio::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for DraftRecordingChannel {
+        fn name(&self) -> &str {
+            "draft-channel"
+        }
+
+        async fn send(&self, message: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.events.lock().await.push(format!("send:{message}"));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn supports_voice_replies(&self) -> bool {
+            true
+        }
+
+        async fn send_voice_reply(
+            &self,
+            speech: &speech::SynthesizedSpeech,
+            _recipient: &str,
+        ) -> anyhow::Result<()> {
+            self.events
+                .lock()
+                .await
+                .push(format!("voice:{}", String::from_utf8_lossy(&speech.audio)));
+            Ok(())
+        }
+
+        fn supports_image_replies(&self) -> bool {
+            true
+        }
+
+        async fn send_image(
+            &self,
+            image: Vec<u8>,
+            mime_type: &str,
+            _recipient: &str,
+        ) -> anyhow::Result<()> {
+            self.events.lock().await.push(format!(
+                "image:{mime_type}:{}",
+                String::from_utf8_lossy(&image)
+            ));
+            Ok(())
+        }
+
+        fn supports_draft_updates(&self) -> bool {
+            true
+        }
+
+        async fn send_draft(&self, message: &str, _recipient: &str) -> anyhow::Result<String> {
+            self.events.lock().await.push(format!("draft:{message}"));
+            Ok("draft-1".to_string())
+        }
+
+        async fn update_draft(
+            &self,
+            message: &str,
+            _recipient: &str,
+            _message_id: &str,
+        ) -> anyhow::Result<()> {
+            self.events.lock().await.push(format!("update:{message}"));
+            Ok(())
+        }
+
+        async fn finalize_draft(
+            &self,
+            message: &str,
+            _recipient: &str,
+            message_id: &str,
+        ) -> anyhow::Result<()> {
+            self.events
+                .lock()
+                .await
+                .push(format!("finalize:{message_id}:{message}"));
+            Ok(())
+        }
+    }
+
+    struct StreamingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for StreamingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("Hello world".to_string())
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        async fn stream_chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatStream> {
+            let events = vec![
+                Ok(StreamEvent::TextDelta("Hello".to_string())),
+                Ok(StreamEvent::TextDelta(" world".to_string())),
+            ];
+            Ok(futures_util::stream::iter(events).boxed())
+        }
+    }
+
+    fn draft_runtime_ctx(
+        channel: Arc<dyn Channel>,
+        provider: Arc<dyn Provider>,
+    ) -> Arc<ChannelRuntimeContext> {
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider,
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            show_reasoning: false,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(crate::config::SpeechConfig::default()),
+            approvals: Arc::new(ApprovalBroker::new(
+                Arc::new(SecurityPolicy::default()),
+                Duration::from_secs(600),
+            )),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            turns: Arc::new(ActiveTurns::new(TurnInterruptMode::default())),
+        })
+    }
+
+    fn draft_channel_message() -> traits::ChannelMessage {
+        traits::ChannelMessage {
+            id: "msg-1".to_string(),
+            sender: "alice".to_string(),
+            content: "hi".to_string(),
+            channel: "draft-channel".to_string(),
+            timestamp: 1,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_streams_into_draft_and_finalizes() {
+        let channel_impl = Arc::new(DraftRecordingChannel::default());
+        let ctx = draft_runtime_ctx(channel_impl.clone(), Arc::new(StreamingProvider));
+
+        process_channel_message(ctx, draft_channel_message()).await;
+
+        let events = channel_impl.events.lock().await;
+        assert_eq!(events.first().map(String::as_str), Some("draft:Hello"));
+        assert_eq!(
+            events.last().map(String::as_str),
+            Some("finalize:draft-1:Hello world")
+        );
+        assert!(!events.iter().any(|e| e.starts_with("send:")));
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_sends_normally_without_streaming_provider() {
+        let channel_impl = Arc::new(DraftRecordingChannel::default());
+        let ctx = draft_runtime_ctx(
+            channel_impl.clone(),
+            Arc::new(SlowProvider {
+                delay: Duration::from_millis(1),
+            }),
+        );
+
+        process_channel_message(ctx, draft_channel_message()).await;
+
+        let events = channel_impl.events.lock().await;
+        assert_eq!(events.len(), 1);
+        assert!(events[0].starts_with("send:echo:"));
+    }
+
+    struct SpellingSynthesizer;
+
+    #[async_trait::async_trait]
+    impl SpeechSynthesizer for SpellingSynthesizer {
+        fn name(&self) -> &str {
+            "spelling"
+        }
+
+        async fn synthesize(&self, text: &str) -> anyhow::Result<speech::SynthesizedSpeech> {
+            Ok(speech::SynthesizedSpeech {
+                audio: text.to_uppercase().into_bytes(),
+                mime_type: "audio/ogg".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_answers_voice_with_voice() {
+        let channel_impl = Arc::new(DraftRecordingChannel::default());
+        let mut ctx = Arc::try_unwrap(draft_runtime_ctx(
+            channel_impl.clone(),
+            Arc::new(StreamingProvider),
+        ))
+        .ok()
+        .unwrap();
+        ctx.synthesizer = Some(Arc::new(SpellingSynthesizer));
+        let ctx = Arc::new(ctx);
+
+        let mut msg = draft_channel_message();
+        msg.attachments = vec![traits::MediaAttachment::new(traits::MediaType::Voice)];
+        process_channel_message(Arc::clone(&ctx), msg).await;
+        assert_eq!(*channel_impl.events.lock().await, ["voice:HELLO WORLD"]);
+
+        let history = ctx.conversations.iter().next().unwrap().value().clone();
+        assert_eq!(history.last().unwrap().content, "Hello world");
+
+        channel_impl.events.lock().await.clear();
+        process_channel_message(ctx, draft_channel_message()).await;
+        let events = channel_impl.events.lock().await;
+        assert!(events.iter().all(|e| !e.starts_with("voice:")));
+    }
+
+    /// Stands in for `mock_price` but renders a chart for the user.
+    struct MockChartTool;
+
+    #[async_trait::async_trait]
+    impl Tool for MockChartTool {
+        fn name(&self) -> &str {
+            "mock_price"
+        }
+
+        fn description(&self) -> &str {
+            "Return a mocked BTC price chart"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        fn delivers_images(&self) -> bool {
+            true
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            use base64::Engine;
+            Ok(ToolResult {
+                success: true,
+                output: "chart rendered".to_string(),
+                error: None,
+                image_base64: Some(base64::engine::general_purpose::STANDARD.encode("CHART")),
+                image_mime: Some("image/png".to_string()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn process_channel_message_sends_tool_images_after_reply() {
+        let channel_impl = Arc::new(DraftRecordingChannel::default());
+        let mut ctx = Arc::try_unwrap(draft_runtime_ctx(
+            channel_impl.clone(),
+            Arc::new(ToolCallingProvider),
+        ))
+        .ok()
+        .unwrap();
+        ctx.tools_registry = Arc::new(vec![Box::new(MockChartTool)]);
+
+        process_channel_message(Arc::new(ctx), draft_channel_message()).await;
+        let events = channel_impl.events.lock().await;
+        assert_eq!(events.len(), 2);
+        assert!(events[0].starts_with("send:BTC is currently around"));
+        assert_eq!(events[1], "image:image/png:CHART");
+    }
+
+    /// `mock_price` that needs approval and counts approved runs.
+    #[derive(Default)]
+    struct GuardedPriceTool {
+        approved_runs: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for GuardedPriceTool {
+        fn name(&self) -> &str {
+            "mock_price"
+        }
+
+        fn description(&self) -> &str {
+            "Return a mocked BTC price after approval"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+            let approved = args.get("approved").and_then(serde_json::Value::as_bool);
+            if approved != Some(true) {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some("APPROVAL_REQUIRED: needs the user's consent".to_string()),
+                    image_base64: None,
+                    image_mime: None,
+                });
+            }
+            assert_eq!(args["symbol"], "BTC");
+            self.approved_runs
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ToolResult {
+                success: true,
+                output: "BTC 65000".to_string(),
+                error: None,
+                image_base64: None,
+                image_mime: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn approval_reply_runs_parked_call_without_the_model() {
+        let channel_impl = Arc::new(DraftRecordingChannel::default());
+        let tool = GuardedPriceTool::default();
+        let approved_runs = Arc::clone(&tool.approved_runs);
+        let mut ctx = Arc::try_unwrap(draft_runtime_ctx(
+            channel_impl.clone(),
+            Arc::new(ToolCallingProvider),
+        ))
+        .ok()
+        .unwrap();
+        ctx.tools_registry = Arc::new(vec![Box::new(tool)]);
+        let ctx = Arc::new(ctx);
+
+        process_channel_message(Arc::clone(&ctx), draft_channel_message()).await;
+        let prompt = channel_impl
+            .events
+            .lock()
+            .await
+            .iter()
+            .find_map(|e| {
+                e.strip_prefix("send:🔐 Approval required (")
+                    .map(str::to_string)
+            })
+            .expect("approval request sent");
+        let id = prompt[..6].to_string();
+        // The draft channel has no buttons, so the choices come as a menu.
+        assert!(prompt.contains(&format!("• ✅ Approve: approve {id}")));
+        assert_eq!(approved_runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // A reply from someone else cannot decide it.
+        let mut other = draft_channel_message();
+        other.sender = "mallory".to_string();
+        other.content = format!("approve {id}");
+        process_channel_message(Arc::clone(&ctx), other).await;
+        assert_eq!(approved_runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        channel_impl.events.lock().await.clear();
+        let mut reply = draft_channel_message();
+        reply.content = format!("approve {id}");
+        reply.kind = MessageKind::Callback;
+        process_channel_message(Arc::clone(&ctx), reply.clone()).await;
+        assert_eq!(approved_runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+        {
+            let events = channel_impl.events.lock().await;
+            assert_eq!(events.len(), 1);
+            assert!(events[0].starts_with(&format!("send:✅ Approved {id}")));
+            assert!(events[0].contains("BTC 65000"));
+        }
+        let history = ctx
+            .conversations
+            .get("draft-channel_alice")
+            .unwrap()
+            .clone();
+        assert!(history
+            .last()
+            .unwrap()
+            .content
+            .starts_with(&format!("[Approval] The user approved request {id}")));
+
+        // Requests are single use.
+        process_channel_message(Arc::clone(&ctx), reply).await;
+        assert_eq!(approved_runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    struct NoopMemory;
+
+    #[async_trait::async_trait]
+    impl Memory for NoopMemory {
+        fn name(&self) -> &str {
+            "noop"
+        }
+
+        async fn store(
+            &self,
+            _key: &str,
+            _content: &str,
+            _category: crate::memory::MemoryCategory,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn recall(
+            &self,
+            _query: &str,
+            _limit: usize,
+        ) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn get(&self, _key: &str) -> anyhow::Result<Option<crate::memory::MemoryEntry>> {
+            Ok(None)
+        }
+
+        async fn list(
+            &self,
+            _category: Option<&crate::memory::MemoryCategory>,
+        ) -> anyhow::Result<Vec<crate::memory::MemoryEntry>> {
+            Ok(Vec::new())
         }
 
         async fn forget(&self, _key: &str) -> anyhow::Result<bool> {
@@ -1669,6 +2940,20 @@ mod tests {
             temperature: 0.0,
             auto_save_memory: false,
             conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            show_reasoning: false,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(crate::config::SpeechConfig::default()),
+            approvals: Arc::new(ApprovalBroker::new(
+                Arc::new(SecurityPolicy::default()),
+                Duration::from_secs(600),
+            )),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            turns: Arc::new(ActiveTurns::new(TurnInterruptMode::default())),
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(4);
@@ -1678,7 +2963,7 @@ mod tests {
             content: "hello".to_string(),
             channel: "test-channel".to_string(),
             timestamp: 1,
-            attachments: vec![],
+            ..Default::default()
         })
         .await
         .unwrap();
@@ -1688,7 +2973,7 @@ mod tests {
             content: "world".to_string(),
             channel: "test-channel".to_string(),
             timestamp: 2,
-            attachments: vec![],
+            ..Default::default()
         })
         .await
         .unwrap();
@@ -1708,6 +2993,76 @@ mod tests {
         assert_eq!(sent_messages.len(), 2);
     }
 
+    #[tokio::test]
+    async fn stop_message_cancels_the_running_turn() {
+        let channel_impl = Arc::new(RecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let conversations = Arc::new(DashMap::new());
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::new(SlowProvider {
+                delay: Duration::from_secs(30),
+            }),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            conversations: Arc::clone(&conversations),
+            cost_tracker: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            show_reasoning: false,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(crate::config::SpeechConfig::default()),
+            approvals: Arc::new(ApprovalBroker::new(
+                Arc::new(SecurityPolicy::default()),
+                Duration::from_secs(600),
+            )),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            turns: Arc::new(ActiveTurns::new(TurnInterruptMode::Steer)),
+        });
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(4);
+        // One worker: the stop must get through while it is busy.
+        let dispatch = tokio::spawn(run_message_dispatch_loop(rx, runtime_ctx, 1));
+        for (id, content) in [("1", "deploy everything"), ("2", "Stop!")] {
+            tx.send(traits::ChannelMessage {
+                id: id.to_string(),
+                sender: "alice".to_string(),
+                content: content.to_string(),
+                channel: "test-channel".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        drop(tx);
+
+        tokio::time::timeout(Duration::from_secs(5), dispatch)
+            .await
+            .expect("cancelled turn should end promptly")
+            .unwrap();
+
+        let sent_messages = channel_impl.sent_messages.lock().await;
+        assert_eq!(sent_messages.as_slice(), ["alice:⏹️ Stopped."]);
+        let history = conversations.get("test-channel_alice").unwrap();
+        assert!(history
+            .iter()
+            .any(|msg| msg.content.contains("deploy everything")));
+        let last = history.last().unwrap();
+        assert!(last.content.contains("Stopped by the user"));
+    }
+
     #[test]
     fn prompt_contains_all_sections() {
         let ws = make_workspace();
@@ -1753,6 +3108,32 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
         assert!(prompt.contains("Prefer `trash` over `rm`"));
     }
 
+    #[test]
+    fn brokered_prompt_never_asks_the_model_to_self_approve() {
+        let ws = make_workspace();
+        let autonomy = crate::config::AutonomyConfig {
+            allowed_commands: vec!["ls".into(), "rm".into()],
+            ..crate::config::AutonomyConfig::default()
+        };
+        let prompt = system_prompt_with_approvals(
+            ws.path(),
+            "model",
+            &[],
+            &[],
+            None,
+            &[],
+            Some(&autonomy),
+            None,
+            true,
+        );
+
+        assert!(prompt.contains("## Approval Requests"));
+        assert!(!prompt.contains("## Approval Protocol"));
+        assert!(!prompt.contains("approved=true"));
+        assert!(!prompt.contains("with `approved: true`"));
+        assert!(prompt.contains("`rm` (approval needed)"));
+    }
+
     #[test]
     fn prompt_injects_workspace_files() {
         let ws = make_workspace();
@@ -1932,7 +3313,7 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             content: "hello".into(),
             channel: "slack".into(),
             timestamp: 1,
-            attachments: vec![],
+            ..Default::default()
         };
 
         assert_eq!(conversation_memory_key(&msg), "slack_U123_msg_abc123");
@@ -1946,7 +3327,7 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             content: "first".into(),
             channel: "slack".into(),
             timestamp: 1,
-            attachments: vec![],
+            ..Default::default()
         };
         let msg2 = traits::ChannelMessage {
             id: "msg_2".into(),
@@ -1954,7 +3335,7 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             content: "second".into(),
             channel: "slack".into(),
             timestamp: 2,
-            attachments: vec![],
+            ..Default::default()
         };
 
         assert_ne!(
@@ -1974,7 +3355,7 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             content: "I'm Paul".into(),
             channel: "slack".into(),
             timestamp: 1,
-            attachments: vec![],
+            ..Default::default()
         };
         let msg2 = traits::ChannelMessage {
             id: "msg_2".into(),
@@ -1982,7 +3363,7 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             content: "I'm 45".into(),
             channel: "slack".into(),
             timestamp: 2,
-            attachments: vec![],
+            ..Default::default()
         };
 
         mem.store(
@@ -2243,4 +3624,56 @@ let prompt = build_system_prompt(ws.path(), "model", &[], &[], None, &[], None,
             .contains("listen boom"));
         assert!(calls.load(Ordering::SeqCst) >= 1);
     }
+
+    struct EchoTranscriber;
+
+    #[async_trait::async_trait]
+    impl Transcriber for EchoTranscriber {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        async fn transcribe(
+            &self,
+            path: &std::path::Path,
+            _mime_type: Option<&str>,
+        ) -> anyhow::Result<String> {
+            Ok(std::fs::read_to_string(path)?)
+        }
+    }
+
+    #[tokio::test]
+    async fn transcribe_attachments_appends_transcript_and_keeps_file() {
+        let tmp = TempDir::new().unwrap();
+        let voice_path = tmp.path().join("voice.ogg");
+        std::fs::write(&voice_path, "remind me to call mom").unwrap();
+
+        let mut voice = traits::MediaAttachment::new(traits::MediaType::Voice);
+        voice.file_path = Some(voice_path.display().to_string());
+        let mut photo = traits::MediaAttachment::new(traits::MediaType::Photo);
+        photo.file_path = Some(voice_path.display().to_string());
+        let msg = traits::ChannelMessage {
+            content: "[Voice message, 3s]".into(),
+            attachments: vec![voice, photo],
+            ..Default::default()
+        };
+
+        let msg = transcribe_attachments(Some(&EchoTranscriber), msg).await;
+        assert_eq!(
+            msg.content,
+            "[Voice message, 3s]\n[Voice transcript] remind me to call mom"
+        );
+        assert_eq!(msg.attachments.len(), 2);
+        assert_eq!(
+            msg.attachments[0]
+                .metadata
+                .get("transcript")
+                .map(String::as_str),
+            Some("remind me to call mom")
+        );
+        assert!(msg.attachments[1].metadata.is_empty());
+
+        let untouched = transcribe_attachments(None, msg.clone()).await;
+        assert_eq!(untouched.content, msg.content);
+    }
 }
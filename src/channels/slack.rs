@@ -1,25 +1,73 @@
-use super::traits::{Channel, ChannelMessage};
+use super::traits::{Button, Channel, ChannelMessage, MessageKind};
+use crate::util::truncate_with_ellipsis;
 use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
-/// Slack channel — polls conversations.history via Web API
+/// Slack limits section text to 3000 characters.
+const SLACK_MAX_SECTION_TEXT: usize = 3000;
+
+/// Slack channel — polls conversations.history via Web API. With an app
+/// token, button presses are received over Socket Mode.
 pub struct SlackChannel {
     bot_token: String,
+    app_token: Option<String>,
     channel_id: Option<String>,
     allowed_users: Vec<String>,
     client: reqwest::Client,
 }
 
+/// Block Kit blocks for a message with buttons: a section with the text and
+/// an actions block whose button values are the callback IDs.
+fn interactive_blocks(message: &str, buttons: &[Button]) -> serde_json::Value {
+    let elements: Vec<serde_json::Value> = buttons
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let mut button = serde_json::json!({
+                "type": "button",
+                "text": { "type": "plain_text", "text": b.label },
+                "action_id": format!("zeroclaw_{i}"),
+                "value": b.callback_id,
+            });
+            if i == 0 {
+                button["style"] = "primary".into();
+            }
+            button
+        })
+        .collect();
+    serde_json::json!([
+        {
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": truncate_with_ellipsis(message, SLACK_MAX_SECTION_TEXT - 3),
+            },
+        },
+        { "type": "actions", "elements": elements },
+    ])
+}
+
 impl SlackChannel {
     pub fn new(bot_token: String, channel_id: Option<String>, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
+            app_token: None,
             channel_id,
             allowed_users,
             client: reqwest::Client::new(),
         }
     }
 
+    /// Receive button presses over Socket Mode using an app-level token
+    /// (`xapp-…`). Without one, buttons fall back to a text menu.
+    #[must_use]
+    pub fn with_app_token(mut self, app_token: Option<String>) -> Self {
+        self.app_token = app_token.filter(|t| !t.is_empty());
+        self
+    }
+
     /// Check if a Slack user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -44,25 +92,14 @@ impl SlackChannel {
             .and_then(|u| u.as_str())
             .map(String::from)
     }
-}
-
-#[async_trait]
-impl Channel for SlackChannel {
-    fn name(&self) -> &str {
-        "slack"
-    }
-
-    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
-        let body = serde_json::json!({
-            "channel": channel,
-            "text": message
-        });
 
+    /// Post a `chat.postMessage` body, checking both HTTP and Slack errors.
+    async fn post_message(&self, body: &serde_json::Value) -> anyhow::Result<()> {
         let resp = self
             .client
             .post("https://slack.com/api/chat.postMessage")
             .bearer_auth(&self.bot_token)
-            .json(&body)
+            .json(body)
             .send()
             .await?;
 
@@ -89,7 +126,140 @@ impl Channel for SlackChannel {
         Ok(())
     }
 
-    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+    /// Turn a Socket Mode `block_actions` payload into callback messages,
+    /// one per pressed button from an allowed user.
+    fn parse_block_actions(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
+        if payload.get("type").and_then(|t| t.as_str()) != Some("block_actions") {
+            return Vec::new();
+        }
+        let user = payload
+            .pointer("/user/id")
+            .and_then(|u| u.as_str())
+            .unwrap_or("");
+        if !self.is_user_allowed(user) {
+            tracing::warn!("Slack: ignoring button press from unauthorized user: {user}");
+            return Vec::new();
+        }
+        let Some(channel) = payload.pointer("/channel/id").and_then(|c| c.as_str()) else {
+            return Vec::new();
+        };
+
+        payload
+            .get("actions")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|action| action.get("value").and_then(|v| v.as_str()))
+            .filter(|value| !value.is_empty())
+            .map(|value| ChannelMessage {
+                id: Uuid::new_v4().to_string(),
+                sender: channel.to_string(),
+                content: value.to_string(),
+                channel: "slack".to_string(),
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                kind: MessageKind::Callback,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Open a Socket Mode connection and return its WebSocket URL.
+    async fn open_socket(&self, app_token: &str) -> anyhow::Result<String> {
+        let resp: serde_json::Value = self
+            .client
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        resp.get("url")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .ok_or_else(|| {
+                let err = resp
+                    .get("error")
+                    .and_then(|e| e.as_str())
+                    .unwrap_or("unknown");
+                anyhow::anyhow!("Slack apps.connections.open failed: {err}")
+            })
+    }
+
+    /// Receive button presses over Socket Mode, reconnecting when Slack
+    /// asks to or the connection drops. Returns once `tx` is closed.
+    async fn listen_interactions(
+        &self,
+        app_token: &str,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        loop {
+            let (ws_stream, _) = match self.open_socket(app_token).await {
+                Ok(url) => match tokio_tungstenite::connect_async(&url).await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::warn!("Slack Socket Mode connect error: {e}");
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!("{e}");
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            tracing::info!("Slack Socket Mode connected for interactions");
+            let (mut write, mut read) = ws_stream.split();
+
+            while let Some(frame) = read.next().await {
+                let text = match frame {
+                    Ok(Message::Text(t)) => t,
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => continue,
+                };
+                let Ok(envelope) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+
+                // Every envelope must be acknowledged within 3 seconds.
+                if let Some(id) = envelope.get("envelope_id").and_then(|e| e.as_str()) {
+                    let ack = serde_json::json!({ "envelope_id": id });
+                    if write.send(Message::Text(ack.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+
+                match envelope.get("type").and_then(|t| t.as_str()) {
+                    Some("interactive") => {
+                        let Some(payload) = envelope.get("payload") else {
+                            continue;
+                        };
+                        for msg in self.parse_block_actions(payload) {
+                            if tx.send(msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    Some("disconnect") => break,
+                    _ => {}
+                }
+            }
+
+            if tx.is_closed() {
+                return Ok(());
+            }
+            tracing::info!("Slack Socket Mode connection closed, reconnecting");
+        }
+    }
+
+    /// Poll `conversations.history` of the configured channel for messages.
+    async fn poll_history(
+        &self,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
         let channel_id = self
             .channel_id
             .clone()
@@ -168,7 +338,7 @@ impl Channel for SlackChannel {
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap_or_default()
                             .as_secs(),
-                        attachments: vec![],
+                        ..Default::default()
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -178,6 +348,31 @@ impl Channel for SlackChannel {
             }
         }
     }
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "channel": channel,
+            "text": message
+        });
+        self.post_message(&body).await
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let Some(app_token) = self.app_token.as_deref() else {
+            return self.poll_history(tx).await;
+        };
+        tokio::select! {
+            result = self.poll_history(tx.clone()) => result,
+            result = self.listen_interactions(app_token, tx) => result,
+        }
+    }
 
     async fn health_check(&self) -> bool {
         self.client
@@ -188,6 +383,29 @@ impl Channel for SlackChannel {
             .map(|r| r.status().is_success())
             .unwrap_or(false)
     }
+
+    fn supports_buttons(&self) -> bool {
+        self.app_token.is_some()
+    }
+
+    async fn send_interactive(
+        &self,
+        message: &str,
+        buttons: &[Button],
+        channel: &str,
+    ) -> anyhow::Result<()> {
+        if !self.supports_buttons() {
+            return self
+                .send(&super::traits::text_menu(message, buttons), channel)
+                .await;
+        }
+        let body = serde_json::json!({
+            "channel": channel,
+            "text": message,
+            "blocks": interactive_blocks(message, buttons),
+        });
+        self.post_message(&body).await
+    }
 }
 
 #[cfg(test)]
@@ -200,6 +418,40 @@ mod tests {
         assert_eq!(ch.name(), "slack");
     }
 
+    #[test]
+    fn buttons_need_an_app_token() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec![]);
+        assert!(!ch.supports_buttons());
+        let ch = ch.with_app_token(Some(String::new()));
+        assert!(!ch.supports_buttons());
+        let ch = ch.with_app_token(Some("xapp-fake".into()));
+        assert!(ch.supports_buttons());
+    }
+
+    #[test]
+    fn block_actions_become_callback_messages() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U111".into()]);
+        let payload = serde_json::json!({
+            "type": "block_actions",
+            "user": { "id": "U111" },
+            "channel": { "id": "C1" },
+            "actions": [{ "action_id": "zeroclaw_0", "value": "approve ab12cd" }]
+        });
+        let msgs = ch.parse_block_actions(&payload);
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].kind, MessageKind::Callback);
+        assert_eq!(msgs[0].sender, "C1");
+        assert_eq!(msgs[0].content, "approve ab12cd");
+
+        let mut stranger = payload.clone();
+        stranger["user"]["id"] = "U999".into();
+        assert!(ch.parse_block_actions(&stranger).is_empty());
+
+        let blocks = interactive_blocks("Run it?", &[Button::new("Approve", "approve ab12cd")]);
+        assert_eq!(blocks[1]["elements"][0]["value"], "approve ab12cd");
+        assert_eq!(blocks[1]["elements"][0]["style"], "primary");
+    }
+
     #[test]
     fn slack_channel_with_channel_id() {
         let ch = SlackChannel::new("xoxb-fake".into(), Some("C12345".into()), vec![]);
@@ -0,0 +1,334 @@
+use super::transcription::openai_api_key;
+use crate::config::SpeechConfig;
+use async_trait::async_trait;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Synthesized audio ready to send as a voice message.
+#[derive(Debug, Clone)]
+pub struct SynthesizedSpeech {
+    pub audio: Vec<u8>,
+    pub mime_type: String,
+}
+
+impl SynthesizedSpeech {
+    /// File name with an extension matching the audio format, for uploads.
+    pub fn file_name(&self) -> &'static str {
+        match self.mime_type.as_str() {
+            "audio/mpeg" => "reply.mp3",
+            "audio/aac" => "reply.aac",
+            "audio/wav" => "reply.wav",
+            _ => "reply.ogg",
+        }
+    }
+}
+
+/// Trait for text-to-speech backends — turn reply text into audio
+#[async_trait]
+pub trait SpeechSynthesizer: Send + Sync {
+    /// Backend name
+    fn name(&self) -> &str;
+
+    /// Synthesize `text` into audio
+    async fn synthesize(&self, text: &str) -> anyhow::Result<SynthesizedSpeech>;
+}
+
+/// MIME type for a speech API `response_format`.
+fn mime_for_format(format: &str) -> &'static str {
+    match format {
+        "mp3" => "audio/mpeg",
+        "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        _ => "audio/ogg",
+    }
+}
+
+// ── OpenAI-compatible HTTP backend ───────────────────────────
+
+/// OpenAI `/audio/speech` API; also served by local TTS servers
+/// (openedai-speech, Kokoro-FastAPI, LocalAI).
+pub struct OpenAiSpeech {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    voice: String,
+    format: String,
+}
+
+impl OpenAiSpeech {
+    pub fn new(
+        base_url: &str,
+        api_key: Option<&str>,
+        model: &str,
+        voice: &str,
+        format: &str,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .connect_timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|k| !k.is_empty()).map(ToString::to_string),
+            model: model.to_string(),
+            voice: voice.to_string(),
+            format: format.to_string(),
+        }
+    }
+
+    fn speech_url(&self) -> String {
+        let Ok(url) = reqwest::Url::parse(&self.base_url) else {
+            return format!("{}/v1/audio/speech", self.base_url);
+        };
+
+        let path = url.path().trim_end_matches('/');
+        if path.ends_with("/audio/speech") {
+            self.base_url.clone()
+        } else if path.is_empty() {
+            format!("{}/v1/audio/speech", self.base_url)
+        } else {
+            format!("{}/audio/speech", self.base_url)
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for OpenAiSpeech {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    async fn synthesize(&self, text: &str) -> anyhow::Result<SynthesizedSpeech> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": text,
+            "voice": self.voice,
+            "response_format": self.format,
+        });
+
+        let mut request = self.client.post(self.speech_url()).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let resp = request.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Speech API error {status}: {text}");
+        }
+
+        Ok(SynthesizedSpeech {
+            audio: resp.bytes().await?.to_vec(),
+            mime_type: mime_for_format(&self.format).to_string(),
+        })
+    }
+}
+
+// ── Local command backend ────────────────────────────────────
+
+/// Runs a local TTS CLI (piper, espeak-ng) through `sh -c`, writing the
+/// text to stdin and reading audio from stdout.
+pub struct CommandSpeech {
+    command: String,
+    format: String,
+    timeout: Duration,
+}
+
+impl CommandSpeech {
+    pub fn new(command: &str, format: &str, timeout: Duration) -> Self {
+        Self {
+            command: command.to_string(),
+            format: format.to_string(),
+            timeout,
+        }
+    }
+
+    async fn run(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "Speech command failed ({}): {}",
+                output.status,
+                stderr.trim()
+            );
+        }
+        if output.stdout.is_empty() {
+            anyhow::bail!("Speech command produced no audio");
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[async_trait]
+impl SpeechSynthesizer for CommandSpeech {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    async fn synthesize(&self, text: &str) -> anyhow::Result<SynthesizedSpeech> {
+        let audio = tokio::time::timeout(self.timeout, self.run(text))
+            .await
+            .map_err(|_| anyhow::anyhow!("Speech command timed out after {:?}", self.timeout))??;
+
+        Ok(SynthesizedSpeech {
+            audio,
+            mime_type: mime_for_format(&self.format).to_string(),
+        })
+    }
+}
+
+// ── Text preparation ─────────────────────────────────────────
+
+/// Strip Markdown so the synthesizer reads the words, not the markup.
+/// Code blocks are replaced with a short placeholder.
+pub fn speakable_text(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if !in_code_block {
+                out.push_str("(code omitted)\n");
+            }
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let line = trimmed
+            .trim_start_matches('#')
+            .trim_start_matches('>')
+            .trim_start();
+        let line = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .unwrap_or(line);
+        out.extend(line.chars().filter(|c| !matches!(c, '*' | '_' | '`' | '~')));
+        out.push('\n');
+    }
+
+    out.trim().to_string()
+}
+
+// ── Factory ──────────────────────────────────────────────────
+
+/// Build the configured synthesizer, or `None` when voice replies are off.
+pub fn create_speech_synthesizer(
+    config: &SpeechConfig,
+) -> anyhow::Result<Option<Box<dyn SpeechSynthesizer>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    match config.backend.as_str() {
+        "openai" => {
+            let api_key = openai_api_key(config.api_key.as_deref(), &config.api_url);
+            Ok(Some(Box::new(OpenAiSpeech::new(
+                &config.api_url,
+                api_key.as_deref(),
+                &config.model,
+                &config.voice,
+                &config.format,
+                timeout,
+            ))))
+        }
+        "command" => {
+            let command = config
+                .command
+                .as_deref()
+                .filter(|c| !c.trim().is_empty())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("speech.backend = \"command\" requires speech.command")
+                })?;
+            Ok(Some(Box::new(CommandSpeech::new(
+                command,
+                &config.format,
+                timeout,
+            ))))
+        }
+        other => anyhow::bail!("Unknown speech backend: {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speech_url_defaults_to_v1() {
+        let speech = |url: &str| {
+            OpenAiSpeech::new(url, None, "tts-1", "alloy", "opus", Duration::from_secs(5))
+                .speech_url()
+        };
+        assert_eq!(
+            speech("https://api.openai.com"),
+            "https://api.openai.com/v1/audio/speech"
+        );
+        assert_eq!(
+            speech("http://localhost:8880/v1"),
+            "http://localhost:8880/v1/audio/speech"
+        );
+    }
+
+    #[test]
+    fn speakable_text_strips_markdown() {
+        let text = speakable_text(
+            "## Plan\n\n- **Buy** milk\n- call `mom`\n\n```bash\nrm -rf /tmp/x\n```\n> _done_",
+        );
+        assert_eq!(text, "Plan\n\nBuy milk\ncall mom\n\n(code omitted)\ndone");
+    }
+
+    #[test]
+    fn factory_respects_enabled_and_backend() {
+        let mut config = SpeechConfig::default();
+        assert!(create_speech_synthesizer(&config).unwrap().is_none());
+
+        config.enabled = true;
+        assert_eq!(
+            create_speech_synthesizer(&config).unwrap().unwrap().name(),
+            "openai"
+        );
+
+        config.backend = "command".into();
+        assert!(create_speech_synthesizer(&config).is_err());
+        config.command = Some("cat".into());
+        assert_eq!(
+            create_speech_synthesizer(&config).unwrap().unwrap().name(),
+            "command"
+        );
+    }
+
+    #[tokio::test]
+    async fn command_backend_pipes_text_through() {
+        let speech = CommandSpeech::new("tr a-z A-Z", "mp3", Duration::from_secs(5));
+        let out = speech.synthesize("hello").await.unwrap();
+        assert_eq!(out.audio, b"HELLO");
+        assert_eq!(out.mime_type, "audio/mpeg");
+        assert_eq!(out.file_name(), "reply.mp3");
+
+        let silent = CommandSpeech::new("cat >/dev/null", "opus", Duration::from_secs(5));
+        assert!(silent.synthesize("hello").await.is_err());
+    }
+}
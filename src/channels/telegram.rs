@@ -1,4 +1,6 @@
-use super::traits::{Channel, ChannelMessage, MediaAttachment, MediaType};
+use super::speech::SynthesizedSpeech;
+use super::traits::{Button, Channel, ChannelMessage, MediaAttachment, MediaType, MessageKind};
+use crate::util::truncate_with_ellipsis;
 use async_trait::async_trait;
 use reqwest::multipart::{Form, Part};
 use std::path::{Path, PathBuf};
@@ -7,6 +9,9 @@ use uuid::Uuid;
 
 /// Telegram's maximum message length for text messages
 const TELEGRAM_MAX_MESSAGE_LENGTH: usize = 4096;
+/// Reasoning shown ahead of a reply is cut to this many characters so
+/// the first message keeps room for the reply.
+const TELEGRAM_MAX_REASONING_CHARS: usize = 1500;
 
 /// Split a message into chunks that respect Telegram's 4096 character limit.
 /// Tries to split at word boundaries when possible, and handles continuation.
@@ -52,6 +57,19 @@ fn split_message_for_telegram(message: &str) -> Vec<String> {
     chunks
 }
 
+/// Inline keyboard markup with up to three buttons per row.
+fn inline_keyboard(buttons: &[Button]) -> serde_json::Value {
+    let rows: Vec<Vec<serde_json::Value>> = buttons
+        .chunks(3)
+        .map(|row| {
+            row.iter()
+                .map(|b| serde_json::json!({ "text": b.label, "callback_data": b.callback_id }))
+                .collect()
+        })
+        .collect();
+    serde_json::json!({ "inline_keyboard": rows })
+}
+
 /// Telegram channel — long-polls the Bot API for updates
 pub struct TelegramChannel {
     bot_token: String,
@@ -83,6 +101,101 @@ impl TelegramChannel {
         identities.into_iter().any(|id| self.is_user_allowed(id))
     }
 
+    /// Turn an inline keyboard press into a callback message. Returns `None`
+    /// for presses by users outside the allowlist or without data.
+    fn parse_callback_query(&self, query: &serde_json::Value) -> Option<ChannelMessage> {
+        let from = query.get("from")?;
+        let username = from
+            .get("username")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("unknown");
+        let user_id = from
+            .get("id")
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string());
+
+        let mut identities = vec![username];
+        if let Some(ref id) = user_id {
+            identities.push(id.as_str());
+        }
+        if !self.is_any_user_allowed(identities.iter().copied()) {
+            tracing::warn!("Telegram: ignoring button press from unauthorized user: {username}");
+            return None;
+        }
+
+        let chat_id = query
+            .pointer("/message/chat/id")
+            .and_then(serde_json::Value::as_i64)?;
+        let data = query
+            .get("data")
+            .and_then(serde_json::Value::as_str)
+            .filter(|d| !d.is_empty())?;
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: chat_id.to_string(),
+            content: data.to_string(),
+            channel: "telegram".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            kind: MessageKind::Callback,
+            ..Default::default()
+        })
+    }
+
+    /// Stop the loading indicator Telegram shows on a pressed button.
+    async fn answer_callback_query(&self, query_id: &str) {
+        let body = serde_json::json!({ "callback_query_id": query_id });
+        if let Err(e) = self
+            .client
+            .post(self.api_url("answerCallbackQuery"))
+            .json(&body)
+            .send()
+            .await
+        {
+            tracing::debug!("Telegram answerCallbackQuery failed: {e}");
+        }
+    }
+
+    /// Edit the text of a previously sent message.
+    async fn edit_message_text(
+        &self,
+        chat_id: &str,
+        message_id: &str,
+        text: &str,
+        parse_mode: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut body = serde_json::json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": text,
+        });
+        if let Some(mode) = parse_mode {
+            body["parse_mode"] = serde_json::Value::String(mode.to_string());
+        }
+
+        let resp = self
+            .client
+            .post(self.api_url("editMessageText"))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp.text().await.unwrap_or_default();
+            // Editing with identical content is rejected but harmless.
+            if err.contains("message is not modified") {
+                return Ok(());
+            }
+            anyhow::bail!("Telegram editMessageText failed ({status}): {err}");
+        }
+
+        Ok(())
+    }
+
     /// Send a document/file to a Telegram chat
     pub async fn send_document(
         &self,
@@ -346,6 +459,40 @@ impl TelegramChannel {
         Ok(())
     }
 
+    /// Send a voice message from bytes (in-memory) to a Telegram chat
+    pub async fn send_voice_bytes(
+        &self,
+        chat_id: &str,
+        file_bytes: Vec<u8>,
+        file_name: &str,
+        caption: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let part = Part::bytes(file_bytes).file_name(file_name.to_string());
+
+        let mut form = Form::new()
+            .text("chat_id", chat_id.to_string())
+            .part("voice", part);
+
+        if let Some(cap) = caption {
+            form = form.text("caption", cap.to_string());
+        }
+
+        let resp = self
+            .client
+            .post(self.api_url("sendVoice"))
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let err = resp.text().await?;
+            anyhow::bail!("Telegram sendVoice failed: {err}");
+        }
+
+        tracing::info!("Telegram voice sent to {chat_id}: {file_name}");
+        Ok(())
+    }
+
     /// Send a file by URL (Telegram will download it)
     pub async fn send_document_by_url(
         &self,
@@ -894,7 +1041,7 @@ impl Channel for TelegramChannel {
             // Retry without parse_mode as a compatibility fallback.
             let plain_body = serde_json::json!({
                 "chat_id": chat_id,
-                "text": text,
+                "text": super::formatting::telegram_html_to_plain(&text),
             });
             let plain_resp = self
                 .client
@@ -934,7 +1081,7 @@ impl Channel for TelegramChannel {
             let body = serde_json::json!({
                 "offset": offset,
                 "timeout": 30,
-                "allowed_updates": ["message"]
+                "allowed_updates": ["message", "callback_query"]
             });
 
             let resp = match self.client.post(&url).json(&body).send().await {
@@ -962,6 +1109,18 @@ impl Channel for TelegramChannel {
                         offset = uid + 1;
                     }
 
+                    if let Some(query) = update.get("callback_query") {
+                        if let Some(id) = query.get("id").and_then(serde_json::Value::as_str) {
+                            self.answer_callback_query(id).await;
+                        }
+                        if let Some(msg) = self.parse_callback_query(query) {
+                            if tx.send(msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        continue;
+                    }
+
                     let Some(message) = update.get("message") else {
                         continue;
                     };
@@ -1034,6 +1193,7 @@ Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --ch
                             .unwrap_or_default()
                             .as_secs(),
                         attachments,
+                        ..Default::default()
                     };
 
                     if tx.send(msg).await.is_err() {
@@ -1045,6 +1205,153 @@ Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --ch
         }
     }
 
+    fn format_reasoning(&self, reasoning: &str) -> Option<String> {
+        Some(super::formatting::reasoning_to_telegram_html(
+            reasoning,
+            TELEGRAM_MAX_REASONING_CHARS,
+        ))
+    }
+
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
+
+    async fn send_voice_reply(
+        &self,
+        speech: &SynthesizedSpeech,
+        chat_id: &str,
+    ) -> anyhow::Result<()> {
+        self.send_voice_bytes(chat_id, speech.audio.clone(), speech.file_name(), None)
+            .await
+    }
+
+    fn supports_image_replies(&self) -> bool {
+        true
+    }
+
+    async fn send_image(
+        &self,
+        image: Vec<u8>,
+        mime_type: &str,
+        chat_id: &str,
+    ) -> anyhow::Result<()> {
+        let file_name = match mime_type {
+            "image/jpeg" => "image.jpg",
+            "image/gif" => "image.gif",
+            "image/webp" => "image.webp",
+            _ => "image.png",
+        };
+        self.send_photo_bytes(chat_id, image, file_name, None).await
+    }
+
+    fn supports_draft_updates(&self) -> bool {
+        true
+    }
+
+    async fn send_draft(&self, message: &str, chat_id: &str) -> anyhow::Result<String> {
+        // Drafts are sent as plain text: partial markup would fail HTML parsing.
+        let body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": truncate_with_ellipsis(message, TELEGRAM_MAX_MESSAGE_LENGTH - 3),
+        });
+        let resp = self
+            .client
+            .post(self.api_url("sendMessage"))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Telegram sendMessage (draft) failed ({status}): {err}");
+        }
+
+        let data: serde_json::Value = resp.json().await?;
+        data.get("result")
+            .and_then(|r| r.get("message_id"))
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Telegram sendMessage response missing message_id"))
+    }
+
+    async fn update_draft(
+        &self,
+        message: &str,
+        chat_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<()> {
+        let text = truncate_with_ellipsis(message, TELEGRAM_MAX_MESSAGE_LENGTH - 3);
+        self.edit_message_text(chat_id, message_id, &text, None)
+            .await
+    }
+
+    async fn finalize_draft(
+        &self,
+        message: &str,
+        chat_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<()> {
+        if message.len() > TELEGRAM_MAX_MESSAGE_LENGTH {
+            // Too long for a single edit: replace the draft with a split send.
+            let body = serde_json::json!({
+                "chat_id": chat_id,
+                "message_id": message_id,
+            });
+            if let Err(e) = self
+                .client
+                .post(self.api_url("deleteMessage"))
+                .json(&body)
+                .send()
+                .await
+            {
+                tracing::warn!("Telegram deleteMessage for draft failed: {e}");
+            }
+            return self.send(message, chat_id).await;
+        }
+
+        if let Err(e) = self
+            .edit_message_text(chat_id, message_id, message, Some("HTML"))
+            .await
+        {
+            tracing::warn!("Telegram editMessageText with HTML failed; retrying plain: {e}");
+            let plain = super::formatting::telegram_html_to_plain(message);
+            self.edit_message_text(chat_id, message_id, &plain, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn supports_buttons(&self) -> bool {
+        true
+    }
+
+    async fn send_interactive(
+        &self,
+        message: &str,
+        buttons: &[Button],
+        chat_id: &str,
+    ) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": truncate_with_ellipsis(message, TELEGRAM_MAX_MESSAGE_LENGTH - 3),
+            "reply_markup": inline_keyboard(buttons),
+        });
+        let resp = self
+            .client
+            .post(self.api_url("sendMessage"))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Telegram sendMessage (buttons) failed ({status}): {err}");
+        }
+        Ok(())
+    }
+
     async fn start_typing(&self, recipient: &str) -> anyhow::Result<()> {
         let body = serde_json::json!({
             "chat_id": recipient,
@@ -1090,6 +1397,34 @@ mod tests {
         assert_eq!(ch.name(), "telegram");
     }
 
+    #[test]
+    fn telegram_callback_query_becomes_callback_message() {
+        let ch = TelegramChannel::new("t".into(), vec!["alice".into()]);
+        let query = serde_json::json!({
+            "id": "q1",
+            "from": { "id": 7, "username": "alice" },
+            "message": { "message_id": 10, "chat": { "id": 42 } },
+            "data": "approve ab12cd"
+        });
+        let msg = ch.parse_callback_query(&query).unwrap();
+        assert_eq!(msg.kind, MessageKind::Callback);
+        assert_eq!(msg.sender, "42");
+        assert_eq!(msg.content, "approve ab12cd");
+
+        let mut stranger = query.clone();
+        stranger["from"] = serde_json::json!({ "id": 8, "username": "mallory" });
+        assert!(ch.parse_callback_query(&stranger).is_none());
+
+        let keyboard = inline_keyboard(&[
+            Button::new("A", "a"),
+            Button::new("B", "b"),
+            Button::new("C", "c"),
+            Button::new("D", "d"),
+        ]);
+        assert_eq!(keyboard["inline_keyboard"].as_array().unwrap().len(), 2);
+        assert_eq!(keyboard["inline_keyboard"][1][0]["callback_data"], "d");
+    }
+
     #[test]
     fn telegram_api_url() {
         let ch = TelegramChannel::new("123:ABC".into(), vec![]);
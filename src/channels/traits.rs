@@ -1,5 +1,7 @@
+use super::speech::SynthesizedSpeech;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::fmt::Write as _;
 
 // --- ZeroClaw fork: media type support for all Telegram-compatible media ---
 
@@ -59,6 +61,11 @@ impl MediaType {
     pub fn is_image(&self) -> bool {
         matches!(self, Self::Photo | Self::Sticker | Self::Animation)
     }
+
+    /// Whether this media type carries speech worth transcribing.
+    pub fn is_audio(&self) -> bool {
+        matches!(self, Self::Audio | Self::Voice | Self::VideoNote)
+    }
 }
 
 /// A media attachment from a messaging platform.
@@ -94,6 +101,45 @@ impl MediaAttachment {
 
 // --- end ZeroClaw fork ---
 
+/// What kind of inbound event a [`ChannelMessage`] is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Something the user wrote (or sent as media).
+    #[default]
+    Text,
+    /// The user pressed a button sent with [`Channel::send_interactive`];
+    /// `content` holds the button's callback ID.
+    Callback,
+}
+
+/// A button on an interactive message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Button {
+    pub label: String,
+    /// Delivered as the content of a [`MessageKind::Callback`] message when
+    /// pressed. Channels without buttons ask the user to type it instead, so
+    /// keep it short and typeable (and within 64 bytes for Telegram).
+    pub callback_id: String,
+}
+
+impl Button {
+    pub fn new(label: impl Into<String>, callback_id: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            callback_id: callback_id.into(),
+        }
+    }
+}
+
+/// Render buttons as a text menu for channels that cannot show them.
+pub fn text_menu(message: &str, buttons: &[Button]) -> String {
+    let mut menu = format!("{message}\n\nReply with one of:");
+    for button in buttons {
+        let _ = write!(menu, "\n• {}: {}", button.label, button.callback_id);
+    }
+    menu
+}
+
 /// A message received from or sent to a channel
 #[derive(Debug, Clone, Default)]
 pub struct ChannelMessage {
@@ -105,6 +151,7 @@ pub struct ChannelMessage {
     // --- ZeroClaw fork ---
     pub attachments: Vec<MediaAttachment>,
     // --- end ZeroClaw fork ---
+    pub kind: MessageKind,
 }
 
 /// Core channel trait — implement for any messaging platform
@@ -134,6 +181,91 @@ pub trait Channel: Send + Sync {
     async fn stop_typing(&self, _recipient: &str) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Render model reasoning to show ahead of a reply, or `None` to leave
+    /// it out. Channels that can collapse long text override this.
+    fn format_reasoning(&self, _reasoning: &str) -> Option<String> {
+        None
+    }
+
+    /// Whether this channel can deliver synthesized audio as a voice message.
+    fn supports_voice_replies(&self) -> bool {
+        false
+    }
+
+    /// Send synthesized speech as a voice message.
+    async fn send_voice_reply(
+        &self,
+        _speech: &SynthesizedSpeech,
+        _recipient: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support voice replies", self.name())
+    }
+
+    /// Whether this channel can send images produced by tools.
+    fn supports_image_replies(&self) -> bool {
+        false
+    }
+
+    /// Send an image (raw bytes with its MIME type) to the recipient.
+    async fn send_image(
+        &self,
+        _image: Vec<u8>,
+        _mime_type: &str,
+        _recipient: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support image replies", self.name())
+    }
+
+    /// Whether this channel can edit a message after sending it. When true,
+    /// replies are streamed into a draft that is progressively updated.
+    fn supports_draft_updates(&self) -> bool {
+        false
+    }
+
+    /// Send the first version of a streamed reply and return its platform
+    /// message ID for later edits.
+    async fn send_draft(&self, _message: &str, _recipient: &str) -> anyhow::Result<String> {
+        anyhow::bail!("{} does not support draft updates", self.name())
+    }
+
+    /// Replace the content of a draft with the (partial) reply so far.
+    async fn update_draft(
+        &self,
+        _message: &str,
+        _recipient: &str,
+        _message_id: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support draft updates", self.name())
+    }
+
+    /// Replace a draft with the complete reply. Unlike `update_draft`, this
+    /// must deliver the whole message, even if it exceeds the platform limit.
+    async fn finalize_draft(
+        &self,
+        message: &str,
+        recipient: &str,
+        message_id: &str,
+    ) -> anyhow::Result<()> {
+        self.update_draft(message, recipient, message_id).await
+    }
+
+    /// Whether this channel renders buttons natively. Channels that do not
+    /// fall back to a text menu in `send_interactive`.
+    fn supports_buttons(&self) -> bool {
+        false
+    }
+
+    /// Send a message with buttons. Presses come back through `listen` as
+    /// [`MessageKind::Callback`] messages from the same recipient.
+    async fn send_interactive(
+        &self,
+        message: &str,
+        buttons: &[Button],
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        self.send(&text_menu(message, buttons), recipient).await
+    }
 }
 
 #[cfg(test)]
@@ -198,6 +330,31 @@ mod tests {
         assert!(channel.send("hello", "bob").await.is_ok());
     }
 
+    #[tokio::test]
+    async fn default_draft_methods_are_unsupported() {
+        let channel = DummyChannel;
+
+        assert!(!channel.supports_draft_updates());
+        assert!(channel.send_draft("hello", "bob").await.is_err());
+        assert!(channel.update_draft("hello", "bob", "1").await.is_err());
+        assert!(channel.finalize_draft("hello", "bob", "1").await.is_err());
+    }
+
+    #[test]
+    fn text_menu_lists_callback_ids_to_type() {
+        let menu = text_menu(
+            "Run it?",
+            &[
+                Button::new("Approve", "approve ab12cd"),
+                Button::new("Deny", "deny ab12cd"),
+            ],
+        );
+        assert_eq!(
+            menu,
+            "Run it?\n\nReply with one of:\n• Approve: approve ab12cd\n• Deny: deny ab12cd"
+        );
+    }
+
     #[tokio::test]
     async fn listen_sends_message_to_channel() {
         let channel = DummyChannel;
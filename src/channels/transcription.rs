@@ -0,0 +1,304 @@
+use crate::config::TranscriptionConfig;
+use async_trait::async_trait;
+use std::path::Path;
+use std::time::Duration;
+
+/// Trait for speech-to-text backends — turn an audio file into text
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    /// Backend name
+    fn name(&self) -> &str;
+
+    /// Transcribe the audio file at `path`
+    async fn transcribe(&self, path: &Path, mime_type: Option<&str>) -> anyhow::Result<String>;
+}
+
+// ── Whisper-compatible HTTP backend ──────────────────────────
+
+/// OpenAI `/audio/transcriptions` API; also served by local whisper servers
+/// (faster-whisper-server, whisper.cpp `--convert`, LocalAI).
+pub struct WhisperTranscriber {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    language: Option<String>,
+}
+
+impl WhisperTranscriber {
+    pub fn new(
+        base_url: &str,
+        api_key: Option<&str>,
+        model: &str,
+        language: Option<&str>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .connect_timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|k| !k.is_empty()).map(ToString::to_string),
+            model: model.to_string(),
+            language: language.map(ToString::to_string),
+        }
+    }
+
+    fn transcriptions_url(&self) -> String {
+        let Ok(url) = reqwest::Url::parse(&self.base_url) else {
+            return format!("{}/v1/audio/transcriptions", self.base_url);
+        };
+
+        let path = url.path().trim_end_matches('/');
+        if path.ends_with("/audio/transcriptions") {
+            self.base_url.clone()
+        } else if path.is_empty() {
+            format!("{}/v1/audio/transcriptions", self.base_url)
+        } else {
+            format!("{}/audio/transcriptions", self.base_url)
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for WhisperTranscriber {
+    fn name(&self) -> &str {
+        "whisper"
+    }
+
+    async fn transcribe(&self, path: &Path, mime_type: Option<&str>) -> anyhow::Result<String> {
+        let bytes = tokio::fs::read(path).await?;
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("audio.ogg")
+            .to_string();
+        let file = reqwest::multipart::Part::bytes(bytes)
+            .file_name(file_name)
+            .mime_str(mime_type.unwrap_or("application/octet-stream"))?;
+
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", self.model.clone())
+            .text("response_format", "json");
+        if let Some(language) = &self.language {
+            form = form.text("language", language.clone());
+        }
+
+        let mut request = self.client.post(self.transcriptions_url()).multipart(form);
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let resp = request.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Transcription API error {status}: {text}");
+        }
+
+        let json: serde_json::Value = resp.json().await?;
+        json.get("text")
+            .and_then(|t| t.as_str())
+            .map(|t| t.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Invalid transcription response: missing 'text'"))
+    }
+}
+
+// ── Local command backend ────────────────────────────────────
+
+/// Runs a local speech-to-text CLI (whisper.cpp, openai-whisper, vosk)
+/// through `sh -c`. `{file}` in the command is replaced with the audio path,
+/// which is passed as a positional argument rather than spliced into the
+/// script; stdout is the transcript.
+pub struct CommandTranscriber {
+    command: String,
+    timeout: Duration,
+}
+
+impl CommandTranscriber {
+    pub fn new(command: &str, timeout: Duration) -> Self {
+        Self {
+            command: command.to_string(),
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for CommandTranscriber {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    async fn transcribe(&self, path: &Path, _mime_type: Option<&str>) -> anyhow::Result<String> {
+        let script = self.command.replace("{file}", "\"$1\"");
+        let mut child = tokio::process::Command::new("sh");
+        child
+            .arg("-c")
+            .arg(&script)
+            .arg("sh")
+            .arg(path)
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(self.timeout, child.output())
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!("Transcription command timed out after {:?}", self.timeout)
+            })??;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "Transcription command failed ({}): {}",
+                output.status,
+                stderr.trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+// ── Factory ──────────────────────────────────────────────────
+
+/// The configured key, falling back to `OPENAI_API_KEY` only when `api_url`
+/// is OpenAI's own API, so the key never reaches a local or third-party server.
+pub(crate) fn openai_api_key(configured: Option<&str>, api_url: &str) -> Option<String> {
+    if let Some(key) = configured {
+        return Some(key.to_string());
+    }
+    let is_openai =
+        reqwest::Url::parse(api_url).is_ok_and(|url| url.host_str() == Some("api.openai.com"));
+    if is_openai {
+        std::env::var("OPENAI_API_KEY").ok()
+    } else {
+        None
+    }
+}
+
+/// Build the configured transcriber, or `None` when transcription is off.
+pub fn create_transcriber(
+    config: &TranscriptionConfig,
+) -> anyhow::Result<Option<Box<dyn Transcriber>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    match config.backend.as_str() {
+        "whisper" | "openai" => {
+            let api_key = openai_api_key(config.api_key.as_deref(), &config.api_url);
+            Ok(Some(Box::new(WhisperTranscriber::new(
+                &config.api_url,
+                api_key.as_deref(),
+                &config.model,
+                config.language.as_deref(),
+                timeout,
+            ))))
+        }
+        "command" => {
+            let command = config
+                .command
+                .as_deref()
+                .filter(|c| !c.trim().is_empty())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "transcription.backend = \"command\" requires transcription.command"
+                    )
+                })?;
+            Ok(Some(Box::new(CommandTranscriber::new(command, timeout))))
+        }
+        other => anyhow::bail!("Unknown transcription backend: {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whisper(base_url: &str) -> WhisperTranscriber {
+        WhisperTranscriber::new(base_url, None, "whisper-1", None, Duration::from_secs(5))
+    }
+
+    #[test]
+    fn transcriptions_url_defaults_to_v1() {
+        assert_eq!(
+            whisper("https://api.openai.com").transcriptions_url(),
+            "https://api.openai.com/v1/audio/transcriptions"
+        );
+        assert_eq!(
+            whisper("http://localhost:8000/v1/").transcriptions_url(),
+            "http://localhost:8000/v1/audio/transcriptions"
+        );
+        assert_eq!(
+            whisper("http://localhost:8080/inference/audio/transcriptions").transcriptions_url(),
+            "http://localhost:8080/inference/audio/transcriptions"
+        );
+    }
+
+    #[test]
+    fn env_key_is_only_sent_to_openai() {
+        assert_eq!(
+            openai_api_key(Some("sk-local"), "http://localhost:8000").as_deref(),
+            Some("sk-local")
+        );
+        assert_eq!(openai_api_key(None, "http://localhost:8000"), None);
+        assert_eq!(
+            openai_api_key(None, "https://api.openai.com.evil.test"),
+            None
+        );
+    }
+
+    #[test]
+    fn factory_respects_enabled_and_backend() {
+        let mut config = TranscriptionConfig::default();
+        assert!(create_transcriber(&config).unwrap().is_none());
+
+        config.enabled = true;
+        assert_eq!(
+            create_transcriber(&config).unwrap().unwrap().name(),
+            "whisper"
+        );
+
+        config.backend = "command".into();
+        assert!(create_transcriber(&config).is_err());
+
+        config.command = Some("cat {file}".into());
+        assert_eq!(
+            create_transcriber(&config).unwrap().unwrap().name(),
+            "command"
+        );
+
+        config.backend = "telepathy".into();
+        assert!(create_transcriber(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn command_backend_reads_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voice note's.ogg");
+        std::fs::write(&path, "  hello from the phone\n").unwrap();
+
+        let transcriber = CommandTranscriber::new("cat {file}", Duration::from_secs(5));
+        let text = transcriber
+            .transcribe(&path, Some("audio/ogg"))
+            .await
+            .unwrap();
+        assert_eq!(text, "hello from the phone");
+    }
+
+    #[tokio::test]
+    async fn command_backend_surfaces_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voice.ogg");
+        std::fs::write(&path, "").unwrap();
+
+        let transcriber =
+            CommandTranscriber::new("echo 'model missing' >&2; exit 3", Duration::from_secs(5));
+        let err = transcriber.transcribe(&path, None).await.unwrap_err();
+        assert!(err.to_string().contains("model missing"));
+    }
+}
@@ -0,0 +1,216 @@
+//! Running turns per conversation.
+//!
+//! A channel turn can run for a long time. [`ActiveTurns`] tracks the one in
+//! flight for each conversation so that a follow-up from the same sender
+//! can cancel it or, in steering mode, be handed to it: the tool loop reads
+//! a turn's [`SteeringInbox`] between steps and adds what it finds to the
+//! conversation.
+
+use crate::agent::steering::SteeringInbox;
+use crate::config::TurnInterruptMode;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Messages that end the running turn instead of starting a new one.
+const STOP_COMMANDS: &[&str] = &["stop", "/stop", "cancel", "/cancel", "abort"];
+
+/// Whether `message` is a bare request to stop, like "Stop!".
+pub fn is_stop_command(message: &str) -> bool {
+    let word = message
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_ascii_lowercase();
+    STOP_COMMANDS.contains(&word.as_str())
+}
+
+struct TurnEntry {
+    id: u64,
+    cancel: watch::Sender<bool>,
+    finished: watch::Receiver<()>,
+    inbox: SteeringInbox,
+}
+
+/// The turns currently running, keyed by conversation.
+pub struct ActiveTurns {
+    mode: TurnInterruptMode,
+    turns: Mutex<HashMap<String, TurnEntry>>,
+    next_id: AtomicU64,
+}
+
+impl ActiveTurns {
+    pub fn new(mode: TurnInterruptMode) -> Self {
+        Self {
+            mode,
+            turns: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    pub fn mode(&self) -> TurnInterruptMode {
+        self.mode
+    }
+
+    /// Register a turn for `key`, first waiting for any turn still running
+    /// there to wind down so the new one starts from its saved history.
+    pub async fn begin(self: &Arc<Self>, key: &str) -> TurnGuard {
+        loop {
+            let mut running = {
+                let mut turns = self.turns.lock();
+                match turns.get(key) {
+                    Some(entry) => entry.finished.clone(),
+                    None => {
+                        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                        let (cancel, cancelled) = watch::channel(false);
+                        let (finished_tx, finished) = watch::channel(());
+                        let inbox = SteeringInbox::new();
+                        turns.insert(
+                            key.to_string(),
+                            TurnEntry {
+                                id,
+                                cancel,
+                                finished,
+                                inbox: inbox.clone(),
+                            },
+                        );
+                        return TurnGuard {
+                            turns: Arc::clone(self),
+                            key: key.to_string(),
+                            id,
+                            cancelled,
+                            inbox,
+                            _finished: finished_tx,
+                        };
+                    }
+                }
+            };
+            // Resolves once the running turn's guard is dropped.
+            while running.changed().await.is_ok() {}
+        }
+    }
+
+    /// Ask the turn running for `key` to stop. Returns whether there was one.
+    pub fn cancel(&self, key: &str) -> bool {
+        self.turns
+            .lock()
+            .get(key)
+            .is_some_and(|entry| entry.cancel.send(true).is_ok())
+    }
+
+    /// Hand `message` to the turn running for `key`, or give it back when
+    /// there is none.
+    pub fn steer(&self, key: &str, message: String) -> Result<(), String> {
+        let inbox = self.turns.lock().get(key).map(|entry| entry.inbox.clone());
+        match inbox {
+            Some(inbox) => inbox.push(message),
+            None => Err(message),
+        }
+    }
+}
+
+/// A registered turn. Dropping it unregisters the turn and lets the next
+/// one for the conversation begin.
+pub struct TurnGuard {
+    turns: Arc<ActiveTurns>,
+    key: String,
+    id: u64,
+    cancelled: watch::Receiver<bool>,
+    inbox: SteeringInbox,
+    _finished: watch::Sender<()>,
+}
+
+impl TurnGuard {
+    pub fn inbox(&self) -> &SteeringInbox {
+        &self.inbox
+    }
+
+    /// Resolves when the turn is cancelled.
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|cancel| *cancel).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Unregister the turn and return steering messages it never read, so
+    /// they can be answered as a turn of their own.
+    pub fn finish(self) -> Vec<String> {
+        self.unregister();
+        self.inbox.close()
+    }
+
+    fn unregister(&self) {
+        let mut turns = self.turns.turns.lock();
+        if turns
+            .get(&self.key)
+            .is_some_and(|entry| entry.id == self.id)
+        {
+            turns.remove(&self.key);
+        }
+    }
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        self.unregister();
+        self.inbox.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn stop_commands_are_bare_words() {
+        assert!(is_stop_command("stop"));
+        assert!(is_stop_command("  Stop! "));
+        assert!(is_stop_command("/cancel"));
+        assert!(!is_stop_command("stop using staging"));
+        assert!(!is_stop_command("don't stop"));
+    }
+
+    #[tokio::test]
+    async fn steering_reaches_the_running_turn_and_leftovers_come_back() {
+        let turns = Arc::new(ActiveTurns::new(TurnInterruptMode::Steer));
+        assert_eq!(turns.steer("tg_alice", "early".into()), Err("early".into()));
+
+        let turn = turns.begin("tg_alice").await;
+        turns.steer("tg_alice", "use staging".into()).unwrap();
+        assert_eq!(turn.inbox().drain(), vec!["use staging".to_string()]);
+
+        turns.steer("tg_alice", "and be quick".into()).unwrap();
+        assert_eq!(turn.finish(), vec!["and be quick".to_string()]);
+        assert!(turns.steer("tg_alice", "late".into()).is_err());
+    }
+
+    #[tokio::test]
+    async fn cancel_wakes_the_turn_and_the_next_begin_waits_for_it() {
+        let turns = Arc::new(ActiveTurns::new(TurnInterruptMode::Cancel));
+        assert!(!turns.cancel("tg_alice"));
+
+        let first = turns.begin("tg_alice").await;
+        let next = tokio::spawn({
+            let turns = Arc::clone(&turns);
+            async move { turns.begin("tg_alice").await.id }
+        });
+
+        assert!(turns.cancel("tg_alice"));
+        tokio::time::timeout(Duration::from_secs(1), first.cancelled())
+            .await
+            .expect("turn should observe cancellation");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!next.is_finished(), "next turn must wait for the first");
+
+        let first_id = first.id;
+        drop(first);
+        let next_id = tokio::time::timeout(Duration::from_secs(1), next)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(next_id, first_id);
+    }
+}
@@ -1,5 +1,7 @@
-use super::traits::{Channel, ChannelMessage};
+use super::speech::SynthesizedSpeech;
+use super::traits::{Channel, ChannelMessage, MediaAttachment, MediaType};
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// `WhatsApp` channel — uses `WhatsApp` Business Cloud API
@@ -42,6 +44,133 @@ impl WhatsAppChannel {
         &self.verify_token
     }
 
+    /// Upload media to the Cloud API and return its media ID
+    async fn upload_media(
+        &self,
+        bytes: Vec<u8>,
+        file_name: &str,
+        mime_type: &str,
+    ) -> anyhow::Result<String> {
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/media",
+            self.phone_number_id
+        );
+        let part = reqwest::multipart::Part::bytes(bytes)
+            .file_name(file_name.to_string())
+            .mime_str(mime_type)?;
+        let form = reqwest::multipart::Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", mime_type.to_string())
+            .part("file", part);
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            tracing::error!("WhatsApp media upload failed: {status} — {error_body}");
+            anyhow::bail!("WhatsApp media upload error: {status}");
+        }
+
+        let json: serde_json::Value = resp.json().await?;
+        json.get("id")
+            .and_then(|id| id.as_str())
+            .map(ToString::to_string)
+            .ok_or_else(|| anyhow::anyhow!("WhatsApp media upload returned no id"))
+    }
+
+    /// Download an inbound media file to `workspace/downloads/`.
+    /// Returns the local filesystem path.
+    pub async fn download_media(
+        &self,
+        media_id: &str,
+        workspace: &Path,
+    ) -> anyhow::Result<PathBuf> {
+        // Step 1: Look up the short-lived download URL
+        let url = format!("https://graph.facebook.com/v18.0/{media_id}");
+        let resp = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("WhatsApp media lookup failed: {}", resp.status());
+        }
+        let json: serde_json::Value = resp.json().await?;
+        let media_url = json
+            .get("url")
+            .and_then(|u| u.as_str())
+            .ok_or_else(|| anyhow::anyhow!("WhatsApp media lookup returned no url"))?;
+
+        // Step 2: Download the file (the URL also needs the access token)
+        let file_resp = self
+            .client
+            .get(media_url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .send()
+            .await?;
+        if !file_resp.status().is_success() {
+            anyhow::bail!("WhatsApp media download failed: {}", file_resp.status());
+        }
+        let bytes = file_resp.bytes().await?;
+
+        // Step 3: Save to workspace/downloads/
+        let downloads_dir = workspace.join("downloads");
+        tokio::fs::create_dir_all(&downloads_dir).await?;
+        let safe_id: String = media_id
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        let mime_type = json
+            .get("mime_type")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        let local_path =
+            downloads_dir.join(format!("whatsapp_{safe_id}.{}", audio_extension(mime_type)));
+        tokio::fs::write(&local_path, &bytes).await?;
+
+        tracing::info!(
+            "WhatsApp media downloaded: {media_id} -> {}",
+            local_path.display()
+        );
+        Ok(local_path)
+    }
+
+    /// Download the voice and audio attachments of `msg` so they can be
+    /// transcribed. Failures are logged and leave the attachment as is.
+    pub async fn download_audio(&self, msg: &mut ChannelMessage, workspace: &Path) {
+        for att in &mut msg.attachments {
+            if !att.media_type.is_audio() || att.file_path.is_some() {
+                continue;
+            }
+            let Some(media_id) = att.file_id.as_deref() else {
+                continue;
+            };
+            match self.download_media(media_id, workspace).await {
+                Ok(path) => att.file_path = Some(path.display().to_string()),
+                Err(e) => tracing::warn!("Failed to download WhatsApp {}: {e}", att.media_type),
+            }
+        }
+    }
+
+    fn audio_message_body(recipient: &str, media_id: &str) -> serde_json::Value {
+        let to = recipient.strip_prefix('+').unwrap_or(recipient);
+        serde_json::json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": to,
+            "type": "audio",
+            "audio": { "id": media_id }
+        })
+    }
+
     /// Parse an incoming webhook payload from Meta and extract messages
     pub fn parse_webhook_payload(&self, payload: &serde_json::Value) -> Vec<ChannelMessage> {
         let mut messages = Vec::new();
@@ -88,16 +217,38 @@ impl WhatsAppChannel {
                         continue;
                     }
 
-                    // Extract text content (support text messages only for now)
-                    let content = if let Some(text_obj) = msg.get("text") {
-                        text_obj
+                    // Extract text content, or a voice/audio attachment
+                    let (content, attachments) = if let Some(text_obj) = msg.get("text") {
+                        let body = text_obj
                             .get("body")
                             .and_then(|b| b.as_str())
                             .unwrap_or("")
-                            .to_string()
+                            .to_string();
+                        (body, Vec::new())
+                    } else if let Some(audio) = msg.get("audio") {
+                        let is_voice = audio
+                            .get("voice")
+                            .and_then(serde_json::Value::as_bool)
+                            .unwrap_or(false);
+                        let mut att = MediaAttachment::new(if is_voice {
+                            MediaType::Voice
+                        } else {
+                            MediaType::Audio
+                        });
+                        att.file_id = audio.get("id").and_then(|i| i.as_str()).map(String::from);
+                        att.mime_type = audio
+                            .get("mime_type")
+                            .and_then(|m| m.as_str())
+                            .map(String::from);
+                        let content = if is_voice {
+                            "[Voice message]"
+                        } else {
+                            "[Audio]"
+                        };
+                        (content.to_string(), vec![att])
                     } else {
-                        // Could be image, audio, etc. — skip for now
-                        tracing::debug!("WhatsApp: skipping non-text message from {from}");
+                        // Could be image, document, etc. — skip for now
+                        tracing::debug!("WhatsApp: skipping unsupported message from {from}");
                         continue;
                     };
 
@@ -123,7 +274,8 @@ impl WhatsAppChannel {
                         content,
                         channel: "whatsapp".to_string(),
                         timestamp,
-                        attachments: vec![],
+                        attachments,
+                        ..Default::default()
                     });
                 }
             }
@@ -133,6 +285,18 @@ impl WhatsAppChannel {
     }
 }
 
+/// File extension for a WhatsApp audio MIME type such as
+/// `audio/ogg; codecs=opus`, so transcribers can tell the format.
+fn audio_extension(mime_type: &str) -> &'static str {
+    match mime_type.split(';').next().unwrap_or_default().trim() {
+        "audio/mpeg" => "mp3",
+        "audio/mp4" => "m4a",
+        "audio/aac" => "aac",
+        "audio/amr" => "amr",
+        _ => "ogg",
+    }
+}
+
 #[async_trait]
 impl Channel for WhatsAppChannel {
     fn name(&self) -> &str {
@@ -194,6 +358,42 @@ impl Channel for WhatsAppChannel {
         }
     }
 
+    fn supports_voice_replies(&self) -> bool {
+        true
+    }
+
+    async fn send_voice_reply(
+        &self,
+        speech: &SynthesizedSpeech,
+        recipient: &str,
+    ) -> anyhow::Result<()> {
+        let media_id = self
+            .upload_media(speech.audio.clone(), speech.file_name(), &speech.mime_type)
+            .await?;
+        let url = format!(
+            "https://graph.facebook.com/v18.0/{}/messages",
+            self.phone_number_id
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.access_token))
+            .header("Content-Type", "application/json")
+            .json(&Self::audio_message_body(recipient, &media_id))
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let error_body = resp.text().await.unwrap_or_default();
+            tracing::error!("WhatsApp audio send failed: {status} — {error_body}");
+            anyhow::bail!("WhatsApp API error: {status}");
+        }
+
+        Ok(())
+    }
+
     async fn health_check(&self) -> bool {
         // Check if we can reach the WhatsApp API
         let url = format!("https://graph.facebook.com/v18.0/{}", self.phone_number_id);
@@ -709,7 +909,7 @@ mod tests {
     }
 
     #[test]
-    fn whatsapp_parse_audio_message_skipped() {
+    fn whatsapp_parse_voice_note_as_attachment() {
         let ch = WhatsAppChannel::new("tok".into(), "123".into(), "ver".into(), vec!["*".into()]);
         let payload = serde_json::json!({
             "entry": [{
@@ -719,14 +919,23 @@ mod tests {
                             "from": "111",
                             "timestamp": "1",
                             "type": "audio",
-                            "audio": { "id": "audio123", "mime_type": "audio/ogg" }
+                            "audio": {
+                                "id": "audio123",
+                                "mime_type": "audio/ogg; codecs=opus",
+                                "voice": true
+                            }
                         }]
                     }
                 }]
             }]
         });
         let msgs = ch.parse_webhook_payload(&payload);
-        assert!(msgs.is_empty());
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].content, "[Voice message]");
+        let att = &msgs[0].attachments[0];
+        assert_eq!(att.media_type, MediaType::Voice);
+        assert_eq!(att.file_id.as_deref(), Some("audio123"));
+        assert_eq!(audio_extension(att.mime_type.as_deref().unwrap()), "ogg");
     }
 
     #[test]
@@ -1108,4 +1317,13 @@ mod tests {
             "<script>alert('xss')</script> & \"quotes\" 'apostrophe'"
         );
     }
+
+    #[test]
+    fn whatsapp_audio_message_references_uploaded_media() {
+        let body = WhatsAppChannel::audio_message_body("+1234567890", "media-1");
+        assert_eq!(body["to"], "1234567890");
+        assert_eq!(body["type"], "audio");
+        assert_eq!(body["audio"]["id"], "media-1");
+        assert!(make_channel().supports_voice_replies());
+    }
 }
@@ -5,10 +5,13 @@ pub use schema::{
     AgentConfig, AuditConfig, AutonomyConfig, BrowserComputerUseConfig, BrowserConfig,
     ChannelsConfig, ComposioConfig, Config, CostConfig, DelegateAgentConfig, DiscordConfig,
     DockerRuntimeConfig, GatewayConfig, HardwareConfig, HardwareTransport, HeartbeatConfig,
-    HttpRequestConfig, IMessageConfig, IdentityConfig, LarkConfig, MatrixConfig, MemoryConfig,
-    ModelRouteConfig, ObservabilityConfig, PeripheralBoardConfig, PeripheralsConfig,
-    ReliabilityConfig, ResourceLimitsConfig, RuntimeConfig, SandboxBackend, SandboxConfig,
-    SchedulerConfig, SecretsConfig, SecurityConfig, SlackConfig, TelegramConfig, TunnelConfig,
+    HttpRequestConfig, IMessageConfig, IdentityConfig, ImageGenerationConfig, LarkConfig,
+    MatrixConfig, MemoryConfig, ModelCapabilities, ModelRouteConfig, ObservabilityConfig,
+    PeripheralBoardConfig, PeripheralsConfig, ReasoningEffort, RedactionConfig, ReliabilityConfig,
+    ReplayConfig, ReplayMode, ResourceLimitsConfig, RoutingClassifierConfig, RoutingClassifierKind,
+    RoutingConfig, RoutingRuleConfig, RuntimeConfig, SandboxBackend, SandboxConfig,
+    SchedulerConfig, SecretsConfig, SecurityConfig, SlackConfig, SpeechConfig, TelegramConfig,
+    TranscriptionConfig, TunnelConfig, TurnBudgetConfig, TurnInterruptMode, VoiceReplyMode,
     WebhookConfig,
 };
 
@@ -7,6 +7,9 @@ use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+/// Model used when `default_model` is not set.
+pub const DEFAULT_MODEL: &str = "anthropic/claude-sonnet-4-20250514";
+
 // ── Top-level config ──────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +37,16 @@ pub struct Config {
     #[serde(default)]
     pub reliability: ReliabilityConfig,
 
+    #[serde(default)]
+    pub replay: ReplayConfig,
+
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// Sandboxing, resource limits and audit logging (`[security]`).
+    #[serde(default)]
+    pub security: SecurityConfig,
+
     #[serde(default)]
     pub scheduler: SchedulerConfig,
 
@@ -44,12 +57,27 @@ pub struct Config {
     #[serde(default)]
     pub model_routes: Vec<ModelRouteConfig>,
 
+    /// Rules that choose a model route per incoming message. When absent,
+    /// the built-in technical/general split applies (see `agent::routing`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<RoutingConfig>,
+
+    /// Per-model capability overrides, keyed by model name.
+    #[serde(default)]
+    pub model_capabilities: HashMap<String, ModelCapabilities>,
+
     #[serde(default)]
     pub heartbeat: HeartbeatConfig,
 
     #[serde(default)]
     pub channels_config: ChannelsConfig,
 
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+
+    #[serde(default)]
+    pub speech: SpeechConfig,
+
     #[serde(default)]
     pub memory: MemoryConfig,
 
@@ -71,6 +99,9 @@ pub struct Config {
     #[serde(default)]
     pub http_request: HttpRequestConfig,
 
+    #[serde(default)]
+    pub image_generation: ImageGenerationConfig,
+
     #[serde(default)]
     pub identity: IdentityConfig,
 
@@ -196,18 +227,46 @@ pub struct AgentConfig {
     /// When true: bootstrap_max_chars=6000, rag_chunk_limit=2. Use for 13B or smaller models.
     #[serde(default)]
     pub compact_context: bool,
-    #[serde(default = "default_agent_max_tool_iterations")]
-    pub max_tool_iterations: usize,
+    /// Deprecated alias for `[agent.budget].max_iterations`, used only when
+    /// that is unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tool_iterations: Option<usize>,
     #[serde(default = "default_agent_max_history_messages")]
     pub max_history_messages: usize,
+    /// Run independent read-only tool calls from one response concurrently.
     #[serde(default)]
     pub parallel_tools: bool,
+    /// Most tool calls running at once when `parallel_tools` is on.
+    #[serde(default = "default_agent_max_parallel_tools")]
+    pub max_parallel_tools: usize,
     #[serde(default = "default_agent_tool_dispatcher")]
     pub tool_dispatcher: String,
+    /// Limits on each turn of the tool-call loop (`[agent.budget]`).
+    #[serde(default)]
+    pub budget: TurnBudgetConfig,
 }
 
-fn default_agent_max_tool_iterations() -> usize {
-    10
+impl AgentConfig {
+    /// `[agent.budget]` with the legacy `max_tool_iterations` folded in.
+    pub fn turn_budget(&self) -> TurnBudgetConfig {
+        TurnBudgetConfig {
+            max_iterations: self.budget.max_iterations.or(self.max_tool_iterations),
+            ..self.budget.clone()
+        }
+    }
+
+    /// How many tool calls may run at once: 1 unless `parallel_tools` is on.
+    pub fn tool_parallelism(&self) -> usize {
+        if self.parallel_tools {
+            self.max_parallel_tools.max(1)
+        } else {
+            1
+        }
+    }
+}
+
+fn default_agent_max_parallel_tools() -> usize {
+    4
 }
 
 fn default_agent_max_history_messages() -> usize {
@@ -222,10 +281,50 @@ impl Default for AgentConfig {
     fn default() -> Self {
         Self {
             compact_context: false,
-            max_tool_iterations: default_agent_max_tool_iterations(),
+            max_tool_iterations: None,
             max_history_messages: default_agent_max_history_messages(),
             parallel_tools: false,
+            max_parallel_tools: default_agent_max_parallel_tools(),
             tool_dispatcher: default_agent_tool_dispatcher(),
+            budget: TurnBudgetConfig::default(),
+        }
+    }
+}
+
+/// Limits on one agent turn: everything the tool-call loop does to answer a
+/// single message. Set under `[agent.budget]` and per channel under
+/// `[channels_config.budgets.<channel>]`; unset fields fall back to the
+/// agent budget, then to the built-in defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnBudgetConfig {
+    /// Most LLM round-trips per turn (default: 200)
+    #[serde(default)]
+    pub max_iterations: Option<usize>,
+    /// Wall-clock limit per turn in seconds, checked between steps
+    #[serde(default)]
+    pub max_duration_secs: Option<u64>,
+    /// Most tool calls executed per turn
+    #[serde(default)]
+    pub max_tool_calls: Option<usize>,
+    /// Most tokens (input + output over all LLM calls) per turn
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    /// Identical tool calls in a row — or cycles of two alternating calls —
+    /// before the model is told it is looping; repeating once more ends the
+    /// turn (default: 3, 0 = off)
+    #[serde(default)]
+    pub max_repeated_calls: Option<usize>,
+}
+
+impl TurnBudgetConfig {
+    /// Fill unset fields from `base`.
+    pub fn or(&self, base: &Self) -> Self {
+        Self {
+            max_iterations: self.max_iterations.or(base.max_iterations),
+            max_duration_secs: self.max_duration_secs.or(base.max_duration_secs),
+            max_tool_calls: self.max_tool_calls.or(base.max_tool_calls),
+            max_tokens: self.max_tokens.or(base.max_tokens),
+            max_repeated_calls: self.max_repeated_calls.or(base.max_repeated_calls),
         }
     }
 }
@@ -286,9 +385,14 @@ pub struct CostConfig {
     /// Per-model pricing (USD per 1M tokens)
     #[serde(default)]
     pub prices: std::collections::HashMap<String, ModelPricing>,
+
+    /// Model (or `hint:<name>` route) to switch to once spending reaches
+    /// `warn_at_percent` of the daily or monthly limit (default: none)
+    #[serde(default)]
+    pub downgrade_model: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelPricing {
     /// Input price per 1M tokens
     #[serde(default)]
@@ -297,6 +401,26 @@ pub struct ModelPricing {
     /// Output price per 1M tokens
     #[serde(default)]
     pub output: f64,
+
+    /// Price per 1M input tokens read from the prompt cache (default: `input`)
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+
+    /// Price per 1M input tokens written to the prompt cache (default: `input`)
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    /// Effective price per 1M cache-read tokens.
+    pub fn cache_read_price(&self) -> f64 {
+        self.cache_read.unwrap_or(self.input)
+    }
+
+    /// Effective price per 1M cache-write tokens.
+    pub fn cache_write_price(&self) -> f64 {
+        self.cache_write.unwrap_or(self.input)
+    }
 }
 
 fn default_daily_limit() -> f64 {
@@ -320,6 +444,7 @@ impl Default for CostConfig {
             warn_at_percent: default_warn_percent(),
             allow_override: false,
             prices: get_default_pricing(),
+            downgrade_model: None,
         }
     }
 }
@@ -334,6 +459,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -341,6 +468,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 75.0,
+            cache_read: Some(1.5),
+            cache_write: Some(18.75),
         },
     );
     prices.insert(
@@ -348,6 +477,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -355,6 +486,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.25,
             output: 1.25,
+            cache_read: Some(0.03),
+            cache_write: Some(0.3),
         },
     );
 
@@ -364,6 +497,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 5.0,
             output: 15.0,
+            cache_read: None,
+            cache_write: None,
         },
     );
     prices.insert(
@@ -371,6 +506,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.15,
             output: 0.60,
+            cache_read: None,
+            cache_write: None,
         },
     );
     prices.insert(
@@ -378,6 +515,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 60.0,
+            cache_read: None,
+            cache_write: None,
         },
     );
 
@@ -387,6 +526,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.10,
             output: 0.40,
+            cache_read: None,
+            cache_write: None,
         },
     );
     prices.insert(
@@ -394,6 +535,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 1.25,
             output: 5.0,
+            cache_read: None,
+            cache_write: None,
         },
     );
 
@@ -702,6 +845,57 @@ fn default_http_timeout_secs() -> u64 {
     30
 }
 
+// ── Image generation tool ────────────────────────────────────────
+
+/// `image_generate` tool backed by an OpenAI-compatible
+/// `/images/generations` endpoint (OpenAI, LocalAI, stable-diffusion servers)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationConfig {
+    /// Enable the `image_generate` tool
+    #[serde(default)]
+    pub enabled: bool,
+    /// Base URL of the image API
+    #[serde(default = "default_openai_api_url")]
+    pub api_url: String,
+    /// API key for the image API (local servers usually need none)
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model name sent to the image API
+    #[serde(default = "default_image_generation_model")]
+    pub model: String,
+    /// Default image size when the model does not ask for one
+    #[serde(default = "default_image_generation_size")]
+    pub size: String,
+    /// Request timeout in seconds (default: 120)
+    #[serde(default = "default_image_generation_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_image_generation_model() -> String {
+    "dall-e-3".into()
+}
+
+fn default_image_generation_size() -> String {
+    "1024x1024".into()
+}
+
+fn default_image_generation_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for ImageGenerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_url: default_openai_api_url(),
+            api_key: None,
+            model: default_image_generation_model(),
+            size: default_image_generation_size(),
+            timeout_secs: default_image_generation_timeout_secs(),
+        }
+    }
+}
+
 // ── Memory ───────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -722,7 +916,7 @@ pub struct MemoryConfig {
     /// For sqlite backend: prune conversation rows older than this many days
     #[serde(default = "default_conversation_retention_days")]
     pub conversation_retention_days: u32,
-    /// Embedding provider: "none" | "openai" | "custom:URL"
+    /// Embedding provider: "none" | "openai" | "ollama" | "ollama:URL" | "custom:URL"
     #[serde(default = "default_embedding_provider")]
     pub embedding_provider: String,
     /// Embedding model name (e.g. "text-embedding-3-small")
@@ -877,6 +1071,14 @@ pub struct AutonomyConfig {
     /// Block high-risk shell commands even if allowlisted.
     #[serde(default = "default_true")]
     pub block_high_risk_commands: bool,
+
+    /// Seconds a tool call parked for approval on a channel stays valid.
+    #[serde(default = "default_approval_timeout_secs")]
+    pub approval_timeout_secs: u64,
+}
+
+fn default_approval_timeout_secs() -> u64 {
+    600
 }
 
 impl Default for AutonomyConfig {
@@ -928,6 +1130,7 @@ impl Default for AutonomyConfig {
             max_cost_per_day_cents: 500,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: false,
+            approval_timeout_secs: default_approval_timeout_secs(),
         }
     }
 }
@@ -1052,6 +1255,26 @@ pub struct ReliabilityConfig {
     /// Max retries for cron job execution attempts.
     #[serde(default = "default_scheduler_retries")]
     pub scheduler_retries: u32,
+    /// Start the next provider in parallel when the current one is slower
+    /// than usual, and use whichever answers first.
+    #[serde(default)]
+    pub hedge_requests: bool,
+    /// Latency percentile of a provider's recent calls after which a hedged
+    /// request is sent.
+    #[serde(default = "default_hedge_percentile")]
+    pub hedge_percentile: f64,
+    /// Hedge delay (ms) used until a provider has enough latency history.
+    #[serde(default = "default_hedge_delay_ms")]
+    pub hedge_delay_ms: u64,
+    /// Consecutive failures that open a provider/model circuit breaker (0 = off).
+    #[serde(default = "default_circuit_breaker_failures")]
+    pub circuit_breaker_failures: u32,
+    /// Seconds an open circuit breaker skips its provider/model.
+    #[serde(default = "default_circuit_breaker_cooldown_secs")]
+    pub circuit_breaker_cooldown_secs: u64,
+    /// Where provider health is persisted - computed from home, not serialized
+    #[serde(skip)]
+    pub health_path: Option<PathBuf>,
 }
 
 fn default_provider_retries() -> u32 {
@@ -1078,6 +1301,22 @@ fn default_scheduler_retries() -> u32 {
     2
 }
 
+fn default_hedge_percentile() -> f64 {
+    95.0
+}
+
+fn default_hedge_delay_ms() -> u64 {
+    5_000
+}
+
+fn default_circuit_breaker_failures() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_cooldown_secs() -> u64 {
+    60
+}
+
 impl Default for ReliabilityConfig {
     fn default() -> Self {
         Self {
@@ -1090,6 +1329,72 @@ impl Default for ReliabilityConfig {
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
             scheduler_retries: default_scheduler_retries(),
+            hedge_requests: false,
+            hedge_percentile: default_hedge_percentile(),
+            hedge_delay_ms: default_hedge_delay_ms(),
+            circuit_breaker_failures: default_circuit_breaker_failures(),
+            circuit_breaker_cooldown_secs: default_circuit_breaker_cooldown_secs(),
+            health_path: None,
+        }
+    }
+}
+
+// ── Provider record/replay ───────────────────────────────────────
+
+/// Provider record/replay mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayMode {
+    /// Talk to the provider normally (default)
+    #[default]
+    Off,
+    /// Forward calls to the provider and append each exchange to the cassette
+    Record,
+    /// Answer from the cassette only; no network access
+    Replay,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    /// Record or replay provider traffic (`off`, `record`, `replay`).
+    #[serde(default)]
+    pub mode: ReplayMode,
+    /// JSONL cassette path. Relative paths resolve against the workspace;
+    /// defaults to `<workspace>/provider-cassette.jsonl`.
+    #[serde(default)]
+    pub cassette: Option<PathBuf>,
+}
+
+impl ReplayConfig {
+    pub fn cassette_path(&self, workspace_dir: &Path) -> PathBuf {
+        match &self.cassette {
+            Some(path) if path.is_absolute() => path.clone(),
+            Some(path) => workspace_dir.join(path),
+            None => workspace_dir.join("provider-cassette.jsonl"),
+        }
+    }
+}
+
+// ── Outbound redaction ───────────────────────────────────────────
+
+/// Redaction of secrets from prompts before they are sent to a provider.
+/// Placeholders the model returns in tool-call arguments are restored locally.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Replace API keys, tokens and private keys with placeholders
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Extra regexes to redact; with a capture group only the group is replaced
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
         }
     }
 }
@@ -1145,6 +1450,12 @@ impl Default for SchedulerConfig {
 /// hint = "fast"
 /// provider = "groq"
 /// model = "llama-3.3-70b-versatile"
+///
+/// [[model_routes]]
+/// hint = "deep"
+/// provider = "anthropic"
+/// model = "claude-sonnet-4-20250514"
+/// thinking_budget = 8000
 /// ```
 ///
 /// Usage: pass `hint:reasoning` as the model parameter to route the request.
@@ -1159,6 +1470,163 @@ pub struct ModelRouteConfig {
     /// Optional API key override for this route's provider
     #[serde(default)]
     pub api_key: Option<String>,
+    /// Extended-thinking token budget (Anthropic; OpenRouter forwards it as
+    /// `reasoning.max_tokens`). Thinking stays off when unset.
+    #[serde(default)]
+    pub thinking_budget: Option<u32>,
+    /// Reasoning effort for OpenAI and OpenRouter reasoning models
+    #[serde(default)]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+/// How much a reasoning model should think before answering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+// ── Model capabilities ───────────────────────────────────────────
+
+/// What a model supports. Unset fields are filled from the refreshed model
+/// catalog (`zeroclaw models refresh`) and built-in family defaults.
+///
+/// ```toml
+/// [model_capabilities."llama3.1:8b"]
+/// context_window = 131072
+/// vision = false
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// Context window in tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// Maximum completion tokens per response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// Accepts image input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    /// Supports native tool calling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_tools: Option<bool>,
+    /// Supports schema-constrained structured output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_output: Option<bool>,
+    /// Price per 1M tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+// ── Routing rules ────────────────────────────────────────────────
+
+/// Rules that pick a `[[model_routes]]` hint for each incoming message.
+///
+/// ```toml
+/// [routing]
+/// default_hint = "fast"
+///
+/// [[routing.rules]]
+/// name = "code"
+/// patterns = ["```", "(?i)\\berror:"]
+/// keywords = ["cargo ", "docker", "stack trace"]
+///
+/// [[routing.rules]]
+/// name = "photos"
+/// hint = "vision"
+/// attachments = ["image"]
+/// ```
+///
+/// Rules are checked in order and the first match wins. When none matches,
+/// the optional classifier runs, then `default_hint` applies. Without a
+/// `[routing]` section, technical messages stay on the default model and
+/// everything else goes to `hint:fast` if that route exists; an empty
+/// `[routing]` section turns routing off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub rules: Vec<RoutingRuleConfig>,
+    /// Hint for messages no rule or classifier claims (`None` = default model).
+    #[serde(default)]
+    pub default_hint: Option<String>,
+    /// Fallback classifier consulted when no rule matches.
+    #[serde(default)]
+    pub classifier: Option<RoutingClassifierConfig>,
+}
+
+/// A single routing rule. Every condition that is set must match; within a
+/// list, any entry may match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingRuleConfig {
+    /// Label shown by `zeroclaw route explain`
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Route hint for matching messages; omit to pin them to the default model
+    #[serde(default)]
+    pub hint: Option<String>,
+    /// Case-insensitive substrings
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Regular expressions
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Attachment kinds (`image`, `file`, `any`, or a media type like `voice`)
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    /// Channel names (e.g. "telegram", "gateway")
+    #[serde(default)]
+    pub channels: Vec<String>,
+    /// Sender identifiers
+    #[serde(default)]
+    pub senders: Vec<String>,
+    /// Minimum message length in characters
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    /// Maximum message length in characters
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    /// Match only follow-ups (`true`) or only opening messages (`false`)
+    #[serde(default)]
+    pub follow_up: Option<bool>,
+}
+
+/// How the fallback classifier picks a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutingClassifierKind {
+    /// Ask a (cheap) model to choose among the route descriptions
+    Llm,
+    /// Compare the message embedding with each route description
+    Embedding,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingClassifierConfig {
+    pub kind: RoutingClassifierKind,
+    /// Route hint → description of the messages it should handle.
+    pub routes: std::collections::BTreeMap<String, String>,
+    /// Model for the LLM classifier (model name or `hint:<name>`; default: default model)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Minimum cosine similarity for the embedding classifier
+    #[serde(default = "default_routing_min_similarity")]
+    pub min_similarity: f32,
+}
+
+fn default_routing_min_similarity() -> f32 {
+    0.3
 }
 
 // ── Heartbeat ────────────────────────────────────────────────────
@@ -1244,11 +1712,226 @@ pub struct CustomTunnelConfig {
     pub url_pattern: Option<String>,
 }
 
+// ── Transcription ────────────────────────────────────────────────
+
+/// Speech-to-text for voice notes and audio attachments on channels.
+///
+/// ```toml
+/// [transcription]
+/// enabled = true
+/// backend = "whisper"
+/// api_url = "http://localhost:8000"   # local whisper server, or OpenAI
+/// model = "whisper-1"
+///
+/// # or a local CLI; `{file}` is replaced with the audio path
+/// # backend = "command"
+/// # command = "whisper-cli -nt -f {file}"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionConfig {
+    /// Transcribe voice, audio and video-note attachments before the agent
+    /// sees the message
+    #[serde(default)]
+    pub enabled: bool,
+    /// `whisper` (OpenAI-compatible `/audio/transcriptions`) or `command`
+    #[serde(default = "default_transcription_backend")]
+    pub backend: String,
+    /// Base URL of the Whisper-compatible API
+    #[serde(default = "default_openai_api_url")]
+    pub api_url: String,
+    /// API key for the Whisper API (local servers usually need none)
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model name sent to the Whisper API
+    #[serde(default = "default_transcription_model")]
+    pub model: String,
+    /// ISO-639-1 language hint; auto-detected when unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Shell command for the `command` backend; prints the transcript to stdout
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Give up on a single transcription after this many seconds
+    #[serde(default = "default_transcription_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_transcription_backend() -> String {
+    "whisper".into()
+}
+
+fn default_openai_api_url() -> String {
+    "https://api.openai.com".into()
+}
+
+fn default_transcription_model() -> String {
+    "whisper-1".into()
+}
+
+fn default_transcription_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: default_transcription_backend(),
+            api_url: default_openai_api_url(),
+            api_key: None,
+            model: default_transcription_model(),
+            language: None,
+            command: None,
+            timeout_secs: default_transcription_timeout_secs(),
+        }
+    }
+}
+
+// ── Speech ───────────────────────────────────────────────────────
+
+/// Text-to-speech for voice replies on channels that can send audio.
+///
+/// ```toml
+/// [speech]
+/// enabled = true
+/// backend = "openai"            # or "command"
+/// voice = "alloy"
+/// reply_mode = "when_voice"     # off | when_voice | always
+///
+/// # local CLI reading text on stdin and writing OGG/Opus to stdout
+/// # command = "piper -m en_US-amy-medium.onnx -f - | ffmpeg -loglevel error -i - -c:a libopus -f ogg -"
+///
+/// [speech.channels]
+/// telegram = "always"
+///
+/// [speech.users]
+/// "123456789" = "off"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechConfig {
+    /// Allow agent replies to be sent as voice messages
+    #[serde(default)]
+    pub enabled: bool,
+    /// `openai` (OpenAI-compatible `/audio/speech`) or `command`
+    #[serde(default = "default_speech_backend")]
+    pub backend: String,
+    /// Base URL of the speech API
+    #[serde(default = "default_openai_api_url")]
+    pub api_url: String,
+    /// API key for the speech API (local servers usually need none)
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model name sent to the speech API
+    #[serde(default = "default_speech_model")]
+    pub model: String,
+    /// Voice name sent to the speech API
+    #[serde(default = "default_speech_voice")]
+    pub voice: String,
+    /// Audio format: `opus` (OGG, plays as a voice note), `mp3`, `aac` or `wav`
+    #[serde(default = "default_speech_format")]
+    pub format: String,
+    /// Shell command for the `command` backend; reads the reply text on
+    /// stdin and writes audio in `format` to stdout
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Give up on a single synthesis after this many seconds
+    #[serde(default = "default_speech_timeout_secs")]
+    pub timeout_secs: u64,
+    /// When to answer with voice instead of text
+    #[serde(default)]
+    pub reply_mode: VoiceReplyMode,
+    /// Per-channel `reply_mode` overrides, keyed by channel name
+    #[serde(default)]
+    pub channels: HashMap<String, VoiceReplyMode>,
+    /// Per-user `reply_mode` overrides, keyed by sender ID; these win over
+    /// channel overrides
+    #[serde(default)]
+    pub users: HashMap<String, VoiceReplyMode>,
+}
+
+/// When a channel reply is spoken rather than written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceReplyMode {
+    /// Always reply with text
+    Off,
+    /// Reply with voice when the incoming message was a voice note
+    #[default]
+    WhenVoice,
+    /// Reply with voice to every message
+    Always,
+}
+
+impl VoiceReplyMode {
+    pub fn wants_voice(self, sent_voice: bool) -> bool {
+        match self {
+            Self::Off => false,
+            Self::WhenVoice => sent_voice,
+            Self::Always => true,
+        }
+    }
+}
+
+fn default_speech_backend() -> String {
+    "openai".into()
+}
+
+fn default_speech_model() -> String {
+    "tts-1".into()
+}
+
+fn default_speech_voice() -> String {
+    "alloy".into()
+}
+
+fn default_speech_format() -> String {
+    "opus".into()
+}
+
+fn default_speech_timeout_secs() -> u64 {
+    60
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: default_speech_backend(),
+            api_url: default_openai_api_url(),
+            api_key: None,
+            model: default_speech_model(),
+            voice: default_speech_voice(),
+            format: default_speech_format(),
+            command: None,
+            timeout_secs: default_speech_timeout_secs(),
+            reply_mode: VoiceReplyMode::default(),
+            channels: HashMap::new(),
+            users: HashMap::new(),
+        }
+    }
+}
+
+impl SpeechConfig {
+    /// Effective reply mode for a sender on a channel; user overrides win
+    /// over channel overrides, which win over `reply_mode`.
+    pub fn reply_mode_for(&self, channel: &str, sender: &str) -> VoiceReplyMode {
+        self.users
+            .get(sender)
+            .or_else(|| self.channels.get(channel))
+            .copied()
+            .unwrap_or(self.reply_mode)
+    }
+}
+
 // ── Channels ─────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelsConfig {
     pub cli: bool,
+    /// Show model reasoning ahead of replies on channels that can collapse
+    /// it (Telegram, Discord)
+    #[serde(default)]
+    pub show_reasoning: bool,
     pub telegram: Option<TelegramConfig>,
     pub discord: Option<DiscordConfig>,
     pub slack: Option<SlackConfig>,
@@ -1260,12 +1943,35 @@ pub struct ChannelsConfig {
     pub irc: Option<IrcConfig>,
     pub lark: Option<LarkConfig>,
     pub dingtalk: Option<DingTalkConfig>,
+    /// Per-channel overrides of `[agent.budget]`, keyed by channel name
+    #[serde(default)]
+    pub budgets: HashMap<String, TurnBudgetConfig>,
+    /// What a new message does while the same sender's previous turn is
+    /// still running
+    #[serde(default)]
+    pub on_new_message: TurnInterruptMode,
+}
+
+/// Handling of a message that arrives while the sender's last turn runs.
+/// In every mode but `off`, a bare "stop" or "cancel" ends the running turn.
+/// Defaults to `off`, which keeps the behaviour from before these modes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnInterruptMode {
+    /// Start another turn alongside the running one
+    #[default]
+    Off,
+    /// Cancel the running turn, then answer the new message
+    Cancel,
+    /// Hand the message to the running turn at its next step
+    Steer,
 }
 
 impl Default for ChannelsConfig {
     fn default() -> Self {
         Self {
             cli: true,
+            show_reasoning: false,
             telegram: None,
             discord: None,
             slack: None,
@@ -1277,6 +1983,8 @@ impl Default for ChannelsConfig {
             irc: None,
             lark: None,
             dingtalk: None,
+            budgets: HashMap::new(),
+            on_new_message: TurnInterruptMode::default(),
         }
     }
 }
@@ -1302,6 +2010,8 @@ pub struct DiscordConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackConfig {
     pub bot_token: String,
+    /// App-level token (`xapp-…`). When set, button presses are received
+    /// over Socket Mode; without it buttons fall back to a text menu.
     pub app_token: Option<String>,
     pub channel_id: Option<String>,
     #[serde(default)]
@@ -1581,9 +2291,16 @@ impl Default for Config {
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
+            replay: ReplayConfig::default(),
+            redaction: RedactionConfig::default(),
+            security: SecurityConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            speech: SpeechConfig::default(),
             scheduler: SchedulerConfig::default(),
             agent: AgentConfig::default(),
             model_routes: Vec::new(),
+            routing: None,
+            model_capabilities: HashMap::new(),
             heartbeat: HeartbeatConfig::default(),
             channels_config: ChannelsConfig::default(),
             memory: MemoryConfig::default(),
@@ -1593,6 +2310,7 @@ impl Default for Config {
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            image_generation: ImageGenerationConfig::default(),
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
             peripherals: PeripheralsConfig::default(),
@@ -1624,12 +2342,14 @@ impl Config {
             // Set computed paths that are skipped during serialization
             config.config_path = config_path.clone();
             config.workspace_dir = zeroclaw_dir.join("workspace");
+            config.reliability.health_path = Some(zeroclaw_dir.join("provider_health.json"));
             config.apply_env_overrides();
             Ok(config)
         } else {
             let mut config = Config::default();
             config.config_path = config_path.clone();
             config.workspace_dir = zeroclaw_dir.join("workspace");
+            config.reliability.health_path = Some(zeroclaw_dir.join("provider_health.json"));
             config.save()?;
             config.apply_env_overrides();
             Ok(config)
@@ -1700,6 +2420,21 @@ impl Config {
             self.gateway.allow_public_bind = val == "1" || val.eq_ignore_ascii_case("true");
         }
 
+        // Provider record/replay: ZEROCLAW_REPLAY_MODE / ZEROCLAW_REPLAY_CASSETTE
+        if let Ok(mode) = std::env::var("ZEROCLAW_REPLAY_MODE") {
+            match mode.trim().to_ascii_lowercase().as_str() {
+                "off" => self.replay.mode = ReplayMode::Off,
+                "record" => self.replay.mode = ReplayMode::Record,
+                "replay" => self.replay.mode = ReplayMode::Replay,
+                _ => {}
+            }
+        }
+        if let Ok(cassette) = std::env::var("ZEROCLAW_REPLAY_CASSETTE") {
+            if !cassette.is_empty() {
+                self.replay.cassette = Some(PathBuf::from(cassette));
+            }
+        }
+
         // Temperature: ZEROCLAW_TEMPERATURE
         if let Ok(temp_str) = std::env::var("ZEROCLAW_TEMPERATURE") {
             if let Ok(temp) = temp_str.parse::<f64>() {
@@ -1916,20 +2651,29 @@ mod tests {
                 max_cost_per_day_cents: 1000,
                 require_approval_for_medium_risk: false,
                 block_high_risk_commands: true,
+                approval_timeout_secs: 600,
             },
             runtime: RuntimeConfig {
                 kind: "docker".into(),
                 ..RuntimeConfig::default()
             },
             reliability: ReliabilityConfig::default(),
+            replay: ReplayConfig::default(),
+            redaction: RedactionConfig::default(),
+            security: SecurityConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            speech: SpeechConfig::default(),
             scheduler: SchedulerConfig::default(),
             model_routes: Vec::new(),
+            routing: None,
+            model_capabilities: HashMap::new(),
             heartbeat: HeartbeatConfig {
                 enabled: true,
                 interval_minutes: 15,
             },
             channels_config: ChannelsConfig {
                 cli: true,
+                show_reasoning: false,
                 telegram: Some(TelegramConfig {
                     bot_token: "123:ABC".into(),
                     allowed_users: vec!["user1".into()],
@@ -1944,6 +2688,8 @@ mod tests {
                 irc: None,
                 lark: None,
                 dingtalk: None,
+                budgets: HashMap::new(),
+                on_new_message: TurnInterruptMode::default(),
             },
             memory: MemoryConfig::default(),
             tunnel: TunnelConfig::default(),
@@ -1952,6 +2698,7 @@ mod tests {
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            image_generation: ImageGenerationConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
@@ -2005,10 +2752,13 @@ default_temperature = 0.7
     fn agent_config_defaults() {
         let cfg = AgentConfig::default();
         assert!(!cfg.compact_context);
-        assert_eq!(cfg.max_tool_iterations, 10);
+        assert_eq!(cfg.max_tool_iterations, None);
         assert_eq!(cfg.max_history_messages, 50);
         assert!(!cfg.parallel_tools);
+        assert_eq!(cfg.max_parallel_tools, 4);
+        assert_eq!(cfg.tool_parallelism(), 1);
         assert_eq!(cfg.tool_dispatcher, "auto");
+        assert_eq!(cfg.budget, TurnBudgetConfig::default());
     }
 
     #[test]
@@ -2020,16 +2770,67 @@ compact_context = true
 max_tool_iterations = 20
 max_history_messages = 80
 parallel_tools = true
+max_parallel_tools = 8
 tool_dispatcher = "xml"
 "#;
         let parsed: Config = toml::from_str(raw).unwrap();
         assert!(parsed.agent.compact_context);
-        assert_eq!(parsed.agent.max_tool_iterations, 20);
+        assert_eq!(parsed.agent.max_tool_iterations, Some(20));
+        assert_eq!(parsed.agent.turn_budget().max_iterations, Some(20));
         assert_eq!(parsed.agent.max_history_messages, 80);
         assert!(parsed.agent.parallel_tools);
+        assert_eq!(parsed.agent.tool_parallelism(), 8);
         assert_eq!(parsed.agent.tool_dispatcher, "xml");
     }
 
+    #[test]
+    fn turn_budgets_deserialize_with_channel_overrides() {
+        let raw = r#"
+default_temperature = 0.7
+[agent.budget]
+max_tool_calls = 40
+max_duration_secs = 300
+
+[channels_config]
+cli = true
+
+[channels_config.budgets.telegram]
+max_tool_calls = 10
+max_tokens = 50000
+"#;
+        let parsed: Config = toml::from_str(raw).unwrap();
+        let agent = &parsed.agent.budget;
+        assert_eq!(agent.max_tool_calls, Some(40));
+        assert_eq!(agent.max_iterations, None);
+
+        let telegram = parsed.channels_config.budgets["telegram"].or(agent);
+        assert_eq!(telegram.max_tool_calls, Some(10));
+        assert_eq!(telegram.max_tokens, Some(50_000));
+        assert_eq!(telegram.max_duration_secs, Some(300));
+        assert_eq!(telegram.max_repeated_calls, None);
+    }
+
+    #[test]
+    fn on_new_message_defaults_to_off() {
+        let parsed: Config = toml::from_str("default_temperature = 0.7").unwrap();
+        assert_eq!(
+            parsed.channels_config.on_new_message,
+            TurnInterruptMode::Off
+        );
+
+        let raw = r#"
+default_temperature = 0.7
+[channels_config]
+cli = true
+on_new_message = "steer"
+"#;
+        let parsed: Config = toml::from_str(raw).unwrap();
+        assert_eq!(
+            parsed.channels_config.on_new_message,
+            TurnInterruptMode::Steer
+        );
+    }
+
     #[test]
     fn config_save_and_load_tmpdir() {
         let dir = std::env::temp_dir().join("zeroclaw_test_config");
@@ -2048,8 +2849,15 @@ tool_dispatcher = "xml"
             autonomy: AutonomyConfig::default(),
             runtime: RuntimeConfig::default(),
             reliability: ReliabilityConfig::default(),
+            replay: ReplayConfig::default(),
+            redaction: RedactionConfig::default(),
+            security: SecurityConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            speech: SpeechConfig::default(),
             scheduler: SchedulerConfig::default(),
             model_routes: Vec::new(),
+            routing: None,
+            model_capabilities: HashMap::new(),
             heartbeat: HeartbeatConfig::default(),
             channels_config: ChannelsConfig::default(),
             memory: MemoryConfig::default(),
@@ -2059,6 +2867,7 @@ tool_dispatcher = "xml"
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            image_generation: ImageGenerationConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
@@ -2218,6 +3027,7 @@ tool_dispatcher = "xml"
     fn channels_config_with_imessage_and_matrix() {
         let c = ChannelsConfig {
             cli: true,
+            show_reasoning: false,
             telegram: None,
             discord: None,
             slack: None,
@@ -2236,6 +3046,8 @@ tool_dispatcher = "xml"
             irc: None,
             lark: None,
             dingtalk: None,
+            budgets: HashMap::new(),
+            on_new_message: TurnInterruptMode::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -2305,6 +3117,47 @@ channel_id = "C123"
         assert_eq!(parsed.channel_id.as_deref(), Some("C123"));
     }
 
+    #[test]
+    fn transcription_config_defaults_for_partial_toml() {
+        let toml_str = r#"
+enabled = true
+backend = "command"
+command = "whisper-cli -nt -f {file}"
+"#;
+        let parsed: TranscriptionConfig = toml::from_str(toml_str).unwrap();
+        assert!(parsed.enabled);
+        assert_eq!(parsed.model, "whisper-1");
+        assert_eq!(parsed.timeout_secs, 120);
+        assert!(!Config::default().transcription.enabled);
+    }
+
+    #[test]
+    fn speech_reply_mode_prefers_user_then_channel_overrides() {
+        let toml_str = r#"
+enabled = true
+reply_mode = "off"
+
+[channels]
+telegram = "when_voice"
+
+[users]
+"42" = "always"
+"#;
+        let parsed: SpeechConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.format, "opus");
+        assert_eq!(parsed.reply_mode_for("discord", "7"), VoiceReplyMode::Off);
+        assert_eq!(
+            parsed.reply_mode_for("telegram", "7"),
+            VoiceReplyMode::WhenVoice
+        );
+        assert_eq!(
+            parsed.reply_mode_for("telegram", "42"),
+            VoiceReplyMode::Always
+        );
+        assert!(VoiceReplyMode::WhenVoice.wants_voice(true));
+        assert!(!VoiceReplyMode::WhenVoice.wants_voice(false));
+    }
+
     #[test]
     fn webhook_config_with_secret() {
         let json = r#"{"port":8080,"secret":"my-secret-key"}"#;
@@ -2379,6 +3232,7 @@ channel_id = "C123"
     fn channels_config_with_whatsapp() {
         let c = ChannelsConfig {
             cli: true,
+            show_reasoning: false,
             telegram: None,
             discord: None,
             slack: None,
@@ -2396,6 +3250,8 @@ channel_id = "C123"
             irc: None,
             lark: None,
             dingtalk: None,
+            budgets: HashMap::new(),
+            on_new_message: TurnInterruptMode::default(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -2868,6 +3724,25 @@ default_temperature = 0.7
         std::env::remove_var("ZEROCLAW_TEMPERATURE");
     }
 
+    #[test]
+    fn env_override_replay_mode_and_cassette() {
+        let _env_guard = env_override_test_guard();
+        let mut config = Config::default();
+        assert_eq!(config.replay.mode, ReplayMode::Off);
+
+        std::env::set_var("ZEROCLAW_REPLAY_MODE", "Replay");
+        std::env::set_var("ZEROCLAW_REPLAY_CASSETTE", "bugs/issue-42.jsonl");
+        config.apply_env_overrides();
+        assert_eq!(config.replay.mode, ReplayMode::Replay);
+        assert_eq!(
+            config.replay.cassette_path(Path::new("/ws")),
+            PathBuf::from("/ws/bugs/issue-42.jsonl")
+        );
+
+        std::env::remove_var("ZEROCLAW_REPLAY_MODE");
+        std::env::remove_var("ZEROCLAW_REPLAY_CASSETTE");
+    }
+
     #[test]
     fn env_override_invalid_port_ignored() {
         let _env_guard = env_override_test_guard();
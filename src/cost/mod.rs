@@ -2,4 +2,6 @@ pub mod tracker;
 pub mod types;
 
 pub use tracker::CostTracker;
-pub use types::{BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod};
+pub use types::{
+    BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod, UsageScope,
+};
@@ -1,5 +1,9 @@
-use super::types::{BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod};
-use crate::config::schema::CostConfig;
+use super::types::{
+    BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod, UsageScope,
+};
+use crate::config::schema::{CostConfig, ModelPricing};
+use crate::config::Config;
+use crate::providers::ChatUsage;
 use anyhow::{anyhow, Context, Result};
 use chrono::{Datelike, NaiveDate, Utc};
 use std::collections::HashMap;
@@ -9,11 +13,16 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Cost tracker for API usage monitoring and budget enforcement.
+///
+/// Clones share storage and session totals; see [`CostTracker::scoped`].
+#[derive(Clone)]
 pub struct CostTracker {
-    config: CostConfig,
+    config: Arc<CostConfig>,
     storage: Arc<Mutex<CostStorage>>,
     session_id: String,
     session_costs: Arc<Mutex<Vec<CostRecord>>>,
+    daily_cap_usd: Option<f64>,
+    scope: UsageScope,
 }
 
 impl CostTracker {
@@ -26,13 +35,111 @@ impl CostTracker {
         })?;
 
         Ok(Self {
-            config,
+            config: Arc::new(config),
             storage: Arc::new(Mutex::new(storage)),
             session_id: uuid::Uuid::new_v4().to_string(),
             session_costs: Arc::new(Mutex::new(Vec::new())),
+            daily_cap_usd: None,
+            scope: UsageScope::default(),
         })
     }
 
+    /// Create a tracker from `[cost]`, capped by `autonomy.max_cost_per_day_cents`.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(config.cost.clone(), &config.workspace_dir)?
+            .with_daily_cap_cents(config.autonomy.max_cost_per_day_cents))
+    }
+
+    /// Hard daily spending cap that applies even when `[cost]` is disabled.
+    /// Zero means no cap.
+    #[must_use]
+    pub fn with_daily_cap_cents(mut self, cents: u32) -> Self {
+        self.daily_cap_usd = (cents > 0).then(|| f64::from(cents) / 100.0);
+        self
+    }
+
+    /// A handle that attributes recorded usage to `scope`. Storage, session
+    /// totals and limits are shared with `self`.
+    #[must_use]
+    pub fn scoped(&self, scope: UsageScope) -> Self {
+        Self {
+            scope,
+            ..self.clone()
+        }
+    }
+
+    /// Whether budgets are checked and usage is recorded.
+    pub fn is_active(&self) -> bool {
+        self.config.enabled || self.daily_cap_usd.is_some()
+    }
+
+    /// Daily limit in effect: the stricter of `[cost]` and the autonomy cap.
+    fn daily_limit_usd(&self) -> Option<f64> {
+        let configured = self.config.enabled.then_some(self.config.daily_limit_usd);
+        match (configured, self.daily_cap_usd) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Model to switch to once spending crosses the warning threshold.
+    pub fn downgrade_model(&self) -> Option<&str> {
+        self.config
+            .downgrade_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+    }
+
+    /// Look up pricing for `model`. Keys are usually `provider/model`, so a
+    /// bare model name also matches a key ending in `/<model>`.
+    pub fn pricing_for(&self, model: &str) -> Option<&ModelPricing> {
+        self.config.prices.get(model).or_else(|| {
+            let suffix = format!("/{model}");
+            self.config
+                .prices
+                .iter()
+                .find(|(key, _)| key.ends_with(&suffix))
+                .map(|(_, pricing)| pricing)
+        })
+    }
+
+    /// Price token counts for `model`. Unknown models cost nothing.
+    pub fn usage_for(&self, model: &str, input_tokens: u64, output_tokens: u64) -> TokenUsage {
+        let (input_price, output_price) = self
+            .pricing_for(model)
+            .map_or((0.0, 0.0), |p| (p.input, p.output));
+        TokenUsage::new(
+            model,
+            input_tokens,
+            output_tokens,
+            input_price,
+            output_price,
+        )
+    }
+
+    /// Price a provider usage report, billing prompt-cache reads and writes
+    /// at their own rates.
+    pub fn chat_usage_for(&self, model: &str, usage: &ChatUsage) -> TokenUsage {
+        let (input_price, read_price, write_price) =
+            self.pricing_for(model).map_or((0.0, 0.0, 0.0), |p| {
+                (p.input, p.cache_read_price(), p.cache_write_price())
+            });
+        self.usage_for(model, usage.input_tokens, usage.output_tokens)
+            .with_cache(
+                usage.cached_input_tokens,
+                usage.cache_write_input_tokens,
+                input_price,
+                read_price,
+                write_price,
+            )
+    }
+
+    /// Record the usage reported for one LLM call.
+    pub fn record_chat_usage(&self, model: &str, usage: &ChatUsage) -> Result<()> {
+        self.record_usage(self.chat_usage_for(model, usage))
+    }
+
     /// Get the session ID.
     pub fn session_id(&self) -> &str {
         &self.session_id
@@ -52,7 +159,7 @@ impl CostTracker {
 
     /// Check if a request is within budget.
     pub fn check_budget(&self, estimated_cost_usd: f64) -> Result<BudgetCheck> {
-        if !self.config.enabled {
+        if !self.is_active() {
             return Ok(BudgetCheck::Allowed);
         }
 
@@ -65,35 +172,42 @@ impl CostTracker {
         let mut storage = self.lock_storage()?;
         let (daily_cost, monthly_cost) = storage.get_aggregated_costs()?;
 
+        let daily_limit = self.daily_limit_usd().unwrap_or(f64::INFINITY);
+        let monthly_limit = if self.config.enabled {
+            self.config.monthly_limit_usd
+        } else {
+            f64::INFINITY
+        };
+
         // Check daily limit
         let projected_daily = daily_cost + estimated_cost_usd;
-        if projected_daily > self.config.daily_limit_usd {
+        if projected_daily > daily_limit {
             return Ok(BudgetCheck::Exceeded {
                 current_usd: daily_cost,
-                limit_usd: self.config.daily_limit_usd,
+                limit_usd: daily_limit,
                 period: UsagePeriod::Day,
             });
         }
 
         // Check monthly limit
         let projected_monthly = monthly_cost + estimated_cost_usd;
-        if projected_monthly > self.config.monthly_limit_usd {
+        if projected_monthly > monthly_limit {
             return Ok(BudgetCheck::Exceeded {
                 current_usd: monthly_cost,
-                limit_usd: self.config.monthly_limit_usd,
+                limit_usd: monthly_limit,
                 period: UsagePeriod::Month,
             });
         }
 
         // Check warning thresholds
         let warn_threshold = f64::from(self.config.warn_at_percent.min(100)) / 100.0;
-        let daily_warn_threshold = self.config.daily_limit_usd * warn_threshold;
-        let monthly_warn_threshold = self.config.monthly_limit_usd * warn_threshold;
+        let daily_warn_threshold = daily_limit * warn_threshold;
+        let monthly_warn_threshold = monthly_limit * warn_threshold;
 
         if projected_daily >= daily_warn_threshold {
             return Ok(BudgetCheck::Warning {
                 current_usd: daily_cost,
-                limit_usd: self.config.daily_limit_usd,
+                limit_usd: daily_limit,
                 period: UsagePeriod::Day,
             });
         }
@@ -101,7 +215,7 @@ impl CostTracker {
         if projected_monthly >= monthly_warn_threshold {
             return Ok(BudgetCheck::Warning {
                 current_usd: monthly_cost,
-                limit_usd: self.config.monthly_limit_usd,
+                limit_usd: monthly_limit,
                 period: UsagePeriod::Month,
             });
         }
@@ -111,7 +225,7 @@ impl CostTracker {
 
     /// Record a usage event.
     pub fn record_usage(&self, usage: TokenUsage) -> Result<()> {
-        if !self.config.enabled {
+        if !self.is_active() {
             return Ok(());
         }
 
@@ -121,7 +235,7 @@ impl CostTracker {
             ));
         }
 
-        let record = CostRecord::new(&self.session_id, usage);
+        let record = CostRecord::new(&self.session_id, usage).with_scope(self.scope.clone());
 
         // Persist first for durability guarantees.
         {
@@ -526,6 +640,99 @@ mod tests {
         assert!((today_cost - valid_usage.cost_usd).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn autonomy_cap_applies_when_cost_tracking_disabled() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(CostConfig::default(), tmp.path())
+            .unwrap()
+            .with_daily_cap_cents(1);
+        assert!(tracker.is_active());
+
+        tracker
+            .record_usage(TokenUsage::new("test/model", 20_000, 0, 1.0, 1.0))
+            .unwrap();
+
+        match tracker.check_budget(0.0).unwrap() {
+            BudgetCheck::Exceeded {
+                limit_usd, period, ..
+            } => {
+                assert!((limit_usd - 0.01).abs() < f64::EPSILON);
+                assert_eq!(period, UsagePeriod::Day);
+            }
+            other => panic!("expected exceeded, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stricter_of_configured_and_autonomy_daily_limit_wins() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(enabled_config(), tmp.path())
+            .unwrap()
+            .with_daily_cap_cents(200);
+        assert_eq!(tracker.daily_limit_usd(), Some(2.0));
+
+        let tracker = tracker.with_daily_cap_cents(0);
+        assert_eq!(tracker.daily_limit_usd(), Some(10.0));
+    }
+
+    #[test]
+    fn scoped_tracker_shares_storage_and_tags_records() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+        let scoped = tracker.scoped(UsageScope::channel("telegram", "alice"));
+
+        scoped
+            .record_chat_usage(
+                "claude-sonnet-4-20250514",
+                &ChatUsage {
+                    input_tokens: 1_000_000,
+                    output_tokens: 0,
+                    cached_input_tokens: 0,
+                    cache_write_input_tokens: 0,
+                },
+            )
+            .unwrap();
+
+        // Bare model names resolve against `provider/model` pricing keys.
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.request_count, 1);
+        assert!((summary.daily_cost_usd - 3.0).abs() < 1e-9);
+
+        let line = fs::read_to_string(resolve_storage_path(tmp.path()).unwrap()).unwrap();
+        let record: CostRecord = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(record.scope, UsageScope::channel("telegram", "alice"));
+    }
+
+    #[test]
+    fn chat_usage_bills_cache_tokens_at_cache_rates() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+
+        let usage = tracker.chat_usage_for(
+            "claude-sonnet-4-20250514",
+            &ChatUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 0,
+                cached_input_tokens: 900_000,
+                cache_write_input_tokens: 100_000,
+            },
+        );
+
+        // 900k reads at $0.30/M + 100k writes at $3.75/M
+        assert!((usage.cost_usd - 0.645).abs() < 1e-9);
+        assert_eq!(usage.cache_read_tokens, 900_000);
+        assert_eq!(usage.cache_write_tokens, 100_000);
+    }
+
+    #[test]
+    fn unknown_model_is_priced_at_zero() {
+        let tmp = TempDir::new().unwrap();
+        let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+        let usage = tracker.usage_for("local/llama3", 1000, 1000);
+        assert!(usage.cost_usd.abs() < f64::EPSILON);
+        assert_eq!(usage.total_tokens, 2000);
+    }
+
     #[test]
     fn invalid_budget_estimate_is_rejected() {
         let tmp = TempDir::new().unwrap();
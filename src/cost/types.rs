@@ -11,6 +11,12 @@ pub struct TokenUsage {
     pub output_tokens: u64,
     /// Total tokens
     pub total_tokens: u64,
+    /// Portion of `input_tokens` read from the provider's prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Portion of `input_tokens` written to the provider's prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: u64,
     /// Calculated cost in USD
     pub cost_usd: f64,
     /// Timestamp of the request
@@ -49,11 +55,38 @@ impl TokenUsage {
             input_tokens,
             output_tokens,
             total_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
             cost_usd,
             timestamp: chrono::Utc::now(),
         }
     }
 
+    /// Re-price the cached portion of `input_tokens`. `new()` charges every
+    /// input token at `input_price_per_million`; cache reads and writes are
+    /// billed at their own rates instead.
+    #[must_use]
+    pub fn with_cache(
+        mut self,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+        input_price_per_million: f64,
+        cache_read_price_per_million: f64,
+        cache_write_price_per_million: f64,
+    ) -> Self {
+        let input_price = Self::sanitize_price(input_price_per_million);
+        let read_price = Self::sanitize_price(cache_read_price_per_million);
+        let write_price = Self::sanitize_price(cache_write_price_per_million);
+
+        let read_adjust = (cache_read_tokens as f64 / 1_000_000.0) * (read_price - input_price);
+        let write_adjust = (cache_write_tokens as f64 / 1_000_000.0) * (write_price - input_price);
+
+        self.cache_read_tokens = cache_read_tokens;
+        self.cache_write_tokens = cache_write_tokens;
+        self.cost_usd = (self.cost_usd + read_adjust + write_adjust).max(0.0);
+        self
+    }
+
     /// Get the total cost.
     pub fn cost(&self) -> f64 {
         self.cost_usd
@@ -68,6 +101,27 @@ pub enum UsagePeriod {
     Month,
 }
 
+/// Who a usage event is attributed to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageScope {
+    /// Channel the request came from (e.g., "telegram")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Sender on that channel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+}
+
+impl UsageScope {
+    /// Scope for a message from `sender` on `channel`.
+    pub fn channel(channel: impl Into<String>, sender: impl Into<String>) -> Self {
+        Self {
+            channel: Some(channel.into()),
+            sender: Some(sender.into()),
+        }
+    }
+}
+
 /// A single cost record for persistent storage.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CostRecord {
@@ -77,6 +131,9 @@ pub struct CostRecord {
     pub usage: TokenUsage,
     /// Session identifier (for grouping)
     pub session_id: String,
+    /// Channel/sender attribution
+    #[serde(default)]
+    pub scope: UsageScope,
 }
 
 impl CostRecord {
@@ -86,8 +143,16 @@ impl CostRecord {
             id: uuid::Uuid::new_v4().to_string(),
             usage,
             session_id: session_id.into(),
+            scope: UsageScope::default(),
         }
     }
+
+    /// Attribute the record to a channel/sender.
+    #[must_use]
+    pub fn with_scope(mut self, scope: UsageScope) -> Self {
+        self.scope = scope;
+        self
+    }
 }
 
 /// Budget enforcement result.
@@ -181,6 +246,19 @@ mod tests {
         assert_eq!(usage.total_tokens, 2000);
     }
 
+    #[test]
+    fn token_usage_prices_cache_reads_and_writes() {
+        // 1M input tokens: 600k uncached, 300k cache reads, 100k cache writes.
+        let usage = TokenUsage::new("test/model", 1_000_000, 0, 3.0, 15.0)
+            .with_cache(300_000, 100_000, 3.0, 0.3, 3.75);
+
+        // 0.6*3 + 0.3*0.3 + 0.1*3.75 = 1.8 + 0.09 + 0.375
+        assert!((usage.cost_usd - 2.265).abs() < 1e-9);
+        assert_eq!(usage.cache_read_tokens, 300_000);
+        assert_eq!(usage.cache_write_tokens, 100_000);
+        assert_eq!(usage.total_tokens, 1_000_000);
+    }
+
     #[test]
     fn cost_record_creation() {
         let usage = TokenUsage::new("test/model", 100, 50, 1.0, 2.0);
@@ -189,5 +267,21 @@ mod tests {
         assert_eq!(record.session_id, "session-123");
         assert!(!record.id.is_empty());
         assert_eq!(record.usage.model, "test/model");
+        assert_eq!(record.scope, UsageScope::default());
+    }
+
+    #[test]
+    fn cost_record_without_scope_deserializes() {
+        let usage = TokenUsage::new("test/model", 100, 50, 1.0, 2.0);
+        let mut json = serde_json::to_value(CostRecord::new("s", usage)).unwrap();
+        json.as_object_mut().unwrap().remove("scope");
+
+        let record: CostRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(record.scope, UsageScope::default());
+
+        let scoped = record.with_scope(UsageScope::channel("telegram", "alice"));
+        let json = serde_json::to_string(&scoped).unwrap();
+        assert!(json.contains("\"channel\":\"telegram\""));
+        assert!(json.contains("\"sender\":\"alice\""));
     }
 }
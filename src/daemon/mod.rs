@@ -66,7 +66,9 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
             max_backoff,
             move || {
                 let cfg = heartbeat_cfg.clone();
-                async move { run_heartbeat_worker(cfg).await }
+                // Boxed: with the image tool registered, the agent run inside
+                // is over clippy's large_futures limit.
+                async move { Box::pin(run_heartbeat_worker(cfg)).await }
             },
         ));
     }
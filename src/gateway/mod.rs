@@ -7,17 +7,26 @@
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
 
+use crate::agent::budget::TurnBudgets;
 use crate::agent::loop_::{
-    agent_turn, auto_compact_history, build_context, build_tool_instructions, trim_history,
-    trim_history_by_size,
+    agent_turn, auto_compact_history, build_context, build_tool_instructions, create_cost_tracker,
+    trim_history, trim_history_by_tokens, ContextBudget, TurnHooks,
 };
-use crate::channels::{build_system_prompt, Channel, WhatsAppChannel};
-use crate::config::Config;
+use crate::agent::routing::{ModelRouter, RouteInput};
+use crate::channels::traits::{text_menu, ChannelMessage, MediaType};
+use crate::channels::{
+    approval_broker, approval_buttons, decide_approval, speak_reply, speech,
+    system_prompt_with_approvals, transcribe_attachments, transcription, ApprovalReplyContext,
+    Channel, SpeechSynthesizer, Transcriber, WhatsAppChannel,
+};
+use crate::config::{Config, SpeechConfig};
+use crate::cost::{CostTracker, UsageScope};
 use crate::memory::{self, Memory, MemoryCategory};
-use crate::observability::{self, Observer};
+use crate::observability::{self, Observer, ObserverEvent, PrometheusObserver};
+use crate::providers::capabilities::CapabilityRegistry;
 use crate::providers::{self, ChatMessage, Provider};
 use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
-use crate::security::SecurityPolicy;
+use crate::security::{ApprovalBroker, ApprovalDecision, ApprovalScope, SecurityPolicy};
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use crate::{runtime, skills};
@@ -33,6 +42,7 @@ use axum::{
 use dashmap::DashMap;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tower_http::limit::RequestBodyLimitLayer;
@@ -192,6 +202,14 @@ pub struct AppState {
     pub whatsapp: Option<Arc<WhatsAppChannel>>,
     /// `WhatsApp` app secret for webhook signature verification (`X-Hub-Signature-256`)
     pub whatsapp_app_secret: Option<Arc<str>>,
+    /// Speech-to-text for `WhatsApp` voice notes (`None` when disabled).
+    pub transcriber: Option<Arc<dyn Transcriber>>,
+    /// Text-to-speech for `WhatsApp` voice replies (`None` when disabled).
+    pub synthesizer: Option<Arc<dyn SpeechSynthesizer>>,
+    /// Who gets voice replies (`[speech]` reply modes).
+    pub speech: Arc<SpeechConfig>,
+    /// Workspace root; downloaded media goes under `downloads/`.
+    pub workspace_dir: PathBuf,
     // ── Agent loop infrastructure ──
     pub provider_name: String,
     pub tools_registry: Arc<Vec<Box<dyn Tool>>>,
@@ -199,6 +217,23 @@ pub struct AppState {
     pub system_prompt: Arc<str>,
     /// Per-user conversation history keyed by sender_id.
     pub conversations: Arc<DashMap<String, Vec<ChatMessage>>>,
+    /// Spending tracker shared by all webhook conversations (`None` when disabled).
+    pub cost_tracker: Option<CostTracker>,
+    /// Metrics registry rendered at `/metrics` (`None` unless backend = "prometheus").
+    pub prometheus: Option<PrometheusObserver>,
+    /// `/metrics` needs the pairing bearer token: the gateway is reachable
+    /// through a tunnel or bound publicly.
+    pub metrics_auth: bool,
+    /// Picks a model route per webhook message from `[routing]` config.
+    pub model_router: Arc<ModelRouter>,
+    /// Model limits used to size conversation history.
+    pub capabilities: Arc<CapabilityRegistry>,
+    /// Most tool calls run at once in a turn (1 = one after another).
+    pub max_parallel_tools: usize,
+    /// Turn limits, looked up by the channel a message arrived on.
+    pub turn_budgets: Arc<TurnBudgets>,
+    /// Holds webhook tool calls that need approval until the sender decides.
+    pub approvals: Arc<ApprovalBroker>,
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
@@ -228,12 +263,25 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .default_model
         .clone()
         .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_routed_provider(
-        &provider_name,
-        config.api_key.as_deref(),
-        &config.reliability,
-        &config.model_routes,
-        &model,
+    let capabilities = Arc::new(CapabilityRegistry::from_config(&config));
+    let observer: Arc<dyn Observer> =
+        Arc::from(observability::create_observer(&config.observability));
+    let provider = providers::with_replay(
+        providers::create_routed_provider(
+            &provider_name,
+            config.api_key.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &model,
+            Arc::clone(&capabilities),
+        )?,
+        &config.replay,
+        &config.workspace_dir,
+    )?;
+    let provider: Arc<dyn Provider> = Arc::from(providers::with_redaction(
+        provider,
+        &config,
+        Arc::clone(&observer),
     )?);
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
@@ -247,8 +295,11 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.autonomy,
         &config.workspace_dir,
     ));
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
+    let prometheus = if config.observability.backend == "prometheus" {
+        PrometheusObserver::shared().ok()
+    } else {
+        None
+    };
     let rt: Arc<dyn runtime::RuntimeAdapter> =
         Arc::from(runtime::create_runtime(&config.runtime)?);
     let (composio_key, composio_entity_id) = if config.composio.enabled {
@@ -290,7 +341,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     } else {
         None
     };
-    let mut system_prompt_str = build_system_prompt(
+    // Webhook turns park APPROVAL_REQUIRED calls like channels do, so the
+    // model must not negotiate approval in chat.
+    let mut system_prompt_str = system_prompt_with_approvals(
         &config.workspace_dir,
         &model,
         &tool_descs,
@@ -299,10 +352,14 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         &config.model_routes,
         Some(&config.autonomy),
         bootstrap_max_chars,
+        true,
     );
-    system_prompt_str.push_str(&build_tool_instructions(&tools_registry));
+    if !provider.supports_native_tools() {
+        system_prompt_str.push_str(&build_tool_instructions(&tools_registry));
+    }
     let system_prompt: Arc<str> = Arc::from(system_prompt_str);
     let conversations: Arc<DashMap<String, Vec<ChatMessage>>> = Arc::new(DashMap::new());
+    let model_router = Arc::new(ModelRouter::from_config(&config)?);
 
     // Restore persisted conversations from SQLite so users can
     // continue where they left off after a daemon restart.
@@ -317,8 +374,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
                             ChatMessage::system(system_prompt.as_ref());
                     }
                     // Trim restored conversations to prevent "prompt too long" on first request
-                    trim_history(&mut history);
-                    trim_history_by_size(&mut history);
+                    let budget = ContextBudget::for_model(&capabilities, &model);
+                    trim_history(&mut history, &budget);
+                    trim_history_by_tokens(&mut history, &budget);
                     conversations.insert(sender_id, history);
                 }
             }
@@ -414,6 +472,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         println!("  POST /whatsapp  — WhatsApp message webhook");
     }
     println!("  GET  /health    — health check");
+    if prometheus.is_some() {
+        println!("  GET  /metrics   — Prometheus metrics");
+    }
     if let Some(code) = pairing.pairing_code() {
         println!();
         println!("  🔐 PAIRING REQUIRED — use this one-time code:");
@@ -447,15 +508,28 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         idempotency_store,
         whatsapp: whatsapp_channel,
         whatsapp_app_secret,
+        transcriber: transcription::create_transcriber(&config.transcription)?.map(Arc::from),
+        synthesizer: speech::create_speech_synthesizer(&config.speech)?.map(Arc::from),
+        speech: Arc::new(config.speech.clone()),
+        workspace_dir: config.workspace_dir.clone(),
         tools_registry,
         observer,
         system_prompt,
         conversations,
+        cost_tracker: create_cost_tracker(&config),
+        prometheus,
+        metrics_auth: tunnel_url.is_some() || config.gateway.allow_public_bind,
+        model_router,
+        capabilities,
+        max_parallel_tools: config.agent.tool_parallelism(),
+        turn_budgets: Arc::new(TurnBudgets::from_config(&config)),
+        approvals: Arc::new(approval_broker(&config, security)),
     };
 
     // Build router with middleware
     let app = Router::new()
         .route("/health", get(handle_health))
+        .route("/metrics", get(handle_metrics))
         .route("/pair", post(handle_pair))
         .route("/webhook", post(handle_webhook))
         .route("/whatsapp", get(handle_whatsapp_verify))
@@ -487,6 +561,55 @@ async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
     Json(body)
 }
 
+/// Whether the request carries a paired bearer token (always true when
+/// pairing is off).
+fn has_paired_bearer(pairing: &PairingGuard, headers: &HeaderMap) -> bool {
+    let auth = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    pairing.is_authenticated(auth.strip_prefix("Bearer ").unwrap_or(""))
+}
+
+/// GET /metrics — Prometheus text exposition. Public on a local bind; behind
+/// a tunnel or a public bind it needs the pairing bearer token.
+async fn handle_metrics(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    if state.metrics_auth && !has_paired_bearer(&state.pairing, &headers) {
+        tracing::warn!("Metrics: rejected — not paired / invalid bearer token");
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            "Unauthorized — send Authorization: Bearer <token>\n".to_string(),
+        );
+    }
+    let Some(prometheus) = state.prometheus.as_ref() else {
+        return (
+            StatusCode::NOT_FOUND,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            "Metrics disabled. Set [observability] backend = \"prometheus\" to enable.\n"
+                .to_string(),
+        );
+    };
+    match prometheus.encode() {
+        Ok(body) => (
+            StatusCode::OK,
+            [(
+                header::CONTENT_TYPE,
+                "text/plain; version=0.0.4; charset=utf-8",
+            )],
+            body,
+        ),
+        Err(e) => {
+            tracing::error!("{e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                "Failed to encode metrics\n".to_string(),
+            )
+        }
+    }
+}
+
 /// POST /pair — exchange one-time code for bearer token
 async fn handle_pair(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     let client_key = client_key_from_headers(&headers);
@@ -567,19 +690,12 @@ async fn handle_webhook(
     }
 
     // ── Bearer token auth (pairing) ──
-    if state.pairing.require_pairing() {
-        let auth = headers
-            .get(header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        let token = auth.strip_prefix("Bearer ").unwrap_or("");
-        if !state.pairing.is_authenticated(token) {
-            tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
-            let err = serde_json::json!({
-                "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
-            });
-            return (StatusCode::UNAUTHORIZED, Json(err));
-        }
+    if !has_paired_bearer(&state.pairing, &headers) {
+        tracing::warn!("Webhook: rejected — not paired / invalid bearer token");
+        let err = serde_json::json!({
+            "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+        });
+        return (StatusCode::UNAUTHORIZED, Json(err));
     }
 
     // ── Webhook secret auth (optional, additional layer) ──
@@ -630,6 +746,24 @@ async fn handle_webhook(
     let message = &webhook_body.message;
     let sender_id = &webhook_body.sender_id;
 
+    // Approval replies decide a parked call directly, without the model.
+    if let Some((decision, id)) = ApprovalDecision::parse(message) {
+        let approval_ctx = ApprovalReplyContext {
+            approvals: &state.approvals,
+            tools_registry: &state.tools_registry,
+            observer: state.observer.as_ref(),
+            conversations: &state.conversations,
+            memory: state.mem.as_ref(),
+            system_prompt: &state.system_prompt,
+        };
+        let reply = decide_approval(&approval_ctx, sender_id, decision, &id).await;
+        let body = serde_json::json!({
+            "response": reply,
+            "model": state.model,
+        });
+        return (StatusCode::OK, Json(body));
+    }
+
     if state.auto_save {
         let key = webhook_memory_key();
         let _ = state
@@ -639,20 +773,6 @@ async fn handle_webhook(
     }
 
     // --- ZeroClaw fork: model selection, per-user history, channel awareness ---
-    // Auto-routing between models is disabled — keyword-based classification
-    // is too fragile and misroutes tool-use tasks to models that can't handle
-    // them. All messages use the primary model unless explicitly overridden.
-    let selected_model = webhook_body
-        .model
-        .clone()
-        .unwrap_or_else(|| state.model.clone());
-
-    tracing::info!(
-        sender = sender_id.as_str(),
-        model = selected_model.as_str(),
-        "Webhook model selection"
-    );
-
     // Build or retrieve per-user conversation history
     let mut history = state
         .conversations
@@ -670,8 +790,50 @@ async fn handle_webhook(
         None
     };
 
+    state.observer.record_event(&ObserverEvent::ChannelMessage {
+        channel: channel.unwrap_or("webhook").to_string(),
+        direction: "inbound".to_string(),
+    });
+
+    let cost_tracker = state
+        .cost_tracker
+        .as_ref()
+        .map(|t| t.scoped(UsageScope::channel(channel.unwrap_or("webhook"), sender_id)));
+
+    // An explicit model in the request bypasses the `[routing]` rules.
+    let selected_model = match webhook_body.model.clone() {
+        Some(model) => model,
+        None => {
+            let input = RouteInput {
+                message,
+                channel: Some(channel.unwrap_or("webhook")),
+                sender: Some(sender_id),
+                attachments: &[],
+                follow_up: history.len() > 1,
+            };
+            let decision = state
+                .model_router
+                .route(
+                    &input,
+                    state.provider.as_ref(),
+                    &state.model,
+                    cost_tracker.as_ref(),
+                )
+                .await;
+            tracing::debug!(reason = %decision.reason, "Webhook routing decision");
+            decision.model(&state.model)
+        }
+    };
+
+    tracing::info!(
+        sender = sender_id.as_str(),
+        model = selected_model.as_str(),
+        "Webhook model selection"
+    );
+
     // Enrich message with memory context + channel awareness
-    let context = build_context(state.mem.as_ref(), message).await;
+    let budget = ContextBudget::for_model(&state.capabilities, &selected_model);
+    let context = budget.fit_memory_context(build_context(state.mem.as_ref(), message).await);
     let channel_hint = match channel {
         Some("telegram") => "[Platform: Telegram] The user is chatting with you via Telegram. You ARE the Telegram bot — never suggest \"sending via Telegram\" or ask for bot tokens. Use standard Markdown in your response; the system converts it to Telegram HTML automatically.\n\n",
         Some("discord") => "[Platform: Discord] The user is chatting with you via Discord. You ARE the Discord bot.\n\n",
@@ -680,6 +842,7 @@ async fn handle_webhook(
     let enriched = format!("{channel_hint}{context}{message}");
     history.push(ChatMessage::user(&enriched));
 
+    let mut approval_scope = ApprovalScope::new(&state.approvals, sender_id);
     match agent_turn(
         state.provider.as_ref(),
         &mut history,
@@ -689,19 +852,35 @@ async fn handle_webhook(
         &selected_model,
         state.temperature,
         true, // silent — channel mode
+        &state.capabilities,
+        state.max_parallel_tools,
+        state.turn_budgets.for_channel(channel.unwrap_or("webhook")),
+        TurnHooks {
+            cost_tracker: cost_tracker.as_ref(),
+            approvals: Some(&mut approval_scope),
+            ..TurnHooks::default()
+        },
     )
     .await
     {
-        Ok(response) => {
+        Ok(mut response) => {
+            // Calls parked for approval are asked about after the reply; the
+            // sender answers with `approve <id>` / `deny <id>`.
+            for request in &approval_scope.parked {
+                let menu = text_menu(&request.prompt(), &approval_buttons(request));
+                response = format!("{response}\n\n{menu}");
+            }
+
             // Intelligent compaction before hard trim preserves context signal
             let _ = auto_compact_history(
                 &mut history,
                 state.provider.as_ref(),
                 &selected_model,
+                &budget,
             )
             .await;
-            trim_history(&mut history);
-            trim_history_by_size(&mut history);
+            trim_history(&mut history, &budget);
+            trim_history_by_tokens(&mut history, &budget);
             let subject = crate::agent::routing::extract_subject(&history);
             let history_json = serde_json::to_string(&history).unwrap_or_default();
             state
@@ -736,11 +915,33 @@ async fn handle_webhook(
                 _ => response.clone(),
             };
 
+            state.observer.record_event(&ObserverEvent::ChannelMessage {
+                channel: channel.unwrap_or("webhook").to_string(),
+                direction: "outbound".to_string(),
+            });
+
+            let approvals: Vec<_> = approval_scope
+                .parked
+                .iter()
+                .map(|request| {
+                    serde_json::json!({
+                        "id": request.id,
+                        "tool": request.tool,
+                        "action": request.action(),
+                        "prompt": request.prompt(),
+                        "buttons": approval_buttons(request)
+                            .iter()
+                            .map(|b| serde_json::json!({"label": b.label, "reply": b.callback_id}))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
             let body = serde_json::json!({
                 "response": formatted_response,
                 "response_raw": response,
                 "model": selected_model,
                 "format": channel.unwrap_or("plain"),
+                "approvals": approvals,
             });
             (StatusCode::OK, Json(body))
         }
@@ -771,10 +972,11 @@ async fn handle_webhook(
                 &mut history,
                 state.provider.as_ref(),
                 &selected_model,
+                &budget,
             )
             .await;
-            trim_history(&mut history);
-            trim_history_by_size(&mut history);
+            trim_history(&mut history, &budget);
+            trim_history_by_tokens(&mut history, &budget);
             let subject = crate::agent::routing::extract_subject(&history);
             let history_json = serde_json::to_string(&history).unwrap_or_default();
             state
@@ -913,54 +1115,82 @@ async fn handle_whatsapp_message(
     }
 
     // Process each message
-    for msg in &messages {
-        tracing::info!(
-            "WhatsApp message from {}: {}",
-            msg.sender,
-            truncate_with_ellipsis(&msg.content, 50)
-        );
-
-        // Auto-save to memory
-        if state.auto_save {
-            let key = whatsapp_memory_key(msg);
-            let _ = state
-                .mem
-                .store(&key, &msg.content, MemoryCategory::Conversation)
-                .await;
+    for mut msg in messages {
+        if state.transcriber.is_some() {
+            wa.download_audio(&mut msg, &state.workspace_dir).await;
         }
+        reply_to_whatsapp_message(&state, wa.as_ref(), msg).await;
+    }
 
-        // Call the LLM
-        match state
-            .provider
-            .simple_chat(&msg.content, &state.model, state.temperature)
-            .await
-        {
-            Ok(response) => {
-                // Send reply via WhatsApp
-                if let Err(e) = wa.send(&response, &msg.sender).await {
-                    tracing::error!("Failed to send WhatsApp reply: {e}");
+    // Acknowledge the webhook
+    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
+}
+
+/// Answer one `WhatsApp` message, transcribing voice notes first and
+/// replying by voice when `[speech]` asks for it.
+async fn reply_to_whatsapp_message(state: &AppState, wa: &dyn Channel, msg: ChannelMessage) {
+    let msg = transcribe_attachments(state.transcriber.as_deref(), msg).await;
+    tracing::info!(
+        "WhatsApp message from {}: {}",
+        msg.sender,
+        truncate_with_ellipsis(&msg.content, 50)
+    );
+
+    // Auto-save to memory
+    if state.auto_save {
+        let key = whatsapp_memory_key(&msg);
+        let _ = state
+            .mem
+            .store(&key, &msg.content, MemoryCategory::Conversation)
+            .await;
+    }
+
+    // Call the LLM
+    match state
+        .provider
+        .simple_chat(&msg.content, &state.model, state.temperature)
+        .await
+    {
+        Ok(response) => {
+            let sent_voice = msg
+                .attachments
+                .iter()
+                .any(|a| a.media_type == MediaType::Voice);
+            let voice = state.synthesizer.as_deref().filter(|_| {
+                wa.supports_voice_replies()
+                    && state
+                        .speech
+                        .reply_mode_for(&msg.channel, &msg.sender)
+                        .wants_voice(sent_voice)
+            });
+            if let Some(synthesizer) = voice {
+                match speak_reply(synthesizer, wa, &response, &msg.sender).await {
+                    Ok(()) => return,
+                    Err(e) => tracing::warn!("Voice reply failed, sending text instead: {e}"),
                 }
             }
-            Err(e) => {
-                tracing::error!("LLM error for WhatsApp message: {e:#}");
-                let _ = wa
-                    .send(
-                        "Sorry, I couldn't process your message right now.",
-                        &msg.sender,
-                    )
-                    .await;
+
+            // Send reply via WhatsApp
+            if let Err(e) = wa.send(&response, &msg.sender).await {
+                tracing::error!("Failed to send WhatsApp reply: {e}");
             }
         }
+        Err(e) => {
+            tracing::error!("LLM error for WhatsApp message: {e:#}");
+            let _ = wa
+                .send(
+                    "Sorry, I couldn't process your message right now.",
+                    &msg.sender,
+                )
+                .await;
+        }
     }
-
-    // Acknowledge the webhook
-    (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::channels::traits::ChannelMessage;
+    use crate::channels::traits::{MediaAttachment, MediaType};
     use crate::memory::{Memory, MemoryCategory, MemoryEntry};
     use crate::observability::NoopObserver;
     use crate::providers::Provider;
@@ -1092,7 +1322,7 @@ mod tests {
             content: "hello".into(),
             channel: "whatsapp".into(),
             timestamp: 1,
-            attachments: vec![],
+            ..Default::default()
         };
 
         let key = whatsapp_memory_key(&msg);
@@ -1240,10 +1470,22 @@ mod tests {
             idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
             whatsapp: None,
             whatsapp_app_secret: None,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(SpeechConfig::default()),
+            workspace_dir: PathBuf::new(),
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::from("test"),
             conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            prometheus: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            approvals: test_approvals(),
+            metrics_auth: false,
         };
 
         let mut headers = HeaderMap::new();
@@ -1276,6 +1518,225 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 1);
     }
 
+    fn test_approvals() -> Arc<ApprovalBroker> {
+        Arc::new(ApprovalBroker::new(
+            Arc::new(SecurityPolicy::default()),
+            Duration::from_secs(600),
+        ))
+    }
+
+    fn metrics_test_state(prometheus: Option<PrometheusObserver>) -> AppState {
+        AppState {
+            provider: Arc::new(MockProvider::default()),
+            provider_name: "test".into(),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret: None,
+            pairing: Arc::new(PairingGuard::new(true, &[])),
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(SpeechConfig::default()),
+            workspace_dir: PathBuf::new(),
+            tools_registry: Arc::new(vec![]),
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::from("test"),
+            conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            prometheus,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            approvals: test_approvals(),
+            metrics_auth: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_is_not_found_when_disabled() {
+        let response = handle_metrics(State(metrics_test_state(None)), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_renders_recorded_metrics() {
+        let prometheus = PrometheusObserver::new().unwrap();
+        prometheus.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(5),
+            success: true,
+        });
+
+        let response = handle_metrics(
+            State(metrics_test_state(Some(prometheus))),
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4"));
+
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(payload.to_vec()).unwrap();
+        assert!(text.contains(r#"zeroclaw_tool_calls_total{success="true",tool="shell"} 1"#));
+    }
+
+    #[tokio::test]
+    async fn metrics_endpoint_requires_bearer_token_when_exposed() {
+        let mut state = metrics_test_state(Some(PrometheusObserver::new().unwrap()));
+        state.pairing = Arc::new(PairingGuard::new(true, &["secret-token".into()]));
+        state.metrics_auth = true;
+
+        let response = handle_metrics(State(state.clone()), HeaderMap::new())
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        let response = handle_metrics(State(state.clone()), headers)
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            "Bearer secret-token".parse().unwrap(),
+        );
+        let response = handle_metrics(State(state), headers).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// Model that tries to approve its own `guarded` call.
+    struct SelfApprovingProvider;
+
+    #[async_trait]
+    impl Provider for SelfApprovingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("unused".into())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            if messages
+                .iter()
+                .any(|m| m.content.contains("[Tool results]"))
+            {
+                return Ok("Waiting for your approval.".into());
+            }
+            Ok(r#"<tool_call>
+{"name":"guarded","arguments":{"approved":true}}
+</tool_call>"#
+                .into())
+        }
+    }
+
+    /// Tool that needs approval and counts approved runs.
+    #[derive(Default)]
+    struct GuardedTool {
+        runs: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for GuardedTool {
+        fn name(&self) -> &str {
+            "guarded"
+        }
+
+        fn description(&self) -> &str {
+            "Runs only after approval"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> anyhow::Result<tools::ToolResult> {
+            let approved = args.get("approved").and_then(serde_json::Value::as_bool);
+            if approved != Some(true) {
+                return Ok(tools::ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some("APPROVAL_REQUIRED: needs the user's consent".into()),
+                    image_base64: None,
+                    image_mime: None,
+                });
+            }
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(tools::ToolResult {
+                success: true,
+                output: "ran".into(),
+                error: None,
+                image_base64: None,
+                image_mime: None,
+            })
+        }
+    }
+
+    async fn post_webhook(state: &AppState, message: &str) -> serde_json::Value {
+        let body = Ok(Json(WebhookBody {
+            message: message.into(),
+            sender_id: "alice".into(),
+            model: None,
+        }));
+        let response = handle_webhook(State(state.clone()), HeaderMap::new(), body)
+            .await
+            .into_response();
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[tokio::test]
+    async fn webhook_parks_self_approved_calls_for_the_sender() {
+        let tool = GuardedTool::default();
+        let runs = Arc::clone(&tool.runs);
+        let mut state = metrics_test_state(None);
+        state.pairing = Arc::new(PairingGuard::new(false, &[]));
+        state.provider = Arc::new(SelfApprovingProvider);
+        state.tools_registry = Arc::new(vec![Box::new(tool)]);
+
+        let reply = post_webhook(&state, "run it").await;
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        let id = reply["approvals"][0]["id"].as_str().unwrap().to_string();
+        assert_eq!(
+            reply["approvals"][0]["buttons"][0]["reply"],
+            format!("approve {id}")
+        );
+        assert!(reply["response"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("approve {id}")));
+
+        let reply = post_webhook(&state, &format!("approve {id}")).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(reply["response"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("✅ Approved {id}")));
+    }
+
     #[tokio::test]
     async fn webhook_autosave_stores_distinct_keys_per_request() {
         let provider_impl = Arc::new(MockProvider::default());
@@ -1296,10 +1757,22 @@ mod tests {
             idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300))),
             whatsapp: None,
             whatsapp_app_secret: None,
+            transcriber: None,
+            synthesizer: None,
+            speech: Arc::new(SpeechConfig::default()),
+            workspace_dir: PathBuf::new(),
             tools_registry: Arc::new(vec![]),
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::from("test"),
             conversations: Arc::new(DashMap::new()),
+            cost_tracker: None,
+            prometheus: None,
+            model_router: Arc::new(ModelRouter::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            max_parallel_tools: 1,
+            turn_budgets: Arc::new(TurnBudgets::default()),
+            approvals: test_approvals(),
+            metrics_auth: false,
             provider_name: "test".into(),
         };
 
@@ -1340,6 +1813,152 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 2);
     }
 
+    /// Replies with the message it was given.
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(message.to_string())
+        }
+    }
+
+    struct FixedTranscriber;
+
+    #[async_trait]
+    impl Transcriber for FixedTranscriber {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn transcribe(
+            &self,
+            _path: &std::path::Path,
+            _mime_type: Option<&str>,
+        ) -> anyhow::Result<String> {
+            Ok("call me back after lunch".into())
+        }
+    }
+
+    struct FixedSynthesizer;
+
+    #[async_trait]
+    impl SpeechSynthesizer for FixedSynthesizer {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn synthesize(&self, text: &str) -> anyhow::Result<speech::SynthesizedSpeech> {
+            Ok(speech::SynthesizedSpeech {
+                audio: text.as_bytes().to_vec(),
+                mime_type: "audio/ogg".into(),
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<String>>,
+        voice: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "whatsapp"
+        }
+
+        async fn send(&self, message: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn supports_voice_replies(&self) -> bool {
+            true
+        }
+
+        async fn send_voice_reply(
+            &self,
+            speech: &speech::SynthesizedSpeech,
+            _recipient: &str,
+        ) -> anyhow::Result<()> {
+            let text = String::from_utf8_lossy(&speech.audio).into_owned();
+            self.voice.lock().unwrap().push(text);
+            Ok(())
+        }
+    }
+
+    fn whatsapp_voice_note(path: &std::path::Path) -> ChannelMessage {
+        let mut voice = MediaAttachment::new(MediaType::Voice);
+        voice.file_path = Some(path.display().to_string());
+        ChannelMessage {
+            id: "wamid-1".into(),
+            sender: "+1234567890".into(),
+            content: "[Voice message]".into(),
+            channel: "whatsapp".into(),
+            attachments: vec![voice],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn whatsapp_voice_note_is_transcribed_before_the_llm_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voice.ogg");
+        std::fs::write(&path, b"OggS").unwrap();
+
+        let mut state = metrics_test_state(None);
+        state.provider = Arc::new(EchoProvider);
+        state.transcriber = Some(Arc::new(FixedTranscriber));
+        let wa = RecordingChannel::default();
+
+        reply_to_whatsapp_message(&state, &wa, whatsapp_voice_note(&path)).await;
+
+        let sent = wa.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].contains("[Voice transcript] call me back after lunch"));
+    }
+
+    #[tokio::test]
+    async fn whatsapp_voice_note_gets_a_voice_reply_when_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voice.ogg");
+        std::fs::write(&path, b"OggS").unwrap();
+
+        let mut state = metrics_test_state(None);
+        state.provider = Arc::new(EchoProvider);
+        state.transcriber = Some(Arc::new(FixedTranscriber));
+        state.synthesizer = Some(Arc::new(FixedSynthesizer));
+        let wa = RecordingChannel::default();
+
+        // The default reply mode answers voice with voice.
+        reply_to_whatsapp_message(&state, &wa, whatsapp_voice_note(&path)).await;
+        assert!(wa.sent.lock().unwrap().is_empty());
+        let voice = wa.voice.lock().unwrap().clone();
+        assert_eq!(voice.len(), 1);
+        assert!(voice[0].contains("call me back after lunch"));
+
+        // Typed messages still get text.
+        let mut typed = whatsapp_voice_note(&path);
+        typed.attachments.clear();
+        typed.content = "hello".into();
+        reply_to_whatsapp_message(&state, &wa, typed).await;
+        assert_eq!(wa.sent.lock().unwrap().as_slice(), ["hello"]);
+    }
+
     // ══════════════════════════════════════════════════════════
     // WhatsApp Signature Verification Tests (CWE-345 Prevention)
     // ══════════════════════════════════════════════════════════
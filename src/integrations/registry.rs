@@ -603,7 +603,13 @@ pub fn all_integrations() -> Vec<IntegrationEntry> {
             name: "Image Gen",
             description: "AI image generation",
             category: IntegrationCategory::MediaCreative,
-            status_fn: |_| IntegrationStatus::ComingSoon,
+            status_fn: |c| {
+                if c.image_generation.enabled {
+                    IntegrationStatus::Active
+                } else {
+                    IntegrationStatus::Available
+                }
+            },
         },
         IntegrationEntry {
             name: "GIF Search",
@@ -818,6 +824,22 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn image_gen_active_when_enabled() {
+        let mut config = Config::default();
+        let entries = all_integrations();
+        let image = entries.iter().find(|e| e.name == "Image Gen").unwrap();
+        assert!(matches!(
+            (image.status_fn)(&config),
+            IntegrationStatus::Available
+        ));
+        config.image_generation.enabled = true;
+        assert!(matches!(
+            (image.status_fn)(&config),
+            IntegrationStatus::Active
+        ));
+    }
+
     #[test]
     fn coming_soon_integrations_stay_coming_soon() {
         let config = Config::default();
@@ -43,6 +43,7 @@ mod rag {
     pub use zeroclaw::rag::*;
 }
 mod config;
+mod cost;
 mod cron;
 mod daemon;
 mod doctor;
@@ -191,6 +192,12 @@ enum Commands {
         model_command: ModelCommands,
     },
 
+    /// Inspect config-driven model routing
+    Route {
+        #[command(subcommand)]
+        route_command: RouteCommands,
+    },
+
     /// Manage channels (telegram, discord, slack)
     Channel {
         #[command(subcommand)]
@@ -291,6 +298,35 @@ enum ModelCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum RouteCommands {
+    /// Show which routing rule (or classifier) picks the model for a message
+    Explain {
+        /// Message to route
+        message: String,
+
+        /// Channel the message arrives on (e.g. telegram, webhook)
+        #[arg(long)]
+        channel: Option<String>,
+
+        /// Sender identifier
+        #[arg(long)]
+        sender: Option<String>,
+
+        /// Attachment media type (photo, document, voice, ...); repeatable
+        #[arg(long = "attachment")]
+        attachments: Vec<String>,
+
+        /// Treat the message as a follow-up in an ongoing conversation
+        #[arg(long)]
+        follow_up: bool,
+
+        /// Also run the fallback classifier (calls the provider)
+        #[arg(long)]
+        classify: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum ChannelCommands {
     /// List configured channels
@@ -509,6 +545,33 @@ async fn main() -> Result<()> {
             }
         },
 
+        Commands::Route { route_command } => match route_command {
+            RouteCommands::Explain {
+                message,
+                channel,
+                sender,
+                attachments,
+                follow_up,
+                classify,
+            } => {
+                let attachments = attachments
+                    .iter()
+                    .map(|name| {
+                        agent::routing::parse_media_type(name)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown attachment type: {name}"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let input = agent::routing::RouteInput {
+                    message: &message,
+                    channel: channel.as_deref(),
+                    sender: sender.as_deref(),
+                    attachments: &attachments,
+                    follow_up,
+                };
+                agent::routing::explain(&config, &input, classify).await
+            }
+        },
+
         Commands::Service { service_command } => service::handle_command(&service_command, &config),
 
         Commands::Doctor => doctor::run(&config),
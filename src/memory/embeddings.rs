@@ -152,6 +152,92 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Ollama embedding provider (local, no API key) ────────────
+
+pub struct OllamaEmbedding {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dims: usize,
+}
+
+impl OllamaEmbedding {
+    pub fn new(base_url: &str, model: &str, dims: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            dims,
+        }
+    }
+
+    fn embed_url(&self) -> String {
+        format!("{}/api/embed", self.base_url)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbedding {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": texts,
+        });
+
+        let resp = self
+            .client
+            .post(self.embed_url())
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Ollama embedding error {status}: {text}");
+        }
+
+        let json: serde_json::Value = resp.json().await?;
+        parse_ollama_embeddings(&json)
+    }
+}
+
+fn parse_ollama_embeddings(json: &serde_json::Value) -> anyhow::Result<Vec<Vec<f32>>> {
+    let data = json
+        .get("embeddings")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| {
+            anyhow::anyhow!("Invalid Ollama embedding response: missing 'embeddings'")
+        })?;
+
+    data.iter()
+        .map(|item| {
+            let embedding = item
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("Invalid embedding item"))?;
+
+            #[allow(clippy::cast_possible_truncation)]
+            let vec: Vec<f32> = embedding
+                .iter()
+                .filter_map(|v| v.as_f64().map(|f| f as f32))
+                .collect();
+            Ok(vec)
+        })
+        .collect()
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
 pub fn create_embedding_provider(
@@ -170,6 +256,11 @@ pub fn create_embedding_provider(
                 dims,
             ))
         }
+        "ollama" => Box::new(OllamaEmbedding::new("http://localhost:11434", model, dims)),
+        name if name.starts_with("ollama:") => {
+            let base_url = name.strip_prefix("ollama:").unwrap_or("");
+            Box::new(OllamaEmbedding::new(base_url, model, dims))
+        }
         name if name.starts_with("custom:") => {
             let base_url = name.strip_prefix("custom:").unwrap_or("");
             let key = api_key.unwrap_or("");
@@ -217,6 +308,37 @@ mod tests {
         assert_eq!(p.dimensions(), 768);
     }
 
+    #[test]
+    fn factory_ollama_default_url() {
+        let p = create_embedding_provider("ollama", None, "nomic-embed-text", 768);
+        assert_eq!(p.name(), "ollama");
+        assert_eq!(p.dimensions(), 768);
+    }
+
+    #[test]
+    fn factory_ollama_custom_url() {
+        let p = create_embedding_provider("ollama:http://gpu-box:11434/", None, "bge-m3", 1024);
+        assert_eq!(p.name(), "ollama");
+        assert_eq!(p.dimensions(), 1024);
+    }
+
+    #[test]
+    fn ollama_embed_url() {
+        let p = OllamaEmbedding::new("http://localhost:11434/", "nomic-embed-text", 768);
+        assert_eq!(p.embed_url(), "http://localhost:11434/api/embed");
+    }
+
+    #[test]
+    fn ollama_response_parses_batch() {
+        let json = serde_json::json!({
+            "model": "nomic-embed-text",
+            "embeddings": [[0.5, -1.0], [0.25, 2.0]]
+        });
+        let vectors = parse_ollama_embeddings(&json).unwrap();
+        assert_eq!(vectors, vec![vec![0.5, -1.0], vec![0.25, 2.0]]);
+        assert!(parse_ollama_embeddings(&serde_json::json!({"error": "x"})).is_err());
+    }
+
     // ── Edge cases ───────────────────────────────────────────────
 
     #[tokio::test]
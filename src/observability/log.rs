@@ -28,6 +28,26 @@ impl Observer for LogObserver {
                     "llm.request"
                 );
             }
+            ObserverEvent::ContextUsage {
+                model,
+                tokenizer,
+                system_tokens,
+                memory_tokens,
+                tool_tokens,
+                history_tokens,
+                limit_tokens,
+            } => {
+                info!(
+                    model = %model,
+                    tokenizer = %tokenizer,
+                    system_tokens = system_tokens,
+                    memory_tokens = memory_tokens,
+                    tool_tokens = tool_tokens,
+                    history_tokens = history_tokens,
+                    limit_tokens = limit_tokens,
+                    "llm.context"
+                );
+            }
             ObserverEvent::LlmResponse {
                 provider,
                 model,
@@ -63,9 +83,15 @@ impl Observer for LogObserver {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 info!(tool = %tool, duration_ms = ms, success = success, "tool.call");
             }
+            ObserverEvent::SecretsRedacted { kind, count } => {
+                info!(kind = %kind, count = count, "llm.redaction");
+            }
             ObserverEvent::TurnComplete => {
                 info!("turn.complete");
             }
+            ObserverEvent::BudgetExceeded { budget, limit } => {
+                info!(budget = %budget, limit = limit, "turn.budget_exceeded");
+            }
             ObserverEvent::ChannelMessage { channel, direction } => {
                 info!(channel = %channel, direction = %direction, "channel.message");
             }
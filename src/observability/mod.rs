@@ -2,14 +2,16 @@ pub mod log;
 pub mod multi;
 pub mod noop;
 pub mod otel;
+pub mod prometheus;
 pub mod traits;
 pub mod verbose;
 
 pub use self::log::LogObserver;
 pub use self::multi::MultiObserver;
+pub use self::prometheus::PrometheusObserver;
 pub use noop::NoopObserver;
 pub use otel::OtelObserver;
-pub use traits::{Observer, ObserverEvent};
+pub use traits::{Observer, ObserverEvent, ObserverMetric};
 pub use verbose::VerboseObserver;
 
 use crate::config::ObservabilityConfig;
@@ -39,6 +41,13 @@ pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
                 }
             }
         }
+        "prometheus" => match PrometheusObserver::shared() {
+            Ok(obs) => Box::new(obs),
+            Err(e) => {
+                tracing::error!("Failed to create Prometheus observer: {e}. Falling back to noop.");
+                Box::new(NoopObserver)
+            }
+        },
         "none" | "noop" => Box::new(NoopObserver),
         _ => {
             tracing::warn!(
@@ -111,6 +120,15 @@ mod tests {
         assert_eq!(create_observer(&cfg).name(), "otel");
     }
 
+    #[test]
+    fn factory_prometheus_returns_prometheus() {
+        let cfg = ObservabilityConfig {
+            backend: "prometheus".into(),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "prometheus");
+    }
+
     #[test]
     fn factory_unknown_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
@@ -21,6 +21,8 @@ pub struct OtelObserver {
     tool_duration: Histogram<f64>,
     channel_messages: Counter<u64>,
     heartbeat_ticks: Counter<u64>,
+    secrets_redacted: Counter<u64>,
+    budgets_exceeded: Counter<u64>,
     errors: Counter<u64>,
     request_latency: Histogram<f64>,
     tokens_used: Counter<u64>,
@@ -123,6 +125,16 @@ impl OtelObserver {
             .with_description("Total heartbeat ticks")
             .build();
 
+        let secrets_redacted = meter
+            .u64_counter("zeroclaw.secrets.redacted")
+            .with_description("Secrets redacted from outbound prompts")
+            .build();
+
+        let budgets_exceeded = meter
+            .u64_counter("zeroclaw.turn.budget_exceeded")
+            .with_description("Agent turns stopped by a budget")
+            .build();
+
         let errors = meter
             .u64_counter("zeroclaw.errors")
             .with_description("Total errors by component")
@@ -160,6 +172,8 @@ impl OtelObserver {
             tool_duration,
             channel_messages,
             heartbeat_ticks,
+            secrets_redacted,
+            budgets_exceeded,
             errors,
             request_latency,
             tokens_used,
@@ -184,6 +198,7 @@ impl Observer for OtelObserver {
                 );
             }
             ObserverEvent::LlmRequest { .. }
+            | ObserverEvent::ContextUsage { .. }
             | ObserverEvent::ToolCallStart { .. }
             | ObserverEvent::TurnComplete => {}
             ObserverEvent::LlmResponse {
@@ -298,6 +313,16 @@ impl Observer for OtelObserver {
             ObserverEvent::HeartbeatTick => {
                 self.heartbeat_ticks.add(1, &[]);
             }
+            ObserverEvent::SecretsRedacted { kind, count } => {
+                self.secrets_redacted.add(
+                    u64::try_from(*count).unwrap_or(u64::MAX),
+                    &[KeyValue::new("kind", kind.clone())],
+                );
+            }
+            ObserverEvent::BudgetExceeded { budget, .. } => {
+                self.budgets_exceeded
+                    .add(1, &[KeyValue::new("budget", budget.clone())]);
+            }
             ObserverEvent::Error { component, message } => {
                 // Create an error span for visibility in trace backends
                 let mut span = tracer.build(
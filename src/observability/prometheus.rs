@@ -0,0 +1,350 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use ::prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use std::sync::{Arc, OnceLock};
+
+/// Latency buckets (seconds) sized for LLM calls, which range from
+/// sub-second cache hits to multi-minute reasoning runs.
+const LLM_LATENCY_BUCKETS: &[f64] = &[
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+/// Prometheus-backed observer — keeps counters and histograms in a registry
+/// that the gateway renders at `/metrics`.
+///
+/// Cloning is cheap: clones share the same registry and instruments.
+#[derive(Clone)]
+pub struct PrometheusObserver {
+    inner: Arc<Metrics>,
+}
+
+struct Metrics {
+    registry: Registry,
+    agent_starts: IntCounterVec,
+    agent_duration: Histogram,
+    llm_calls: IntCounterVec,
+    llm_duration: HistogramVec,
+    tool_calls: IntCounterVec,
+    tool_duration: HistogramVec,
+    channel_messages: IntCounterVec,
+    heartbeat_ticks: IntCounter,
+    secrets_redacted: IntCounterVec,
+    budgets_exceeded: IntCounterVec,
+    errors: IntCounterVec,
+    request_latency: Histogram,
+    tokens_used: IntCounter,
+    active_sessions: IntGauge,
+    queue_depth: IntGauge,
+}
+
+impl PrometheusObserver {
+    /// Create an observer with its own private registry.
+    pub fn new() -> Result<Self, String> {
+        Self::with_registry(
+            Registry::new_custom(Some("zeroclaw".into()), None)
+                .map_err(|e| format!("Failed to create Prometheus registry: {e}"))?,
+        )
+    }
+
+    /// Process-wide observer shared by every component, so the gateway's
+    /// `/metrics` endpoint also reports activity from channels and the agent.
+    pub fn shared() -> Result<Self, String> {
+        static SHARED: OnceLock<Result<PrometheusObserver, String>> = OnceLock::new();
+        SHARED.get_or_init(Self::new).clone()
+    }
+
+    fn with_registry(registry: Registry) -> Result<Self, String> {
+        let metrics = Metrics::register(registry)
+            .map_err(|e| format!("Failed to register Prometheus metrics: {e}"))?;
+        Ok(Self {
+            inner: Arc::new(metrics),
+        })
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn encode(&self) -> Result<String, String> {
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.inner.registry.gather(), &mut buf)
+            .map_err(|e| format!("Failed to encode Prometheus metrics: {e}"))?;
+        String::from_utf8(buf).map_err(|e| format!("Prometheus output is not UTF-8: {e}"))
+    }
+}
+
+impl Metrics {
+    fn register(registry: Registry) -> ::prometheus::Result<Self> {
+        let agent_starts = IntCounterVec::new(
+            Opts::new("agent_starts_total", "Total agent invocations"),
+            &["provider", "model"],
+        )?;
+        let agent_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "agent_duration_seconds",
+                "Agent invocation duration in seconds",
+            )
+            .buckets(LLM_LATENCY_BUCKETS.to_vec()),
+        )?;
+        let llm_calls = IntCounterVec::new(
+            Opts::new("llm_calls_total", "Total LLM provider calls"),
+            &["provider", "model", "success"],
+        )?;
+        let llm_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "llm_duration_seconds",
+                "LLM provider call duration in seconds",
+            )
+            .buckets(LLM_LATENCY_BUCKETS.to_vec()),
+            &["provider", "model"],
+        )?;
+        let tool_calls = IntCounterVec::new(
+            Opts::new("tool_calls_total", "Total tool calls"),
+            &["tool", "success"],
+        )?;
+        let tool_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "tool_duration_seconds",
+                "Tool execution duration in seconds",
+            ),
+            &["tool"],
+        )?;
+        let channel_messages = IntCounterVec::new(
+            Opts::new("channel_messages_total", "Total channel messages"),
+            &["channel", "direction"],
+        )?;
+        let heartbeat_ticks = IntCounter::new("heartbeat_ticks_total", "Total heartbeat ticks")?;
+        let secrets_redacted = IntCounterVec::new(
+            Opts::new(
+                "secrets_redacted_total",
+                "Secrets redacted from outbound prompts",
+            ),
+            &["kind"],
+        )?;
+        let budgets_exceeded = IntCounterVec::new(
+            Opts::new(
+                "turn_budget_exceeded_total",
+                "Agent turns stopped by a budget",
+            ),
+            &["budget"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("errors_total", "Total errors by component"),
+            &["component"],
+        )?;
+        let request_latency = Histogram::with_opts(HistogramOpts::new(
+            "request_latency_seconds",
+            "Request latency in seconds",
+        ))?;
+        let tokens_used = IntCounter::new("tokens_used_total", "Total tokens consumed")?;
+        let active_sessions =
+            IntGauge::new("sessions_active", "Current number of active sessions")?;
+        let queue_depth = IntGauge::new("queue_depth", "Current message queue depth")?;
+
+        registry.register(Box::new(agent_starts.clone()))?;
+        registry.register(Box::new(agent_duration.clone()))?;
+        registry.register(Box::new(llm_calls.clone()))?;
+        registry.register(Box::new(llm_duration.clone()))?;
+        registry.register(Box::new(tool_calls.clone()))?;
+        registry.register(Box::new(tool_duration.clone()))?;
+        registry.register(Box::new(channel_messages.clone()))?;
+        registry.register(Box::new(heartbeat_ticks.clone()))?;
+        registry.register(Box::new(secrets_redacted.clone()))?;
+        registry.register(Box::new(budgets_exceeded.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(request_latency.clone()))?;
+        registry.register(Box::new(tokens_used.clone()))?;
+        registry.register(Box::new(active_sessions.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+
+        Ok(Self {
+            registry,
+            agent_starts,
+            agent_duration,
+            llm_calls,
+            llm_duration,
+            tool_calls,
+            tool_duration,
+            channel_messages,
+            heartbeat_ticks,
+            secrets_redacted,
+            budgets_exceeded,
+            errors,
+            request_latency,
+            tokens_used,
+            active_sessions,
+            queue_depth,
+        })
+    }
+}
+
+impl Observer for PrometheusObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        let m = &self.inner;
+        match event {
+            ObserverEvent::AgentStart { provider, model } => {
+                m.agent_starts.with_label_values(&[provider, model]).inc();
+            }
+            ObserverEvent::LlmRequest { .. }
+            | ObserverEvent::ContextUsage { .. }
+            | ObserverEvent::ToolCallStart { .. }
+            | ObserverEvent::TurnComplete => {}
+            ObserverEvent::LlmResponse {
+                provider,
+                model,
+                duration,
+                success,
+                error_message: _,
+            } => {
+                m.llm_calls
+                    .with_label_values(&[provider.as_str(), model, bool_label(*success)])
+                    .inc();
+                m.llm_duration
+                    .with_label_values(&[provider, model])
+                    .observe(duration.as_secs_f64());
+            }
+            ObserverEvent::AgentEnd { duration, .. } => {
+                // Tokens arrive via record_metric(TokensUsed) to avoid double-counting.
+                m.agent_duration.observe(duration.as_secs_f64());
+            }
+            ObserverEvent::ToolCall {
+                tool,
+                duration,
+                success,
+            } => {
+                m.tool_calls
+                    .with_label_values(&[tool.as_str(), bool_label(*success)])
+                    .inc();
+                m.tool_duration
+                    .with_label_values(&[tool])
+                    .observe(duration.as_secs_f64());
+            }
+            ObserverEvent::ChannelMessage { channel, direction } => {
+                m.channel_messages
+                    .with_label_values(&[channel, direction])
+                    .inc();
+            }
+            ObserverEvent::HeartbeatTick => m.heartbeat_ticks.inc(),
+            ObserverEvent::SecretsRedacted { kind, count } => {
+                m.secrets_redacted
+                    .with_label_values(&[kind])
+                    .inc_by(u64::try_from(*count).unwrap_or(u64::MAX));
+            }
+            ObserverEvent::BudgetExceeded { budget, .. } => {
+                m.budgets_exceeded.with_label_values(&[budget]).inc();
+            }
+            ObserverEvent::Error { component, .. } => {
+                m.errors.with_label_values(&[component]).inc();
+            }
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        let m = &self.inner;
+        match metric {
+            ObserverMetric::RequestLatency(d) => m.request_latency.observe(d.as_secs_f64()),
+            ObserverMetric::TokensUsed(t) => m.tokens_used.inc_by(*t),
+            ObserverMetric::ActiveSessions(s) => {
+                m.active_sessions.set(i64::try_from(*s).unwrap_or(i64::MAX));
+            }
+            ObserverMetric::QueueDepth(d) => {
+                m.queue_depth.set(i64::try_from(*d).unwrap_or(i64::MAX));
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "prometheus"
+    }
+}
+
+fn bool_label(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn prometheus_observer_name() {
+        assert_eq!(PrometheusObserver::new().unwrap().name(), "prometheus");
+    }
+
+    #[test]
+    fn records_llm_and_tool_events_with_labels() {
+        let obs = PrometheusObserver::new().unwrap();
+        obs.record_event(&ObserverEvent::LlmResponse {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+            duration: Duration::from_millis(1500),
+            success: true,
+            error_message: None,
+        });
+        obs.record_event(&ObserverEvent::ToolCall {
+            tool: "shell".into(),
+            duration: Duration::from_millis(10),
+            success: false,
+        });
+        obs.record_event(&ObserverEvent::ChannelMessage {
+            channel: "telegram".into(),
+            direction: "inbound".into(),
+        });
+        obs.record_event(&ObserverEvent::BudgetExceeded {
+            budget: "tool_loop".into(),
+            limit: 3,
+        });
+
+        let text = obs.encode().unwrap();
+        assert!(text.contains(
+            r#"zeroclaw_llm_calls_total{model="claude-sonnet",provider="openrouter",success="true"} 1"#
+        ));
+        assert!(text.contains(
+            r#"zeroclaw_llm_duration_seconds_count{model="claude-sonnet",provider="openrouter"} 1"#
+        ));
+        assert!(text.contains(r#"zeroclaw_tool_calls_total{success="false",tool="shell"} 1"#));
+        assert!(text.contains(
+            r#"zeroclaw_channel_messages_total{channel="telegram",direction="inbound"} 1"#
+        ));
+        assert!(text.contains(r#"zeroclaw_turn_budget_exceeded_total{budget="tool_loop"} 1"#));
+    }
+
+    #[test]
+    fn records_metrics() {
+        let obs = PrometheusObserver::new().unwrap();
+        obs.record_metric(&ObserverMetric::TokensUsed(500));
+        obs.record_metric(&ObserverMetric::TokensUsed(250));
+        obs.record_metric(&ObserverMetric::QueueDepth(7));
+        obs.record_metric(&ObserverMetric::ActiveSessions(3));
+        obs.record_metric(&ObserverMetric::RequestLatency(Duration::from_secs(2)));
+
+        let text = obs.encode().unwrap();
+        assert!(text.contains("zeroclaw_tokens_used_total 750"));
+        assert!(text.contains("zeroclaw_queue_depth 7"));
+        assert!(text.contains("zeroclaw_sessions_active 3"));
+        assert!(text.contains("zeroclaw_request_latency_seconds_count 1"));
+    }
+
+    #[test]
+    fn clones_share_registry() {
+        let obs = PrometheusObserver::new().unwrap();
+        let clone = obs.clone();
+        clone.record_event(&ObserverEvent::HeartbeatTick);
+        assert!(obs
+            .encode()
+            .unwrap()
+            .contains("zeroclaw_heartbeat_ticks_total 1"));
+    }
+
+    #[test]
+    fn shared_returns_same_registry() {
+        let a = PrometheusObserver::shared().unwrap();
+        let b = PrometheusObserver::shared().unwrap();
+        assert!(Arc::ptr_eq(&a.inner, &b.inner));
+    }
+}
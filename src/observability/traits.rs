@@ -16,6 +16,17 @@ pub enum ObserverEvent {
         model: String,
         messages_count: usize,
     },
+    /// Token breakdown of the context about to be sent, counted with the
+    /// model's tokenizer. `limit_tokens` is the model's usable context.
+    ContextUsage {
+        model: String,
+        tokenizer: String,
+        system_tokens: usize,
+        memory_tokens: usize,
+        tool_tokens: usize,
+        history_tokens: usize,
+        limit_tokens: usize,
+    },
     /// Result of a single LLM provider call.
     LlmResponse {
         provider: String,
@@ -37,8 +48,20 @@ pub enum ObserverEvent {
         duration: Duration,
         success: bool,
     },
+    /// Secrets of one kind were replaced with placeholders in a prompt
+    /// before it left for the provider.
+    SecretsRedacted {
+        kind: String,
+        count: usize,
+    },
     /// The agent produced a final answer for the current user message.
     TurnComplete,
+    /// A turn stopped early on one of its budgets: `iterations`,
+    /// `duration_secs`, `tool_calls`, `tokens` or `tool_loop`.
+    BudgetExceeded {
+        budget: String,
+        limit: u64,
+    },
     ChannelMessage {
         channel: String,
         direction: String,
@@ -26,6 +26,22 @@ impl Observer for VerboseObserver {
                     provider, model, messages_count
                 );
             }
+            ObserverEvent::ContextUsage {
+                tokenizer,
+                system_tokens,
+                memory_tokens,
+                tool_tokens,
+                history_tokens,
+                limit_tokens,
+                ..
+            } => {
+                let used = system_tokens + memory_tokens + tool_tokens + history_tokens;
+                eprintln!(
+                    "> Context {used}/{limit_tokens} tokens (system={system_tokens}, \
+                     memory={memory_tokens}, tools={tool_tokens}, history={history_tokens}, \
+                     tokenizer={tokenizer})"
+                );
+            }
             ObserverEvent::LlmResponse {
                 duration, success, ..
             } => {
@@ -46,6 +62,9 @@ impl Observer for VerboseObserver {
             ObserverEvent::TurnComplete => {
                 eprintln!("< Complete");
             }
+            ObserverEvent::BudgetExceeded { budget, limit } => {
+                eprintln!("< Stopped (budget={budget}, limit={limit})");
+            }
             _ => {}
         }
     }
@@ -76,6 +95,15 @@ mod tests {
             model: "claude".into(),
             messages_count: 3,
         });
+        obs.record_event(&ObserverEvent::ContextUsage {
+            model: "claude".into(),
+            tokenizer: "heuristic:claude".into(),
+            system_tokens: 1200,
+            memory_tokens: 80,
+            tool_tokens: 900,
+            history_tokens: 300,
+            limit_tokens: 176_000,
+        });
         obs.record_event(&ObserverEvent::LlmResponse {
             provider: "openrouter".into(),
             model: "claude".into(),
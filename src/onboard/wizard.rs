@@ -1,19 +1,20 @@
-use crate::config::schema::{DingTalkConfig, IrcConfig, WhatsAppConfig};
+use crate::config::schema::{DingTalkConfig, IrcConfig, TurnInterruptMode, WhatsAppConfig};
 use crate::config::{
     AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, DiscordConfig,
-    HeartbeatConfig, IMessageConfig, MatrixConfig, MemoryConfig, ObservabilityConfig,
-    RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, WebhookConfig,
+    HeartbeatConfig, IMessageConfig, MatrixConfig, MemoryConfig, ModelCapabilities,
+    ObservabilityConfig, RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, WebhookConfig,
 };
 use crate::hardware::{self, HardwareConfig};
 use crate::memory::{
     default_memory_backend_key, memory_backend_profile, selectable_memory_backends,
 };
+use crate::providers::capabilities::parse_model_catalog;
 use anyhow::{Context, Result};
 use console::style;
 use dialoguer::{Confirm, Input, Select};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -113,9 +114,16 @@ pub fn run_wizard() -> Result<Config> {
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
+        replay: crate::config::ReplayConfig::default(),
+        redaction: crate::config::RedactionConfig::default(),
+        security: crate::config::SecurityConfig::default(),
+        transcription: crate::config::TranscriptionConfig::default(),
+        speech: crate::config::SpeechConfig::default(),
         scheduler: crate::config::schema::SchedulerConfig::default(),
         agent: crate::config::schema::AgentConfig::default(),
         model_routes: Vec::new(),
+        routing: None,
+        model_capabilities: std::collections::HashMap::new(),
         heartbeat: HeartbeatConfig::default(),
         channels_config,
         memory: memory_config, // User-selected memory backend
@@ -125,6 +133,7 @@ pub fn run_wizard() -> Result<Config> {
         secrets: secrets_config,
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
+        image_generation: crate::config::ImageGenerationConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
         peripherals: crate::config::PeripheralsConfig::default(),
@@ -325,9 +334,16 @@ pub fn run_quick_setup(
         autonomy: AutonomyConfig::default(),
         runtime: RuntimeConfig::default(),
         reliability: crate::config::ReliabilityConfig::default(),
+        replay: crate::config::ReplayConfig::default(),
+        redaction: crate::config::RedactionConfig::default(),
+        security: crate::config::SecurityConfig::default(),
+        transcription: crate::config::TranscriptionConfig::default(),
+        speech: crate::config::SpeechConfig::default(),
         scheduler: crate::config::schema::SchedulerConfig::default(),
         agent: crate::config::schema::AgentConfig::default(),
         model_routes: Vec::new(),
+        routing: None,
+        model_capabilities: std::collections::HashMap::new(),
         heartbeat: HeartbeatConfig::default(),
         channels_config: ChannelsConfig::default(),
         memory: memory_config,
@@ -337,6 +353,7 @@ pub fn run_quick_setup(
         secrets: SecretsConfig::default(),
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
+        image_generation: crate::config::ImageGenerationConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
         peripherals: crate::config::PeripheralsConfig::default(),
@@ -845,7 +862,7 @@ fn fetch_openai_compatible_models(endpoint: &str, api_key: Option<&str>) -> Resu
     Ok(parse_openai_compatible_model_ids(&payload))
 }
 
-fn fetch_openrouter_models(api_key: Option<&str>) -> Result<Vec<String>> {
+fn fetch_openrouter_models(api_key: Option<&str>) -> Result<LiveModels> {
     let client = build_model_fetch_client()?;
     let mut request = client.get("https://openrouter.ai/api/v1/models");
     if let Some(api_key) = api_key {
@@ -859,7 +876,10 @@ fn fetch_openrouter_models(api_key: Option<&str>) -> Result<Vec<String>> {
         .json()
         .context("failed to parse OpenRouter model list response")?;
 
-    Ok(parse_openai_compatible_model_ids(&payload))
+    Ok(LiveModels {
+        ids: parse_openai_compatible_model_ids(&payload),
+        capabilities: parse_model_catalog(&payload),
+    })
 }
 
 fn fetch_anthropic_models(api_key: Option<&str>) -> Result<Vec<String>> {
@@ -881,9 +901,9 @@ fn fetch_anthropic_models(api_key: Option<&str>) -> Result<Vec<String>> {
     Ok(parse_openai_compatible_model_ids(&payload))
 }
 
-fn fetch_gemini_models(api_key: Option<&str>) -> Result<Vec<String>> {
+fn fetch_gemini_models(api_key: Option<&str>) -> Result<LiveModels> {
     let Some(api_key) = api_key else {
-        return Ok(Vec::new());
+        return Ok(LiveModels::default());
     };
 
     let client = build_model_fetch_client()?;
@@ -896,7 +916,10 @@ fn fetch_gemini_models(api_key: Option<&str>) -> Result<Vec<String>> {
         .json()
         .context("failed to parse Gemini model list response")?;
 
-    Ok(parse_gemini_model_ids(&payload))
+    Ok(LiveModels {
+        ids: parse_gemini_model_ids(&payload),
+        capabilities: parse_model_catalog(&payload),
+    })
 }
 
 fn fetch_ollama_models() -> Result<Vec<String>> {
@@ -912,7 +935,23 @@ fn fetch_ollama_models() -> Result<Vec<String>> {
     Ok(parse_ollama_model_ids(&payload))
 }
 
-fn fetch_live_models_for_provider(provider_name: &str, api_key: &str) -> Result<Vec<String>> {
+/// Model ids plus whatever capabilities the provider's catalog reports.
+#[derive(Debug, Clone, Default)]
+struct LiveModels {
+    ids: Vec<String>,
+    capabilities: BTreeMap<String, ModelCapabilities>,
+}
+
+impl From<Vec<String>> for LiveModels {
+    fn from(ids: Vec<String>) -> Self {
+        Self {
+            ids,
+            capabilities: BTreeMap::new(),
+        }
+    }
+}
+
+fn fetch_live_models_for_provider(provider_name: &str, api_key: &str) -> Result<LiveModels> {
     let provider_name = canonical_provider_name(provider_name);
     let api_key = if api_key.trim().is_empty() {
         std::env::var(provider_env_var(provider_name))
@@ -924,7 +963,7 @@ fn fetch_live_models_for_provider(provider_name: &str, api_key: &str) -> Result<
     };
 
     let models = match provider_name {
-        "openrouter" => fetch_openrouter_models(api_key.as_deref())?,
+        "openrouter" => return fetch_openrouter_models(api_key.as_deref()),
         "openai" => {
             fetch_openai_compatible_models("https://api.openai.com/v1/models", api_key.as_deref())?
         }
@@ -945,12 +984,12 @@ fn fetch_live_models_for_provider(provider_name: &str, api_key: &str) -> Result<
             api_key.as_deref(),
         )?,
         "anthropic" => fetch_anthropic_models(api_key.as_deref())?,
-        "gemini" => fetch_gemini_models(api_key.as_deref())?,
+        "gemini" => return fetch_gemini_models(api_key.as_deref()),
         "ollama" => fetch_ollama_models()?,
         _ => Vec::new(),
     };
 
-    Ok(models)
+    Ok(models.into())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -958,6 +997,8 @@ struct ModelCacheEntry {
     provider: String,
     fetched_at_unix: u64,
     models: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    capabilities: BTreeMap<String, ModelCapabilities>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -1018,6 +1059,7 @@ fn cache_live_models_for_provider(
     workspace_dir: &Path,
     provider_name: &str,
     models: &[String],
+    capabilities: &BTreeMap<String, ModelCapabilities>,
 ) -> Result<()> {
     let normalized_models = normalize_model_ids(models.to_vec());
     if normalized_models.is_empty() {
@@ -1034,17 +1076,31 @@ fn cache_live_models_for_provider(
     {
         entry.fetched_at_unix = now;
         entry.models = normalized_models;
+        entry.capabilities.clone_from(capabilities);
     } else {
         state.entries.push(ModelCacheEntry {
             provider: provider_name.to_string(),
             fetched_at_unix: now,
             models: normalized_models,
+            capabilities: capabilities.clone(),
         });
     }
 
     save_model_cache_state(workspace_dir, &state)
 }
 
+/// Per-provider model capabilities recorded by the last model refresh.
+pub fn cached_model_capabilities(
+    workspace_dir: &Path,
+) -> Result<HashMap<String, BTreeMap<String, ModelCapabilities>>> {
+    Ok(load_model_cache_state(workspace_dir)?
+        .entries
+        .into_iter()
+        .filter(|entry| !entry.capabilities.is_empty())
+        .map(|entry| (entry.provider, entry.capabilities))
+        .collect())
+}
+
 fn load_cached_models_for_provider_internal(
     workspace_dir: &Path,
     provider_name: &str,
@@ -1168,8 +1224,16 @@ pub fn run_models_refresh(
     let api_key = config.api_key.clone().unwrap_or_default();
 
     match fetch_live_models_for_provider(&provider_name, &api_key) {
-        Ok(models) if !models.is_empty() => {
-            cache_live_models_for_provider(&config.workspace_dir, &provider_name, &models)?;
+        Ok(LiveModels {
+            ids: models,
+            capabilities,
+        }) if !models.is_empty() => {
+            cache_live_models_for_provider(
+                &config.workspace_dir,
+                &provider_name,
+                &models,
+                &capabilities,
+            )?;
             println!(
                 "Refreshed '{}' model cache with {} models.",
                 provider_name,
@@ -1649,11 +1713,15 @@ fn setup_provider(workspace_dir: &Path) -> Result<(String, String, String)> {
 
             if should_fetch_now {
                 match fetch_live_models_for_provider(provider_name, &api_key) {
-                    Ok(live_model_ids) if !live_model_ids.is_empty() => {
+                    Ok(LiveModels {
+                        ids: live_model_ids,
+                        capabilities,
+                    }) if !live_model_ids.is_empty() => {
                         cache_live_models_for_provider(
                             workspace_dir,
                             provider_name,
                             &live_model_ids,
+                            &capabilities,
                         )?;
 
                         let fetched_count = live_model_ids.len();
@@ -2228,6 +2296,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
 
     let mut config = ChannelsConfig {
         cli: true,
+        show_reasoning: false,
         telegram: None,
         discord: None,
         slack: None,
@@ -2239,6 +2308,8 @@ fn setup_channels() -> Result<ChannelsConfig> {
         irc: None,
         lark: None,
         dingtalk: None,
+        budgets: HashMap::new(),
+        on_new_message: TurnInterruptMode::default(),
     };
 
     loop {
@@ -2588,7 +2659,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
                 }
 
                 let app_token: String = Input::new()
-                    .with_prompt("  App token (xapp-..., optional, Enter to skip)")
+                    .with_prompt("  App token (xapp-..., optional, enables buttons via Socket Mode; Enter to skip)")
                     .allow_empty(true)
                     .interact_text()?;
 
@@ -4382,7 +4453,7 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let models = vec!["gpt-5.1".to_string(), "gpt-5-mini".to_string()];
 
-        cache_live_models_for_provider(tmp.path(), "openai", &models).unwrap();
+        cache_live_models_for_provider(tmp.path(), "openai", &models, &BTreeMap::new()).unwrap();
 
         let cached =
             load_cached_models_for_provider(tmp.path(), "openai", MODEL_CACHE_TTL_SECS).unwrap();
@@ -4401,6 +4472,7 @@ mod tests {
                 provider: "openai".to_string(),
                 fetched_at_unix: now_unix_secs().saturating_sub(MODEL_CACHE_TTL_SECS + 120),
                 models: vec!["gpt-5.1".to_string()],
+                capabilities: BTreeMap::new(),
             }],
         };
 
@@ -4418,7 +4490,13 @@ mod tests {
     fn run_models_refresh_uses_fresh_cache_without_network() {
         let tmp = TempDir::new().unwrap();
 
-        cache_live_models_for_provider(tmp.path(), "openai", &["gpt-5.1".to_string()]).unwrap();
+        cache_live_models_for_provider(
+            tmp.path(),
+            "openai",
+            &["gpt-5.1".to_string()],
+            &BTreeMap::new(),
+        )
+        .unwrap();
 
         let config = Config {
             workspace_dir: tmp.path().to_path_buf(),
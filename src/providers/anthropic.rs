@@ -1,11 +1,20 @@
+use crate::providers::streaming::{self, Framing};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ToolCall as ProviderToolCall,
+    ChatStream, ChatUsage, ContentPartType, Provider, Reasoning, ResponseSchema, StreamEvent,
+    ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// Output tokens left for the answer on top of the thinking budget.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
 
 pub struct AnthropicProvider {
     credential: Option<String>,
@@ -84,11 +93,58 @@ struct NativeChatRequest {
     model: String,
     max_tokens: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    system: Option<String>,
+    system: Option<Vec<SystemBlock>>,
     messages: Vec<NativeMessage>,
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<NativeToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<NativeThinking>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+}
+
+/// Enables extended thinking with a token budget.
+#[derive(Debug, Serialize)]
+struct NativeThinking {
+    #[serde(rename = "type")]
+    kind: String,
+    budget_tokens: u32,
+}
+
+/// Forces the model to call the named tool.
+#[derive(Debug, Serialize)]
+struct NativeToolChoice {
+    #[serde(rename = "type")]
+    kind: String,
+    name: String,
+}
+
+/// Prompt-cache breakpoint: everything up to and including the marked block
+/// is cached and re-read at a discount on the next request.
+#[derive(Debug, Clone, Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl CacheControl {
+    fn ephemeral() -> Self {
+        Self {
+            kind: "ephemeral".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
 }
 
 #[derive(Debug, Serialize)]
@@ -100,32 +156,123 @@ struct NativeMessage {
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 enum NativeContentOut {
+    /// Signed thinking from an earlier response, replayed unchanged.
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
-    Text { text: String },
+    Text {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    #[serde(rename = "image")]
+    Image {
+        source: NativeImageSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: String,
         name: String,
         input: serde_json::Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
     },
     #[serde(rename = "tool_result")]
     ToolResult {
         tool_use_id: String,
         content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
     },
 }
 
+impl NativeContentOut {
+    fn set_cache_control(&mut self) {
+        let (Self::Text { cache_control, .. }
+        | Self::Image { cache_control, .. }
+        | Self::ToolUse { cache_control, .. }
+        | Self::ToolResult { cache_control, .. }) = self
+        else {
+            // Thinking blocks cannot carry a breakpoint themselves.
+            return;
+        };
+        *cache_control = Some(CacheControl::ephemeral());
+    }
+
+    /// Rebuild a thinking block kept in [`Reasoning::blocks`].
+    fn from_reasoning_block(block: &serde_json::Value) -> Option<Self> {
+        let field = |name: &str| {
+            block
+                .get(name)
+                .and_then(serde_json::Value::as_str)
+                .map(ToString::to_string)
+        };
+        match block.get("type").and_then(serde_json::Value::as_str)? {
+            "thinking" => Some(Self::Thinking {
+                thinking: field("thinking")?,
+                signature: field("signature")?,
+            }),
+            "redacted_thinking" => Some(Self::RedactedThinking {
+                data: field("data")?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NativeImageSource {
+    #[serde(rename = "type")]
+    kind: String,
+    media_type: String,
+    data: String,
+}
+
 #[derive(Debug, Serialize)]
 struct NativeToolSpec {
     name: String,
     description: String,
     input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
 }
 
 #[derive(Debug, Deserialize)]
 struct NativeChatResponse {
     #[serde(default)]
     content: Vec<NativeContentIn>,
+    #[serde(default)]
+    usage: Option<NativeUsage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct NativeUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+}
+
+impl From<NativeUsage> for ChatUsage {
+    fn from(usage: NativeUsage) -> Self {
+        // Anthropic reports cached prompt tokens separately from `input_tokens`.
+        Self {
+            input_tokens: usage.input_tokens
+                + usage.cache_read_input_tokens
+                + usage.cache_creation_input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_input_tokens: usage.cache_read_input_tokens,
+            cache_write_input_tokens: usage.cache_creation_input_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -140,6 +287,12 @@ struct NativeContentIn {
     name: Option<String>,
     #[serde(default)]
     input: Option<serde_json::Value>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    data: Option<String>,
 }
 
 impl AnthropicProvider {
@@ -194,6 +347,7 @@ impl AnthropicProvider {
                     name: tool.name.clone(),
                     description: tool.description.clone(),
                     input_schema: tool.parameters.clone(),
+                    cache_control: None,
                 })
                 .collect(),
         )
@@ -205,7 +359,13 @@ impl AnthropicProvider {
             .get("tool_calls")
             .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
 
-        let mut blocks = Vec::new();
+        let mut blocks: Vec<NativeContentOut> = value
+            .get("reasoning")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(NativeContentOut::from_reasoning_block)
+            .collect();
         if let Some(text) = value
             .get("content")
             .and_then(serde_json::Value::as_str)
@@ -214,6 +374,7 @@ impl AnthropicProvider {
         {
             blocks.push(NativeContentOut::Text {
                 text: text.to_string(),
+                cache_control: None,
             });
         }
         for call in tool_calls {
@@ -223,6 +384,7 @@ impl AnthropicProvider {
                 id: call.id,
                 name: call.name,
                 input,
+                cache_control: None,
             });
         }
         Some(blocks)
@@ -244,6 +406,7 @@ impl AnthropicProvider {
             content: vec![NativeContentOut::ToolResult {
                 tool_use_id,
                 content: result,
+                cache_control: None,
             }],
         })
     }
@@ -270,6 +433,7 @@ impl AnthropicProvider {
                             role: "assistant".to_string(),
                             content: vec![NativeContentOut::Text {
                                 text: msg.content.clone(),
+                                cache_control: None,
                             }],
                         });
                     }
@@ -282,6 +446,7 @@ impl AnthropicProvider {
                             role: "user".to_string(),
                             content: vec![NativeContentOut::Text {
                                 text: msg.content.clone(),
+                                cache_control: None,
                             }],
                         });
                     }
@@ -289,9 +454,7 @@ impl AnthropicProvider {
                 _ => {
                     native_messages.push(NativeMessage {
                         role: "user".to_string(),
-                        content: vec![NativeContentOut::Text {
-                            text: msg.content.clone(),
-                        }],
+                        content: Self::user_content_blocks(msg),
                     });
                 }
             }
@@ -300,6 +463,89 @@ impl AnthropicProvider {
         (system_prompt, native_messages)
     }
 
+    fn user_content_blocks(msg: &ChatMessage) -> Vec<NativeContentOut> {
+        let Some(parts) = msg.parts.as_ref() else {
+            return vec![NativeContentOut::Text {
+                text: msg.content.clone(),
+                cache_control: None,
+            }];
+        };
+        parts
+            .iter()
+            .map(|p| match p.content_type {
+                ContentPartType::Text => NativeContentOut::Text {
+                    text: p.text.clone().unwrap_or_default(),
+                    cache_control: None,
+                },
+                ContentPartType::Image => NativeContentOut::Image {
+                    source: NativeImageSource {
+                        kind: "base64".to_string(),
+                        media_type: p
+                            .mime_type
+                            .clone()
+                            .unwrap_or_else(|| "image/jpeg".to_string()),
+                        data: p.image_base64.clone().unwrap_or_default(),
+                    },
+                    cache_control: None,
+                },
+            })
+            .collect()
+    }
+
+    /// Build a Messages API request with prompt-cache breakpoints on the
+    /// system prompt, the tool definitions and the end of the history. Tool
+    /// iterations resend the same prefix, so each call reads everything up
+    /// to the previous breakpoint from cache.
+    ///
+    /// A reasoning budget enables extended thinking. The API then requires
+    /// the default temperature and room for the answer beyond the budget.
+    fn build_native_request(
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+        stream: Option<bool>,
+    ) -> NativeChatRequest {
+        let (system_prompt, mut messages) = Self::convert_messages(request.messages);
+        let mut tools = Self::convert_tools(request.tools);
+
+        if let Some(tool) = tools.as_mut().and_then(|t| t.last_mut()) {
+            tool.cache_control = Some(CacheControl::ephemeral());
+        }
+        if let Some(block) = messages.last_mut().and_then(|m| m.content.last_mut()) {
+            block.set_cache_control();
+        }
+
+        let thinking = request
+            .reasoning
+            .and_then(|r| r.budget_tokens)
+            .map(|budget| NativeThinking {
+                kind: "enabled".to_string(),
+                budget_tokens: budget.max(MIN_THINKING_BUDGET),
+            });
+        let (max_tokens, temperature) = match &thinking {
+            Some(t) => (t.budget_tokens + DEFAULT_MAX_TOKENS, 1.0),
+            None => (DEFAULT_MAX_TOKENS, temperature),
+        };
+
+        NativeChatRequest {
+            model: model.to_string(),
+            max_tokens,
+            system: system_prompt.map(|text| {
+                vec![SystemBlock {
+                    kind: "text".to_string(),
+                    text,
+                    cache_control: Some(CacheControl::ephemeral()),
+                }]
+            }),
+            messages,
+            temperature,
+            tools,
+            tool_choice: None,
+            thinking,
+            stream,
+        }
+    }
+
     fn parse_text_response(response: ChatResponse) -> anyhow::Result<String> {
         response
             .content
@@ -312,9 +558,29 @@ impl AnthropicProvider {
     fn parse_native_response(response: NativeChatResponse) -> ProviderChatResponse {
         let mut text_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut reasoning = Reasoning::default();
+        let usage = response.usage.map(Into::into);
 
         for block in response.content {
             match block.kind.as_str() {
+                "thinking" => {
+                    let thinking = block.thinking.unwrap_or_default();
+                    if !reasoning.text.is_empty() {
+                        reasoning.text.push('\n');
+                    }
+                    reasoning.text.push_str(thinking.trim());
+                    reasoning.blocks.push(serde_json::json!({
+                        "type": "thinking",
+                        "thinking": thinking,
+                        "signature": block.signature.unwrap_or_default(),
+                    }));
+                }
+                "redacted_thinking" => {
+                    reasoning.blocks.push(serde_json::json!({
+                        "type": "redacted_thinking",
+                        "data": block.data.unwrap_or_default(),
+                    }));
+                }
                 "text" => {
                     if let Some(text) = block.text.map(|t| t.trim().to_string()) {
                         if !text.is_empty() {
@@ -347,6 +613,8 @@ impl AnthropicProvider {
                 Some(text_parts.join("\n"))
             },
             tool_calls,
+            usage,
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
         }
     }
 }
@@ -408,15 +676,7 @@ impl Provider for AnthropicProvider {
             )
         })?;
 
-        let (system_prompt, messages) = Self::convert_messages(request.messages);
-        let native_request = NativeChatRequest {
-            model: model.to_string(),
-            max_tokens: 4096,
-            system: system_prompt,
-            messages,
-            temperature,
-            tools: Self::convert_tools(request.tools),
-        };
+        let native_request = Self::build_native_request(request, model, temperature, None);
 
         let req = self
             .client
@@ -434,9 +694,277 @@ impl Provider for AnthropicProvider {
         Ok(Self::parse_native_response(native_response))
     }
 
+    /// Anthropic has no JSON mode, so the schema becomes the input schema of
+    /// a single tool the model is forced to call; its input is the answer.
+    async fn generate_structured(
+        &self,
+        request: ProviderChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let credential = self.credential.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
+            )
+        })?;
+
+        // Forcing a tool call is not allowed with extended thinking.
+        let mut native_request = Self::build_native_request(
+            ProviderChatRequest {
+                messages: request.messages,
+                tools: None,
+                reasoning: None,
+            },
+            model,
+            temperature,
+            None,
+        );
+        native_request.tools = Some(vec![NativeToolSpec {
+            name: schema.name.clone(),
+            description: if schema.description.is_empty() {
+                "Record the answer in the required structure.".to_string()
+            } else {
+                schema.description.clone()
+            },
+            input_schema: schema.schema.clone(),
+            cache_control: None,
+        }]);
+        native_request.tool_choice = Some(NativeToolChoice {
+            kind: "tool".to_string(),
+            name: schema.name.clone(),
+        });
+
+        let req = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&native_request);
+
+        let response = self.apply_auth(req, credential).send().await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Anthropic", response).await);
+        }
+
+        let native_response: NativeChatResponse = response.json().await?;
+        let parsed = Self::parse_native_response(native_response);
+        parsed
+            .tool_calls
+            .into_iter()
+            .find(|call| call.name == schema.name)
+            .map(|call| call.arguments)
+            .or(parsed.text)
+            .ok_or_else(|| anyhow::anyhow!("No structured response from Anthropic"))
+    }
+
     fn supports_native_tools(&self) -> bool {
         true
     }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let credential = self.credential.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
+            )
+        })?;
+
+        let native_request = Self::build_native_request(request, model, temperature, Some(true));
+
+        let req = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&native_request);
+
+        let response = self.apply_auth(req, credential).send().await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Anthropic", response).await);
+        }
+
+        let mut state = StreamState::default();
+        Ok(streaming::decode_response(
+            response,
+            Framing::Sse,
+            move |payload| state.parse(payload),
+        ))
+    }
+}
+
+/// Tracks which content blocks of a streamed message are tool calls, since
+/// Anthropic numbers text and `tool_use` blocks in one shared sequence.
+/// Thinking blocks are buffered until they close so they can be replayed
+/// with their signature.
+#[derive(Debug, Default)]
+struct StreamState {
+    tool_indices: HashMap<u64, usize>,
+    thinking: HashMap<u64, (String, String)>,
+}
+
+impl StreamState {
+    fn usage_event(usage: Option<&serde_json::Value>) -> Vec<StreamEvent> {
+        usage
+            .and_then(|u| serde_json::from_value::<NativeUsage>(u.clone()).ok())
+            .map(|u| StreamEvent::Usage(u.into()))
+            .into_iter()
+            .collect()
+    }
+
+    fn parse(&mut self, payload: &str) -> anyhow::Result<Vec<StreamEvent>> {
+        let event: serde_json::Value = serde_json::from_str(payload)
+            .map_err(|e| anyhow::anyhow!("Malformed Anthropic stream event: {e}"))?;
+        let block_index = event.get("index").and_then(serde_json::Value::as_u64);
+
+        match event.get("type").and_then(serde_json::Value::as_str) {
+            Some("content_block_start") => {
+                let Some(block) = event.get("content_block") else {
+                    return Ok(Vec::new());
+                };
+                match block.get("type").and_then(serde_json::Value::as_str) {
+                    Some("tool_use") => {
+                        let index = self.tool_indices.len();
+                        self.tool_indices
+                            .insert(block_index.unwrap_or_default(), index);
+                        Ok(vec![StreamEvent::ToolCallDelta {
+                            index,
+                            id: block
+                                .get("id")
+                                .and_then(serde_json::Value::as_str)
+                                .map(ToString::to_string),
+                            name: block
+                                .get("name")
+                                .and_then(serde_json::Value::as_str)
+                                .map(ToString::to_string),
+                            arguments: String::new(),
+                        }])
+                    }
+                    Some("text") => Ok(block
+                        .get("text")
+                        .and_then(serde_json::Value::as_str)
+                        .filter(|t| !t.is_empty())
+                        .map(|t| StreamEvent::TextDelta(t.to_string()))
+                        .into_iter()
+                        .collect()),
+                    Some("thinking") => {
+                        let text = block
+                            .get("thinking")
+                            .and_then(serde_json::Value::as_str)
+                            .unwrap_or_default()
+                            .to_string();
+                        self.thinking.insert(
+                            block_index.unwrap_or_default(),
+                            (text.clone(), String::new()),
+                        );
+                        Ok((!text.is_empty())
+                            .then_some(StreamEvent::ReasoningDelta(text))
+                            .into_iter()
+                            .collect())
+                    }
+                    Some("redacted_thinking") => {
+                        Ok(vec![StreamEvent::ReasoningBlock(block.clone())])
+                    }
+                    _ => Ok(Vec::new()),
+                }
+            }
+            Some("content_block_stop") => Ok(block_index
+                .and_then(|i| self.thinking.remove(&i))
+                .map(|(thinking, signature)| {
+                    StreamEvent::ReasoningBlock(serde_json::json!({
+                        "type": "thinking",
+                        "thinking": thinking,
+                        "signature": signature,
+                    }))
+                })
+                .into_iter()
+                .collect()),
+            Some("content_block_delta") => {
+                let Some(delta) = event.get("delta") else {
+                    return Ok(Vec::new());
+                };
+                match delta.get("type").and_then(serde_json::Value::as_str) {
+                    Some("text_delta") => Ok(delta
+                        .get("text")
+                        .and_then(serde_json::Value::as_str)
+                        .map(|t| StreamEvent::TextDelta(t.to_string()))
+                        .into_iter()
+                        .collect()),
+                    Some("thinking_delta") => {
+                        let text = delta
+                            .get("thinking")
+                            .and_then(serde_json::Value::as_str)
+                            .unwrap_or_default();
+                        if let Some((thinking, _)) =
+                            block_index.and_then(|i| self.thinking.get_mut(&i))
+                        {
+                            thinking.push_str(text);
+                        }
+                        Ok(vec![StreamEvent::ReasoningDelta(text.to_string())])
+                    }
+                    Some("signature_delta") => {
+                        if let Some((_, signature)) =
+                            block_index.and_then(|i| self.thinking.get_mut(&i))
+                        {
+                            signature.push_str(
+                                delta
+                                    .get("signature")
+                                    .and_then(serde_json::Value::as_str)
+                                    .unwrap_or_default(),
+                            );
+                        }
+                        Ok(Vec::new())
+                    }
+                    Some("input_json_delta") => {
+                        let Some(index) =
+                            block_index.and_then(|i| self.tool_indices.get(&i)).copied()
+                        else {
+                            return Ok(Vec::new());
+                        };
+                        let partial = delta
+                            .get("partial_json")
+                            .and_then(serde_json::Value::as_str)
+                            .unwrap_or_default();
+                        Ok(vec![StreamEvent::ToolCallDelta {
+                            index,
+                            id: None,
+                            name: None,
+                            arguments: partial.to_string(),
+                        }])
+                    }
+                    _ => Ok(Vec::new()),
+                }
+            }
+            Some("message_start") => Ok(Self::usage_event(
+                event.get("message").and_then(|m| m.get("usage")),
+            )),
+            Some("message_delta") => Ok(Self::usage_event(event.get("usage"))),
+            Some("error") => {
+                let message = event
+                    .get("error")
+                    .and_then(|e| e.get("message"))
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("unknown stream error");
+                anyhow::bail!(
+                    "Anthropic stream error: {}",
+                    super::sanitize_api_error(message)
+                )
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -597,4 +1125,269 @@ mod tests {
             assert!(json.contains(&format!("{temp}")));
         }
     }
+
+    #[test]
+    fn stream_state_maps_text_and_tool_blocks() {
+        let mut state = StreamState::default();
+        let text = state
+            .parse(r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#)
+            .unwrap();
+        assert_eq!(text, vec![StreamEvent::TextDelta("Hi".into())]);
+
+        let start = state
+            .parse(r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"shell","input":{}}}"#)
+            .unwrap();
+        assert_eq!(
+            start,
+            vec![StreamEvent::ToolCallDelta {
+                index: 0,
+                id: Some("toolu_1".into()),
+                name: Some("shell".into()),
+                arguments: String::new(),
+            }]
+        );
+
+        let args = state
+            .parse(r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#)
+            .unwrap();
+        assert_eq!(
+            args,
+            vec![StreamEvent::ToolCallDelta {
+                index: 0,
+                id: None,
+                name: None,
+                arguments: "{\"command\":".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn stream_state_ignores_bookkeeping_events() {
+        let mut state = StreamState::default();
+        for payload in [
+            r#"{"type":"message_start","message":{"id":"msg_1","content":[]}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"}}"#,
+            r#"{"type":"message_stop"}"#,
+        ] {
+            assert!(state.parse(payload).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn stream_state_reports_usage_from_message_events() {
+        let mut state = StreamState::default();
+        let start = state
+            .parse(r#"{"type":"message_start","message":{"usage":{"input_tokens":20,"cache_read_input_tokens":80,"output_tokens":1}}}"#)
+            .unwrap();
+        assert_eq!(
+            start,
+            vec![StreamEvent::Usage(ChatUsage {
+                input_tokens: 100,
+                output_tokens: 1,
+                cached_input_tokens: 80,
+                cache_write_input_tokens: 0,
+            })]
+        );
+        let delta = state
+            .parse(r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":15}}"#)
+            .unwrap();
+        assert_eq!(
+            delta,
+            vec![StreamEvent::Usage(ChatUsage {
+                input_tokens: 0,
+                output_tokens: 15,
+                cached_input_tokens: 0,
+                cache_write_input_tokens: 0,
+            })]
+        );
+    }
+
+    #[test]
+    fn native_response_includes_usage() {
+        let response: NativeChatResponse = serde_json::from_str(
+            r#"{"content":[{"type":"text","text":"hi"}],"usage":{"input_tokens":12,"output_tokens":3}}"#,
+        )
+        .unwrap();
+        let parsed = AnthropicProvider::parse_native_response(response);
+        assert_eq!(
+            parsed.usage,
+            Some(ChatUsage {
+                input_tokens: 12,
+                output_tokens: 3,
+                cached_input_tokens: 0,
+                cache_write_input_tokens: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn stream_state_surfaces_error_events() {
+        let mut state = StreamState::default();
+        let err = state
+            .parse(r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("Overloaded"));
+    }
+
+    #[test]
+    fn native_user_message_keeps_images() {
+        let msg = ChatMessage::with_image("what is this?", "aGVsbG8=", "image/png");
+        let (_, messages) = AnthropicProvider::convert_messages(&[msg]);
+        let json = serde_json::to_value(&messages[0]).unwrap();
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][1]["type"], "image");
+        assert_eq!(json["content"][1]["source"]["media_type"], "image/png");
+        assert_eq!(json["content"][1]["source"]["data"], "aGVsbG8=");
+    }
+
+    #[test]
+    fn native_request_sets_cache_breakpoints() {
+        let tools = vec![
+            ToolSpec {
+                name: "shell".to_string(),
+                description: "Run a command".to_string(),
+                parameters: serde_json::json!({"type": "object"}),
+            },
+            ToolSpec {
+                name: "file_read".to_string(),
+                description: "Read a file".to_string(),
+                parameters: serde_json::json!({"type": "object"}),
+            },
+        ];
+        let messages = vec![
+            ChatMessage::system("You are ZeroClaw"),
+            ChatMessage::user("list files"),
+            ChatMessage::assistant("Sure."),
+            ChatMessage::user("now"),
+        ];
+        let request = AnthropicProvider::build_native_request(
+            ProviderChatRequest {
+                messages: &messages,
+                tools: Some(&tools),
+                reasoning: None,
+            },
+            "claude-sonnet-4",
+            0.7,
+            None,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["system"][0]["text"], "You are ZeroClaw");
+        assert_eq!(json["system"][0]["cache_control"]["type"], "ephemeral");
+        assert!(json["tools"][0].get("cache_control").is_none());
+        assert_eq!(json["tools"][1]["cache_control"]["type"], "ephemeral");
+        assert!(json["messages"][0]["content"][0]
+            .get("cache_control")
+            .is_none());
+        assert_eq!(
+            json["messages"][2]["content"][0]["cache_control"]["type"],
+            "ephemeral"
+        );
+    }
+
+    #[test]
+    fn native_usage_reports_cache_writes() {
+        let response: NativeChatResponse = serde_json::from_str(
+            r#"{"content":[],"usage":{"input_tokens":10,"cache_creation_input_tokens":2000,"cache_read_input_tokens":5000,"output_tokens":3}}"#,
+        )
+        .unwrap();
+        let parsed = AnthropicProvider::parse_native_response(response);
+        assert_eq!(
+            parsed.usage,
+            Some(ChatUsage {
+                input_tokens: 7010,
+                output_tokens: 3,
+                cached_input_tokens: 5000,
+                cache_write_input_tokens: 2000,
+            })
+        );
+    }
+
+    #[test]
+    fn native_request_enables_thinking_with_budget() {
+        let messages = vec![ChatMessage::user("prove it")];
+        let request = AnthropicProvider::build_native_request(
+            ProviderChatRequest {
+                messages: &messages,
+                tools: None,
+                reasoning: Some(crate::providers::ReasoningOptions {
+                    budget_tokens: Some(500),
+                    effort: None,
+                }),
+            },
+            "claude-sonnet-4",
+            0.2,
+            None,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["thinking"]["type"], "enabled");
+        assert_eq!(json["thinking"]["budget_tokens"], MIN_THINKING_BUDGET);
+        assert_eq!(json["max_tokens"], MIN_THINKING_BUDGET + DEFAULT_MAX_TOKENS);
+        assert_eq!(json["temperature"], 1.0);
+    }
+
+    #[test]
+    fn native_response_keeps_signed_thinking() {
+        let response: NativeChatResponse = serde_json::from_str(
+            r#"{"content":[{"type":"thinking","thinking":"Check the dir first.","signature":"sig_1"},{"type":"redacted_thinking","data":"opaque"},{"type":"tool_use","id":"toolu_1","name":"shell","input":{"command":"ls"}}]}"#,
+        )
+        .unwrap();
+        let parsed = AnthropicProvider::parse_native_response(response);
+        let reasoning = parsed.reasoning.unwrap();
+
+        assert_eq!(reasoning.text, "Check the dir first.");
+        assert_eq!(reasoning.blocks.len(), 2);
+        assert_eq!(reasoning.blocks[0]["signature"], "sig_1");
+        assert_eq!(reasoning.blocks[1]["type"], "redacted_thinking");
+        assert_eq!(parsed.tool_calls.len(), 1);
+    }
+
+    #[test]
+    fn native_history_replays_thinking_before_tool_use() {
+        let history = serde_json::json!({
+            "content": "",
+            "tool_calls": [{"id": "toolu_1", "name": "shell", "arguments": "{}"}],
+            "reasoning": [{"type": "thinking", "thinking": "hmm", "signature": "sig_1"}],
+        });
+        let (_, messages) =
+            AnthropicProvider::convert_messages(&[ChatMessage::assistant(history.to_string())]);
+        let json = serde_json::to_value(&messages[0]).unwrap();
+
+        assert_eq!(json["content"][0]["type"], "thinking");
+        assert_eq!(json["content"][0]["signature"], "sig_1");
+        assert_eq!(json["content"][1]["type"], "tool_use");
+    }
+
+    #[test]
+    fn stream_state_emits_thinking_block_on_stop() {
+        let mut state = StreamState::default();
+        state
+            .parse(r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#)
+            .unwrap();
+        let delta = state
+            .parse(r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Let me see"}}"#)
+            .unwrap();
+        assert_eq!(
+            delta,
+            vec![StreamEvent::ReasoningDelta("Let me see".into())]
+        );
+        state
+            .parse(r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig_1"}}"#)
+            .unwrap();
+
+        let stop = state
+            .parse(r#"{"type":"content_block_stop","index":0}"#)
+            .unwrap();
+        assert_eq!(
+            stop,
+            vec![StreamEvent::ReasoningBlock(serde_json::json!({
+                "type": "thinking",
+                "thinking": "Let me see",
+                "signature": "sig_1",
+            }))]
+        );
+    }
 }
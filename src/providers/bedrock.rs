@@ -0,0 +1,1027 @@
+//! Amazon Bedrock provider using the Converse API.
+//!
+//! Requests are signed with AWS Signature Version 4. Credentials come from
+//! the explicit key (`ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]`), the
+//! standard `AWS_*` environment variables, or the shared credentials file,
+//! in that order. The region comes from the provider name
+//! (`bedrock:eu-central-1`), `AWS_REGION`/`AWS_DEFAULT_REGION`, or the shared
+//! config file, falling back to `us-east-1`.
+
+use crate::providers::traits::{
+    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
+    ChatUsage, ContentPartType, Provider, Reasoning, ToolCall as ProviderToolCall,
+};
+use crate::tools::ToolSpec;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use directories::UserDirs;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// SigV4 service name for the Bedrock runtime API.
+const SERVICE: &str = "bedrock";
+
+/// Output tokens left for the answer on top of any thinking budget.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Smallest thinking budget Anthropic models on Bedrock accept.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// AWS access key pair, optionally with a session token for temporary
+/// credentials.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AwsCredentials {
+    /// Parse `ACCESS_KEY_ID:SECRET_ACCESS_KEY[:SESSION_TOKEN]`.
+    fn from_key(key: &str) -> Option<Self> {
+        let mut parts = key.trim().splitn(3, ':');
+        let access_key_id = parts.next().filter(|p| !p.is_empty())?;
+        let secret_access_key = parts.next().filter(|p| !p.is_empty())?;
+        Some(Self {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: parts
+                .next()
+                .filter(|p| !p.is_empty())
+                .map(ToString::to_string),
+        })
+    }
+
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: non_empty_env("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: non_empty_env("AWS_SECRET_ACCESS_KEY")?,
+            session_token: non_empty_env("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// Read `profile` from a shared credentials file.
+    fn from_credentials_file(contents: &str, profile: &str) -> Option<Self> {
+        let value = |key| profile_value(contents, profile, key);
+        Some(Self {
+            access_key_id: value("aws_access_key_id")?,
+            secret_access_key: value("aws_secret_access_key")?,
+            session_token: value("aws_session_token"),
+        })
+    }
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Profile selected by `AWS_PROFILE`, or `default`.
+fn aws_profile() -> String {
+    non_empty_env("AWS_PROFILE").unwrap_or_else(|| "default".to_string())
+}
+
+/// Path of a shared AWS file: the override variable, or `~/.aws/<name>`.
+fn aws_file(override_var: &str, name: &str) -> Option<PathBuf> {
+    non_empty_env(override_var)
+        .map(PathBuf::from)
+        .or_else(|| UserDirs::new().map(|u| u.home_dir().join(".aws").join(name)))
+}
+
+/// Look up `key` in the `profile` section of an INI-style AWS file. The
+/// config file names sections `[profile name]`; the credentials file and the
+/// default profile use `[name]`.
+fn profile_value(contents: &str, profile: &str, key: &str) -> Option<String> {
+    let mut in_profile = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = section.trim();
+            let name = section.strip_prefix("profile ").unwrap_or(section).trim();
+            in_profile = name == profile;
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        if let Some((k, v)) = line.split_once('=') {
+            if k.trim() == key {
+                return Some(v.trim().to_string()).filter(|v| !v.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters, as
+/// SigV4 requires.
+fn uri_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Headers to add to a request so AWS accepts it: `x-amz-date`, the session
+/// token when present, and `authorization`. `headers` are the other headers
+/// to sign (lowercase names); `host` is always signed.
+#[allow(clippy::too_many_arguments)]
+fn sign_request(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    method: &str,
+    url: &reqwest::Url,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let mut signed: Vec<(String, String)> = vec![
+        ("host".into(), host),
+        ("x-amz-date".into(), amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        signed.push(("x-amz-security-token".into(), token.clone()));
+    }
+    signed.extend(
+        headers
+            .iter()
+            .map(|(k, v)| (k.to_ascii_lowercase(), v.trim().to_string())),
+    );
+    signed.sort();
+
+    // Non-S3 services sign the path with every segment encoded again.
+    let canonical_uri = url
+        .path()
+        .split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (uri_encode(&k), uri_encode(&v)))
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("&");
+    let canonical_headers = signed.iter().fold(String::new(), |mut out, (k, v)| {
+        let _ = writeln!(out, "{k}:{v}");
+        out
+    });
+    let signed_headers = signed
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{canonical_uri}\n{canonical_query}\n{canonical_headers}\n{signed_headers}\n{}",
+        hex::encode(Sha256::digest(body))
+    );
+
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut signing_key = format!("AWS4{}", credentials.secret_access_key).into_bytes();
+    for part in [date.as_str(), region, service, "aws4_request"] {
+        signing_key = hmac_sha256(&signing_key, part);
+    }
+    let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+    let mut out = vec![("x-amz-date".to_string(), amz_date)];
+    if let Some(token) = &credentials.session_token {
+        out.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    out.push((
+        "authorization".to_string(),
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ),
+    ));
+    out
+}
+
+// ── Converse API wire types ───────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseRequest {
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemBlock>,
+    inference_config: InferenceConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_model_request_fields: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InferenceConfig {
+    max_tokens: u32,
+    temperature: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolConfig {
+    tools: Vec<ToolEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolEntry {
+    tool_spec: ToolSpecBlock,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolSpecBlock {
+    name: String,
+    description: String,
+    input_schema: InputSchema,
+}
+
+#[derive(Debug, Serialize)]
+struct InputSchema {
+    json: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: &'static str,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ContentBlock {
+    Text(String),
+    Image(ImageBlock),
+    ToolUse(ToolUseBlock),
+    ToolResult(ToolResultBlock),
+    /// Reasoning from an earlier response, replayed unchanged.
+    ReasoningContent(serde_json::Value),
+}
+
+#[derive(Debug, Serialize)]
+struct ImageBlock {
+    format: String,
+    source: ImageSource,
+}
+
+#[derive(Debug, Serialize)]
+struct ImageSource {
+    bytes: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolUseBlock {
+    tool_use_id: String,
+    name: String,
+    input: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolResultBlock {
+    tool_use_id: String,
+    content: Vec<ToolResultContent>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolResultContent {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseResponse {
+    output: ConverseOutput,
+    #[serde(default)]
+    usage: Option<ConverseUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseOutput {
+    #[serde(default)]
+    message: Option<ResponseMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    #[serde(default)]
+    content: Vec<ResponseContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResponseContent {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    tool_use: Option<ToolUseBlock>,
+    #[serde(default)]
+    reasoning_content: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    cache_write_input_tokens: u64,
+}
+
+impl From<ConverseUsage> for ChatUsage {
+    fn from(usage: ConverseUsage) -> Self {
+        // Bedrock reports cached prompt tokens separately from `inputTokens`.
+        Self {
+            input_tokens: usage.input_tokens
+                + usage.cache_read_input_tokens
+                + usage.cache_write_input_tokens,
+            output_tokens: usage.output_tokens,
+            cached_input_tokens: usage.cache_read_input_tokens,
+            cache_write_input_tokens: usage.cache_write_input_tokens,
+        }
+    }
+}
+
+// ── Provider ──────────────────────────────────────────────────────
+
+pub struct BedrockProvider {
+    credentials: Option<AwsCredentials>,
+    region: String,
+    endpoint: String,
+    client: Client,
+}
+
+impl BedrockProvider {
+    /// Resolve credentials, region and endpoint from the key, environment
+    /// and shared AWS files. `region` overrides the environment.
+    pub fn new(api_key: Option<&str>, region: Option<&str>) -> Self {
+        let profile = aws_profile();
+        let read =
+            |var, name| aws_file(var, name).and_then(|path| std::fs::read_to_string(path).ok());
+
+        let credentials = api_key
+            .and_then(AwsCredentials::from_key)
+            .or_else(AwsCredentials::from_env)
+            .or_else(|| {
+                read("AWS_SHARED_CREDENTIALS_FILE", "credentials")
+                    .and_then(|contents| AwsCredentials::from_credentials_file(&contents, &profile))
+            });
+        let region = region
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(ToString::to_string)
+            .or_else(|| non_empty_env("AWS_REGION"))
+            .or_else(|| non_empty_env("AWS_DEFAULT_REGION"))
+            .or_else(|| {
+                read("AWS_CONFIG_FILE", "config")
+                    .and_then(|contents| profile_value(&contents, &profile, "region"))
+            })
+            .unwrap_or_else(|| DEFAULT_REGION.to_string());
+        let endpoint = non_empty_env("AWS_ENDPOINT_URL_BEDROCK_RUNTIME")
+            .or_else(|| non_empty_env("AWS_ENDPOINT_URL"));
+
+        Self::with_endpoint(credentials, &region, endpoint.as_deref())
+    }
+
+    /// Provider with explicit settings. `endpoint` defaults to the regional
+    /// `bedrock-runtime` endpoint.
+    pub fn with_endpoint(
+        credentials: Option<AwsCredentials>,
+        region: &str,
+        endpoint: Option<&str>,
+    ) -> Self {
+        let endpoint = endpoint.map_or_else(
+            || format!("https://bedrock-runtime.{region}.amazonaws.com"),
+            |e| e.trim_end_matches('/').to_string(),
+        );
+        Self {
+            credentials,
+            region: region.to_string(),
+            endpoint,
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(600))
+                .connect_timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| Client::new()),
+        }
+    }
+
+    fn converse_url(&self, model: &str) -> String {
+        format!("{}/model/{}/converse", self.endpoint, uri_encode(model))
+    }
+
+    fn convert_tools(tools: Option<&[ToolSpec]>) -> Option<ToolConfig> {
+        let items = tools.filter(|t| !t.is_empty())?;
+        Some(ToolConfig {
+            tools: items
+                .iter()
+                .map(|tool| ToolEntry {
+                    tool_spec: ToolSpecBlock {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        input_schema: InputSchema {
+                            json: tool.parameters.clone(),
+                        },
+                    },
+                })
+                .collect(),
+        })
+    }
+
+    /// Image format name Bedrock expects (`png`, `jpeg`, `gif`, `webp`).
+    fn image_format(mime_type: Option<&str>) -> String {
+        match mime_type
+            .and_then(|m| m.strip_prefix("image/"))
+            .unwrap_or("jpeg")
+        {
+            "jpg" => "jpeg".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    fn user_content(msg: &ChatMessage) -> Vec<ContentBlock> {
+        let Some(parts) = msg.parts.as_ref() else {
+            return vec![ContentBlock::Text(msg.content.clone())];
+        };
+        parts
+            .iter()
+            .map(|p| match p.content_type {
+                ContentPartType::Text => ContentBlock::Text(p.text.clone().unwrap_or_default()),
+                ContentPartType::Image => ContentBlock::Image(ImageBlock {
+                    format: Self::image_format(p.mime_type.as_deref()),
+                    source: ImageSource {
+                        bytes: p.image_base64.clone().unwrap_or_default(),
+                    },
+                }),
+            })
+            .collect()
+    }
+
+    /// Assistant turns that called tools are stored as JSON with
+    /// `content`, `tool_calls` and optional `reasoning`.
+    fn assistant_tool_call_content(content: &str) -> Option<Vec<ContentBlock>> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let tool_calls = value
+            .get("tool_calls")
+            .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
+
+        let mut blocks: Vec<ContentBlock> = value
+            .get("reasoning")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|b| b.get("reasoningContent").cloned())
+            .map(ContentBlock::ReasoningContent)
+            .collect();
+        if let Some(text) = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            blocks.push(ContentBlock::Text(text.to_string()));
+        }
+        for call in tool_calls {
+            let input = serde_json::from_str::<serde_json::Value>(&call.arguments)
+                .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new()));
+            blocks.push(ContentBlock::ToolUse(ToolUseBlock {
+                tool_use_id: call.id,
+                name: call.name,
+                input,
+            }));
+        }
+        Some(blocks)
+    }
+
+    fn tool_result_content(content: &str) -> Option<ContentBlock> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let tool_use_id = value
+            .get("tool_call_id")
+            .and_then(serde_json::Value::as_str)?
+            .to_string();
+        let text = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        Some(ContentBlock::ToolResult(ToolResultBlock {
+            tool_use_id,
+            content: vec![ToolResultContent { text }],
+        }))
+    }
+
+    /// Split out system prompts and convert the rest. Converse requires
+    /// alternating roles, so consecutive messages of one role (e.g. several
+    /// tool results) are merged.
+    fn convert_messages(messages: &[ChatMessage]) -> (Vec<SystemBlock>, Vec<Message>) {
+        let mut system = Vec::new();
+        let mut converted: Vec<Message> = Vec::new();
+
+        for msg in messages {
+            let (role, content) = match msg.role.as_str() {
+                "system" => {
+                    system.push(SystemBlock {
+                        text: msg.content.clone(),
+                    });
+                    continue;
+                }
+                "assistant" => (
+                    "assistant",
+                    Self::assistant_tool_call_content(&msg.content)
+                        .unwrap_or_else(|| vec![ContentBlock::Text(msg.content.clone())]),
+                ),
+                "tool" => (
+                    "user",
+                    vec![Self::tool_result_content(&msg.content)
+                        .unwrap_or_else(|| ContentBlock::Text(msg.content.clone()))],
+                ),
+                _ => ("user", Self::user_content(msg)),
+            };
+            match converted.last_mut() {
+                Some(last) if last.role == role => last.content.extend(content),
+                _ => converted.push(Message { role, content }),
+            }
+        }
+
+        (system, converted)
+    }
+
+    /// A reasoning budget enables extended thinking on Anthropic models,
+    /// which then need the default temperature and room for the answer.
+    fn build_request(request: ProviderChatRequest<'_>, temperature: f64) -> ConverseRequest {
+        let (system, messages) = Self::convert_messages(request.messages);
+        let budget = request
+            .reasoning
+            .and_then(|r| r.budget_tokens)
+            .map(|b| b.max(MIN_THINKING_BUDGET));
+        let (max_tokens, temperature) = match budget {
+            Some(budget) => (budget + DEFAULT_MAX_TOKENS, 1.0),
+            None => (DEFAULT_MAX_TOKENS, temperature),
+        };
+
+        ConverseRequest {
+            messages,
+            system,
+            inference_config: InferenceConfig {
+                max_tokens,
+                temperature,
+            },
+            tool_config: Self::convert_tools(request.tools),
+            additional_model_request_fields: budget.map(|budget| {
+                serde_json::json!({
+                    "thinking": { "type": "enabled", "budget_tokens": budget }
+                })
+            }),
+        }
+    }
+
+    fn parse_response(response: ConverseResponse) -> ProviderChatResponse {
+        let mut text_parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        let mut reasoning = Reasoning::default();
+
+        let content = response
+            .output
+            .message
+            .map(|m| m.content)
+            .unwrap_or_default();
+        for block in content {
+            if let Some(text) = block.text.map(|t| t.trim().to_string()) {
+                if !text.is_empty() {
+                    text_parts.push(text);
+                }
+            }
+            if let Some(tool_use) = block.tool_use {
+                tool_calls.push(ProviderToolCall {
+                    id: tool_use.tool_use_id,
+                    name: tool_use.name,
+                    arguments: tool_use.input.to_string(),
+                });
+            }
+            if let Some(value) = block.reasoning_content {
+                if let Some(text) = value
+                    .pointer("/reasoningText/text")
+                    .and_then(serde_json::Value::as_str)
+                {
+                    if !reasoning.text.is_empty() {
+                        reasoning.text.push('\n');
+                    }
+                    reasoning.text.push_str(text.trim());
+                }
+                reasoning
+                    .blocks
+                    .push(serde_json::json!({ "reasoningContent": value }));
+            }
+        }
+
+        ProviderChatResponse {
+            text: (!text_parts.is_empty()).then(|| text_parts.join("\n")),
+            tool_calls,
+            usage: response.usage.map(Into::into),
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
+        }
+    }
+
+    async fn converse(
+        &self,
+        model: &str,
+        body: &ConverseRequest,
+    ) -> anyhow::Result<ConverseResponse> {
+        let credentials = self.credentials.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Amazon Bedrock credentials not set. Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, \
+                 or configure a profile in ~/.aws/credentials."
+            )
+        })?;
+
+        let url = reqwest::Url::parse(&self.converse_url(model))?;
+        let body = serde_json::to_vec(body)?;
+        let content_type = "application/json";
+        let auth_headers = sign_request(
+            credentials,
+            &self.region,
+            SERVICE,
+            "POST",
+            &url,
+            &[("content-type", content_type)],
+            &body,
+            Utc::now(),
+        );
+
+        let mut request = self
+            .client
+            .post(url)
+            .header("content-type", content_type)
+            .body(body);
+        for (name, value) in auth_headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(super::api_error("Amazon Bedrock", response).await);
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl Provider for BedrockProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::new();
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, temperature).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let response = self
+            .chat(
+                ProviderChatRequest {
+                    messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                model,
+                temperature,
+            )
+            .await?;
+        response
+            .text
+            .ok_or_else(|| anyhow::anyhow!("No response from Amazon Bedrock"))
+    }
+
+    async fn chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let body = Self::build_request(request, temperature);
+        let response = self.converse(model, &body).await?;
+        Ok(Self::parse_response(response))
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        // Any response (even 403) means the TLS connection is established.
+        let _ = self.client.get(&self.endpoint).send().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::{Arc, Mutex};
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn sigv4_matches_aws_test_suite() {
+        // "get-vanilla" from the AWS Signature Version 4 test suite.
+        let url = reqwest::Url::parse("https://example.amazonaws.com/").unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+        let headers = sign_request(
+            &example_credentials(),
+            "us-east-1",
+            "service",
+            "GET",
+            &url,
+            &[],
+            b"",
+            now,
+        );
+        assert_eq!(headers[0], ("x-amz-date".into(), "20150830T123600Z".into()));
+        assert_eq!(
+            headers[1].1,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn model_ids_are_encoded_twice_when_signing() {
+        let provider = BedrockProvider::with_endpoint(None, "us-west-2", None);
+        let url = provider.converse_url("anthropic.claude-3-5-sonnet-20240620-v1:0");
+        assert_eq!(
+            url,
+            "https://bedrock-runtime.us-west-2.amazonaws.com/model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/converse"
+        );
+
+        let url = reqwest::Url::parse(&url).unwrap();
+        let canonical: Vec<String> = url.path().split('/').map(uri_encode).collect();
+        assert_eq!(
+            canonical[2],
+            "anthropic.claude-3-5-sonnet-20240620-v1%253A0"
+        );
+    }
+
+    #[test]
+    fn credentials_parse_from_key_and_profile_files() {
+        let creds = AwsCredentials::from_key("AKID:secret:token").unwrap();
+        assert_eq!(creds.access_key_id, "AKID");
+        assert_eq!(creds.secret_access_key, "secret");
+        assert_eq!(creds.session_token.as_deref(), Some("token"));
+        assert!(AwsCredentials::from_key("just-an-api-key").is_none());
+
+        let credentials_file = "[default]\naws_access_key_id = A1\naws_secret_access_key = S1\n\n\
+                                [work]\naws_access_key_id=A2\naws_secret_access_key=S2\n";
+        let work = AwsCredentials::from_credentials_file(credentials_file, "work").unwrap();
+        assert_eq!(work.access_key_id, "A2");
+        assert!(work.session_token.is_none());
+        assert!(AwsCredentials::from_credentials_file(credentials_file, "missing").is_none());
+
+        let config_file = "[default]\nregion = us-east-2\n[profile work]\nregion = eu-west-1\n";
+        assert_eq!(
+            profile_value(config_file, "work", "region").as_deref(),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            profile_value(config_file, "default", "region").as_deref(),
+            Some("us-east-2")
+        );
+    }
+
+    #[test]
+    fn converse_request_maps_tools_images_and_tool_results() {
+        let messages = vec![
+            ChatMessage::system("Be brief"),
+            ChatMessage::with_image("What is this?", "aGVsbG8=", "image/jpg"),
+            ChatMessage::assistant(
+                serde_json::json!({
+                    "content": "Checking",
+                    "tool_calls": [{"id": "t1", "name": "shell", "arguments": "{\"command\":\"ls\"}"}]
+                })
+                .to_string(),
+            ),
+            ChatMessage::tool(r#"{"tool_call_id":"t1","content":"a.txt"}"#),
+            ChatMessage::user("Thanks"),
+        ];
+        let tools = vec![ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = BedrockProvider::build_request(
+            ProviderChatRequest {
+                messages: &messages,
+                tools: Some(&tools),
+                reasoning: None,
+            },
+            0.3,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["system"], serde_json::json!([{"text": "Be brief"}]));
+        assert_eq!(json["inferenceConfig"]["temperature"], 0.3);
+        assert_eq!(json["toolConfig"]["tools"][0]["toolSpec"]["name"], "shell");
+        assert_eq!(
+            json["toolConfig"]["tools"][0]["toolSpec"]["inputSchema"]["json"]["type"],
+            "object"
+        );
+
+        let messages = json["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["content"][1]["image"]["format"], "jpeg");
+        assert_eq!(
+            messages[0]["content"][1]["image"]["source"]["bytes"],
+            "aGVsbG8="
+        );
+        assert_eq!(messages[1]["content"][1]["toolUse"]["toolUseId"], "t1");
+        assert_eq!(
+            messages[1]["content"][1]["toolUse"]["input"]["command"],
+            "ls"
+        );
+        // The tool result and the next user message share one user turn.
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["toolResult"]["toolUseId"], "t1");
+        assert_eq!(
+            messages[2]["content"][0]["toolResult"]["content"][0]["text"],
+            "a.txt"
+        );
+        assert_eq!(messages[2]["content"][1]["text"], "Thanks");
+        assert!(json.get("additionalModelRequestFields").is_none());
+    }
+
+    #[test]
+    fn converse_response_maps_text_tools_reasoning_and_usage() {
+        let response: ConverseResponse = serde_json::from_value(serde_json::json!({
+            "output": {"message": {"role": "assistant", "content": [
+                {"reasoningContent": {"reasoningText": {"text": "think", "signature": "sig"}}},
+                {"text": "Let me look"},
+                {"toolUse": {"toolUseId": "t9", "name": "file_read", "input": {"path": "a"}}}
+            ]}},
+            "stopReason": "tool_use",
+            "usage": {"inputTokens": 10, "outputTokens": 5, "totalTokens": 15, "cacheReadInputTokens": 90}
+        }))
+        .unwrap();
+        let parsed = BedrockProvider::parse_response(response);
+
+        assert_eq!(parsed.text.as_deref(), Some("Let me look"));
+        assert_eq!(parsed.tool_calls[0].id, "t9");
+        assert_eq!(parsed.tool_calls[0].arguments, r#"{"path":"a"}"#);
+        let reasoning = parsed.reasoning.unwrap();
+        assert_eq!(reasoning.text, "think");
+        assert_eq!(
+            reasoning.blocks[0]["reasoningContent"]["reasoningText"]["signature"],
+            "sig"
+        );
+        let usage = parsed.usage.unwrap();
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.cached_input_tokens, 90);
+        assert_eq!(usage.output_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn chat_sends_signed_request_to_endpoint() {
+        let seen: Arc<Mutex<Vec<(String, String, String)>>> = Arc::default();
+        let recorded = Arc::clone(&seen);
+        let app = axum::Router::new().route(
+            "/model/{model}/converse",
+            axum::routing::post(
+                move |axum::extract::Path(model): axum::extract::Path<String>,
+                      headers: axum::http::HeaderMap| async move {
+                    let header = |name: &str| {
+                        headers
+                            .get(name)
+                            .and_then(|v| v.to_str().ok())
+                            .unwrap_or_default()
+                            .to_string()
+                    };
+                    recorded.lock().unwrap().push((
+                        model,
+                        header("authorization"),
+                        header("x-amz-security-token"),
+                    ));
+                    axum::Json(serde_json::json!({
+                        "output": {"message": {"role": "assistant", "content": [{"text": "hi there"}]}},
+                        "usage": {"inputTokens": 3, "outputTokens": 2}
+                    }))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let credentials = AwsCredentials {
+            session_token: Some("session".into()),
+            ..example_credentials()
+        };
+        let provider = BedrockProvider::with_endpoint(
+            Some(credentials),
+            "eu-central-1",
+            Some(&format!("http://{addr}/")),
+        );
+        let reply = provider
+            .chat_with_system(Some("sys"), "hello", "amazon.nova-lite-v1:0", 0.5)
+            .await
+            .unwrap();
+        assert_eq!(reply, "hi there");
+
+        let seen = seen.lock().unwrap();
+        let (model, authorization, token) = &seen[0];
+        assert_eq!(model, "amazon.nova-lite-v1:0");
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/eu-central-1/bedrock/aws4_request"));
+        assert!(authorization
+            .contains("SignedHeaders=content-type;host;x-amz-date;x-amz-security-token"));
+        assert_eq!(token, "session");
+    }
+
+    #[tokio::test]
+    async fn chat_fails_without_credentials() {
+        let provider = BedrockProvider::with_endpoint(None, "us-east-1", None);
+        let err = provider
+            .simple_chat("hello", "amazon.nova-lite-v1:0", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("credentials not set"));
+    }
+}
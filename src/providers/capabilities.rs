@@ -0,0 +1,353 @@
+//! Model capability registry.
+//!
+//! Answers "what can this model do?" — context window, output limit, vision,
+//! native tools, structured output and pricing — by layering, per field:
+//!
+//! 1. `[model_capabilities."<model>"]` overrides from config,
+//! 2. the provider catalog cached by `zeroclaw models refresh`,
+//! 3. built-in defaults for well-known model families.
+//!
+//! Unknown fields stay `None`, and callers keep their conservative defaults.
+
+use super::tokenizer::{Tokenizer, Tokenizers};
+use crate::config::schema::ModelPricing;
+use crate::config::{Config, ModelCapabilities, ModelRouteConfig};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Known limits for popular model families, matched by id prefix (after any
+/// `vendor/` namespace). More specific prefixes come first.
+/// Columns: prefix, context window, max output tokens, vision, native tools.
+const FAMILY_DEFAULTS: &[(&str, u64, u64, bool, bool)] = &[
+    ("claude", 200_000, 8_192, true, true),
+    ("gemini-1.5-pro", 2_097_152, 8_192, true, true),
+    ("gemini", 1_048_576, 8_192, true, true),
+    ("gpt-5", 400_000, 128_000, true, true),
+    ("gpt-4.1", 1_047_576, 32_768, true, true),
+    ("gpt-4o", 128_000, 16_384, true, true),
+    ("gpt-4-turbo", 128_000, 4_096, true, true),
+    ("gpt-3.5-turbo", 16_385, 4_096, false, true),
+    ("o1", 200_000, 100_000, true, true),
+    ("o3", 200_000, 100_000, true, true),
+    ("o4", 200_000, 100_000, true, true),
+    ("grok", 131_072, 8_192, false, true),
+    ("deepseek", 65_536, 8_192, false, true),
+    ("mistral-large", 131_072, 8_192, false, true),
+    ("llama-3.3", 131_072, 8_192, false, true),
+    ("llama3.1", 131_072, 8_192, false, true),
+    ("llama3.2", 131_072, 8_192, false, true),
+];
+
+/// Built-in defaults for `model`'s family, if it is a well-known one.
+pub fn family_defaults(model: &str) -> Option<ModelCapabilities> {
+    let base = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .to_ascii_lowercase();
+    FAMILY_DEFAULTS
+        .iter()
+        .find(|(prefix, ..)| base.starts_with(prefix))
+        .map(
+            |&(_, context_window, max_output_tokens, vision, native_tools)| ModelCapabilities {
+                context_window: Some(context_window),
+                max_output_tokens: Some(max_output_tokens),
+                vision: Some(vision),
+                native_tools: Some(native_tools),
+                structured_output: Some(native_tools),
+                pricing: None,
+            },
+        )
+}
+
+/// Fill the unset fields of `caps` from `fallback`.
+fn merge(mut caps: ModelCapabilities, fallback: &ModelCapabilities) -> ModelCapabilities {
+    caps.context_window = caps.context_window.or(fallback.context_window);
+    caps.max_output_tokens = caps.max_output_tokens.or(fallback.max_output_tokens);
+    caps.vision = caps.vision.or(fallback.vision);
+    caps.native_tools = caps.native_tools.or(fallback.native_tools);
+    caps.structured_output = caps.structured_output.or(fallback.structured_output);
+    if caps.pricing.is_none() {
+        caps.pricing.clone_from(&fallback.pricing);
+    }
+    caps
+}
+
+fn has_any(caps: &ModelCapabilities) -> bool {
+    caps != &ModelCapabilities::default()
+}
+
+fn u64_field(value: &Value, key: &str) -> Option<u64> {
+    value.get(key).and_then(Value::as_u64)
+}
+
+/// Per-token price string (OpenRouter) converted to USD per 1M tokens.
+fn per_million(value: Option<&Value>) -> Option<f64> {
+    let raw = value?;
+    let per_token = raw
+        .as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .or_else(|| raw.as_f64())?;
+    Some(per_token * 1_000_000.0)
+}
+
+/// Extract capabilities from a provider's model-list payload. Understands the
+/// OpenRouter catalog (context length, modalities, supported parameters,
+/// pricing) and Gemini's token limits; other catalogs yield nothing.
+pub fn parse_model_catalog(payload: &Value) -> BTreeMap<String, ModelCapabilities> {
+    let models = payload
+        .get("data")
+        .or_else(|| payload.get("models"))
+        .and_then(Value::as_array);
+    let mut catalog = BTreeMap::new();
+
+    for model in models.into_iter().flatten() {
+        let Some(id) = model
+            .get("id")
+            .or_else(|| model.get("name"))
+            .and_then(Value::as_str)
+        else {
+            continue;
+        };
+        let id = id.trim_start_matches("models/").to_string();
+
+        let supported: Vec<&str> = model
+            .get("supported_parameters")
+            .and_then(Value::as_array)
+            .map(|params| params.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let input_modalities = model
+            .get("architecture")
+            .and_then(|a| a.get("input_modalities"))
+            .and_then(Value::as_array);
+
+        let caps = ModelCapabilities {
+            context_window: u64_field(model, "context_length")
+                .or_else(|| u64_field(model, "inputTokenLimit")),
+            max_output_tokens: model
+                .get("top_provider")
+                .and_then(|p| u64_field(p, "max_completion_tokens"))
+                .or_else(|| u64_field(model, "outputTokenLimit")),
+            vision: input_modalities
+                .map(|modalities| modalities.iter().any(|m| m.as_str() == Some("image"))),
+            native_tools: (!supported.is_empty()).then(|| supported.contains(&"tools")),
+            structured_output: (!supported.is_empty()).then(|| {
+                supported.contains(&"structured_outputs") || supported.contains(&"response_format")
+            }),
+            pricing: model.get("pricing").and_then(|pricing| {
+                Some(ModelPricing {
+                    input: per_million(pricing.get("prompt"))?,
+                    output: per_million(pricing.get("completion"))?,
+                    cache_read: per_million(pricing.get("input_cache_read")),
+                    cache_write: per_million(pricing.get("input_cache_write")),
+                })
+            }),
+        };
+        if has_any(&caps) {
+            catalog.insert(id, caps);
+        }
+    }
+
+    catalog
+}
+
+/// Capability lookups keyed by provider and model.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityRegistry {
+    overrides: HashMap<String, ModelCapabilities>,
+    /// provider → model → capabilities, from the refreshed catalog.
+    catalog: HashMap<String, BTreeMap<String, ModelCapabilities>>,
+    routes: Vec<ModelRouteConfig>,
+    default_provider: String,
+    tokenizers: Arc<Tokenizers>,
+}
+
+impl CapabilityRegistry {
+    pub fn new(
+        overrides: HashMap<String, ModelCapabilities>,
+        catalog: HashMap<String, BTreeMap<String, ModelCapabilities>>,
+        routes: Vec<ModelRouteConfig>,
+        default_provider: impl Into<String>,
+    ) -> Self {
+        Self {
+            overrides,
+            catalog,
+            routes,
+            default_provider: default_provider.into(),
+            tokenizers: Arc::default(),
+        }
+    }
+
+    /// Count tokens with `tokenizers` (BPE vocabularies) where available.
+    #[must_use]
+    pub fn with_tokenizers(mut self, tokenizers: Tokenizers) -> Self {
+        self.tokenizers = Arc::new(tokenizers);
+        self
+    }
+
+    /// Registry for `config`: capability overrides, the cached model catalog,
+    /// the `[[model_routes]]` needed to resolve `hint:` models and any BPE
+    /// vocabularies in `<workspace>/tokenizers/`.
+    pub fn from_config(config: &Config) -> Self {
+        let catalog = crate::onboard::wizard::cached_model_capabilities(&config.workspace_dir)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable model catalog cache: {e}");
+                HashMap::new()
+            });
+        Self::new(
+            config.model_capabilities.clone(),
+            catalog,
+            config.model_routes.clone(),
+            config.default_provider.as_deref().unwrap_or("openrouter"),
+        )
+        .with_tokenizers(Tokenizers::new(config.workspace_dir.join("tokenizers")))
+    }
+
+    /// Resolve a model argument (possibly `hint:<name>`) to provider and model.
+    pub fn resolve<'a>(&'a self, model: &'a str) -> (&'a str, &'a str) {
+        if let Some(route) = model
+            .strip_prefix("hint:")
+            .and_then(|hint| self.routes.iter().find(|r| r.hint == hint))
+        {
+            return (&route.provider, &route.model);
+        }
+        (&self.default_provider, model)
+    }
+
+    /// Capabilities of `model` (a model name or `hint:<name>`).
+    pub fn lookup(&self, model: &str) -> ModelCapabilities {
+        let (provider, model) = self.resolve(model);
+        self.lookup_for(provider, model)
+    }
+
+    /// Tokenizer for `model` (a model name or `hint:<name>`).
+    pub fn tokenizer(&self, model: &str) -> Arc<dyn Tokenizer> {
+        self.tokenizers.for_model(self.resolve(model).1)
+    }
+
+    /// Capabilities of `model` as served by `provider`.
+    pub fn lookup_for(&self, provider: &str, model: &str) -> ModelCapabilities {
+        let mut caps = self.overrides.get(model).cloned().unwrap_or_default();
+        if let Some(listed) = self.catalog.get(provider).and_then(|m| m.get(model)) {
+            caps = merge(caps, listed);
+        } else if let Some(listed) = self.catalog.values().find_map(|m| m.get(model)) {
+            caps = merge(caps, listed);
+        }
+        if let Some(defaults) = family_defaults(model) {
+            caps = merge(caps, &defaults);
+        }
+        caps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_defaults_match_namespaced_ids() {
+        let caps = family_defaults("anthropic/claude-sonnet-4").unwrap();
+        assert_eq!(caps.context_window, Some(200_000));
+        assert_eq!(caps.vision, Some(true));
+        assert_eq!(
+            family_defaults("llama3.1:8b").unwrap().context_window,
+            Some(131_072)
+        );
+        assert_eq!(
+            family_defaults("deepseek-chat").unwrap().vision,
+            Some(false)
+        );
+        assert!(family_defaults("my-finetune").is_none());
+    }
+
+    #[test]
+    fn parses_openrouter_catalog() {
+        let payload = serde_json::json!({
+            "data": [{
+                "id": "vendor/model-x",
+                "context_length": 32768,
+                "top_provider": { "max_completion_tokens": 4096 },
+                "architecture": { "input_modalities": ["text"] },
+                "supported_parameters": ["tools", "temperature"],
+                "pricing": { "prompt": "0.000001", "completion": "0.000002" }
+            }, {
+                "id": "vendor/bare"
+            }]
+        });
+        let catalog = parse_model_catalog(&payload);
+        assert_eq!(catalog.len(), 1);
+        let caps = &catalog["vendor/model-x"];
+        assert_eq!(caps.context_window, Some(32_768));
+        assert_eq!(caps.max_output_tokens, Some(4_096));
+        assert_eq!(caps.vision, Some(false));
+        assert_eq!(caps.native_tools, Some(true));
+        assert_eq!(caps.structured_output, Some(false));
+        let pricing = caps.pricing.as_ref().unwrap();
+        assert!((pricing.input - 1.0).abs() < 1e-9);
+        assert!((pricing.output - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parses_gemini_token_limits() {
+        let payload = serde_json::json!({
+            "models": [{
+                "name": "models/gemini-2.0-flash",
+                "inputTokenLimit": 1048576,
+                "outputTokenLimit": 8192
+            }]
+        });
+        let caps = &parse_model_catalog(&payload)["gemini-2.0-flash"];
+        assert_eq!(caps.context_window, Some(1_048_576));
+        assert_eq!(caps.max_output_tokens, Some(8_192));
+    }
+
+    #[test]
+    fn overrides_win_over_catalog_and_defaults() {
+        let overrides = HashMap::from([(
+            "claude-sonnet-4".to_string(),
+            ModelCapabilities {
+                context_window: Some(1_000_000),
+                ..ModelCapabilities::default()
+            },
+        )]);
+        let catalog = HashMap::from([(
+            "anthropic".to_string(),
+            BTreeMap::from([(
+                "claude-sonnet-4".to_string(),
+                ModelCapabilities {
+                    context_window: Some(150_000),
+                    max_output_tokens: Some(64_000),
+                    ..ModelCapabilities::default()
+                },
+            )]),
+        )]);
+        let registry = CapabilityRegistry::new(overrides, catalog, Vec::new(), "anthropic");
+
+        let caps = registry.lookup("claude-sonnet-4");
+        assert_eq!(caps.context_window, Some(1_000_000));
+        assert_eq!(caps.max_output_tokens, Some(64_000));
+        assert_eq!(caps.vision, Some(true));
+    }
+
+    #[test]
+    fn hint_models_resolve_through_routes() {
+        let routes = vec![ModelRouteConfig {
+            hint: "local".into(),
+            provider: "ollama".into(),
+            model: "llama3.2:3b".into(),
+            api_key: None,
+            thinking_budget: None,
+            reasoning_effort: None,
+        }];
+        let registry =
+            CapabilityRegistry::new(HashMap::new(), HashMap::new(), routes, "openrouter");
+        assert_eq!(registry.resolve("hint:local"), ("ollama", "llama3.2:3b"));
+        assert_eq!(registry.lookup("hint:local").context_window, Some(131_072));
+        assert_eq!(registry.resolve("gpt-4o"), ("openrouter", "gpt-4o"));
+        assert_eq!(
+            registry.lookup("unknown-model"),
+            ModelCapabilities::default()
+        );
+    }
+}
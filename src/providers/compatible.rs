@@ -2,11 +2,14 @@
 //! Most LLM APIs follow the same `/v1/chat/completions` format.
 //! This module provides a single implementation that works for all of them.
 
+use crate::providers::streaming::{self, Framing, OpenAiUsage};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ToolCall as ProviderToolCall,
+    ChatStream, ChatUsage, Provider, StreamEvent, ToolCall as ProviderToolCall,
 };
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -143,6 +146,42 @@ struct ChatRequest {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ToolDefinition>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolDefinition {
+    #[serde(rename = "type")]
+    kind: String,
+    function: FunctionDefinition,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionDefinition {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+fn convert_tools(tools: Option<&[ToolSpec]>) -> Option<Vec<ToolDefinition>> {
+    let items = tools?;
+    if items.is_empty() {
+        return None;
+    }
+    Some(
+        items
+            .iter()
+            .map(|tool| ToolDefinition {
+                kind: "function".to_string(),
+                function: FunctionDefinition {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.parameters.clone(),
+                },
+            })
+            .collect(),
+    )
 }
 
 // --- ZeroClaw fork: support multimodal content for vision ---
@@ -150,10 +189,74 @@ struct ChatRequest {
 struct Message {
     role: String,
     content: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
 }
 
 impl Message {
+    fn text(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: serde_json::Value::String(content.to_string()),
+            tool_call_id: None,
+            tool_calls: None,
+        }
+    }
+
+    /// Decode the JSON history entries the agent loop writes for native tool
+    /// calls: assistant `{content, tool_calls}` and tool `{tool_call_id, content}`.
+    fn from_native_tool_message(m: &ChatMessage) -> Option<Self> {
+        let value = serde_json::from_str::<serde_json::Value>(&m.content).ok()?;
+        let content = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .map_or(serde_json::Value::Null, |text| {
+                serde_json::Value::String(text.to_string())
+            });
+        match m.role.as_str() {
+            "assistant" => {
+                let calls = serde_json::from_value::<Vec<ProviderToolCall>>(
+                    value.get("tool_calls")?.clone(),
+                )
+                .ok()?;
+                Some(Self {
+                    role: "assistant".to_string(),
+                    content,
+                    tool_call_id: None,
+                    tool_calls: Some(
+                        calls
+                            .into_iter()
+                            .map(|tc| ToolCall {
+                                id: Some(tc.id),
+                                kind: Some("function".to_string()),
+                                function: Some(Function {
+                                    name: Some(tc.name),
+                                    arguments: Some(tc.arguments),
+                                }),
+                            })
+                            .collect(),
+                    ),
+                })
+            }
+            "tool" => Some(Self {
+                role: "tool".to_string(),
+                content,
+                tool_call_id: value
+                    .get("tool_call_id")
+                    .and_then(serde_json::Value::as_str)
+                    .map(ToString::to_string),
+                tool_calls: None,
+            }),
+            _ => None,
+        }
+    }
+
     fn from_chat_message(m: &crate::providers::traits::ChatMessage) -> Self {
+        if let Some(message) = Self::from_native_tool_message(m) {
+            return message;
+        }
         let content = if let Some(ref parts) = m.parts {
             let content_parts: Vec<serde_json::Value> = parts
                 .iter()
@@ -178,6 +281,8 @@ impl Message {
         Self {
             role: m.role.clone(),
             content,
+            tool_call_id: None,
+            tool_calls: None,
         }
     }
 }
@@ -186,6 +291,8 @@ impl Message {
 #[derive(Debug, Deserialize)]
 struct ApiChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -201,8 +308,26 @@ struct ResponseMessage {
     tool_calls: Option<Vec<ToolCall>>,
 }
 
+impl ResponseMessage {
+    fn has_tool_calls(&self) -> bool {
+        self.tool_calls.as_ref().is_some_and(|t| !t.is_empty())
+    }
+
+    /// Text form for `chat_with_history`: tool calls are serialized as the
+    /// full message JSON so `parse_tool_calls` can handle the OpenAI format.
+    fn into_history_text(self) -> String {
+        if self.has_tool_calls() {
+            serde_json::to_string(&self).unwrap_or_else(|_| self.content.unwrap_or_default())
+        } else {
+            self.content.unwrap_or_default()
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct ToolCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     #[serde(rename = "type")]
     kind: Option<String>,
     function: Option<Function>,
@@ -335,6 +460,89 @@ impl OpenAiCompatibleProvider {
         extract_responses_text(responses)
             .ok_or_else(|| anyhow::anyhow!("No response from {} Responses API", self.name))
     }
+
+    /// Full-history chat completion, optionally offering native tools.
+    /// Returns the assistant message and token usage.
+    async fn chat_completion(
+        &self,
+        messages: &[ChatMessage],
+        tools: Option<&[ToolSpec]>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<(ResponseMessage, Option<ChatUsage>)> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
+                self.name
+            )
+        })?;
+
+        // --- ZeroClaw fork: use from_chat_message for vision support ---
+        let api_messages: Vec<Message> = messages
+            .iter()
+            .map(Message::from_chat_message)
+            .collect();
+
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages: api_messages,
+            temperature,
+            stream: Some(false),
+            tools: convert_tools(tools),
+        };
+
+        let url = self.chat_completions_url();
+        let response = self
+            .apply_auth_header(self.client.post(&url).json(&request), api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+
+            // Mirror chat_with_system: 404 may mean this provider uses the Responses API
+            if status == reqwest::StatusCode::NOT_FOUND && self.supports_responses_fallback {
+                // Extract system prompt and last user message for responses fallback
+                let system = messages.iter().find(|m| m.role == "system");
+                let last_user = messages.iter().rfind(|m| m.role == "user");
+                if let Some(user_msg) = last_user {
+                    return self
+                        .chat_via_responses(
+                            api_key,
+                            system.map(|m| m.content.as_str()),
+                            &user_msg.content,
+                            model,
+                        )
+                        .await
+                        .map(|text| {
+                            let message = ResponseMessage {
+                                content: Some(text),
+                                tool_calls: None,
+                            };
+                            (message, None)
+                        })
+                        .map_err(|responses_err| {
+                            anyhow::anyhow!(
+                                "{} API error (chat completions unavailable; responses fallback failed: {responses_err})",
+                                self.name
+                            )
+                        });
+                }
+            }
+
+            return Err(super::api_error(&self.name, response).await);
+        }
+
+        let chat_response: ApiChatResponse = response.json().await?;
+        let usage = chat_response.usage.map(Into::into);
+
+        chat_response
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| (c.message, usage))
+            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
+    }
 }
 
 #[async_trait]
@@ -356,22 +564,17 @@ impl Provider for OpenAiCompatibleProvider {
         let mut messages = Vec::new();
 
         if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: serde_json::Value::String(sys.to_string()),
-            });
+            messages.push(Message::text("system", sys));
         }
 
-        messages.push(Message {
-            role: "user".to_string(),
-            content: serde_json::Value::String(message.to_string()),
-        });
+        messages.push(Message::text("user", message));
 
         let request = ChatRequest {
             model: model.to_string(),
             messages,
             temperature,
             stream: Some(false),
+            tools: None,
         };
 
         let url = self.chat_completions_url();
@@ -407,22 +610,7 @@ impl Provider for OpenAiCompatibleProvider {
             .choices
             .into_iter()
             .next()
-            .map(|c| {
-                // If tool_calls are present, serialize the full message as JSON
-                // so parse_tool_calls can handle the OpenAI-style format
-                if c.message.tool_calls.is_some()
-                    && c.message
-                        .tool_calls
-                        .as_ref()
-                        .map_or(false, |t| !t.is_empty())
-                {
-                    serde_json::to_string(&c.message)
-                        .unwrap_or_else(|_| c.message.content.unwrap_or_default())
-                } else {
-                    // No tool calls, return content as-is
-                    c.message.content.unwrap_or_default()
-                }
-            })
+            .map(|c| c.message.into_history_text())
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
     }
 
@@ -432,6 +620,63 @@ impl Provider for OpenAiCompatibleProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
+        self.chat_completion(messages, None, model, temperature)
+            .await
+            .map(|(message, _)| message.into_history_text())
+    }
+
+    async fn chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let (message, usage) = self
+            .chat_completion(request.messages, request.tools, model, temperature)
+            .await?;
+
+        let tool_calls = message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|tc| {
+                let function = tc.function?;
+                let name = function.name?;
+                let arguments = function.arguments.unwrap_or_else(|| "{}".to_string());
+                Some(ProviderToolCall {
+                    id: tc.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                    name,
+                    arguments,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(ProviderChatResponse {
+            text: message.content,
+            tool_calls,
+            usage,
+            reasoning: None,
+        })
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
         let api_key = self.api_key.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
@@ -439,35 +684,32 @@ impl Provider for OpenAiCompatibleProvider {
             )
         })?;
 
-        // --- ZeroClaw fork: use from_chat_message for vision support ---
-        let api_messages: Vec<Message> = messages
-            .iter()
-            .map(Message::from_chat_message)
-            .collect();
-
-        let request = ChatRequest {
+        let api_request = ChatRequest {
             model: model.to_string(),
-            messages: api_messages,
+            messages: request
+                .messages
+                .iter()
+                .map(Message::from_chat_message)
+                .collect(),
             temperature,
-            stream: Some(false),
+            stream: Some(true),
+            tools: convert_tools(request.tools),
         };
 
         let url = self.chat_completions_url();
         let response = self
-            .apply_auth_header(self.client.post(&url).json(&request), api_key)
+            .apply_auth_header(self.client.post(&url).json(&api_request), api_key)
             .send()
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
 
-            // Mirror chat_with_system: 404 may mean this provider uses the Responses API
+            // Responses API has no delta format we parse; answer in one chunk.
             if status == reqwest::StatusCode::NOT_FOUND && self.supports_responses_fallback {
-                // Extract system prompt and last user message for responses fallback
-                let system = messages.iter().find(|m| m.role == "system");
-                let last_user = messages.iter().rfind(|m| m.role == "user");
-                if let Some(user_msg) = last_user {
-                    return self
+                let system = request.messages.iter().find(|m| m.role == "system");
+                if let Some(user_msg) = request.messages.iter().rfind(|m| m.role == "user") {
+                    let text = self
                         .chat_via_responses(
                             api_key,
                             system.map(|m| m.content.as_str()),
@@ -480,80 +722,21 @@ impl Provider for OpenAiCompatibleProvider {
                                 "{} API error (chat completions unavailable; responses fallback failed: {responses_err})",
                                 self.name
                             )
-                        });
+                        })?;
+                    return Ok(
+                        stream::once(async move { Ok(StreamEvent::TextDelta(text)) }).boxed(),
+                    );
                 }
             }
 
             return Err(super::api_error(&self.name, response).await);
         }
 
-        let chat_response: ApiChatResponse = response.json().await?;
-
-        chat_response
-            .choices
-            .into_iter()
-            .next()
-            .map(|c| {
-                // If tool_calls are present, serialize the full message as JSON
-                // so parse_tool_calls can handle the OpenAI-style format
-                if c.message.tool_calls.is_some()
-                    && c.message
-                        .tool_calls
-                        .as_ref()
-                        .map_or(false, |t| !t.is_empty())
-                {
-                    serde_json::to_string(&c.message)
-                        .unwrap_or_else(|_| c.message.content.unwrap_or_default())
-                } else {
-                    // No tool calls, return content as-is
-                    c.message.content.unwrap_or_default()
-                }
-            })
-            .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
-    }
-
-    async fn chat(
-        &self,
-        request: ProviderChatRequest<'_>,
-        model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<ProviderChatResponse> {
-        let text = self
-            .chat_with_history(request.messages, model, temperature)
-            .await?;
-
-        // Backward compatible path: chat_with_history may serialize tool_calls JSON into content.
-        if let Ok(message) = serde_json::from_str::<ResponseMessage>(&text) {
-            let tool_calls = message
-                .tool_calls
-                .unwrap_or_default()
-                .into_iter()
-                .filter_map(|tc| {
-                    let function = tc.function?;
-                    let name = function.name?;
-                    let arguments = function.arguments.unwrap_or_else(|| "{}".to_string());
-                    Some(ProviderToolCall {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        name,
-                        arguments,
-                    })
-                })
-                .collect::<Vec<_>>();
-
-            return Ok(ProviderChatResponse {
-                text: message.content,
-                tool_calls,
-            });
-        }
-
-        Ok(ProviderChatResponse {
-            text: Some(text),
-            tool_calls: vec![],
-        })
-    }
-
-    fn supports_native_tools(&self) -> bool {
-        true
+        Ok(streaming::decode_response(
+            response,
+            Framing::Sse,
+            streaming::parse_openai_chunk,
+        ))
     }
 }
 
@@ -603,17 +786,12 @@ mod tests {
         let req = ChatRequest {
             model: "llama-3.3-70b".to_string(),
             messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: serde_json::Value::String("You are ZeroClaw".to_string()),
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: serde_json::Value::String("hello".to_string()),
-                },
+                Message::text("system", "You are ZeroClaw"),
+                Message::text("user", "hello"),
             ],
             temperature: 0.4,
             stream: Some(false),
+            tools: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("llama-3.3-70b"));
@@ -621,6 +799,70 @@ mod tests {
         assert!(json.contains("user"));
     }
 
+    #[test]
+    fn request_serializes_native_tools() {
+        let specs = vec![ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let req = ChatRequest {
+            model: "m".to_string(),
+            messages: vec![Message::text("user", "hi")],
+            temperature: 0.0,
+            stream: None,
+            tools: convert_tools(Some(&specs)),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["tools"][0]["type"], "function");
+        assert_eq!(json["tools"][0]["function"]["name"], "shell");
+        assert!(json["messages"][0].get("tool_calls").is_none());
+    }
+
+    #[test]
+    fn native_tool_history_converts_to_api_messages() {
+        let assistant = ChatMessage::assistant(
+            serde_json::json!({
+                "content": null,
+                "tool_calls": [{"id": "call_1", "name": "shell", "arguments": "{}"}],
+            })
+            .to_string(),
+        );
+        let tool = ChatMessage::tool(
+            serde_json::json!({"tool_call_id": "call_1", "content": "ok"}).to_string(),
+        );
+
+        let assistant = serde_json::to_value(Message::from_chat_message(&assistant)).unwrap();
+        assert!(assistant["content"].is_null());
+        assert_eq!(assistant["tool_calls"][0]["id"], "call_1");
+        assert_eq!(assistant["tool_calls"][0]["function"]["name"], "shell");
+
+        let tool = serde_json::to_value(Message::from_chat_message(&tool)).unwrap();
+        assert_eq!(tool["role"], "tool");
+        assert_eq!(tool["tool_call_id"], "call_1");
+        assert_eq!(tool["content"], "ok");
+    }
+
+    #[test]
+    fn plain_json_user_message_is_sent_as_text() {
+        let msg = ChatMessage::user(r#"{"content": "x", "tool_calls": []}"#);
+        let api = Message::from_chat_message(&msg);
+        assert_eq!(api.role, "user");
+        assert!(api.tool_calls.is_none());
+    }
+
+    #[test]
+    fn response_tool_call_id_is_preserved() {
+        let json = r#"{"choices":[{"message":{"content":null,"tool_calls":[{"id":"call_9","type":"function","function":{"name":"shell","arguments":"{}"}}]}}]}"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        let message = &resp.choices[0].message;
+        assert!(message.has_tool_calls());
+        assert_eq!(
+            message.tool_calls.as_ref().unwrap()[0].id.as_deref(),
+            Some("call_9")
+        );
+    }
+
     #[test]
     fn response_deserializes() {
         let json = r#"{"choices":[{"message":{"content":"Hello from Venice!"}}]}"#;
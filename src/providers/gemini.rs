@@ -3,11 +3,16 @@
 //! - Gemini CLI OAuth tokens (reuse existing ~/.gemini/ authentication)
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
+    ChatUsage, ContentPartType, Provider, ResponseSchema, ToolCall as ProviderToolCall,
+};
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use directories::UserDirs;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Gemini provider supporting multiple authentication methods.
@@ -61,6 +66,21 @@ struct GenerateContentRequest {
     system_instruction: Option<Content>,
     #[serde(rename = "generationConfig")]
     generation_config: GenerationConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<GeminiTool>>,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiTool {
+    #[serde(rename = "functionDeclarations")]
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
 }
 
 #[derive(Debug, Serialize)]
@@ -79,6 +99,14 @@ enum Part {
         #[serde(rename = "inlineData")]
         inline_data: InlineData,
     },
+    FunctionCall {
+        #[serde(rename = "functionCall")]
+        function_call: FunctionCall,
+    },
+    FunctionResponse {
+        #[serde(rename = "functionResponse")]
+        function_response: FunctionResponse,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -89,17 +117,59 @@ struct InlineData {
 }
 // --- end ZeroClaw fork ---
 
+/// A function call requested by the model. Gemini does not assign call ids,
+/// so calls are matched to their responses by name and position.
+#[derive(Debug, Serialize, Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionResponse {
+    name: String,
+    response: serde_json::Value,
+}
+
 #[derive(Debug, Serialize)]
 struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GenerateContentResponse {
     candidates: Option<Vec<Candidate>>,
     error: Option<ApiError>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageMetadata {
+    #[serde(rename = "promptTokenCount", default)]
+    prompt_token_count: u64,
+    #[serde(rename = "candidatesTokenCount", default)]
+    candidates_token_count: u64,
+    #[serde(rename = "cachedContentTokenCount", default)]
+    cached_content_token_count: u64,
+}
+
+impl From<UsageMetadata> for ChatUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        Self {
+            input_tokens: usage.prompt_token_count,
+            output_tokens: usage.candidates_token_count,
+            cached_input_tokens: usage.cached_content_token_count,
+            cache_write_input_tokens: 0,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -109,12 +179,15 @@ struct Candidate {
 
 #[derive(Debug, Deserialize)]
 struct CandidateContent {
+    #[serde(default)]
     parts: Vec<ResponsePart>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ResponsePart {
     text: Option<String>,
+    #[serde(rename = "functionCall")]
+    function_call: Option<FunctionCall>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -264,6 +337,260 @@ impl GeminiProvider {
             _ => req,
         }
     }
+
+    async fn generate_content(
+        &self,
+        auth: &GeminiAuth,
+        model: &str,
+        request: &GenerateContentRequest,
+    ) -> anyhow::Result<GenerateContentResponse> {
+        let url = Self::build_generate_content_url(model, auth);
+        let response = self
+            .build_generate_content_request(auth, &url, request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gemini API error ({status}): {error_text}");
+        }
+
+        let result: GenerateContentResponse = response.json().await?;
+
+        if let Some(err) = result.error {
+            anyhow::bail!("Gemini API error: {}", err.message);
+        }
+
+        Ok(result)
+    }
+
+    fn convert_tools(tools: Option<&[ToolSpec]>) -> Option<Vec<GeminiTool>> {
+        let items = tools?;
+        if items.is_empty() {
+            return None;
+        }
+        Some(vec![GeminiTool {
+            function_declarations: items
+                .iter()
+                .map(|tool| FunctionDeclaration {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: Self::sanitize_schema(tool.parameters.clone()),
+                })
+                .collect(),
+        }])
+    }
+
+    /// Gemini accepts an OpenAPI subset of JSON Schema and rejects requests
+    /// containing keywords outside it, so strip the ones our tools emit.
+    fn sanitize_schema(mut schema: serde_json::Value) -> serde_json::Value {
+        match &mut schema {
+            serde_json::Value::Object(map) => {
+                map.remove("$schema");
+                map.remove("additionalProperties");
+                for value in map.values_mut() {
+                    *value = Self::sanitize_schema(value.take());
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for value in items.iter_mut() {
+                    *value = Self::sanitize_schema(value.take());
+                }
+            }
+            _ => {}
+        }
+        schema
+    }
+
+    /// Convert a native assistant tool-call turn into `functionCall` parts,
+    /// remembering each call's name so its result can be matched later.
+    fn parse_assistant_tool_call_message(
+        content: &str,
+        call_names: &mut HashMap<String, String>,
+    ) -> Option<Vec<Part>> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let tool_calls = value
+            .get("tool_calls")
+            .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
+
+        let mut parts = Vec::new();
+        if let Some(text) = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            parts.push(Part::Text {
+                text: text.to_string(),
+            });
+        }
+        for call in tool_calls {
+            let args = serde_json::from_str::<serde_json::Value>(&call.arguments)
+                .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new()));
+            call_names.insert(call.id, call.name.clone());
+            parts.push(Part::FunctionCall {
+                function_call: FunctionCall {
+                    name: call.name,
+                    args,
+                },
+            });
+        }
+        Some(parts)
+    }
+
+    /// Convert a native tool result into a `functionResponse` part. Gemini
+    /// identifies responses by function name, which is recovered from the
+    /// call id recorded in the preceding assistant turn.
+    fn parse_tool_result_message(
+        content: &str,
+        call_names: &HashMap<String, String>,
+    ) -> Option<Part> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let call_id = value
+            .get("tool_call_id")
+            .and_then(serde_json::Value::as_str)?;
+        let name = call_names.get(call_id)?.clone();
+        let result = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        Some(Part::FunctionResponse {
+            function_response: FunctionResponse {
+                name,
+                response: serde_json::json!({ "content": result }),
+            },
+        })
+    }
+
+    fn convert_messages(messages: &[ChatMessage]) -> (Option<Content>, Vec<Content>) {
+        let mut system_instruction = None;
+        let mut contents: Vec<Content> = Vec::new();
+        let mut call_names = HashMap::new();
+
+        for msg in messages {
+            match msg.role.as_str() {
+                "system" => {
+                    if system_instruction.is_none() {
+                        system_instruction = Some(Content {
+                            role: None,
+                            parts: vec![Part::Text {
+                                text: msg.content.clone(),
+                            }],
+                        });
+                    }
+                }
+                "assistant" => {
+                    let parts =
+                        Self::parse_assistant_tool_call_message(&msg.content, &mut call_names)
+                            .unwrap_or_else(|| {
+                                vec![Part::Text {
+                                    text: msg.content.clone(),
+                                }]
+                            });
+                    contents.push(Content {
+                        role: Some("model".to_string()),
+                        parts,
+                    });
+                }
+                "tool" => {
+                    let part = Self::parse_tool_result_message(&msg.content, &call_names)
+                        .unwrap_or_else(|| Part::Text {
+                            text: msg.content.clone(),
+                        });
+                    // Parallel calls must be answered in a single turn, so
+                    // consecutive tool results share one `user` content.
+                    match contents.last_mut() {
+                        Some(last)
+                            if last.role.as_deref() == Some("user")
+                                && last
+                                    .parts
+                                    .iter()
+                                    .all(|p| matches!(p, Part::FunctionResponse { .. })) =>
+                        {
+                            last.parts.push(part);
+                        }
+                        _ => contents.push(Content {
+                            role: Some("user".to_string()),
+                            parts: vec![part],
+                        }),
+                    }
+                }
+                _ => contents.push(Content {
+                    role: Some("user".to_string()),
+                    parts: Self::user_parts(msg),
+                }),
+            }
+        }
+
+        (system_instruction, contents)
+    }
+
+    fn user_parts(msg: &ChatMessage) -> Vec<Part> {
+        let Some(parts) = msg.parts.as_ref() else {
+            return vec![Part::Text {
+                text: msg.content.clone(),
+            }];
+        };
+        parts
+            .iter()
+            .map(|p| match p.content_type {
+                ContentPartType::Text => Part::Text {
+                    text: p.text.clone().unwrap_or_default(),
+                },
+                ContentPartType::Image => Part::InlineData {
+                    inline_data: InlineData {
+                        mime_type: p
+                            .mime_type
+                            .clone()
+                            .unwrap_or_else(|| "image/jpeg".to_string()),
+                        data: p.image_base64.clone().unwrap_or_default(),
+                    },
+                },
+            })
+            .collect()
+    }
+
+    fn parse_native_response(
+        response: GenerateContentResponse,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let usage = response.usage_metadata.map(Into::into);
+        let candidate = response
+            .candidates
+            .and_then(|c| c.into_iter().next())
+            .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))?;
+
+        let mut text_parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        for part in candidate.content.parts {
+            if let Some(text) = part.text.filter(|t| !t.trim().is_empty()) {
+                text_parts.push(text);
+            }
+            if let Some(call) = part.function_call {
+                let args = if call.args.is_null() {
+                    serde_json::Value::Object(serde_json::Map::new())
+                } else {
+                    call.args
+                };
+                tool_calls.push(ProviderToolCall {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: call.name,
+                    arguments: args.to_string(),
+                });
+            }
+        }
+
+        Ok(ProviderChatResponse {
+            text: if text_parts.is_empty() {
+                None
+            } else {
+                Some(text_parts.join(""))
+            },
+            tool_calls,
+            usage,
+            reasoning: None,
+        })
+    }
 }
 
 #[async_trait]
@@ -304,7 +631,10 @@ impl Provider for GeminiProvider {
             generation_config: GenerationConfig {
                 temperature,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
+            tools: None,
         };
 
         let url = Self::build_generate_content_url(model, auth);
@@ -339,91 +669,104 @@ impl Provider for GeminiProvider {
     // --- ZeroClaw fork: multimodal chat_with_history for vision ---
     async fn chat_with_history(
         &self,
-        messages: &[crate::providers::traits::ChatMessage],
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        use crate::providers::traits::ContentPartType;
-
-        let auth = self.auth.as_ref().ok_or_else(|| {
-            anyhow::anyhow!("Gemini API key not found.")
-        })?;
-
-        // Extract system prompt
-        let system_instruction = messages
-            .iter()
-            .find(|m| m.role == "system")
-            .map(|m| Content {
-                role: None,
-                parts: vec![Part::Text { text: m.content.clone() }],
-            });
-
-        // Convert non-system messages to Gemini contents
-        let contents: Vec<Content> = messages
-            .iter()
-            .filter(|m| m.role != "system")
-            .map(|m| {
-                let role = if m.role == "assistant" { "model" } else { &m.role };
-                let parts = if let Some(ref content_parts) = m.parts {
-                    content_parts
-                        .iter()
-                        .map(|p| match p.content_type {
-                            ContentPartType::Text => Part::Text {
-                                text: p.text.clone().unwrap_or_default(),
-                            },
-                            ContentPartType::Image => Part::InlineData {
-                                inline_data: InlineData {
-                                    mime_type: p.mime_type.clone().unwrap_or_else(|| "image/jpeg".into()),
-                                    data: p.image_base64.clone().unwrap_or_default(),
-                                },
-                            },
-                        })
-                        .collect()
-                } else {
-                    vec![Part::Text { text: m.content.clone() }]
-                };
-                Content {
-                    role: Some(role.to_string()),
-                    parts,
-                }
-            })
-            .collect();
+        let auth = self
+            .auth
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Gemini API key not found."))?;
 
+        let (system_instruction, contents) = Self::convert_messages(messages);
         let request = GenerateContentRequest {
             contents,
             system_instruction,
             generation_config: GenerationConfig {
                 temperature,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
+            tools: None,
         };
 
-        let url = Self::build_generate_content_url(model, auth);
-        let response = self
-            .build_generate_content_request(auth, &url, &request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Gemini API error ({status}): {error_text}");
-        }
-
-        let result: GenerateContentResponse = response.json().await?;
-
-        if let Some(err) = result.error {
-            anyhow::bail!("Gemini API error: {}", err.message);
-        }
+        let result = self.generate_content(auth, model, &request).await?;
 
         result
             .candidates
             .and_then(|c| c.into_iter().next())
-            .and_then(|c| c.content.parts.into_iter().next())
-            .and_then(|p| p.text)
+            .and_then(|c| c.content.parts.into_iter().find_map(|p| p.text))
             .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))
     }
     // --- end ZeroClaw fork ---
+
+    async fn chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let auth = self
+            .auth
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Gemini API key not found."))?;
+
+        let (system_instruction, contents) = Self::convert_messages(request.messages);
+        let native_request = GenerateContentRequest {
+            contents,
+            system_instruction,
+            generation_config: GenerationConfig {
+                temperature,
+                max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
+            },
+            tools: Self::convert_tools(request.tools),
+        };
+
+        let result = self.generate_content(auth, model, &native_request).await?;
+        Self::parse_native_response(result)
+    }
+
+    async fn generate_structured(
+        &self,
+        request: ProviderChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let auth = self
+            .auth
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Gemini API key not found."))?;
+
+        let (system_instruction, contents) = Self::convert_messages(request.messages);
+        let native_request = GenerateContentRequest {
+            contents,
+            system_instruction,
+            generation_config: GenerationConfig {
+                temperature,
+                max_output_tokens: 8192,
+                response_mime_type: Some("application/json".to_string()),
+                response_schema: Some(Self::sanitize_schema(schema.schema.clone())),
+            },
+            tools: None,
+        };
+
+        let result = self.generate_content(auth, model, &native_request).await?;
+        Self::parse_native_response(result)?
+            .text
+            .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -546,7 +889,10 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
+            tools: None,
         };
 
         let request = provider
@@ -582,7 +928,10 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
+            tools: None,
         };
 
         let request = provider
@@ -611,10 +960,14 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
+            tools: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
+        assert!(!json.contains("\"tools\""));
         assert!(json.contains("\"role\":\"user\""));
         assert!(json.contains("\"text\":\"Hello\""));
         assert!(json.contains("\"temperature\":0.7"));
@@ -660,4 +1013,152 @@ mod tests {
         assert!(response.error.is_some());
         assert_eq!(response.error.unwrap().message, "Invalid API key");
     }
+
+    #[test]
+    fn convert_tools_emits_function_declarations() {
+        let tools = vec![ToolSpec {
+            name: "shell".to_string(),
+            description: "Run a command".to_string(),
+            parameters: serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "command": {"type": "string"},
+                    "env": {"type": "object", "additionalProperties": {"type": "string"}}
+                },
+                "required": ["command"]
+            }),
+        }];
+
+        let json =
+            serde_json::to_value(GeminiProvider::convert_tools(Some(&tools)).unwrap()).unwrap();
+        let decl = &json[0]["functionDeclarations"][0];
+        assert_eq!(decl["name"], "shell");
+        assert_eq!(decl["parameters"]["required"][0], "command");
+        let params = decl["parameters"].to_string();
+        assert!(!params.contains("additionalProperties"));
+        assert!(!params.contains("$schema"));
+        assert!(GeminiProvider::convert_tools(Some(&[])).is_none());
+    }
+
+    #[test]
+    fn convert_messages_maps_native_tool_history() {
+        let messages = vec![
+            ChatMessage::system("You are helpful"),
+            ChatMessage::user("Weather in Seoul and Tokyo?"),
+            ChatMessage::assistant(
+                serde_json::json!({
+                    "content": null,
+                    "tool_calls": [
+                        {"id": "call_1", "name": "weather", "arguments": "{\"city\":\"Seoul\"}"},
+                        {"id": "call_2", "name": "weather", "arguments": "{\"city\":\"Tokyo\"}"}
+                    ]
+                })
+                .to_string(),
+            ),
+            ChatMessage::tool(
+                serde_json::json!({"tool_call_id": "call_1", "content": "12C"}).to_string(),
+            ),
+            ChatMessage::tool(
+                serde_json::json!({"tool_call_id": "call_2", "content": "18C"}).to_string(),
+            ),
+        ];
+
+        let (system, contents) = GeminiProvider::convert_messages(&messages);
+        assert!(system.is_some());
+        let json = serde_json::to_value(&contents).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+
+        assert_eq!(json[1]["role"], "model");
+        assert_eq!(json[1]["parts"][0]["functionCall"]["name"], "weather");
+        assert_eq!(json[1]["parts"][1]["functionCall"]["args"]["city"], "Tokyo");
+
+        assert_eq!(json[2]["role"], "user");
+        let responses = json[2]["parts"].as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["functionResponse"]["name"], "weather");
+        assert_eq!(
+            responses[0]["functionResponse"]["response"]["content"],
+            "12C"
+        );
+        assert_eq!(
+            responses[1]["functionResponse"]["response"]["content"],
+            "18C"
+        );
+    }
+
+    #[test]
+    fn convert_messages_sends_images_as_inline_data() {
+        let messages = vec![ChatMessage::with_image(
+            "What is this?",
+            "aGVsbG8=",
+            "image/png",
+        )];
+
+        let (_, contents) = GeminiProvider::convert_messages(&messages);
+        let json = serde_json::to_value(&contents).unwrap();
+        assert_eq!(json[0]["role"], "user");
+        assert_eq!(json[0]["parts"][0]["text"], "What is this?");
+        assert_eq!(json[0]["parts"][1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(json[0]["parts"][1]["inlineData"]["data"], "aGVsbG8=");
+    }
+
+    #[test]
+    fn parse_native_response_extracts_parallel_calls_and_usage() {
+        let json = r#"{
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Checking both."},
+                        {"functionCall": {"name": "weather", "args": {"city": "Seoul"}}},
+                        {"functionCall": {"name": "weather", "args": {"city": "Tokyo"}}}
+                    ]
+                }
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 120,
+                "candidatesTokenCount": 30,
+                "cachedContentTokenCount": 100,
+                "totalTokenCount": 150
+            }
+        }"#;
+
+        let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
+        let parsed = GeminiProvider::parse_native_response(response).unwrap();
+        assert_eq!(parsed.text.as_deref(), Some("Checking both."));
+        assert_eq!(parsed.tool_calls.len(), 2);
+        assert_eq!(parsed.tool_calls[0].name, "weather");
+        assert_eq!(parsed.tool_calls[1].arguments, r#"{"city":"Tokyo"}"#);
+        assert_ne!(parsed.tool_calls[0].id, parsed.tool_calls[1].id);
+        assert_eq!(
+            parsed.usage,
+            Some(ChatUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                cached_input_tokens: 100,
+                cache_write_input_tokens: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn generation_config_serializes_response_schema() {
+        let config = GenerationConfig {
+            temperature: 0.0,
+            max_output_tokens: 8192,
+            response_mime_type: Some("application/json".to_string()),
+            response_schema: Some(GeminiProvider::sanitize_schema(serde_json::json!({
+                "type": "object",
+                "additionalProperties": false
+            }))),
+        };
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["responseMimeType"], "application/json");
+        assert_eq!(
+            json["responseSchema"],
+            serde_json::json!({"type": "object"})
+        );
+    }
 }
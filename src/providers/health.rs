@@ -0,0 +1,381 @@
+//! Rolling provider health for [`super::reliable::ReliableProvider`].
+//!
+//! Every provider/model pair keeps its recent latencies and a decaying error
+//! rate. The reliable provider orders its fallback chain by the resulting
+//! score, skips pairs whose circuit breaker is open, and derives hedging
+//! delays from the latency percentiles. State is saved to a JSON file so a
+//! restart does not forget which providers are slow or failing; saves are
+//! batched and written off the async runtime, so a burst of calls costs one
+//! write.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Latency samples kept per provider/model.
+const MAX_SAMPLES: usize = 50;
+/// Samples needed before percentiles are trusted.
+const MIN_SAMPLES: usize = 5;
+/// Weight of the newest outcome in the error rate.
+const ERROR_RATE_WEIGHT: f64 = 0.2;
+/// Latency-equivalent cost of a fully failing pair when scoring.
+const FAILURE_PENALTY_MS: f64 = 30_000.0;
+/// How long outcomes accumulate before the health file is rewritten.
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PairHealth {
+    #[serde(default)]
+    latencies_ms: VecDeque<u64>,
+    #[serde(default)]
+    error_rate: f64,
+    #[serde(default)]
+    consecutive_failures: u32,
+    /// Unix time (seconds) until which the circuit stays open.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    open_until: Option<u64>,
+}
+
+impl PairHealth {
+    fn push_latency(&mut self, latency: Duration) {
+        if self.latencies_ms.len() == MAX_SAMPLES {
+            self.latencies_ms.pop_front();
+        }
+        self.latencies_ms.push_back(millis(latency));
+    }
+
+    fn percentile_ms(&self, pct: f64) -> Option<u64> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.latencies_ms.iter().copied().collect();
+        sorted.sort_unstable();
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let idx = ((pct.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+        sorted.get(idx).copied()
+    }
+
+    /// Expected cost in milliseconds: typical latency plus a penalty scaled
+    /// by the error rate. Lower is better.
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self) -> Option<f64> {
+        if self.latencies_ms.is_empty() && self.error_rate == 0.0 {
+            return None;
+        }
+        let p50 = self.percentile_ms(50.0).unwrap_or(0) as f64;
+        Some(p50 + self.error_rate * FAILURE_PENALTY_MS)
+    }
+}
+
+/// Latency and error tracking shared by every reliable provider in the
+/// process that persists to the same file.
+pub struct ProviderHealth {
+    pairs: Arc<Mutex<HashMap<String, PairHealth>>>,
+    path: Option<PathBuf>,
+    /// Set while a save is scheduled; later updates ride along with it.
+    save_pending: Arc<AtomicBool>,
+    save_delay: Duration,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl ProviderHealth {
+    /// In-memory tracker. A `failure_threshold` of 0 disables the circuit
+    /// breaker.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            pairs: Arc::new(Mutex::new(HashMap::new())),
+            path: None,
+            save_pending: Arc::new(AtomicBool::new(false)),
+            save_delay: SAVE_DELAY,
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    /// Tracker persisted at `path`, starting from its saved state when the
+    /// file exists and parses.
+    pub fn load(path: &Path, failure_threshold: u32, cooldown: Duration) -> Self {
+        let pairs = match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!(path = %path.display(), "Ignoring unreadable provider health: {e}");
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            pairs: Arc::new(Mutex::new(pairs)),
+            path: Some(path.to_path_buf()),
+            ..Self::new(failure_threshold, cooldown)
+        }
+    }
+
+    /// One tracker per health file for the whole process, so routed
+    /// providers do not overwrite each other's entries. Without a path each
+    /// caller gets its own in-memory tracker.
+    pub fn shared(path: Option<&Path>, failure_threshold: u32, cooldown: Duration) -> Arc<Self> {
+        static SHARED: OnceLock<Mutex<HashMap<PathBuf, Arc<ProviderHealth>>>> = OnceLock::new();
+
+        let Some(path) = path else {
+            return Arc::new(Self::new(failure_threshold, cooldown));
+        };
+        SHARED
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Arc::new(Self::load(path, failure_threshold, cooldown)))
+            .clone()
+    }
+
+    fn key(provider: &str, model: &str) -> String {
+        format!("{provider}/{model}")
+    }
+
+    fn update(&self, provider: &str, model: &str, apply: impl FnOnce(&mut PairHealth)) {
+        apply(
+            self.pairs
+                .lock()
+                .entry(Self::key(provider, model))
+                .or_default(),
+        );
+        self.schedule_save();
+    }
+
+    /// Persist the state `save_delay` from now on a blocking thread, unless
+    /// a save is already scheduled. Outside a Tokio runtime it saves now.
+    fn schedule_save(&self) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        if self.save_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let pairs = Arc::clone(&self.pairs);
+        let pending = Arc::clone(&self.save_pending);
+        let save = move || {
+            pending.store(false, Ordering::Release);
+            let json = serde_json::to_string(&*pairs.lock());
+            let result = json
+                .map_err(anyhow::Error::from)
+                .and_then(|json| write_atomically(&path, &json));
+            if let Err(e) = result {
+                tracing::debug!(path = %path.display(), "Failed to save provider health: {e}");
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let delay = self.save_delay;
+                handle.spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = tokio::task::spawn_blocking(save).await;
+                });
+            }
+            Err(_) => save(),
+        }
+    }
+
+    pub fn record_success(&self, provider: &str, model: &str, latency: Duration) {
+        self.update(provider, model, |pair| {
+            pair.push_latency(latency);
+            pair.error_rate *= 1.0 - ERROR_RATE_WEIGHT;
+            pair.consecutive_failures = 0;
+            pair.open_until = None;
+        });
+    }
+
+    pub fn record_failure(&self, provider: &str, model: &str) {
+        self.update(provider, model, |pair| {
+            pair.error_rate = pair.error_rate * (1.0 - ERROR_RATE_WEIGHT) + ERROR_RATE_WEIGHT;
+            pair.consecutive_failures = pair.consecutive_failures.saturating_add(1);
+            if self.failure_threshold > 0 && pair.consecutive_failures >= self.failure_threshold {
+                pair.open_until = Some(unix_now() + self.cooldown.as_secs().max(1));
+                tracing::warn!(
+                    provider,
+                    model,
+                    failures = pair.consecutive_failures,
+                    cooldown_secs = self.cooldown.as_secs(),
+                    "Circuit breaker opened"
+                );
+            }
+        });
+    }
+
+    /// Record how long a call had been running when it was abandoned (e.g.
+    /// it lost a hedge). The real latency was at least this long.
+    pub fn record_latency(&self, provider: &str, model: &str, latency: Duration) {
+        self.update(provider, model, |pair| pair.push_latency(latency));
+    }
+
+    /// Whether the pair's circuit breaker is open. Once the cooldown passes
+    /// the next call is let through as a probe; another failure reopens it.
+    pub fn is_open(&self, provider: &str, model: &str) -> bool {
+        self.pairs
+            .lock()
+            .get(&Self::key(provider, model))
+            .and_then(|pair| pair.open_until)
+            .is_some_and(|until| until > unix_now())
+    }
+
+    /// Health score (lower is better), or `None` with no history yet.
+    pub fn score(&self, provider: &str, model: &str) -> Option<f64> {
+        self.pairs
+            .lock()
+            .get(&Self::key(provider, model))
+            .and_then(PairHealth::score)
+    }
+
+    /// The `pct` latency percentile of recent successes, once enough
+    /// samples exist.
+    pub fn latency_percentile(&self, provider: &str, model: &str, pct: f64) -> Option<Duration> {
+        self.pairs
+            .lock()
+            .get(&Self::key(provider, model))
+            .filter(|pair| pair.latencies_ms.len() >= MIN_SAMPLES)
+            .and_then(|pair| pair.percentile_ms(pct))
+            .map(Duration::from_millis)
+    }
+
+    /// Stable-sort `(provider, model)` items best first. Pairs without
+    /// history count as healthy as the best known one, so the configured
+    /// order holds until there is evidence against it.
+    pub fn order<T>(&self, items: &mut [T], pair: impl Fn(&T) -> (&str, &str)) {
+        let score = |item: &T| {
+            let (provider, model) = pair(item);
+            self.score(provider, model)
+        };
+        let best = items
+            .iter()
+            .filter_map(&score)
+            .fold(f64::INFINITY, f64::min);
+        // `sort_by` is stable, so equal scores keep the configured order.
+        items.sort_by(|a, b| {
+            let a = score(a).unwrap_or(best);
+            let b = score(b).unwrap_or(best);
+            a.total_cmp(&b)
+        });
+    }
+}
+
+/// Write through a temporary file and rename it into place, so a crash
+/// mid-write never leaves a truncated health file.
+fn write_atomically(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circuit_opens_after_threshold_and_closes_on_success() {
+        let health = ProviderHealth::new(2, Duration::from_secs(60));
+        health.record_failure("a", "m");
+        assert!(!health.is_open("a", "m"));
+        health.record_failure("a", "m");
+        assert!(health.is_open("a", "m"));
+        assert!(!health.is_open("a", "other-model"));
+
+        health.record_success("a", "m", Duration::from_millis(10));
+        assert!(!health.is_open("a", "m"));
+
+        let disabled = ProviderHealth::new(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            disabled.record_failure("a", "m");
+        }
+        assert!(!disabled.is_open("a", "m"));
+    }
+
+    #[test]
+    fn percentile_needs_enough_samples() {
+        let health = ProviderHealth::new(0, Duration::from_secs(1));
+        for ms in [100, 200, 300, 400] {
+            health.record_success("a", "m", Duration::from_millis(ms));
+        }
+        assert_eq!(health.latency_percentile("a", "m", 95.0), None);
+
+        health.record_success("a", "m", Duration::from_millis(1000));
+        assert_eq!(
+            health.latency_percentile("a", "m", 95.0),
+            Some(Duration::from_millis(1000))
+        );
+        assert_eq!(
+            health.latency_percentile("a", "m", 50.0),
+            Some(Duration::from_millis(300))
+        );
+    }
+
+    #[test]
+    fn order_demotes_failing_and_slow_pairs_only() {
+        let health = ProviderHealth::new(0, Duration::from_secs(1));
+        health.record_success("fast", "m", Duration::from_millis(100));
+        health.record_success("slow", "m", Duration::from_millis(5000));
+        health.record_failure("flaky", "m");
+
+        let mut items = vec!["flaky", "unknown", "slow", "fast"];
+        health.order(&mut items, |p| (*p, "m"));
+        assert_eq!(items, ["unknown", "fast", "slow", "flaky"]);
+
+        let mut fresh = vec!["b", "a", "c"];
+        ProviderHealth::new(0, Duration::from_secs(1)).order(&mut fresh, |p| (*p, "m"));
+        assert_eq!(fresh, ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn state_survives_reload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("state").join("provider_health.json");
+
+        let health = ProviderHealth::load(&path, 1, Duration::from_secs(300));
+        health.record_success("a", "m", Duration::from_millis(250));
+        health.record_failure("b", "m");
+
+        let reloaded = ProviderHealth::load(&path, 1, Duration::from_secs(300));
+        assert!(reloaded.is_open("b", "m"));
+        assert!(reloaded.score("a", "m").unwrap() < reloaded.score("b", "m").unwrap());
+    }
+
+    #[tokio::test]
+    async fn saves_are_batched_off_the_runtime() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("provider_health.json");
+        let mut health = ProviderHealth::load(&path, 0, Duration::from_secs(1));
+        health.save_delay = Duration::from_millis(50);
+
+        for ms in 1..=20 {
+            health.record_success("a", "m", Duration::from_millis(ms));
+        }
+        assert!(!path.exists());
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let reloaded = ProviderHealth::load(&path, 0, Duration::from_secs(1));
+        assert_eq!(
+            reloaded.latency_percentile("a", "m", 100.0),
+            Some(Duration::from_millis(20))
+        );
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
@@ -1,17 +1,25 @@
 pub mod anthropic;
+pub mod bedrock;
+pub mod capabilities;
 pub mod compatible;
 pub mod gemini;
+pub mod health;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod redaction;
 pub mod reliable;
+pub mod replay;
 pub mod router;
+pub mod streaming;
+pub mod structured;
+pub mod tokenizer;
 pub mod traits;
 
 #[allow(unused_imports)]
 pub use traits::{
-    ChatMessage, ChatRequest, ChatResponse, ConversationMessage, Provider, ToolCall,
-    ToolResultMessage,
+    ChatMessage, ChatRequest, ChatResponse, ChatStream, ChatUsage, ConversationMessage, Provider,
+    Reasoning, ReasoningOptions, ResponseSchema, StreamEvent, ToolCall, ToolResultMessage,
 };
 
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
@@ -198,6 +206,13 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
         "gemini" | "google" | "google-gemini" => {
             Ok(Box::new(gemini::GeminiProvider::new(key)))
         }
+        // Bedrock signs requests with AWS credentials (key, env or profile).
+        // Format for an explicit region: "bedrock:eu-central-1"
+        "bedrock" | "aws-bedrock" => Ok(Box::new(bedrock::BedrockProvider::new(key, None))),
+        name if name.starts_with("bedrock:") => Ok(Box::new(bedrock::BedrockProvider::new(
+            key,
+            name.strip_prefix("bedrock:"),
+        ))),
 
         // ── OpenAI-compatible providers ──────────────────────
         "venice" => Ok(Box::new(OpenAiCompatibleProvider::new(
@@ -233,12 +248,6 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
             key,
             AuthStyle::Bearer,
         ))),
-        "bedrock" | "aws-bedrock" => Ok(Box::new(OpenAiCompatibleProvider::new(
-            "Amazon Bedrock",
-            "https://bedrock-runtime.us-east-1.amazonaws.com",
-            key,
-            AuthStyle::Bearer,
-        ))),
         "qianfan" | "baidu" => Ok(Box::new(OpenAiCompatibleProvider::new(
             "Qianfan", "https://aip.baidubce.com", key, AuthStyle::Bearer,
         ))),
@@ -357,17 +366,79 @@ pub fn create_resilient_provider(
         }
     }
 
-    let reliable = ReliableProvider::new(
+    let health = health::ProviderHealth::shared(
+        reliability.health_path.as_deref(),
+        reliability.circuit_breaker_failures,
+        std::time::Duration::from_secs(reliability.circuit_breaker_cooldown_secs),
+    );
+    let mut reliable = ReliableProvider::new(
         providers,
         reliability.provider_retries,
         reliability.provider_backoff_ms,
     )
     .with_api_keys(reliability.api_keys.clone())
-    .with_model_fallbacks(reliability.model_fallbacks.clone());
+    .with_model_fallbacks(reliability.model_fallbacks.clone())
+    .with_health(health);
+    if reliability.hedge_requests {
+        reliable = reliable.with_hedging(
+            reliability.hedge_percentile,
+            std::time::Duration::from_millis(reliability.hedge_delay_ms),
+        );
+    }
 
     Ok(Box::new(reliable))
 }
 
+/// Wrap `provider` in a [`replay::ReplayProvider`] when record/replay is
+/// enabled (`[replay]` config or `ZEROCLAW_REPLAY_MODE`).
+pub fn with_replay(
+    provider: Box<dyn Provider>,
+    replay: &crate::config::ReplayConfig,
+    workspace_dir: &std::path::Path,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let cassette = replay.cassette_path(workspace_dir);
+    match replay.mode {
+        crate::config::ReplayMode::Off => Ok(provider),
+        crate::config::ReplayMode::Record => {
+            tracing::info!(cassette = %cassette.display(), "Recording provider traffic");
+            Ok(Box::new(replay::ReplayProvider::record(
+                provider, &cassette,
+            )?))
+        }
+        crate::config::ReplayMode::Replay => {
+            tracing::info!(cassette = %cassette.display(), "Replaying provider traffic");
+            Ok(Box::new(replay::ReplayProvider::replay(&cassette)?))
+        }
+    }
+}
+
+/// Wrap `provider` in a [`redaction::RedactingProvider`] unless
+/// `[redaction]` is disabled. Apply it outermost, after
+/// [`with_replay`], so cassettes never hold the secrets either.
+pub fn with_redaction(
+    provider: Box<dyn Provider>,
+    config: &crate::config::Config,
+    observer: std::sync::Arc<dyn crate::observability::Observer>,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let redaction = &config.redaction;
+    if !redaction.enabled {
+        return Ok(provider);
+    }
+
+    let redactor = redaction::Redactor::new(&redaction.patterns)?;
+    let mut redacting = redaction::RedactingProvider::new(provider, redactor, observer);
+    let zeroclaw_dir = config
+        .config_path
+        .parent()
+        .unwrap_or(&config.workspace_dir)
+        .to_path_buf();
+    match crate::security::AuditLogger::new(config.security.audit.clone(), zeroclaw_dir) {
+        Ok(audit) => redacting = redacting.with_audit(audit),
+        Err(e) => tracing::warn!("Redaction audit logging unavailable: {e}"),
+    }
+    Ok(Box::new(redacting))
+}
+
 /// Create a RouterProvider if model routes are configured, otherwise return a
 /// standard resilient provider. The router wraps individual providers per route,
 /// each with its own retry/fallback chain.
@@ -377,6 +448,7 @@ pub fn create_routed_provider(
     reliability: &crate::config::ReliabilityConfig,
     model_routes: &[crate::config::ModelRouteConfig],
     default_model: &str,
+    capabilities: std::sync::Arc<capabilities::CapabilityRegistry>,
 ) -> anyhow::Result<Box<dyn Provider>> {
     if model_routes.is_empty() {
         return create_resilient_provider(primary_name, api_key, reliability);
@@ -429,16 +501,19 @@ pub fn create_routed_provider(
                 router::Route {
                     provider_name: r.provider.clone(),
                     model: r.model.clone(),
+                    reasoning: ReasoningOptions {
+                        budget_tokens: r.thinking_budget,
+                        effort: r.reasoning_effort,
+                    },
                 },
             )
         })
         .collect();
 
-    Ok(Box::new(router::RouterProvider::new(
-        providers,
-        routes,
-        default_model.to_string(),
-    )))
+    Ok(Box::new(
+        router::RouterProvider::new(providers, routes, default_model.to_string())
+            .with_capabilities(capabilities),
+    ))
 }
 
 #[cfg(test)]
@@ -543,6 +618,7 @@ mod tests {
     fn factory_bedrock() {
         assert!(create_provider("bedrock", Some("key")).is_ok());
         assert!(create_provider("aws-bedrock", Some("key")).is_ok());
+        assert!(create_provider("bedrock:eu-central-1", None).is_ok());
     }
 
     #[test]
@@ -757,6 +833,7 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            ..crate::config::ReliabilityConfig::default()
         };
 
         let provider = create_resilient_provider("openrouter", Some("sk-test"), &reliability);
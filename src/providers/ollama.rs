@@ -1,7 +1,14 @@
-use crate::providers::traits::Provider;
+use crate::providers::streaming::{self, Framing};
+use crate::providers::traits::{
+    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
+    ChatStream, ChatUsage, ContentPartType, Provider, ResponseSchema, StreamEvent,
+    ToolCall as ProviderToolCall,
+};
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 pub struct OllamaProvider {
     base_url: String,
@@ -14,12 +21,37 @@ struct ChatRequest {
     messages: Vec<Message>,
     stream: bool,
     options: Options,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
+    /// JSON Schema the reply must follow (structured outputs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
 struct Message {
     role: String,
     content: String,
+    /// Base64-encoded images for vision models (no data-URI prefix).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    images: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+    /// Name of the tool whose result this `tool` message carries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+impl Message {
+    fn text(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+            images: None,
+            tool_calls: None,
+            tool_name: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -27,14 +59,131 @@ struct Options {
     temperature: f64,
 }
 
+#[derive(Debug, Serialize)]
+struct Tool {
+    #[serde(rename = "type")]
+    kind: String,
+    function: ToolFunction,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolFunction {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+/// Ollama tool calls carry no id and send `arguments` as a JSON object
+/// rather than an encoded string.
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunction {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+impl OllamaToolCall {
+    fn into_provider_call(self) -> ProviderToolCall {
+        let arguments = if self.function.arguments.is_null() {
+            "{}".to_string()
+        } else {
+            self.function.arguments.to_string()
+        };
+        ProviderToolCall {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.function.name,
+            arguments,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiChatResponse {
     message: ResponseMessage,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ResponseMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+/// One NDJSON line of a streaming `/api/chat` response.
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    message: Option<StreamChunkMessage>,
+    #[serde(default)]
+    error: Option<String>,
+    /// Prompt and completion token counts, present on the final `done` chunk.
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChunkMessage {
+    #[serde(default)]
     content: String,
+    #[serde(default)]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+fn usage_from_counts(prompt: Option<u64>, completion: Option<u64>) -> Option<ChatUsage> {
+    if prompt.is_none() && completion.is_none() {
+        return None;
+    }
+    Some(ChatUsage {
+        input_tokens: prompt.unwrap_or_default(),
+        output_tokens: completion.unwrap_or_default(),
+        cached_input_tokens: 0,
+        cache_write_input_tokens: 0,
+    })
+}
+
+/// Parse one streamed line. Ollama sends each tool call whole, so every call
+/// becomes a single delta at the next free index.
+fn parse_stream_chunk(
+    payload: &str,
+    next_tool_index: &mut usize,
+) -> anyhow::Result<Vec<StreamEvent>> {
+    let chunk: StreamChunk = serde_json::from_str(payload)
+        .map_err(|e| anyhow::anyhow!("Malformed Ollama stream chunk: {e}"))?;
+    if let Some(error) = chunk.error {
+        anyhow::bail!("Ollama stream error: {error}");
+    }
+    let mut events = Vec::new();
+    if let Some(message) = chunk.message {
+        if !message.content.is_empty() {
+            events.push(StreamEvent::TextDelta(message.content));
+        }
+        for call in message.tool_calls.unwrap_or_default() {
+            let call = call.into_provider_call();
+            events.push(StreamEvent::ToolCallDelta {
+                index: *next_tool_index,
+                id: Some(call.id),
+                name: Some(call.name),
+                arguments: call.arguments,
+            });
+            *next_tool_index += 1;
+        }
+    }
+    if let Some(usage) = usage_from_counts(chunk.prompt_eval_count, chunk.eval_count) {
+        events.push(StreamEvent::Usage(usage));
+    }
+    Ok(events)
 }
 
 impl OllamaProvider {
@@ -51,6 +200,147 @@ impl OllamaProvider {
                 .unwrap_or_else(|_| Client::new()),
         }
     }
+
+    fn convert_tools(tools: Option<&[ToolSpec]>) -> Option<Vec<Tool>> {
+        let items = tools?;
+        if items.is_empty() {
+            return None;
+        }
+        Some(
+            items
+                .iter()
+                .map(|tool| Tool {
+                    kind: "function".to_string(),
+                    function: ToolFunction {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    /// Map conversation history to `/api/chat` messages, translating the
+    /// native tool-call JSON written by the agent loop and attaching images.
+    fn convert_messages(messages: &[ChatMessage]) -> Vec<Message> {
+        let mut call_names: HashMap<String, String> = HashMap::new();
+        messages
+            .iter()
+            .map(|m| match m.role.as_str() {
+                "assistant" => Self::parse_assistant_tool_call_message(&m.content, &mut call_names)
+                    .unwrap_or_else(|| Message::text("assistant", &m.content)),
+                "tool" => Self::parse_tool_result_message(&m.content, &call_names)
+                    .unwrap_or_else(|| Message::text("tool", &m.content)),
+                _ => Self::message_with_images(m),
+            })
+            .collect()
+    }
+
+    fn parse_assistant_tool_call_message(
+        content: &str,
+        call_names: &mut HashMap<String, String>,
+    ) -> Option<Message> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let tool_calls = value
+            .get("tool_calls")
+            .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
+        let text = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+
+        let calls = tool_calls
+            .into_iter()
+            .map(|call| {
+                let arguments = serde_json::from_str::<serde_json::Value>(&call.arguments)
+                    .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new()));
+                call_names.insert(call.id, call.name.clone());
+                OllamaToolCall {
+                    function: OllamaFunction {
+                        name: call.name,
+                        arguments,
+                    },
+                }
+            })
+            .collect();
+
+        Some(Message {
+            tool_calls: Some(calls),
+            ..Message::text("assistant", text)
+        })
+    }
+
+    fn parse_tool_result_message(
+        content: &str,
+        call_names: &HashMap<String, String>,
+    ) -> Option<Message> {
+        let value = serde_json::from_str::<serde_json::Value>(content).ok()?;
+        let call_id = value
+            .get("tool_call_id")
+            .and_then(serde_json::Value::as_str)?;
+        let result = value
+            .get("content")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        Some(Message {
+            tool_name: call_names.get(call_id).cloned(),
+            ..Message::text("tool", result)
+        })
+    }
+
+    fn message_with_images(msg: &ChatMessage) -> Message {
+        let Some(parts) = msg.parts.as_ref() else {
+            return Message::text(&msg.role, &msg.content);
+        };
+        let text = parts
+            .iter()
+            .filter(|p| p.content_type == ContentPartType::Text)
+            .filter_map(|p| p.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let images: Vec<String> = parts
+            .iter()
+            .filter(|p| p.content_type == ContentPartType::Image)
+            .filter_map(|p| p.image_base64.clone())
+            .collect();
+        Message {
+            images: (!images.is_empty()).then_some(images),
+            ..Message::text(&msg.role, &text)
+        }
+    }
+
+    /// POST to `/api/chat`. Models without tool support reject any request
+    /// that carries `tools`; retry those once without them so the turn
+    /// still gets a plain answer.
+    async fn post_chat(&self, request: &mut ChatRequest) -> anyhow::Result<reqwest::Response> {
+        let url = format!("{}/api/chat", self.base_url);
+
+        let mut response = self.client.post(&url).json(&*request).send().await?;
+
+        if request.tools.is_some() && response.status() == reqwest::StatusCode::BAD_REQUEST {
+            let body = response.text().await.unwrap_or_default();
+            if !body.contains("does not support tools") {
+                anyhow::bail!(
+                    "Ollama API error (400 Bad Request): {}",
+                    super::sanitize_api_error(&body)
+                );
+            }
+            tracing::warn!(
+                model = %request.model,
+                "Ollama model does not support tools; retrying without them"
+            );
+            request.tools = None;
+            response = self.client.post(&url).json(&*request).send().await?;
+        }
+
+        if !response.status().is_success() {
+            let err = super::api_error("Ollama", response).await;
+            anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
+        }
+
+        Ok(response)
+    }
 }
 
 #[async_trait]
@@ -65,36 +355,138 @@ impl Provider for OllamaProvider {
         let mut messages = Vec::new();
 
         if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
+            messages.push(Message::text("system", sys));
         }
 
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
+        messages.push(Message::text("user", message));
 
-        let request = ChatRequest {
+        let mut request = ChatRequest {
             model: model.to_string(),
             messages,
             stream: false,
             options: Options { temperature },
+            tools: None,
+            format: None,
         };
 
-        let url = format!("{}/api/chat", self.base_url);
+        let response = self.post_chat(&mut request).await?;
 
-        let response = self.client.post(&url).json(&request).send().await?;
+        let chat_response: ApiChatResponse = response.json().await?;
+        Ok(chat_response.message.content)
+    }
 
-        if !response.status().is_success() {
-            let err = super::api_error("Ollama", response).await;
-            anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
-        }
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut request = ChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(messages),
+            stream: false,
+            options: Options { temperature },
+            tools: None,
+            format: None,
+        };
+
+        let response = self.post_chat(&mut request).await?;
 
         let chat_response: ApiChatResponse = response.json().await?;
         Ok(chat_response.message.content)
     }
+
+    async fn chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let mut native_request = ChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            stream: false,
+            options: Options { temperature },
+            tools: Self::convert_tools(request.tools),
+            format: None,
+        };
+
+        let response = self.post_chat(&mut native_request).await?;
+
+        let chat_response: ApiChatResponse = response.json().await?;
+        let usage = usage_from_counts(chat_response.prompt_eval_count, chat_response.eval_count);
+        let message = chat_response.message;
+        Ok(ProviderChatResponse {
+            text: (!message.content.is_empty()).then_some(message.content),
+            tool_calls: message
+                .tool_calls
+                .unwrap_or_default()
+                .into_iter()
+                .map(OllamaToolCall::into_provider_call)
+                .collect(),
+            usage,
+            reasoning: None,
+        })
+    }
+
+    async fn generate_structured(
+        &self,
+        request: ProviderChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut native_request = ChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            stream: false,
+            options: Options { temperature },
+            tools: None,
+            format: Some(schema.schema.clone()),
+        };
+
+        let response = self.post_chat(&mut native_request).await?;
+
+        let chat_response: ApiChatResponse = response.json().await?;
+        Ok(chat_response.message.content)
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut native_request = ChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            stream: true,
+            options: Options { temperature },
+            tools: Self::convert_tools(request.tools),
+            format: None,
+        };
+
+        let response = self.post_chat(&mut native_request).await?;
+
+        let mut next_tool_index = 0;
+        Ok(streaming::decode_response(
+            response,
+            Framing::NdJson,
+            move |payload| parse_stream_chunk(payload, &mut next_tool_index),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -130,17 +522,13 @@ mod tests {
         let req = ChatRequest {
             model: "llama3".to_string(),
             messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: "You are ZeroClaw".to_string(),
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: "hello".to_string(),
-                },
+                Message::text("system", "You are ZeroClaw"),
+                Message::text("user", "hello"),
             ],
             stream: false,
             options: Options { temperature: 0.7 },
+            tools: None,
+            format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"stream\":false"));
@@ -153,12 +541,11 @@ mod tests {
     fn request_serializes_without_system() {
         let req = ChatRequest {
             model: "mistral".to_string(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: "test".to_string(),
-            }],
+            messages: vec![Message::text("user", "test")],
             stream: false,
             options: Options { temperature: 0.0 },
+            tools: None,
+            format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("\"role\":\"system\""));
@@ -179,10 +566,166 @@ mod tests {
         assert!(resp.message.content.is_empty());
     }
 
+    #[test]
+    fn stream_chunk_yields_text_delta() {
+        let events = parse_stream_chunk(
+            r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            &mut 0,
+        )
+        .unwrap();
+        assert_eq!(events, vec![StreamEvent::TextDelta("Hel".into())]);
+    }
+
+    #[test]
+    fn stream_chunk_final_line_reports_usage() {
+        let events = parse_stream_chunk(
+            r#"{"message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":30,"eval_count":12}"#,
+            &mut 0,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![StreamEvent::Usage(ChatUsage {
+                input_tokens: 30,
+                output_tokens: 12,
+                cached_input_tokens: 0,
+                cache_write_input_tokens: 0,
+            })]
+        );
+    }
+
+    #[test]
+    fn stream_chunk_error_is_surfaced() {
+        let err = parse_stream_chunk(r#"{"error":"model 'nope' not found"}"#, &mut 0).unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
     #[test]
     fn response_with_multiline() {
         let json = r#"{"message":{"role":"assistant","content":"line1\nline2\nline3"}}"#;
         let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
         assert!(resp.message.content.contains("line1"));
     }
+
+    #[test]
+    fn request_includes_tools_when_provided() {
+        let tools = vec![ToolSpec {
+            name: "shell".to_string(),
+            description: "Run a command".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let req = ChatRequest {
+            model: "qwen3".to_string(),
+            messages: vec![Message::text("user", "ls")],
+            stream: false,
+            options: Options { temperature: 0.0 },
+            tools: OllamaProvider::convert_tools(Some(&tools)),
+            format: None,
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["tools"][0]["type"], "function");
+        assert_eq!(json["tools"][0]["function"]["name"], "shell");
+        assert!(OllamaProvider::convert_tools(Some(&[])).is_none());
+    }
+
+    #[test]
+    fn convert_messages_maps_native_tool_history() {
+        let messages = vec![
+            ChatMessage::user("What time is it?"),
+            ChatMessage::assistant(
+                serde_json::json!({
+                    "content": null,
+                    "tool_calls": [{"id": "call_1", "name": "shell", "arguments": "{\"command\":\"date\"}"}]
+                })
+                .to_string(),
+            ),
+            ChatMessage::tool(
+                serde_json::json!({"tool_call_id": "call_1", "content": "12:00"}).to_string(),
+            ),
+        ];
+
+        let json = serde_json::to_value(OllamaProvider::convert_messages(&messages)).unwrap();
+        assert_eq!(json[1]["role"], "assistant");
+        assert_eq!(json[1]["content"], "");
+        assert_eq!(json[1]["tool_calls"][0]["function"]["name"], "shell");
+        assert_eq!(
+            json[1]["tool_calls"][0]["function"]["arguments"]["command"],
+            "date"
+        );
+        assert_eq!(json[2]["role"], "tool");
+        assert_eq!(json[2]["content"], "12:00");
+        assert_eq!(json[2]["tool_name"], "shell");
+    }
+
+    #[test]
+    fn convert_messages_attaches_images() {
+        let messages = vec![ChatMessage::with_image(
+            "What is this?",
+            "aGVsbG8=",
+            "image/png",
+        )];
+
+        let json = serde_json::to_value(OllamaProvider::convert_messages(&messages)).unwrap();
+        assert_eq!(json[0]["content"], "What is this?");
+        assert_eq!(json[0]["images"][0], "aGVsbG8=");
+
+        let plain =
+            serde_json::to_value(OllamaProvider::convert_messages(&[ChatMessage::user("hi")]))
+                .unwrap();
+        assert!(plain[0].get("images").is_none());
+    }
+
+    #[test]
+    fn response_with_tool_calls_maps_to_provider_calls() {
+        let json = r#"{
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{"function": {"name": "shell", "arguments": {"command": "date"}}}]
+            },
+            "prompt_eval_count": 40,
+            "eval_count": 8
+        }"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        let calls: Vec<ProviderToolCall> = resp
+            .message
+            .tool_calls
+            .unwrap()
+            .into_iter()
+            .map(OllamaToolCall::into_provider_call)
+            .collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "shell");
+        assert_eq!(calls[0].arguments, r#"{"command":"date"}"#);
+        assert!(!calls[0].id.is_empty());
+        assert_eq!(resp.prompt_eval_count, Some(40));
+    }
+
+    #[test]
+    fn stream_chunk_tool_calls_use_increasing_indices() {
+        let mut next = 0;
+        let payload = r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"a","arguments":{}}},{"function":{"name":"b","arguments":{"x":1}}}]},"done":false}"#;
+        let events = parse_stream_chunk(payload, &mut next).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[1],
+            StreamEvent::ToolCallDelta { index: 1, name: Some(name), arguments, .. }
+                if name == "b" && arguments == r#"{"x":1}"#
+        ));
+        assert_eq!(next, 2);
+    }
+
+    #[test]
+    fn request_serializes_format_schema() {
+        let req = ChatRequest {
+            model: "llama3".to_string(),
+            messages: vec![Message::text("user", "hi")],
+            stream: false,
+            options: Options { temperature: 0.0 },
+            tools: None,
+            format: Some(serde_json::json!({"type": "object"})),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["format"]["type"], "object");
+    }
 }
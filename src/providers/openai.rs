@@ -1,6 +1,9 @@
+use crate::config::ReasoningEffort;
+use crate::providers::streaming::{self, Framing, OpenAiStreamOptions, OpenAiUsage};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ToolCall as ProviderToolCall,
+    ChatStream, ChatUsage, ContentPartType, Provider, Reasoning, ResponseSchema,
+    ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -25,6 +28,36 @@ struct Message {
     role: String,
     content: serde_json::Value,
 }
+
+impl Message {
+    fn from_chat_message(m: &ChatMessage) -> Self {
+        let content = if let Some(ref parts) = m.parts {
+            let content_parts: Vec<serde_json::Value> = parts
+                .iter()
+                .map(|p| match p.content_type {
+                    crate::providers::traits::ContentPartType::Text => {
+                        serde_json::json!({"type": "text", "text": p.text.as_deref().unwrap_or("")})
+                    }
+                    crate::providers::traits::ContentPartType::Image => {
+                        let mime = p.mime_type.as_deref().unwrap_or("image/jpeg");
+                        let data = p.image_base64.as_deref().unwrap_or("");
+                        serde_json::json!({
+                            "type": "image_url",
+                            "image_url": {"url": format!("data:{mime};base64,{data}")}
+                        })
+                    }
+                })
+                .collect();
+            serde_json::Value::Array(content_parts)
+        } else {
+            serde_json::Value::String(m.content.clone())
+        };
+        Self {
+            role: m.role.clone(),
+            content,
+        }
+    }
+}
 // --- end ZeroClaw fork ---
 
 #[derive(Debug, Deserialize)]
@@ -46,18 +79,61 @@ struct ResponseMessage {
 struct NativeChatRequest {
     model: String,
     messages: Vec<NativeMessage>,
-    temperature: f64,
+    /// Omitted for reasoning models, which only accept the default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<ReasoningEffort>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAiStreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+}
+
+/// `response_format` asking for output constrained to a JSON Schema.
+#[derive(Debug, Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: String,
+    json_schema: JsonSchemaFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSchemaFormat {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    schema: serde_json::Value,
+    /// Strict mode rejects schemas that leave properties optional, so rely
+    /// on `chat_structured` validation instead.
+    strict: bool,
+}
+
+impl ResponseFormat {
+    fn json_schema(schema: &ResponseSchema) -> Self {
+        Self {
+            kind: "json_schema".to_string(),
+            json_schema: JsonSchemaFormat {
+                name: schema.name.clone(),
+                description: schema.description.clone(),
+                schema: schema.schema.clone(),
+                strict: false,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct NativeMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -96,6 +172,8 @@ struct NativeFunctionCall {
 #[derive(Debug, Deserialize)]
 struct NativeChatResponse {
     choices: Vec<NativeChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -103,6 +181,50 @@ struct NativeChoice {
     message: NativeResponseMessage,
 }
 
+/// Responses API request, used when reasoning is requested: unlike Chat
+/// Completions it returns reasoning summaries.
+#[derive(Debug, Serialize)]
+struct ResponsesRequest {
+    model: String,
+    input: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<serde_json::Value>,
+    reasoning: ResponsesReasoning,
+    /// Stateless: prior reasoning travels back as encrypted items instead.
+    store: bool,
+    include: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponsesReasoning {
+    effort: ReasoningEffort,
+    summary: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsesResponse {
+    #[serde(default)]
+    output: Vec<serde_json::Value>,
+    #[serde(default)]
+    usage: Option<ResponsesUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsesUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    input_tokens_details: Option<ResponsesInputDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsesInputDetails {
+    #[serde(default)]
+    cached_tokens: u64,
+}
+
 #[derive(Debug, Deserialize)]
 struct NativeResponseMessage {
     #[serde(default)]
@@ -165,7 +287,7 @@ impl OpenAiProvider {
                                 let content = value
                                     .get("content")
                                     .and_then(serde_json::Value::as_str)
-                                    .map(ToString::to_string);
+                                    .map(|text| serde_json::Value::String(text.to_string()));
                                 return NativeMessage {
                                     role: "assistant".to_string(),
                                     content,
@@ -186,7 +308,7 @@ impl OpenAiProvider {
                         let content = value
                             .get("content")
                             .and_then(serde_json::Value::as_str)
-                            .map(ToString::to_string);
+                            .map(|text| serde_json::Value::String(text.to_string()));
                         return NativeMessage {
                             role: "tool".to_string(),
                             content,
@@ -196,9 +318,10 @@ impl OpenAiProvider {
                     }
                 }
 
+                let message = Message::from_chat_message(m);
                 NativeMessage {
-                    role: m.role.clone(),
-                    content: Some(m.content.clone()),
+                    role: message.role,
+                    content: Some(message.content),
                     tool_call_id: None,
                     tool_calls: None,
                 }
@@ -206,6 +329,36 @@ impl OpenAiProvider {
             .collect()
     }
 
+    async fn send_native_request(
+        &self,
+        api_key: &str,
+        request: &NativeChatRequest,
+    ) -> anyhow::Result<NativeChatResponse> {
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .json(request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Reasoning models reject any temperature but the default, and only
+    /// reasoning models accept `reasoning_effort`.
+    fn temperature_for(request: &ProviderChatRequest<'_>, temperature: f64) -> Option<f64> {
+        request
+            .reasoning
+            .and_then(|r| r.effort)
+            .is_none()
+            .then_some(temperature)
+    }
+
     fn parse_native_response(message: NativeResponseMessage) -> ProviderChatResponse {
         let tool_calls = message
             .tool_calls
@@ -221,8 +374,175 @@ impl OpenAiProvider {
         ProviderChatResponse {
             text: message.content,
             tool_calls,
+            usage: None,
+            reasoning: None,
+        }
+    }
+
+    /// History as Responses API input items. Reasoning items kept from an
+    /// earlier tool-call turn are sent back ahead of its calls.
+    fn responses_input(messages: &[ChatMessage]) -> Vec<serde_json::Value> {
+        let mut input = Vec::new();
+        for m in messages {
+            let native = serde_json::from_str::<serde_json::Value>(&m.content).ok();
+            if m.role == "assistant" {
+                let calls = native
+                    .as_ref()
+                    .and_then(|v| v.get("tool_calls"))
+                    .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok());
+                if let (Some(value), Some(calls)) = (&native, calls) {
+                    let reasoning = value.get("reasoning").and_then(|v| v.as_array());
+                    input.extend(
+                        reasoning
+                            .into_iter()
+                            .flatten()
+                            .filter(|item| item["type"] == "reasoning")
+                            .cloned(),
+                    );
+                    if let Some(text) = value.get("content").and_then(|v| v.as_str()) {
+                        input.push(serde_json::json!({"role": "assistant", "content": text}));
+                    }
+                    input.extend(calls.into_iter().map(|call| {
+                        serde_json::json!({
+                            "type": "function_call",
+                            "call_id": call.id,
+                            "name": call.name,
+                            "arguments": call.arguments,
+                        })
+                    }));
+                    continue;
+                }
+            }
+            if m.role == "tool" {
+                if let Some(value) = &native {
+                    input.push(serde_json::json!({
+                        "type": "function_call_output",
+                        "call_id": value.get("tool_call_id").and_then(|v| v.as_str()).unwrap_or("unknown"),
+                        "output": value.get("content").and_then(|v| v.as_str()).unwrap_or_default(),
+                    }));
+                    continue;
+                }
+            }
+            let content = match &m.parts {
+                Some(parts) if m.role == "user" => serde_json::Value::Array(
+                    parts
+                        .iter()
+                        .map(|p| match p.content_type {
+                            ContentPartType::Text => serde_json::json!({
+                                "type": "input_text",
+                                "text": p.text.as_deref().unwrap_or(""),
+                            }),
+                            ContentPartType::Image => serde_json::json!({
+                                "type": "input_image",
+                                "image_url": format!(
+                                    "data:{};base64,{}",
+                                    p.mime_type.as_deref().unwrap_or("image/jpeg"),
+                                    p.image_base64.as_deref().unwrap_or("")
+                                ),
+                            }),
+                        })
+                        .collect(),
+                ),
+                _ => serde_json::Value::String(m.content.clone()),
+            };
+            input.push(serde_json::json!({"role": m.role, "content": content}));
+        }
+        input
+    }
+
+    fn responses_tools(tools: Option<&[ToolSpec]>) -> Vec<serde_json::Value> {
+        tools
+            .unwrap_or_default()
+            .iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "type": "function",
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                })
+            })
+            .collect()
+    }
+
+    /// Text, tool calls and reasoning summary from Responses API output.
+    /// Reasoning items are kept whole so they can be replayed.
+    fn parse_responses_output(response: ResponsesResponse) -> ProviderChatResponse {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        let mut reasoning = Reasoning::default();
+        for item in response.output {
+            match item["type"].as_str() {
+                Some("reasoning") => {
+                    let summaries = item["summary"].as_array().into_iter().flatten();
+                    for summary in summaries.filter_map(|s| s["text"].as_str()) {
+                        if !reasoning.text.is_empty() {
+                            reasoning.text.push_str("\n\n");
+                        }
+                        reasoning.text.push_str(summary.trim());
+                    }
+                    reasoning.blocks.push(item);
+                }
+                Some("message") => {
+                    let parts = item["content"].as_array().into_iter().flatten();
+                    for part in parts.filter(|p| p["type"] == "output_text") {
+                        text.push_str(part["text"].as_str().unwrap_or_default());
+                    }
+                }
+                Some("function_call") => tool_calls.push(ProviderToolCall {
+                    id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                    name: item["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: item["arguments"].as_str().unwrap_or("{}").to_string(),
+                }),
+                _ => {}
+            }
+        }
+        ProviderChatResponse {
+            text: (!text.is_empty()).then_some(text),
+            tool_calls,
+            usage: response.usage.map(|u| ChatUsage {
+                input_tokens: u.input_tokens,
+                output_tokens: u.output_tokens,
+                cached_input_tokens: u.input_tokens_details.map_or(0, |d| d.cached_tokens),
+                ..ChatUsage::default()
+            }),
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
         }
     }
+
+    async fn chat_with_reasoning(
+        &self,
+        api_key: &str,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        effort: ReasoningEffort,
+    ) -> anyhow::Result<ProviderChatResponse> {
+        let native_request = ResponsesRequest {
+            model: model.to_string(),
+            input: Self::responses_input(request.messages),
+            tools: Self::responses_tools(request.tools),
+            reasoning: ResponsesReasoning {
+                effort,
+                summary: "auto".to_string(),
+            },
+            store: false,
+            include: vec!["reasoning.encrypted_content".to_string()],
+        };
+
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/responses")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .json(&native_request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenAI", response).await);
+        }
+
+        Ok(Self::parse_responses_output(response.json().await?))
+    }
 }
 
 #[async_trait]
@@ -290,13 +610,114 @@ impl Provider for OpenAiProvider {
             anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
         })?;
 
+        if let Some(effort) = request.reasoning.and_then(|r| r.effort) {
+            return self
+                .chat_with_reasoning(api_key, request, model, effort)
+                .await;
+        }
+
         let tools = Self::convert_tools(request.tools);
         let native_request = NativeChatRequest {
             model: model.to_string(),
             messages: Self::convert_messages(request.messages),
-            temperature,
+            temperature: Self::temperature_for(&request, temperature),
+            reasoning_effort: None,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            stream: None,
+            stream_options: None,
+            response_format: None,
+        };
+
+        let native_response = self.send_native_request(api_key, &native_request).await?;
+        let usage = native_response.usage.map(Into::into);
+        let message = native_response
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message)
+            .ok_or_else(|| anyhow::anyhow!("No response from OpenAI"))?;
+        let mut chat_response = Self::parse_native_response(message);
+        chat_response.usage = usage;
+        Ok(chat_response)
+    }
+
+    async fn generate_structured(
+        &self,
+        request: ProviderChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            temperature: Self::temperature_for(&request, temperature),
+            reasoning_effort: request.reasoning.and_then(|r| r.effort),
+            tools: None,
+            tool_choice: None,
+            stream: None,
+            stream_options: None,
+            response_format: Some(ResponseFormat::json_schema(schema)),
+        };
+
+        let native_response = self.send_native_request(api_key, &native_request).await?;
+        native_response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .ok_or_else(|| anyhow::anyhow!("No response from OpenAI"))
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
+        })?;
+
+        // Reasoning summaries come from the Responses API, which is not
+        // streamed here; deliver its answer in one burst.
+        if let Some(effort) = request.reasoning.and_then(|r| r.effort) {
+            let response = self
+                .chat_with_reasoning(api_key, request, model, effort)
+                .await?;
+            return Ok(streaming::replay_response(response));
+        }
+
+        let tools = Self::convert_tools(request.tools);
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            temperature: Self::temperature_for(&request, temperature),
+            reasoning_effort: None,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            stream: Some(true),
+            stream_options: Some(OpenAiStreamOptions {
+                include_usage: true,
+            }),
+            response_format: None,
         };
 
         let response = self
@@ -311,18 +732,11 @@ impl Provider for OpenAiProvider {
             return Err(super::api_error("OpenAI", response).await);
         }
 
-        let native_response: NativeChatResponse = response.json().await?;
-        let message = native_response
-            .choices
-            .into_iter()
-            .next()
-            .map(|c| c.message)
-            .ok_or_else(|| anyhow::anyhow!("No response from OpenAI"))?;
-        Ok(Self::parse_native_response(message))
-    }
-
-    fn supports_native_tools(&self) -> bool {
-        true
+        Ok(streaming::decode_response(
+            response,
+            Framing::Sse,
+            streaming::parse_openai_chunk,
+        ))
     }
 }
 
@@ -439,4 +853,79 @@ mod tests {
         let resp: ChatResponse = serde_json::from_str(&json).unwrap();
         assert_eq!(resp.choices[0].message.content.len(), 100_000);
     }
+
+    #[test]
+    fn response_format_requests_json_schema() {
+        let schema = ResponseSchema::new("subject", serde_json::json!({"type": "object"}));
+        let json = serde_json::to_value(ResponseFormat::json_schema(&schema)).unwrap();
+        assert_eq!(json["type"], "json_schema");
+        assert_eq!(json["json_schema"]["name"], "subject");
+        assert_eq!(json["json_schema"]["schema"]["type"], "object");
+        assert_eq!(json["json_schema"]["strict"], false);
+        assert!(json["json_schema"].get("description").is_none());
+    }
+
+    #[test]
+    fn reasoning_effort_omits_temperature() {
+        let messages = vec![ChatMessage::user("hi")];
+        let mut request = ProviderChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+        assert_eq!(OpenAiProvider::temperature_for(&request, 0.3), Some(0.3));
+
+        request.reasoning = Some(crate::providers::ReasoningOptions {
+            budget_tokens: None,
+            effort: Some(ReasoningEffort::High),
+        });
+        assert_eq!(OpenAiProvider::temperature_for(&request, 0.3), None);
+        assert_eq!(serde_json::to_value(ReasoningEffort::High).unwrap(), "high");
+    }
+
+    #[test]
+    fn responses_output_yields_reasoning_summary() {
+        let response: ResponsesResponse = serde_json::from_str(
+            r#"{"output":[
+                {"type":"reasoning","id":"rs_1","summary":[{"type":"summary_text","text":"Check the weather first."}],"encrypted_content":"enc"},
+                {"type":"function_call","call_id":"call_1","name":"weather","arguments":"{\"city\":\"Seoul\"}"},
+                {"type":"message","content":[{"type":"output_text","text":"One moment."}]}
+            ],"usage":{"input_tokens":40,"output_tokens":12,"input_tokens_details":{"cached_tokens":8}}}"#,
+        )
+        .unwrap();
+        let parsed = OpenAiProvider::parse_responses_output(response);
+        assert_eq!(parsed.text.as_deref(), Some("One moment."));
+        assert_eq!(parsed.tool_calls[0].id, "call_1");
+        assert_eq!(parsed.tool_calls[0].name, "weather");
+        let reasoning = parsed.reasoning.unwrap();
+        assert_eq!(reasoning.text, "Check the weather first.");
+        assert_eq!(reasoning.blocks[0]["encrypted_content"], "enc");
+        let usage = parsed.usage.unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (40, 12));
+        assert_eq!(usage.cached_input_tokens, 8);
+    }
+
+    #[test]
+    fn responses_input_replays_reasoning_before_tool_calls() {
+        let assistant = serde_json::json!({
+            "content": "Checking.",
+            "tool_calls": [{"id": "call_1", "name": "weather", "arguments": "{}"}],
+            "reasoning": [{"type": "reasoning", "id": "rs_1", "encrypted_content": "enc"}],
+        });
+        let tool = serde_json::json!({"tool_call_id": "call_1", "content": "sunny"});
+        let input = OpenAiProvider::responses_input(&[
+            ChatMessage::system("Be brief."),
+            ChatMessage::user("Weather?"),
+            ChatMessage::assistant(assistant.to_string()),
+            ChatMessage::tool(tool.to_string()),
+        ]);
+        assert_eq!(input[0]["role"], "system");
+        assert_eq!(input[1]["content"], "Weather?");
+        assert_eq!(input[2]["id"], "rs_1");
+        assert_eq!(input[3]["content"], "Checking.");
+        assert_eq!(input[4]["type"], "function_call");
+        assert_eq!(input[4]["call_id"], "call_1");
+        assert_eq!(input[5]["type"], "function_call_output");
+        assert_eq!(input[5]["output"], "sunny");
+    }
 }
@@ -1,6 +1,8 @@
+use crate::config::ReasoningEffort;
+use crate::providers::streaming::{self, Framing, OpenAiStreamOptions, OpenAiUsage};
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ToolCall as ProviderToolCall,
+    ChatStream, Provider, Reasoning, ReasoningOptions, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -82,17 +84,53 @@ struct NativeChatRequest {
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAiStreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<NativeReasoning>,
+}
+
+/// Unified reasoning control; OpenRouter accepts either a token budget or
+/// an effort level and maps it onto the upstream model.
+#[derive(Debug, Serialize)]
+struct NativeReasoning {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effort: Option<ReasoningEffort>,
+}
+
+impl NativeReasoning {
+    fn from_options(options: Option<ReasoningOptions>) -> Option<Self> {
+        let options = options.filter(|o| !o.is_empty())?;
+        Some(match options.budget_tokens {
+            Some(budget) => Self {
+                max_tokens: Some(budget),
+                effort: None,
+            },
+            None => Self {
+                max_tokens: None,
+                effort: options.effort,
+            },
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct NativeMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<NativeToolCall>>,
+    /// Reasoning details from the previous assistant turn; upstream models
+    /// such as Claude need them back to continue a tool-call exchange.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_details: Option<Vec<serde_json::Value>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -127,6 +165,8 @@ struct NativeFunctionCall {
 #[derive(Debug, Deserialize)]
 struct NativeChatResponse {
     choices: Vec<NativeChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -140,6 +180,10 @@ struct NativeResponseMessage {
     content: Option<String>,
     #[serde(default)]
     tool_calls: Option<Vec<NativeToolCall>>,
+    #[serde(default)]
+    reasoning: Option<String>,
+    #[serde(default)]
+    reasoning_details: Option<Vec<serde_json::Value>>,
 }
 
 impl OpenRouterProvider {
@@ -200,12 +244,24 @@ impl OpenRouterProvider {
                                 let content = value
                                     .get("content")
                                     .and_then(serde_json::Value::as_str)
-                                    .map(ToString::to_string);
+                                    .map(|text| serde_json::Value::String(text.to_string()));
+                                let reasoning_details = value
+                                    .get("reasoning")
+                                    .and_then(serde_json::Value::as_array)
+                                    .map(|blocks| {
+                                        blocks
+                                            .iter()
+                                            .filter(|b| Self::is_reasoning_detail(b))
+                                            .cloned()
+                                            .collect::<Vec<_>>()
+                                    })
+                                    .filter(|blocks| !blocks.is_empty());
                                 return NativeMessage {
                                     role: "assistant".to_string(),
                                     content,
                                     tool_call_id: None,
                                     tool_calls: Some(tool_calls),
+                                    reasoning_details,
                                 };
                             }
                         }
@@ -221,26 +277,38 @@ impl OpenRouterProvider {
                         let content = value
                             .get("content")
                             .and_then(serde_json::Value::as_str)
-                            .map(ToString::to_string);
+                            .map(|text| serde_json::Value::String(text.to_string()));
                         return NativeMessage {
                             role: "tool".to_string(),
                             content,
                             tool_call_id,
                             tool_calls: None,
+                            reasoning_details: None,
                         };
                     }
                 }
 
+                let message = Message::from_chat_message(m);
                 NativeMessage {
-                    role: m.role.clone(),
-                    content: Some(m.content.clone()),
+                    role: message.role,
+                    content: Some(message.content),
                     tool_call_id: None,
                     tool_calls: None,
+                    reasoning_details: None,
                 }
             })
             .collect()
     }
 
+    /// Whether a kept reasoning block came from OpenRouter (`reasoning.text`,
+    /// `reasoning.summary`, `reasoning.encrypted`) rather than another provider.
+    fn is_reasoning_detail(block: &serde_json::Value) -> bool {
+        block
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|kind| kind.starts_with("reasoning."))
+    }
+
     fn parse_native_response(message: NativeResponseMessage) -> ProviderChatResponse {
         let tool_calls = message
             .tool_calls
@@ -253,9 +321,16 @@ impl OpenRouterProvider {
             })
             .collect::<Vec<_>>();
 
+        let reasoning = Reasoning {
+            text: message.reasoning.unwrap_or_default(),
+            blocks: message.reasoning_details.unwrap_or_default(),
+        };
+
         ProviderChatResponse {
             text: message.content,
             tool_calls,
+            usage: None,
+            reasoning: (!reasoning.is_empty()).then_some(reasoning),
         }
     }
 }
@@ -400,6 +475,9 @@ impl Provider for OpenRouterProvider {
             temperature,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            stream: None,
+            stream_options: None,
+            reasoning: NativeReasoning::from_options(request.reasoning),
         };
 
         let response = self
@@ -420,18 +498,81 @@ impl Provider for OpenRouterProvider {
         }
 
         let native_response: NativeChatResponse = response.json().await?;
+        let usage = native_response.usage.map(Into::into);
         let message = native_response
             .choices
             .into_iter()
             .next()
             .map(|c| c.message)
             .ok_or_else(|| anyhow::anyhow!("No response from OpenRouter"))?;
-        Ok(Self::parse_native_response(message))
+        let mut chat_response = Self::parse_native_response(message);
+        chat_response.usage = usage;
+        Ok(chat_response)
     }
 
     fn supports_native_tools(&self) -> bool {
         true
     }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+            "OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."
+        )
+        })?;
+
+        let tools = Self::convert_tools(request.tools);
+        let native_request = NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            temperature,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            stream: Some(true),
+            stream_options: Some(OpenAiStreamOptions {
+                include_usage: true,
+            }),
+            reasoning: NativeReasoning::from_options(request.reasoning),
+        };
+
+        let response = self
+            .client
+            .post("https://openrouter.ai/api/v1/chat/completions")
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header(
+                "HTTP-Referer",
+                "https://github.com/theonlyhennygod/zeroclaw",
+            )
+            .header("X-Title", "ZeroClaw")
+            .json(&native_request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(super::api_error("OpenRouter", response).await);
+        }
+
+        // OpenRouter interleaves `: OPENROUTER PROCESSING` comments while the
+        // upstream model warms up; the SSE decoder skips them.
+        Ok(streaming::decode_response(
+            response,
+            Framing::Sse,
+            streaming::parse_openai_chunk,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -558,4 +699,37 @@ mod tests {
 
         assert!(response.choices.is_empty());
     }
+
+    #[test]
+    fn reasoning_prefers_budget_over_effort() {
+        let both = NativeReasoning::from_options(Some(ReasoningOptions {
+            budget_tokens: Some(4000),
+            effort: Some(ReasoningEffort::Low),
+        }));
+        assert_eq!(
+            serde_json::to_value(both).unwrap(),
+            serde_json::json!({"max_tokens": 4000})
+        );
+        let effort = NativeReasoning::from_options(Some(ReasoningOptions {
+            budget_tokens: None,
+            effort: Some(ReasoningEffort::Medium),
+        }));
+        assert_eq!(
+            serde_json::to_value(effort).unwrap(),
+            serde_json::json!({"effort": "medium"})
+        );
+        assert!(NativeReasoning::from_options(Some(ReasoningOptions::default())).is_none());
+    }
+
+    #[test]
+    fn native_response_reads_reasoning_details() {
+        let message: NativeResponseMessage = serde_json::from_str(
+            r#"{"content":"42","reasoning":"Add them up.","reasoning_details":[{"type":"reasoning.encrypted","data":"abc"}]}"#,
+        )
+        .unwrap();
+        let parsed = OpenRouterProvider::parse_native_response(message);
+        let reasoning = parsed.reasoning.unwrap();
+        assert_eq!(reasoning.text, "Add them up.");
+        assert_eq!(reasoning.blocks[0]["type"], "reasoning.encrypted");
+    }
 }
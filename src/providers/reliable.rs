@@ -1,9 +1,13 @@
-use super::traits::ChatMessage;
+use super::health::ProviderHealth;
+use super::traits::{ChatMessage, ChatRequest, ChatResponse, ChatStream, ResponseSchema};
 use super::Provider;
 use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Check if an error is non-retryable (client errors that won't resolve with retries).
 fn is_non_retryable(err: &anyhow::Error) -> bool {
@@ -69,6 +73,22 @@ fn parse_retry_after_ms(err: &anyhow::Error) -> Option<u64> {
     None
 }
 
+/// When to fire the next candidate in parallel with a slow one.
+#[derive(Debug, Clone, Copy)]
+struct HedgePolicy {
+    /// Latency percentile of the candidate's recent successes.
+    percentile: f64,
+    /// Delay used until the candidate has enough latency samples.
+    default_delay: Duration,
+}
+
+/// One provider/model pair in the failover order.
+struct Candidate<'a> {
+    name: &'a str,
+    provider: &'a dyn Provider,
+    model: &'a str,
+}
+
 /// Provider wrapper with retry, fallback, auth rotation, and model failover.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
@@ -79,6 +99,10 @@ pub struct ReliableProvider {
     key_index: AtomicUsize,
     /// Per-model fallback chains: model_name → [fallback_model_1, fallback_model_2, ...]
     model_fallbacks: HashMap<String, Vec<String>>,
+    /// Rolling latency/error tracking that orders candidates and opens
+    /// circuit breakers.
+    health: Option<Arc<ProviderHealth>>,
+    hedge: Option<HedgePolicy>,
 }
 
 impl ReliableProvider {
@@ -94,6 +118,8 @@ impl ReliableProvider {
             api_keys: Vec::new(),
             key_index: AtomicUsize::new(0),
             model_fallbacks: HashMap::new(),
+            health: None,
+            hedge: None,
         }
     }
 
@@ -109,6 +135,24 @@ impl ReliableProvider {
         self
     }
 
+    /// Track provider health: candidates are tried best first and pairs
+    /// with an open circuit breaker are skipped.
+    pub fn with_health(mut self, health: Arc<ProviderHealth>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Hedge slow calls: once a candidate has run longer than `percentile`
+    /// of its recent latencies (or `default_delay` while there are too few
+    /// samples), start the next candidate too and take the first success.
+    pub fn with_hedging(mut self, percentile: f64, default_delay: Duration) -> Self {
+        self.hedge = Some(HedgePolicy {
+            percentile,
+            default_delay,
+        });
+        self
+    }
+
     /// Build the list of models to try: [original, fallback1, fallback2, ...]
     fn model_chain<'a>(&'a self, model: &'a str) -> Vec<&'a str> {
         let mut chain = vec![model];
@@ -136,110 +180,272 @@ impl ReliableProvider {
             base
         }
     }
-}
 
-#[async_trait]
-impl Provider for ReliableProvider {
-    async fn warmup(&self) -> anyhow::Result<()> {
-        for (name, provider) in &self.providers {
-            tracing::info!(provider = name, "Warming up provider connection pool");
-            if let Err(e) = provider.warmup().await {
-                tracing::warn!(provider = name, "Warmup failed (non-fatal): {e}");
+    /// Every provider/model pair to try, in order: models follow the
+    /// fallback chain, and within a model providers are ordered by health.
+    /// Pairs with an open circuit are dropped unless that would leave none.
+    fn candidates<'a, E>(
+        &'a self,
+        model: &'a str,
+        eligible: &E,
+        failures: &mut Vec<String>,
+    ) -> Vec<Candidate<'a>>
+    where
+        E: Fn(&dyn Provider) -> bool,
+    {
+        let mut candidates = Vec::new();
+        for current_model in self.model_chain(model) {
+            let mut tier = Vec::new();
+            for (provider_name, provider) in &self.providers {
+                if !eligible(provider.as_ref()) {
+                    failures.push(format!(
+                        "{provider_name}/{current_model}: skipped (lacks required capabilities)"
+                    ));
+                    continue;
+                }
+                tier.push(Candidate {
+                    name: provider_name,
+                    provider: provider.as_ref(),
+                    model: current_model,
+                });
             }
+            if let Some(health) = &self.health {
+                health.order(&mut tier, |c| (c.name, c.model));
+            }
+            candidates.extend(tier);
         }
-        Ok(())
+
+        if let Some(health) = &self.health {
+            let (open, closed): (Vec<_>, Vec<_>) = candidates
+                .into_iter()
+                .partition(|c| health.is_open(c.name, c.model));
+            if closed.is_empty() {
+                return open;
+            }
+            for c in &open {
+                failures.push(format!("{}/{}: skipped (circuit open)", c.name, c.model));
+            }
+            return closed;
+        }
+        candidates
     }
 
-    async fn chat_with_system(
-        &self,
-        system_prompt: Option<&str>,
-        message: &str,
-        model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
-        let models = self.model_chain(model);
+    /// Call one candidate with retries and backoff, rotating API keys on
+    /// rate limits. Returns the failure log when every attempt fails.
+    async fn try_candidate<'a, T, C>(
+        &'a self,
+        candidate: &Candidate<'a>,
+        original_model: &str,
+        call: &C,
+    ) -> Result<T, Vec<String>>
+    where
+        T: Send,
+        C: Fn(&'a dyn Provider, &'a str) -> BoxFuture<'a, anyhow::Result<T>> + Send + Sync,
+    {
+        let Candidate {
+            name: provider_name,
+            provider,
+            model: current_model,
+        } = *candidate;
         let mut failures = Vec::new();
+        let mut backoff_ms = self.base_backoff_ms;
+        // Whether the last error was the provider's fault; client errors
+        // (bad request, auth) say nothing about its health.
+        let mut provider_failed = false;
+
+        for attempt in 0..=self.max_retries {
+            let started = Instant::now();
+            match call(provider, current_model).await {
+                Ok(resp) => {
+                    if let Some(health) = &self.health {
+                        health.record_success(provider_name, current_model, started.elapsed());
+                    }
+                    if attempt > 0 || current_model != original_model {
+                        tracing::info!(
+                            provider = provider_name,
+                            model = current_model,
+                            attempt,
+                            original_model,
+                            "Provider recovered (failover/retry)"
+                        );
+                    }
+                    return Ok(resp);
+                }
+                Err(e) => {
+                    let non_retryable = is_non_retryable(&e);
+                    let rate_limited = is_rate_limited(&e);
+                    provider_failed = !non_retryable;
+
+                    failures.push(format!(
+                        "{provider_name}/{current_model} attempt {}/{}: {e}",
+                        attempt + 1,
+                        self.max_retries + 1
+                    ));
+
+                    // On rate-limit, try rotating API key
+                    if rate_limited {
+                        if let Some(new_key) = self.rotate_key() {
+                            tracing::info!(
+                                provider = provider_name,
+                                "Rate limited, rotated API key (key ending ...{})",
+                                &new_key[new_key.len().saturating_sub(4)..]
+                            );
+                        }
+                    }
 
-        for current_model in &models {
-            for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+                    if non_retryable {
+                        tracing::warn!(
+                            provider = provider_name,
+                            model = current_model,
+                            "Non-retryable error, moving on"
+                        );
+                        break;
+                    }
 
-                for attempt in 0..=self.max_retries {
-                    match provider
-                        .chat_with_system(system_prompt, message, current_model, temperature)
-                        .await
-                    {
-                        Ok(resp) => {
-                            if attempt > 0 || *current_model != model {
-                                tracing::info!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt,
-                                    original_model = model,
-                                    "Provider recovered (failover/retry)"
-                                );
-                            }
-                            return Ok(resp);
-                        }
-                        Err(e) => {
-                            let non_retryable = is_non_retryable(&e);
-                            let rate_limited = is_rate_limited(&e);
-
-                            failures.push(format!(
-                                "{provider_name}/{current_model} attempt {}/{}: {e}",
-                                attempt + 1,
-                                self.max_retries + 1
-                            ));
-
-                            // On rate-limit, try rotating API key
-                            if rate_limited {
-                                if let Some(new_key) = self.rotate_key() {
-                                    tracing::info!(
-                                        provider = provider_name,
-                                        "Rate limited, rotated API key (key ending ...{})",
-                                        &new_key[new_key.len().saturating_sub(4)..]
-                                    );
-                                }
-                            }
+                    if attempt < self.max_retries {
+                        let wait = self.compute_backoff(backoff_ms, &e);
+                        tracing::warn!(
+                            provider = provider_name,
+                            model = current_model,
+                            attempt = attempt + 1,
+                            backoff_ms = wait,
+                            "Provider call failed, retrying"
+                        );
+                        tokio::time::sleep(Duration::from_millis(wait)).await;
+                        backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+                    }
+                }
+            }
+        }
 
-                            if non_retryable {
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    "Non-retryable error, moving on"
-                                );
-                                break;
-                            }
+        // One failure per candidate, however many retries it took.
+        if let (true, Some(health)) = (provider_failed, &self.health) {
+            health.record_failure(provider_name, current_model);
+        }
+        tracing::warn!(
+            provider = provider_name,
+            model = current_model,
+            "Exhausted retries, trying next provider/model"
+        );
+        Err(failures)
+    }
 
-                            if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt = attempt + 1,
-                                    backoff_ms = wait,
-                                    "Provider call failed, retrying"
-                                );
-                                tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+    /// How long `candidate` may run before the next one is hedged in.
+    fn hedge_delay(&self, policy: HedgePolicy, candidate: &Candidate<'_>) -> Duration {
+        self.health
+            .as_ref()
+            .and_then(|h| h.latency_percentile(candidate.name, candidate.model, policy.percentile))
+            .unwrap_or(policy.default_delay)
+    }
+
+    /// Run candidates with at most two in flight: the next one starts when
+    /// the current one fails or outlives its hedge delay. The first success
+    /// wins and the other call is dropped.
+    async fn run_hedged<'a, T, C>(
+        &'a self,
+        policy: HedgePolicy,
+        candidates: Vec<Candidate<'a>>,
+        original_model: &'a str,
+        call: &C,
+        failures: &mut Vec<String>,
+    ) -> Option<T>
+    where
+        T: Send,
+        C: Fn(&'a dyn Provider, &'a str) -> BoxFuture<'a, anyhow::Result<T>> + Send + Sync,
+    {
+        let start = |index: usize, candidate: Candidate<'a>| async move {
+            let result = self.try_candidate(&candidate, original_model, call).await;
+            (index, result)
+        };
+        let mut pending = candidates.into_iter().enumerate().peekable();
+        let mut in_flight = FuturesUnordered::new();
+        // (index, name, model, started, hedge delay) of each running call.
+        let mut running: Vec<(usize, &str, &str, Instant, Duration)> = Vec::new();
+
+        loop {
+            if in_flight.is_empty() {
+                let (index, candidate) = pending.next()?;
+                running.push((
+                    index,
+                    candidate.name,
+                    candidate.model,
+                    Instant::now(),
+                    self.hedge_delay(policy, &candidate),
+                ));
+                in_flight.push(start(index, candidate));
+            }
+
+            let hedge_at = match running.as_slice() {
+                [(_, _, _, started, delay)] if pending.peek().is_some() => Some(*started + *delay),
+                _ => None,
+            };
+
+            tokio::select! {
+                Some((index, result)) = in_flight.next() => {
+                    running.retain(|(i, ..)| *i != index);
+                    match result {
+                        Ok(resp) => {
+                            if let Some(health) = &self.health {
+                                for (_, name, model, started, _) in &running {
+                                    health.record_latency(name, model, started.elapsed());
+                                }
                             }
+                            return Some(resp);
                         }
+                        Err(mut errors) => failures.append(&mut errors),
+                    }
+                }
+                () = tokio::time::sleep_until(hedge_at.unwrap_or_else(Instant::now).into()), if hedge_at.is_some() => {
+                    if let Some((index, candidate)) = pending.next() {
+                        tracing::info!(
+                            provider = candidate.name,
+                            model = candidate.model,
+                            "Primary is slow, hedging with next provider"
+                        );
+                        running.push((
+                            index,
+                            candidate.name,
+                            candidate.model,
+                            Instant::now(),
+                            self.hedge_delay(policy, &candidate),
+                        ));
+                        in_flight.push(start(index, candidate));
                     }
                 }
-
-                tracing::warn!(
-                    provider = provider_name,
-                    model = *current_model,
-                    "Exhausted retries, trying next provider/model"
-                );
             }
+        }
+    }
 
-            if *current_model != model {
-                tracing::warn!(
-                    original_model = model,
-                    fallback_model = *current_model,
-                    "Model fallback exhausted all providers, trying next fallback model"
-                );
+    /// Run `call` against every model in the fallback chain and every
+    /// provider `eligible` accepts, retrying with backoff and rotating API
+    /// keys on rate limits. Returns the first success.
+    async fn with_failover<'a, T, E, C>(
+        &'a self,
+        model: &'a str,
+        eligible: E,
+        call: C,
+    ) -> anyhow::Result<T>
+    where
+        T: Send,
+        E: Fn(&dyn Provider) -> bool + Send + Sync,
+        C: Fn(&'a dyn Provider, &'a str) -> BoxFuture<'a, anyhow::Result<T>> + Send + Sync,
+    {
+        let mut failures = Vec::new();
+        let candidates = self.candidates(model, &eligible, &mut failures);
+
+        if let Some(policy) = self.hedge {
+            if let Some(resp) = self
+                .run_hedged(policy, candidates, model, &call, &mut failures)
+                .await
+            {
+                return Ok(resp);
+            }
+        } else {
+            for candidate in &candidates {
+                match self.try_candidate(candidate, model, &call).await {
+                    Ok(resp) => return Ok(resp),
+                    Err(mut errors) => failures.append(&mut errors),
+                }
             }
         }
 
@@ -248,6 +454,36 @@ impl Provider for ReliableProvider {
             failures.join("\n")
         )
     }
+}
+
+#[async_trait]
+impl Provider for ReliableProvider {
+    async fn warmup(&self) -> anyhow::Result<()> {
+        for (name, provider) in &self.providers {
+            tracing::info!(provider = name, "Warming up provider connection pool");
+            if let Err(e) = provider.warmup().await {
+                tracing::warn!(provider = name, "Warmup failed (non-fatal): {e}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.with_failover(
+            model,
+            |_| true,
+            |provider, current_model| {
+                provider.chat_with_system(system_prompt, message, current_model, temperature)
+            },
+        )
+        .await
+    }
 
     async fn chat_with_history(
         &self,
@@ -255,87 +491,82 @@ impl Provider for ReliableProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let models = self.model_chain(model);
-        let mut failures = Vec::new();
+        self.with_failover(
+            model,
+            |_| true,
+            |provider, current_model| {
+                provider.chat_with_history(messages, current_model, temperature)
+            },
+        )
+        .await
+    }
 
-        for current_model in &models {
-            for (provider_name, provider) in &self.providers {
-                let mut backoff_ms = self.base_backoff_ms;
+    /// Providers that cannot honour the request's tools are skipped rather
+    /// than silently dropping them.
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.with_failover(
+            model,
+            |provider| request.is_supported_by(provider),
+            |provider, current_model| provider.chat(request, current_model, temperature),
+        )
+        .await
+    }
 
-                for attempt in 0..=self.max_retries {
-                    match provider
-                        .chat_with_history(messages, current_model, temperature)
-                        .await
-                    {
-                        Ok(resp) => {
-                            if attempt > 0 || *current_model != model {
-                                tracing::info!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt,
-                                    original_model = model,
-                                    "Provider recovered (failover/retry)"
-                                );
-                            }
-                            return Ok(resp);
-                        }
-                        Err(e) => {
-                            let non_retryable = is_non_retryable(&e);
-                            let rate_limited = is_rate_limited(&e);
-
-                            failures.push(format!(
-                                "{provider_name}/{current_model} attempt {}/{}: {e}",
-                                attempt + 1,
-                                self.max_retries + 1
-                            ));
-
-                            if rate_limited {
-                                if let Some(new_key) = self.rotate_key() {
-                                    tracing::info!(
-                                        provider = provider_name,
-                                        "Rate limited, rotated API key (key ending ...{})",
-                                        &new_key[new_key.len().saturating_sub(4)..]
-                                    );
-                                }
-                            }
+    async fn chat_structured(
+        &self,
+        request: ChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.with_failover(
+            model,
+            |_| true,
+            |provider, current_model| {
+                provider.chat_structured(request, schema, current_model, temperature)
+            },
+        )
+        .await
+    }
 
-                            if non_retryable {
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    "Non-retryable error, moving on"
-                                );
-                                break;
-                            }
+    fn supports_native_tools(&self) -> bool {
+        self.providers
+            .first()
+            .is_some_and(|(_, p)| p.supports_native_tools())
+    }
 
-                            if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt = attempt + 1,
-                                    backoff_ms = wait,
-                                    "Provider call failed, retrying"
-                                );
-                                tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
-                            }
-                        }
-                    }
-                }
+    fn supports_vision(&self) -> bool {
+        self.providers
+            .first()
+            .is_some_and(|(_, p)| p.supports_vision())
+    }
 
-                tracing::warn!(
-                    provider = provider_name,
-                    model = *current_model,
-                    "Exhausted retries, trying next provider/model"
-                );
-            }
-        }
+    fn supports_streaming(&self) -> bool {
+        self.providers
+            .first()
+            .is_some_and(|(_, p)| p.supports_streaming())
+    }
 
-        anyhow::bail!(
-            "All providers/models failed. Attempts:\n{}",
-            failures.join("\n")
+    /// Retries and fails over while the stream is being established. Once
+    /// deltas start flowing, errors surface to the caller instead — replaying
+    /// a half-delivered answer from another provider would duplicate output.
+    async fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        self.with_failover(
+            model,
+            |provider| request.is_supported_by(provider),
+            |provider, current_model| provider.stream_chat(request, current_model, temperature),
         )
+        .await
     }
 }
 
@@ -645,6 +876,58 @@ mod tests {
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn stream_chat_falls_back_before_first_delta() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "primary down",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "streamed fallback",
+                        error: "fallback err",
+                    }),
+                ),
+            ],
+            1,
+            1,
+        );
+
+        let messages = vec![ChatMessage::user("hello")];
+        let stream = provider
+            .stream_chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                "test",
+                0.0,
+            )
+            .await
+            .unwrap();
+        let response = crate::providers::streaming::collect_stream(stream)
+            .await
+            .unwrap();
+        assert_eq!(response.text.as_deref(), Some("streamed fallback"));
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+        assert!(!provider.supports_streaming());
+    }
+
     // ── New tests: model failover ──
 
     #[tokio::test]
@@ -820,6 +1103,417 @@ mod tests {
         assert_eq!(provider.compute_backoff(500, &err), 500);
     }
 
+    /// Mock with native tool support that answers `chat()` with a tool call.
+    struct ToolMock {
+        calls: Arc<AtomicUsize>,
+        native: bool,
+        fail_until_attempt: usize,
+        error: &'static str,
+    }
+
+    #[async_trait]
+    impl Provider for ToolMock {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("text".to_string())
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let attempt = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt <= self.fail_until_attempt {
+                anyhow::bail!(self.error);
+            }
+            let tool = &request.tools.unwrap()[0];
+            Ok(ChatResponse {
+                text: None,
+                tool_calls: vec![crate::providers::ToolCall {
+                    id: "call_1".into(),
+                    name: tool.name.clone(),
+                    arguments: "{}".into(),
+                }],
+                usage: None,
+                reasoning: None,
+            })
+        }
+
+        fn supports_native_tools(&self) -> bool {
+            self.native
+        }
+    }
+
+    fn shell_spec() -> Vec<crate::tools::ToolSpec> {
+        vec![crate::tools::ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }]
+    }
+
+    fn tool_mock(calls: &Arc<AtomicUsize>, native: bool, fail_until_attempt: usize) -> ToolMock {
+        ToolMock {
+            calls: Arc::clone(calls),
+            native,
+            fail_until_attempt,
+            error: "500 upstream error",
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_forwards_tools_and_retries() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![("primary".into(), Box::new(tool_mock(&calls, true, 1)))],
+            2,
+            1,
+        );
+        assert!(provider.supports_native_tools());
+
+        let messages = vec![ChatMessage::user("list files")];
+        let tools = shell_spec();
+        let response = provider
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: Some(&tools),
+                    reasoning: None,
+                },
+                "test",
+                0.0,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "shell");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_only_falls_back_to_capable_providers() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let text_only_calls = Arc::new(AtomicUsize::new(0));
+        let native_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(tool_mock(&primary_calls, true, usize::MAX)),
+                ),
+                (
+                    "text-only".into(),
+                    Box::new(tool_mock(&text_only_calls, false, 0)),
+                ),
+                ("native".into(), Box::new(tool_mock(&native_calls, true, 0))),
+            ],
+            0,
+            1,
+        );
+
+        let messages = vec![ChatMessage::user("list files")];
+        let tools = shell_spec();
+        let response = provider
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: Some(&tools),
+                    reasoning: None,
+                },
+                "test",
+                0.0,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(text_only_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(native_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_reports_skipped_providers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![("text-only".into(), Box::new(tool_mock(&calls, false, 0)))],
+            0,
+            1,
+        );
+        assert!(!provider.supports_native_tools());
+
+        let messages = vec![ChatMessage::user("list files")];
+        let tools = shell_spec();
+        let err = provider
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: Some(&tools),
+                    reasoning: None,
+                },
+                "test",
+                0.0,
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("skipped (lacks required capabilities)"));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    /// Mock that answers with its name and may or may not send images.
+    struct VisionMock {
+        calls: Arc<AtomicUsize>,
+        vision: bool,
+        name: &'static str,
+    }
+
+    #[async_trait]
+    impl Provider for VisionMock {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.name.to_string())
+        }
+
+        fn supports_vision(&self) -> bool {
+            self.vision
+        }
+    }
+
+    #[tokio::test]
+    async fn image_requests_skip_providers_without_vision() {
+        let text_only_calls = Arc::new(AtomicUsize::new(0));
+        let vision_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "text-only".into(),
+                    Box::new(VisionMock {
+                        calls: Arc::clone(&text_only_calls),
+                        vision: false,
+                        name: "text-only",
+                    }),
+                ),
+                (
+                    "vision".into(),
+                    Box::new(VisionMock {
+                        calls: Arc::clone(&vision_calls),
+                        vision: true,
+                        name: "vision",
+                    }),
+                ),
+            ],
+            0,
+            1,
+        );
+
+        let messages = vec![ChatMessage::with_image(
+            "what is this?",
+            "aGk=",
+            "image/png",
+        )];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+        let response = provider.chat(request, "test", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("vision"));
+        assert_eq!(text_only_calls.load(Ordering::SeqCst), 0);
+
+        // Text-only requests still go to the first provider.
+        let text = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(text, "text-only");
+        assert_eq!(vision_calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Mock that answers after a fixed delay.
+    struct DelayedMock {
+        calls: Arc<AtomicUsize>,
+        delay: Duration,
+        response: &'static str,
+    }
+
+    #[async_trait]
+    impl Provider for DelayedMock {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            Ok(self.response.to_string())
+        }
+    }
+
+    fn delayed(
+        calls: &Arc<AtomicUsize>,
+        delay_ms: u64,
+        response: &'static str,
+    ) -> Box<dyn Provider> {
+        Box::new(DelayedMock {
+            calls: Arc::clone(calls),
+            delay: Duration::from_millis(delay_ms),
+            response,
+        })
+    }
+
+    // ── Hedging and health ──
+
+    #[tokio::test]
+    async fn hedging_takes_fallback_when_primary_is_slow() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                ("primary".into(), delayed(&primary_calls, 5_000, "slow")),
+                ("fallback".into(), delayed(&fallback_calls, 0, "fast")),
+            ],
+            0,
+            1,
+        )
+        .with_hedging(95.0, Duration::from_millis(20));
+
+        let started = Instant::now();
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(result, "fast");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn hedging_does_not_fire_when_primary_is_fast() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                ("primary".into(), delayed(&primary_calls, 0, "primary")),
+                ("fallback".into(), delayed(&fallback_calls, 0, "fallback")),
+            ],
+            0,
+            1,
+        )
+        .with_hedging(95.0, Duration::from_secs(5));
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(result, "primary");
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn open_circuit_skips_failing_primary() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let health = Arc::new(ProviderHealth::new(2, Duration::from_secs(60)));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "500 primary down",
+                    }),
+                ),
+                ("fallback".into(), delayed(&fallback_calls, 0, "fallback")),
+            ],
+            1,
+            1,
+        )
+        .with_health(Arc::clone(&health));
+
+        assert_eq!(
+            provider.simple_chat("a", "test", 0.0).await.unwrap(),
+            "fallback"
+        );
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        // Its retry did not count as a second failure.
+        assert!(!health.is_open("primary", "test"));
+
+        // A second failed request opens the circuit, so primary is skipped.
+        health.record_failure("primary", "test");
+        assert_eq!(
+            provider.simple_chat("b", "test", 0.0).await.unwrap(),
+            "fallback"
+        );
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn client_errors_do_not_open_the_circuit() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let health = Arc::new(ProviderHealth::new(1, Duration::from_secs(60)));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "400 Bad Request: prompt too long",
+                    }),
+                ),
+                ("fallback".into(), delayed(&fallback_calls, 0, "fallback")),
+            ],
+            1,
+            1,
+        )
+        .with_health(Arc::clone(&health));
+
+        for message in ["a", "b"] {
+            assert_eq!(
+                provider.simple_chat(message, "test", 0.0).await.unwrap(),
+                "fallback"
+            );
+        }
+        assert!(!health.is_open("primary", "test"));
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn health_reorders_providers_within_a_model() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let health = Arc::new(ProviderHealth::new(0, Duration::from_secs(60)));
+        health.record_failure("primary", "test");
+        health.record_success("fallback", "test", Duration::from_millis(50));
+
+        let provider = ReliableProvider::new(
+            vec![
+                ("primary".into(), delayed(&primary_calls, 0, "primary")),
+                ("fallback".into(), delayed(&fallback_calls, 0, "fallback")),
+            ],
+            0,
+            1,
+        )
+        .with_health(health);
+
+        let result = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(result, "fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 0);
+    }
+
     // ── Arc<ModelAwareMock> Provider impl for test ──
 
     #[async_trait]
@@ -0,0 +1,545 @@
+//! Record/replay provider for deterministic, offline runs.
+//!
+//! In record mode [`ReplayProvider`] forwards every call to the wrapped
+//! provider and appends the exchange to a JSONL cassette. In replay mode it
+//! answers from the cassette, matching requests by fingerprint, and never
+//! touches the network — so full agent and channel flows can run in CI and a
+//! user's cassette reproduces their session exactly.
+
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ChatUsage, Provider, Reasoning, ResponseSchema,
+    ToolCall,
+};
+use crate::tools::ToolSpec;
+use anyhow::Context;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// One recorded provider exchange — a single line of the cassette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    /// Request fingerprint used for lookup in replay mode.
+    pub fingerprint: String,
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<ToolSpec>>,
+    /// Schema name for `generate_structured` calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ChatUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Reasoning>,
+    /// Whether the recorded provider used native tool calling. Replay reports
+    /// the same capability so the agent builds identical requests.
+    #[serde(default)]
+    pub native_tools: bool,
+}
+
+impl CassetteEntry {
+    fn response(&self) -> ChatResponse {
+        ChatResponse {
+            text: self.text.clone(),
+            tool_calls: self.tool_calls.clone(),
+            usage: self.usage,
+            reasoning: self.reasoning.clone(),
+        }
+    }
+}
+
+/// Stable fingerprint of a request: model, conversation, offered tools and,
+/// for structured calls, the response schema. Temperature is deliberately
+/// left out so replays do not depend on sampling settings.
+///
+/// System messages (which carry the host name, OS and current time) and
+/// `[Memory context]` blocks (which depend on what the memory backend holds)
+/// are excluded too, so a cassette replays on another machine or day.
+pub fn fingerprint(
+    request: ChatRequest<'_>,
+    schema: Option<&ResponseSchema>,
+    model: &str,
+) -> String {
+    let messages: Vec<serde_json::Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| {
+            let mut value = serde_json::to_value(m).unwrap_or_default();
+            value["content"] = without_memory_context(&m.content).into();
+            value
+        })
+        .collect();
+    let canonical = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "tools": request.tools.filter(|tools| !tools.is_empty()),
+        "schema": schema.map(|s| serde_json::json!({ "name": s.name, "schema": s.schema })),
+    });
+    let digest = Sha256::digest(canonical.to_string().as_bytes());
+    hex::encode(&digest[..16])
+}
+
+/// `content` with any injected `[Memory context]` block removed.
+fn without_memory_context(content: &str) -> String {
+    let Some(start) = content.find("[Memory context]\n") else {
+        return content.to_string();
+    };
+    let block = &content[start..];
+    let end = block.find("\n\n").map_or(content.len(), |i| start + i + 2);
+    format!("{}{}", &content[..start], &content[end..])
+}
+
+enum Backend {
+    Record {
+        inner: Box<dyn Provider>,
+        cassette: Mutex<File>,
+    },
+    Replay {
+        entries: Mutex<HashMap<String, VecDeque<CassetteEntry>>>,
+        native_tools: bool,
+    },
+}
+
+/// Provider that records exchanges to, or replays them from, a cassette.
+///
+/// Streaming is not forwarded: callers fall back to `chat`, which keeps
+/// recorded and replayed runs on the same code path.
+pub struct ReplayProvider {
+    backend: Backend,
+}
+
+impl ReplayProvider {
+    /// Wrap `inner`, appending every successful exchange to `path`.
+    pub fn record(inner: Box<dyn Provider>, path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open cassette {}", path.display()))?;
+        Ok(Self {
+            backend: Backend::Record {
+                inner,
+                cassette: Mutex::new(file),
+            },
+        })
+    }
+
+    /// Serve responses from the cassette at `path`.
+    pub fn replay(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open cassette {}", path.display()))?;
+        let mut entries = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CassetteEntry = serde_json::from_str(&line).with_context(|| {
+                format!("Invalid cassette entry at {}:{}", path.display(), index + 1)
+            })?;
+            entries.push(entry);
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Replay provider over in-memory entries, e.g. scripted test fixtures.
+    pub fn from_entries(entries: impl IntoIterator<Item = CassetteEntry>) -> Self {
+        let mut by_fingerprint: HashMap<String, VecDeque<CassetteEntry>> = HashMap::new();
+        let mut native_tools = false;
+        for entry in entries {
+            native_tools |= entry.native_tools;
+            by_fingerprint
+                .entry(entry.fingerprint.clone())
+                .or_default()
+                .push_back(entry);
+        }
+        Self {
+            backend: Backend::Replay {
+                entries: Mutex::new(by_fingerprint),
+                native_tools,
+            },
+        }
+    }
+
+    async fn exchange(
+        &self,
+        request: ChatRequest<'_>,
+        schema: Option<&ResponseSchema>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let fingerprint = fingerprint(request, schema, model);
+        match &self.backend {
+            Backend::Record { inner, cassette } => {
+                let response = match schema {
+                    Some(schema) => ChatResponse {
+                        text: Some(
+                            inner
+                                .generate_structured(request, schema, model, temperature)
+                                .await?,
+                        ),
+                        tool_calls: Vec::new(),
+                        usage: None,
+                        reasoning: None,
+                    },
+                    None => inner.chat(request, model, temperature).await?,
+                };
+                let entry = CassetteEntry {
+                    fingerprint,
+                    model: model.to_string(),
+                    messages: request.messages.to_vec(),
+                    tools: request
+                        .tools
+                        .filter(|tools| !tools.is_empty())
+                        .map(<[ToolSpec]>::to_vec),
+                    schema: schema.map(|s| s.name.clone()),
+                    text: response.text.clone(),
+                    tool_calls: response.tool_calls.clone(),
+                    usage: response.usage,
+                    reasoning: response.reasoning.clone(),
+                    native_tools: inner.supports_native_tools(),
+                };
+                let mut line = serde_json::to_string(&entry)?;
+                line.push('\n');
+                let mut file = cassette.lock();
+                file.write_all(line.as_bytes())?;
+                file.flush()?;
+                Ok(response)
+            }
+            Backend::Replay { entries, .. } => {
+                let mut entries = entries.lock();
+                // Repeated identical requests consume recordings in order; the
+                // last one keeps answering once the queue runs dry.
+                let entry = entries.get_mut(&fingerprint).and_then(|queue| {
+                    if queue.len() > 1 {
+                        queue.pop_front()
+                    } else {
+                        queue.front().cloned()
+                    }
+                });
+                let Some(entry) = entry else {
+                    let last = request
+                        .messages
+                        .last()
+                        .map(|m| crate::util::truncate_with_ellipsis(&m.content, 80))
+                        .unwrap_or_default();
+                    anyhow::bail!(
+                        "No recorded response for request {fingerprint} (model {model}, last message: {last:?}); re-record the cassette"
+                    );
+                };
+                Ok(entry.response())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = system_prompt {
+            messages.push(ChatMessage::system(system));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_with_history(&messages, model, temperature).await
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let request = ChatRequest {
+            messages,
+            tools: None,
+            reasoning: None,
+        };
+        let response = self.exchange(request, None, model, temperature).await?;
+        Ok(response.text.unwrap_or_default())
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.exchange(request, None, model, temperature).await
+    }
+
+    async fn generate_structured(
+        &self,
+        request: ChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let response = self
+            .exchange(request, Some(schema), model, temperature)
+            .await?;
+        Ok(response.text.unwrap_or_default())
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        match &self.backend {
+            Backend::Record { inner, .. } => inner.supports_native_tools(),
+            Backend::Replay { native_tools, .. } => *native_tools,
+        }
+    }
+
+    /// Replays answer image requests too: the cassette already holds the
+    /// recorded responses.
+    fn supports_vision(&self) -> bool {
+        match &self.backend {
+            Backend::Record { inner, .. } => inner.supports_vision(),
+            Backend::Replay { .. } => true,
+        }
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        match &self.backend {
+            Backend::Record { inner, .. } => inner.warmup().await,
+            Backend::Replay { .. } => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("reply {n} to {message}"))
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let text = self
+                .chat_with_history(request.messages, model, temperature)
+                .await?;
+            let tool_calls = if request.has_tools() {
+                vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "shell".into(),
+                    arguments: r#"{"command":"ls"}"#.into(),
+                }]
+            } else {
+                Vec::new()
+            };
+            Ok(ChatResponse {
+                text: Some(text),
+                tool_calls,
+                usage: Some(ChatUsage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    ..ChatUsage::default()
+                }),
+                reasoning: None,
+            })
+        }
+
+        fn supports_native_tools(&self) -> bool {
+            true
+        }
+    }
+
+    fn shell_tool() -> ToolSpec {
+        ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_cassette_replays_without_inner_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes/session.jsonl");
+        let calls = Arc::new(AtomicUsize::new(0));
+        let tools = vec![shell_tool()];
+        let messages = vec![ChatMessage::system("be brief"), ChatMessage::user("hi")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            reasoning: None,
+        };
+
+        let recorder = ReplayProvider::record(
+            Box::new(CountingProvider {
+                calls: Arc::clone(&calls),
+            }),
+            &path,
+        )
+        .unwrap();
+        let recorded = recorder.chat(request, "model-a", 0.7).await.unwrap();
+        let recorded_text = recorder
+            .chat_with_system(None, "ping", "model-a", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let replayer = ReplayProvider::replay(&path).unwrap();
+        assert!(replayer.supports_native_tools());
+        // Temperature is not part of the fingerprint.
+        let replayed = replayer.chat(request, "model-a", 0.0).await.unwrap();
+        assert_eq!(replayed.text, recorded.text);
+        assert_eq!(replayed.tool_calls.len(), 1);
+        assert_eq!(replayed.tool_calls[0].name, "shell");
+        assert_eq!(replayed.usage, recorded.usage);
+        assert_eq!(
+            replayer
+                .chat_with_system(None, "ping", "model-a", 0.0)
+                .await
+                .unwrap(),
+            recorded_text
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn replay_miss_reports_fingerprint() {
+        let replayer = ReplayProvider::from_entries(Vec::new());
+        let err = replayer
+            .chat_with_system(None, "unrecorded", "model-a", 0.0)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No recorded response"));
+        assert!(err.contains("unrecorded"));
+    }
+
+    #[tokio::test]
+    async fn repeated_requests_replay_in_recorded_order() {
+        let messages = vec![ChatMessage::user("again")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+        let entry = |text: &str| CassetteEntry {
+            fingerprint: fingerprint(request, None, "m"),
+            model: "m".into(),
+            messages: messages.clone(),
+            tools: None,
+            schema: None,
+            text: Some(text.into()),
+            tool_calls: Vec::new(),
+            usage: None,
+            reasoning: None,
+            native_tools: false,
+        };
+        let replayer = ReplayProvider::from_entries([entry("first"), entry("second")]);
+
+        for expected in ["first", "second", "second"] {
+            let response = replayer.chat(request, "m", 0.0).await.unwrap();
+            assert_eq!(response.text.as_deref(), Some(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn cassette_replays_under_a_different_system_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let recorded_on = vec![
+            ChatMessage::system("Host: build-01\nTime: 2026-01-01 09:00 UTC"),
+            ChatMessage::user("[Memory context]\n- editor: vim\n\nwhat is my editor?"),
+        ];
+        let recorder = ReplayProvider::record(
+            Box::new(CountingProvider {
+                calls: Arc::new(AtomicUsize::new(0)),
+            }),
+            &path,
+        )
+        .unwrap();
+        let recorded = recorder
+            .chat_with_history(&recorded_on, "m", 0.0)
+            .await
+            .unwrap();
+
+        let replayed_on = vec![
+            ChatMessage::system("Host: laptop\nTime: 2026-03-14 18:30 CET"),
+            ChatMessage::user("[Memory context]\n- editor: helix\n\nwhat is my editor?"),
+        ];
+        let replayer = ReplayProvider::replay(&path).unwrap();
+        let replayed = replayer
+            .chat_with_history(&replayed_on, "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(replayed, recorded);
+
+        let other_question = vec![ChatMessage::user("what is my shell?")];
+        assert!(replayer
+            .chat_with_history(&other_question, "m", 0.0)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn fingerprint_covers_model_tools_and_schema() {
+        let messages = vec![ChatMessage::user("hi")];
+        let tools = vec![shell_tool()];
+        let plain = ChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+        let with_tools = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            reasoning: None,
+        };
+        let no_tools = ChatRequest {
+            messages: &messages,
+            tools: Some(&[]),
+            reasoning: None,
+        };
+        let schema = ResponseSchema::new("answer", serde_json::json!({"type": "object"}));
+
+        let base = fingerprint(plain, None, "m");
+        assert_eq!(base, fingerprint(no_tools, None, "m"));
+        assert_ne!(base, fingerprint(plain, None, "other"));
+        assert_ne!(base, fingerprint(with_tools, None, "m"));
+        assert_ne!(base, fingerprint(plain, Some(&schema), "m"));
+    }
+}
@@ -1,13 +1,19 @@
-use super::traits::{ChatMessage, ChatRequest, ChatResponse};
+use super::capabilities::CapabilityRegistry;
+use super::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ChatStream, ReasoningOptions, ResponseSchema,
+};
 use super::Provider;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A single route: maps a task hint to a provider + model combo.
 #[derive(Debug, Clone)]
 pub struct Route {
     pub provider_name: String,
     pub model: String,
+    /// Reasoning controls applied to requests on this route.
+    pub reasoning: ReasoningOptions,
 }
 
 /// Multi-model router — routes requests to different provider+model combos
@@ -19,10 +25,11 @@ pub struct Route {
 ///
 /// This wraps multiple pre-created providers and selects the right one per request.
 pub struct RouterProvider {
-    routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
+    routes: HashMap<String, (usize, String, ReasoningOptions)>, // hint → (provider_index, model, reasoning)
     providers: Vec<(String, Box<dyn Provider>)>,
     default_index: usize,
     default_model: String,
+    capabilities: Arc<CapabilityRegistry>,
 }
 
 impl RouterProvider {
@@ -43,12 +50,12 @@ impl RouterProvider {
             .collect();
 
         // Resolve routes to provider indices
-        let resolved_routes: HashMap<String, (usize, String)> = routes
+        let resolved_routes: HashMap<String, (usize, String, ReasoningOptions)> = routes
             .into_iter()
             .filter_map(|(hint, route)| {
                 let index = name_to_index.get(route.provider_name.as_str()).copied();
                 match index {
-                    Some(i) => Some((hint, (i, route.model))),
+                    Some(i) => Some((hint, (i, route.model, route.reasoning))),
                     None => {
                         tracing::warn!(
                             hint = hint,
@@ -66,9 +73,18 @@ impl RouterProvider {
             providers,
             default_index: 0,
             default_model,
+            capabilities: Arc::default(),
         }
     }
 
+    /// Consult `capabilities` when routing: routes whose model is known to
+    /// lack tool calling or vision are skipped for requests that need them.
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Arc<CapabilityRegistry>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
     /// Resolve a model parameter to a (provider, actual_model) pair.
     ///
     /// If the model starts with "hint:", look up the hint in the route table.
@@ -76,7 +92,7 @@ impl RouterProvider {
     /// Resolve a model parameter to a (provider_index, actual_model) pair.
     fn resolve(&self, model: &str) -> (usize, String) {
         if let Some(hint) = model.strip_prefix("hint:") {
-            if let Some((idx, resolved_model)) = self.routes.get(hint) {
+            if let Some((idx, resolved_model, _)) = self.routes.get(hint) {
                 return (*idx, resolved_model.clone());
             }
             tracing::warn!(
@@ -88,6 +104,57 @@ impl RouterProvider {
         // Not a hint or hint not found — use default provider with the model as-is
         (self.default_index, model.to_string())
     }
+
+    /// Reasoning controls configured for the route `model` names, if any.
+    fn route_reasoning(&self, model: &str) -> Option<ReasoningOptions> {
+        let hint = model.strip_prefix("hint:")?;
+        self.routes
+            .get(hint)
+            .map(|(_, _, reasoning)| *reasoning)
+            .filter(|reasoning| !reasoning.is_empty())
+    }
+
+    /// Like [`resolve`](Self::resolve), but falls back to the default provider
+    /// and model when the routed provider cannot serve the request. The
+    /// route's reasoning controls are applied unless the caller set its own.
+    fn resolve_for_request<'a>(
+        &self,
+        model: &str,
+        request: ChatRequest<'a>,
+    ) -> (usize, String, ChatRequest<'a>) {
+        let (idx, resolved_model) = self.resolve(model);
+        let routed = ChatRequest {
+            reasoning: request.reasoning.or_else(|| self.route_reasoning(model)),
+            ..request
+        };
+        if idx == self.default_index {
+            return (idx, resolved_model, routed);
+        }
+
+        let (provider_name, provider) = &self.providers[idx];
+        let caps = self.capabilities.lookup_for(provider_name, &resolved_model);
+        let missing = if request.has_tools()
+            && (!provider.supports_native_tools() || caps.native_tools == Some(false))
+        {
+            Some("native tool support")
+        } else if request.has_images()
+            && (!provider.supports_vision() || caps.vision == Some(false))
+        {
+            Some("vision support")
+        } else {
+            None
+        };
+        let Some(missing) = missing else {
+            return (idx, resolved_model, routed);
+        };
+
+        tracing::warn!(
+            provider = provider_name.as_str(),
+            model = resolved_model.as_str(),
+            "Routed model lacks {missing}, using default provider"
+        );
+        (self.default_index, self.default_model.clone(), request)
+    }
 }
 
 #[async_trait]
@@ -132,11 +199,25 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let (provider_idx, resolved_model, request) = self.resolve_for_request(model, request);
         let (_, provider) = &self.providers[provider_idx];
         provider.chat(request, &resolved_model, temperature).await
     }
 
+    async fn chat_structured(
+        &self,
+        request: ChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<serde_json::Value> {
+        let (provider_idx, resolved_model) = self.resolve(model);
+        let (_, provider) = &self.providers[provider_idx];
+        provider
+            .chat_structured(request, schema, &resolved_model, temperature)
+            .await
+    }
+
     fn supports_native_tools(&self) -> bool {
         self.providers
             .get(self.default_index)
@@ -144,6 +225,32 @@ impl Provider for RouterProvider {
             .unwrap_or(false)
     }
 
+    fn supports_vision(&self) -> bool {
+        self.providers
+            .get(self.default_index)
+            .is_some_and(|(_, p)| p.supports_vision())
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.providers
+            .get(self.default_index)
+            .map(|(_, p)| p.supports_streaming())
+            .unwrap_or(false)
+    }
+
+    async fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let (provider_idx, resolved_model, request) = self.resolve_for_request(model, request);
+        let (_, provider) = &self.providers[provider_idx];
+        provider
+            .stream_chat(request, &resolved_model, temperature)
+            .await
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up routed provider");
@@ -228,6 +335,7 @@ mod tests {
                     Route {
                         provider_name: provider_name.to_string(),
                         model: model.to_string(),
+                        reasoning: ReasoningOptions::default(),
                     },
                 )
             })
@@ -323,6 +431,34 @@ mod tests {
         assert_eq!(mocks[0].last_model(), "anthropic/claude-sonnet-4-20250514");
     }
 
+    #[tokio::test]
+    async fn stream_chat_routes_hint_to_correct_provider() {
+        let (router, mocks) = make_router(
+            vec![("fast", "fast-response"), ("smart", "smart-response")],
+            vec![("reasoning", "smart", "claude-opus")],
+        );
+
+        let messages = vec![ChatMessage::user("hello")];
+        let stream = router
+            .stream_chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                "hint:reasoning",
+                0.5,
+            )
+            .await
+            .unwrap();
+        let response = crate::providers::streaming::collect_stream(stream)
+            .await
+            .unwrap();
+        assert_eq!(response.text.as_deref(), Some("smart-response"));
+        assert_eq!(mocks[1].last_model(), "claude-opus");
+        assert_eq!(mocks[0].call_count(), 0);
+    }
+
     #[test]
     fn resolve_preserves_model_for_non_hints() {
         let (router, _) = make_router(vec![("default", "ok")], vec![]);
@@ -355,6 +491,167 @@ mod tests {
         assert!(!router.routes.contains_key("broken"));
     }
 
+    struct ToolCapableMock {
+        native: bool,
+        response: &'static str,
+    }
+
+    #[async_trait]
+    impl Provider for ToolCapableMock {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(self.response.to_string())
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            Ok(ChatResponse {
+                text: Some(format!("{}:{model}", self.response)),
+                tool_calls: vec![],
+                usage: None,
+                reasoning: None,
+            })
+        }
+
+        fn supports_native_tools(&self) -> bool {
+            self.native
+        }
+    }
+
+    fn tool_router(route_native: bool) -> RouterProvider {
+        RouterProvider::new(
+            vec![
+                (
+                    "default".into(),
+                    Box::new(ToolCapableMock {
+                        native: true,
+                        response: "default",
+                    }) as Box<dyn Provider>,
+                ),
+                (
+                    "local".into(),
+                    Box::new(ToolCapableMock {
+                        native: route_native,
+                        response: "local",
+                    }) as Box<dyn Provider>,
+                ),
+            ],
+            vec![(
+                "fast".into(),
+                Route {
+                    provider_name: "local".into(),
+                    model: "llama3".into(),
+                    reasoning: ReasoningOptions::default(),
+                },
+            )],
+            "default-model".into(),
+        )
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_falls_back_when_route_lacks_native_tools() {
+        let router = tool_router(false);
+        assert!(router.supports_native_tools());
+
+        let messages = vec![ChatMessage::user("hello")];
+        let tools = vec![crate::tools::ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let with_tools = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            reasoning: None,
+        };
+        let response = router.chat(with_tools, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("default:default-model"));
+
+        let without_tools = ChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+        let response = router.chat(without_tools, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("local:llama3"));
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_keeps_capable_route() {
+        let router = tool_router(true);
+        let messages = vec![ChatMessage::user("hello")];
+        let tools = vec![crate::tools::ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            reasoning: None,
+        };
+        let response = router.chat(request, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("local:llama3"));
+    }
+
+    #[tokio::test]
+    async fn capability_registry_gates_routes() {
+        let overrides = HashMap::from([(
+            "llama3".to_string(),
+            crate::config::ModelCapabilities {
+                native_tools: Some(false),
+                vision: Some(false),
+                ..Default::default()
+            },
+        )]);
+        let registry = CapabilityRegistry::new(overrides, HashMap::new(), Vec::new(), "default");
+        let router = tool_router(true).with_capabilities(Arc::new(registry));
+
+        let tools = vec![crate::tools::ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let text = vec![ChatMessage::user("hello")];
+        let request = ChatRequest {
+            messages: &text,
+            tools: Some(&tools),
+            reasoning: None,
+        };
+        let response = router.chat(request, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("default:default-model"));
+
+        let photo = vec![ChatMessage::with_image(
+            "what is this?",
+            "aGVsbG8=",
+            "image/png",
+        )];
+        let request = ChatRequest {
+            messages: &photo,
+            tools: None,
+            reasoning: None,
+        };
+        let response = router.chat(request, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("default:default-model"));
+
+        let request = ChatRequest {
+            messages: &text,
+            tools: None,
+            reasoning: None,
+        };
+        let response = router.chat(request, "hint:fast", 0.0).await.unwrap();
+        assert_eq!(response.text.as_deref(), Some("local:llama3"));
+    }
+
     #[tokio::test]
     async fn warmup_calls_all_providers() {
         let (router, _) = make_router(vec![("a", "ok"), ("b", "ok")], vec![]);
@@ -382,4 +679,58 @@ mod tests {
         assert_eq!(result, "response");
         assert_eq!(mock.call_count(), 1);
     }
+
+    #[test]
+    fn route_reasoning_applies_unless_caller_sets_its_own() {
+        let router = RouterProvider::new(
+            vec![
+                (
+                    "default".into(),
+                    Box::new(MockProvider::new("a")) as Box<dyn Provider>,
+                ),
+                (
+                    "smart".into(),
+                    Box::new(MockProvider::new("b")) as Box<dyn Provider>,
+                ),
+            ],
+            vec![(
+                "deep".into(),
+                Route {
+                    provider_name: "smart".into(),
+                    model: "claude-opus".into(),
+                    reasoning: ReasoningOptions {
+                        budget_tokens: Some(8000),
+                        effort: None,
+                    },
+                },
+            )],
+            "default-model".into(),
+        );
+        let messages = vec![ChatMessage::user("hi")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            reasoning: None,
+        };
+
+        let (idx, model, routed) = router.resolve_for_request("hint:deep", request);
+        assert_eq!((idx, model.as_str()), (1, "claude-opus"));
+        assert_eq!(routed.reasoning.unwrap().budget_tokens, Some(8000));
+
+        let own = ReasoningOptions {
+            budget_tokens: Some(2000),
+            effort: None,
+        };
+        let (_, _, routed) = router.resolve_for_request(
+            "hint:deep",
+            ChatRequest {
+                reasoning: Some(own),
+                ..request
+            },
+        );
+        assert_eq!(routed.reasoning, Some(own));
+
+        let (_, _, routed) = router.resolve_for_request("other-model", request);
+        assert!(routed.reasoning.is_none());
+    }
 }
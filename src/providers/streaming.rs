@@ -0,0 +1,616 @@
+//! Shared plumbing for streaming chat responses.
+//!
+//! Backends deliver deltas either as Server-Sent Events (Anthropic, OpenAI and
+//! compatible APIs) or as newline-delimited JSON (Ollama). The decoders here
+//! turn a raw byte stream into payload strings, and [`StreamAccumulator`]
+//! folds the resulting [`StreamEvent`]s back into a [`ChatResponse`].
+
+use super::traits::{ChatResponse, ChatStream, ChatUsage, Reasoning, StreamEvent, ToolCall};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// How payloads are framed inside the response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// `data: ...` lines separated by blank lines; `[DONE]` ends the stream.
+    Sse,
+    /// One JSON document per line.
+    NdJson,
+}
+
+/// Incremental frame decoder. Buffers raw bytes so multi-byte UTF-8
+/// sequences and lines split across network chunks are reassembled.
+#[derive(Debug)]
+pub struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+    data_lines: Vec<String>,
+}
+
+impl FrameDecoder {
+    pub fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            buffer: Vec::new(),
+            data_lines: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of bytes and return every payload completed by it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line_bytes: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line_bytes);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(payload) = self.handle_line(line) {
+                payloads.push(payload);
+            }
+        }
+
+        payloads
+    }
+
+    /// Flush whatever is left once the body has ended.
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut payloads = Vec::new();
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            let line = String::from_utf8_lossy(&rest).trim_end().to_string();
+            if let Some(payload) = self.handle_line(&line) {
+                payloads.push(payload);
+            }
+        }
+        if let Some(payload) = self.handle_line("") {
+            payloads.push(payload);
+        }
+        payloads
+    }
+
+    fn handle_line(&mut self, line: &str) -> Option<String> {
+        match self.framing {
+            Framing::NdJson => {
+                let trimmed = line.trim();
+                (!trimmed.is_empty()).then(|| trimmed.to_string())
+            }
+            Framing::Sse => {
+                if line.is_empty() {
+                    if self.data_lines.is_empty() {
+                        return None;
+                    }
+                    return Some(std::mem::take(&mut self.data_lines).join("\n"));
+                }
+                if let Some(data) = line.strip_prefix("data:") {
+                    self.data_lines
+                        .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+                }
+                // `event:`, `id:`, `retry:` and `:` comments carry nothing we need —
+                // every backend we speak repeats the event type inside the JSON.
+                None
+            }
+        }
+    }
+}
+
+struct DecodeState<S, F> {
+    bytes: std::pin::Pin<Box<S>>,
+    decoder: FrameDecoder,
+    map: F,
+    pending: VecDeque<anyhow::Result<StreamEvent>>,
+    finished: bool,
+}
+
+impl<S, F> DecodeState<S, F>
+where
+    F: FnMut(&str) -> anyhow::Result<Vec<StreamEvent>>,
+{
+    fn push_payloads(&mut self, payloads: Vec<String>) {
+        for payload in payloads {
+            if self.finished {
+                return;
+            }
+            if self.decoder.framing == Framing::Sse && payload.trim() == "[DONE]" {
+                self.finished = true;
+                return;
+            }
+            match (self.map)(&payload) {
+                Ok(events) => self.pending.extend(events.into_iter().map(Ok)),
+                Err(e) => {
+                    self.pending.push_back(Err(e));
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+/// Decode a byte stream into chat deltas. `map` converts one framed payload
+/// into zero or more events; returning an error terminates the stream.
+pub fn decode_stream<S, B, E, F>(bytes: S, framing: Framing, map: F) -> ChatStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]>,
+    E: Into<anyhow::Error>,
+    F: FnMut(&str) -> anyhow::Result<Vec<StreamEvent>> + Send + 'static,
+{
+    let state = DecodeState {
+        bytes: Box::pin(bytes),
+        decoder: FrameDecoder::new(framing),
+        map,
+        pending: VecDeque::new(),
+        finished: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(item) = state.pending.pop_front() {
+                return Some((item, state));
+            }
+            if state.finished {
+                return None;
+            }
+            match state.bytes.next().await {
+                Some(Ok(chunk)) => {
+                    let payloads = state.decoder.feed(chunk.as_ref());
+                    state.push_payloads(payloads);
+                }
+                Some(Err(e)) => {
+                    state.pending.push_back(Err(e.into()));
+                    state.finished = true;
+                }
+                None => {
+                    let payloads = state.decoder.finish();
+                    state.push_payloads(payloads);
+                    state.finished = true;
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Decode a streaming HTTP response body.
+pub fn decode_response<F>(response: reqwest::Response, framing: Framing, map: F) -> ChatStream
+where
+    F: FnMut(&str) -> anyhow::Result<Vec<StreamEvent>> + Send + 'static,
+{
+    decode_stream(response.bytes_stream(), framing, map)
+}
+
+/// Replay a complete response as a single burst of deltas, for backends
+/// that cannot stream a particular request.
+pub fn replay_response(response: ChatResponse) -> ChatStream {
+    let mut events = Vec::new();
+    if let Some(reasoning) = response.reasoning {
+        if !reasoning.text.is_empty() {
+            events.push(Ok(StreamEvent::ReasoningDelta(reasoning.text)));
+        }
+        events.extend(
+            reasoning
+                .blocks
+                .into_iter()
+                .map(|b| Ok(StreamEvent::ReasoningBlock(b))),
+        );
+    }
+    if let Some(text) = response.text.filter(|t| !t.is_empty()) {
+        events.push(Ok(StreamEvent::TextDelta(text)));
+    }
+    for (index, call) in response.tool_calls.into_iter().enumerate() {
+        events.push(Ok(StreamEvent::ToolCallDelta {
+            index,
+            id: Some(call.id),
+            name: Some(call.name),
+            arguments: call.arguments,
+        }));
+    }
+    if let Some(usage) = response.usage {
+        events.push(Ok(StreamEvent::Usage(usage)));
+    }
+    stream::iter(events).boxed()
+}
+
+// ── OpenAI-style `chat.completion.chunk` payloads ───────────────
+
+/// `stream_options` request field asking for a final usage chunk.
+#[derive(Debug, Serialize)]
+pub struct OpenAiStreamOptions {
+    pub include_usage: bool,
+}
+
+/// `usage` object of OpenAI-format responses. Also sent as the final stream
+/// chunk when the request sets `stream_options.include_usage`.
+#[derive(Debug, Deserialize)]
+pub struct OpenAiUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+    #[serde(default)]
+    prompt_tokens_details: Option<OpenAiPromptTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiPromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u64,
+}
+
+impl From<OpenAiUsage> for ChatUsage {
+    fn from(usage: OpenAiUsage) -> Self {
+        Self {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            cached_input_tokens: usage
+                .prompt_tokens_details
+                .map_or(0, |details| details.cached_tokens),
+            cache_write_input_tokens: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChunk {
+    #[serde(default)]
+    choices: Vec<OpenAiChunkChoice>,
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChunkChoice {
+    #[serde(default)]
+    delta: Option<OpenAiChunkDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    /// Reasoning text (OpenRouter).
+    #[serde(default)]
+    reasoning: Option<String>,
+    /// Reasoning text (DeepSeek and other compatible servers).
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<OpenAiChunkToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChunkToolCall {
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<OpenAiChunkFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChunkFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// Parse one SSE payload from an OpenAI-compatible `/chat/completions`
+/// stream. Shared by the OpenAI, OpenRouter and compatible backends.
+pub fn parse_openai_chunk(payload: &str) -> anyhow::Result<Vec<StreamEvent>> {
+    let chunk: OpenAiChunk = serde_json::from_str(payload)
+        .map_err(|e| anyhow::anyhow!("Malformed stream chunk: {e}"))?;
+
+    if let Some(error) = chunk.error {
+        let message = error
+            .get("message")
+            .and_then(serde_json::Value::as_str)
+            .map_or_else(|| error.to_string(), ToString::to_string);
+        anyhow::bail!("Stream error: {}", super::sanitize_api_error(&message));
+    }
+
+    let mut events = Vec::new();
+    for choice in chunk.choices {
+        let Some(delta) = choice.delta else {
+            continue;
+        };
+        if let Some(text) = delta
+            .reasoning
+            .or(delta.reasoning_content)
+            .filter(|t| !t.is_empty())
+        {
+            events.push(StreamEvent::ReasoningDelta(text));
+        }
+        if let Some(text) = delta.content.filter(|t| !t.is_empty()) {
+            events.push(StreamEvent::TextDelta(text));
+        }
+        for (position, call) in delta.tool_calls.unwrap_or_default().into_iter().enumerate() {
+            let (name, arguments) = call
+                .function
+                .map(|f| (f.name, f.arguments.unwrap_or_default()))
+                .unwrap_or_default();
+            events.push(StreamEvent::ToolCallDelta {
+                index: call.index.unwrap_or(position),
+                id: call.id,
+                name,
+                arguments,
+            });
+        }
+    }
+    if let Some(usage) = chunk.usage {
+        events.push(StreamEvent::Usage(usage.into()));
+    }
+    Ok(events)
+}
+
+// ── Accumulation ────────────────────────────────────────────────
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+/// Folds stream deltas into a complete [`ChatResponse`].
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    text: String,
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+    usage: Option<ChatUsage>,
+    reasoning: Reasoning,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::TextDelta(delta) => self.text.push_str(delta),
+            StreamEvent::ToolCallDelta {
+                index,
+                id,
+                name,
+                arguments,
+            } => {
+                let call = self.tool_calls.entry(*index).or_default();
+                if call.id.is_none() {
+                    call.id.clone_from(id);
+                }
+                if let Some(name) = name {
+                    call.name.push_str(name);
+                }
+                call.arguments.push_str(arguments);
+            }
+            StreamEvent::Usage(usage) => match self.usage.as_mut() {
+                Some(existing) => existing.merge(usage),
+                None => self.usage = Some(*usage),
+            },
+            StreamEvent::ReasoningDelta(delta) => self.reasoning.text.push_str(delta),
+            StreamEvent::ReasoningBlock(block) => self.reasoning.blocks.push(block.clone()),
+        }
+    }
+
+    /// Text received so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn finish(self) -> ChatResponse {
+        let tool_calls = self
+            .tool_calls
+            .into_values()
+            .filter(|call| !call.name.is_empty())
+            .map(|call| ToolCall {
+                id: call.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                name: call.name,
+                arguments: if call.arguments.trim().is_empty() {
+                    "{}".to_string()
+                } else {
+                    call.arguments
+                },
+            })
+            .collect();
+
+        ChatResponse {
+            text: if self.text.is_empty() {
+                None
+            } else {
+                Some(self.text)
+            },
+            tool_calls,
+            usage: self.usage,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+        }
+    }
+}
+
+/// Drain a stream into a complete response.
+pub async fn collect_stream(mut stream: ChatStream) -> anyhow::Result<ChatResponse> {
+    let mut accumulator = StreamAccumulator::new();
+    while let Some(event) = stream.next().await {
+        accumulator.push(&event?);
+    }
+    Ok(accumulator.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(parts: &[&str]) -> impl Stream<Item = Result<Vec<u8>, std::io::Error>> {
+        let owned: Vec<_> = parts.iter().map(|p| Ok(p.as_bytes().to_vec())).collect();
+        stream::iter(owned)
+    }
+
+    #[test]
+    fn sse_decoder_joins_split_lines() {
+        let mut decoder = FrameDecoder::new(Framing::Sse);
+        assert!(decoder.feed(b"data: {\"a\"").is_empty());
+        let payloads = decoder.feed(b":1}\n\n");
+        assert_eq!(payloads, vec!["{\"a\":1}"]);
+    }
+
+    #[test]
+    fn sse_decoder_ignores_event_and_comment_lines() {
+        let mut decoder = FrameDecoder::new(Framing::Sse);
+        let payloads = decoder.feed(b": keepalive\n\nevent: ping\ndata: {}\r\n\r\n");
+        assert_eq!(payloads, vec!["{}"]);
+    }
+
+    #[test]
+    fn sse_decoder_handles_utf8_split_across_chunks() {
+        let mut decoder = FrameDecoder::new(Framing::Sse);
+        let bytes = "data: 안녕\n\n".as_bytes();
+        let (a, b) = bytes.split_at(8);
+        assert!(decoder.feed(a).is_empty());
+        assert_eq!(decoder.feed(b), vec!["안녕"]);
+    }
+
+    #[test]
+    fn ndjson_decoder_flushes_trailing_line() {
+        let mut decoder = FrameDecoder::new(Framing::NdJson);
+        assert_eq!(decoder.feed(b"{\"a\":1}\n{\"b\""), vec!["{\"a\":1}"]);
+        assert!(decoder.feed(b":2}").is_empty());
+        assert_eq!(decoder.finish(), vec!["{\"b\":2}"]);
+    }
+
+    #[test]
+    fn parse_openai_chunk_text_and_tool_calls() {
+        let events = parse_openai_chunk(
+            r#"{"choices":[{"delta":{"content":"Hi","tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"shell","arguments":"{\"co"}}]}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::TextDelta("Hi".into()),
+                StreamEvent::ToolCallDelta {
+                    index: 0,
+                    id: Some("call_1".into()),
+                    name: Some("shell".into()),
+                    arguments: "{\"co".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_openai_chunk_skips_empty_deltas() {
+        let events = parse_openai_chunk(r#"{"choices":[{"delta":{"role":"assistant"}}]}"#).unwrap();
+        assert!(events.is_empty());
+        let events = parse_openai_chunk(r#"{"choices":[]}"#).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn parse_openai_chunk_reads_final_usage() {
+        let events = parse_openai_chunk(
+            r#"{"choices":[],"usage":{"prompt_tokens":50,"completion_tokens":7,"prompt_tokens_details":{"cached_tokens":32}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![StreamEvent::Usage(ChatUsage {
+                input_tokens: 50,
+                output_tokens: 7,
+                cached_input_tokens: 32,
+                cache_write_input_tokens: 0,
+            })]
+        );
+    }
+
+    #[test]
+    fn parse_openai_chunk_surfaces_stream_errors() {
+        let err = parse_openai_chunk(r#"{"error":{"message":"overloaded"}}"#).unwrap_err();
+        assert!(err.to_string().contains("overloaded"));
+    }
+
+    #[test]
+    fn accumulator_assembles_partial_tool_calls() {
+        let mut acc = StreamAccumulator::new();
+        acc.push(&StreamEvent::TextDelta("Let me ".into()));
+        acc.push(&StreamEvent::TextDelta("check".into()));
+        acc.push(&StreamEvent::ToolCallDelta {
+            index: 1,
+            id: Some("b".into()),
+            name: Some("file_read".into()),
+            arguments: String::new(),
+        });
+        acc.push(&StreamEvent::ToolCallDelta {
+            index: 0,
+            id: Some("a".into()),
+            name: Some("shell".into()),
+            arguments: "{\"command\":".into(),
+        });
+        acc.push(&StreamEvent::ToolCallDelta {
+            index: 0,
+            id: None,
+            name: None,
+            arguments: "\"date\"}".into(),
+        });
+
+        let response = acc.finish();
+        assert_eq!(response.text.as_deref(), Some("Let me check"));
+        assert_eq!(response.tool_calls.len(), 2);
+        assert_eq!(response.tool_calls[0].id, "a");
+        assert_eq!(response.tool_calls[0].arguments, "{\"command\":\"date\"}");
+        assert_eq!(response.tool_calls[1].name, "file_read");
+        assert_eq!(response.tool_calls[1].arguments, "{}");
+    }
+
+    #[tokio::test]
+    async fn decode_stream_stops_at_done_marker() {
+        let body = chunks(&[
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\ndata: [DONE]\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"ignored\"}}]}\n\n",
+        ]);
+        let response = collect_stream(decode_stream(body, Framing::Sse, parse_openai_chunk))
+            .await
+            .unwrap();
+        assert_eq!(response.text.as_deref(), Some("Hello"));
+    }
+
+    #[tokio::test]
+    async fn decode_stream_propagates_map_errors() {
+        let body = chunks(&["data: not json\n\n"]);
+        let mut stream = decode_stream(body, Framing::Sse, parse_openai_chunk);
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[test]
+    fn accumulator_collects_reasoning() {
+        let events = parse_openai_chunk(
+            r#"{"choices":[{"delta":{"reasoning_content":"Think first.","content":"Done"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::ReasoningDelta("Think first.".into()),
+                StreamEvent::TextDelta("Done".into()),
+            ]
+        );
+
+        let mut acc = StreamAccumulator::new();
+        for event in &events {
+            acc.push(event);
+        }
+        acc.push(&StreamEvent::ReasoningBlock(
+            serde_json::json!({"type": "thinking"}),
+        ));
+        let response = acc.finish();
+        let reasoning = response.reasoning.unwrap();
+        assert_eq!(reasoning.text, "Think first.");
+        assert_eq!(reasoning.blocks.len(), 1);
+        assert_eq!(response.text.as_deref(), Some("Done"));
+    }
+}
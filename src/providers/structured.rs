@@ -0,0 +1,485 @@
+//! Structured (JSON-schema) output shared by all providers.
+//!
+//! Backends with a native mode (OpenAI `response_format`, Anthropic forced
+//! tool use, Gemini `responseSchema`, Ollama `format`) produce candidates via
+//! [`Provider::generate_structured`]; everyone else gets the schema in the
+//! system prompt. Either way, [`generate_validated`] checks each candidate
+//! against the schema and feeds validation errors back to the model until it
+//! produces a conforming value or the attempt budget runs out.
+
+use crate::providers::traits::{ChatMessage, ChatRequest, Provider, ResponseSchema};
+use serde_json::Value;
+
+/// Total candidates requested before giving up on a schema.
+pub const MAX_STRUCTURED_ATTEMPTS: usize = 3;
+
+/// Ask `provider` for a value matching `schema`, retrying with the validation
+/// error until it conforms.
+pub async fn generate_validated<P: Provider + ?Sized>(
+    provider: &P,
+    request: ChatRequest<'_>,
+    schema: &ResponseSchema,
+    model: &str,
+    temperature: f64,
+) -> anyhow::Result<Value> {
+    let mut messages = request.messages.to_vec();
+    let mut last_error = String::new();
+
+    for _ in 0..MAX_STRUCTURED_ATTEMPTS {
+        let raw = provider
+            .generate_structured(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: request.reasoning,
+                },
+                schema,
+                model,
+                temperature,
+            )
+            .await?;
+
+        let error = match extract_json(&raw) {
+            Some(value) => match validate(&schema.schema, &value) {
+                Ok(()) => return Ok(value),
+                Err(e) => e,
+            },
+            None => "response is not valid JSON".to_string(),
+        };
+
+        tracing::debug!(
+            schema = schema.name.as_str(),
+            "Structured output rejected: {error}"
+        );
+        messages.push(ChatMessage::assistant(raw));
+        messages.push(ChatMessage::user(format!(
+            "That response does not match the required JSON Schema: {error}. \
+             Reply again with only the corrected JSON value."
+        )));
+        last_error = error;
+    }
+
+    anyhow::bail!(
+        "Structured output for schema '{}' failed validation after {MAX_STRUCTURED_ATTEMPTS} attempts: {last_error}",
+        schema.name
+    )
+}
+
+/// Copy `messages` with instructions to answer in JSON matching `schema`
+/// merged into the system prompt. Used by providers without a native mode.
+pub fn with_schema_instructions(
+    messages: &[ChatMessage],
+    schema: &ResponseSchema,
+) -> Vec<ChatMessage> {
+    let schema_json =
+        serde_json::to_string_pretty(&schema.schema).unwrap_or_else(|_| schema.schema.to_string());
+    let mut instructions = String::from(
+        "Respond with a single JSON value that conforms to the JSON Schema below. \
+         Output only the JSON: no prose, no Markdown code fences.",
+    );
+    if !schema.description.is_empty() {
+        instructions.push_str("\n\nThe value describes: ");
+        instructions.push_str(&schema.description);
+    }
+    instructions.push_str("\n\nSchema:\n");
+    instructions.push_str(&schema_json);
+
+    let mut out = messages.to_vec();
+    match out.iter_mut().find(|m| m.role == "system") {
+        Some(system) => {
+            system.content.push_str("\n\n");
+            system.content.push_str(&instructions);
+        }
+        None => out.insert(0, ChatMessage::system(instructions)),
+    }
+    out
+}
+
+/// Parse a JSON value out of model output, tolerating surrounding prose and
+/// Markdown code fences.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .map(str::trim);
+    if let Some(value) = unfenced.and_then(|inner| serde_json::from_str(inner).ok()) {
+        return Some(value);
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let close = if trimmed[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = trimmed.rfind(close)?;
+    (end > start)
+        .then(|| serde_json::from_str(&trimmed[start..=end]).ok())
+        .flatten()
+}
+
+/// Validate `value` against `schema`.
+///
+/// Covers the keywords structured-output schemas use in practice: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties`,
+/// `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, numeric bounds
+/// and `anyOf`/`oneOf`/`allOf`. `$ref`, `pattern` and `format` are ignored.
+pub fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    validate_at(schema, value, "$")
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let rules = match schema {
+        Value::Object(rules) => rules,
+        Value::Bool(false) => return Err(format!("{path}: no value is allowed here")),
+        // `true` and non-schema values accept anything.
+        _ => return Ok(()),
+    };
+
+    if let Some(expected) = rules.get("const") {
+        if value != expected {
+            return Err(format!("{path}: expected {expected}"));
+        }
+    }
+    if let Some(options) = rules.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return Err(format!(
+                "{path}: {value} is not one of {}",
+                Value::from(options.clone())
+            ));
+        }
+    }
+    if let Some(ty) = rules.get("type") {
+        let allowed: Vec<&str> = match ty {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| matches_type(value, t)) {
+            return Err(format!(
+                "{path}: expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(all) = rules.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            validate_at(sub, value, path)?;
+        }
+    }
+    if let Some(any) = rules.get("anyOf").and_then(Value::as_array) {
+        if !any.iter().any(|sub| validate_at(sub, value, path).is_ok()) {
+            return Err(format!("{path}: does not match any allowed schema"));
+        }
+    }
+    if let Some(one) = rules.get("oneOf").and_then(Value::as_array) {
+        let matches = one
+            .iter()
+            .filter(|sub| validate_at(sub, value, path).is_ok())
+            .count();
+        if matches != 1 {
+            return Err(format!(
+                "{path}: must match exactly one schema, matched {matches}"
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = rules.get("properties").and_then(Value::as_object);
+            if let Some(required) = rules.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        return Err(format!("{path}: missing required property '{key}'"));
+                    }
+                }
+            }
+            for (key, item) in map {
+                let item_path = format!("{path}.{key}");
+                match properties.and_then(|p| p.get(key)) {
+                    Some(sub) => validate_at(sub, item, &item_path)?,
+                    None => match rules.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{path}: unexpected property '{key}'"));
+                        }
+                        Some(sub @ Value::Object(_)) => validate_at(sub, item, &item_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = rules.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    return Err(format!("{path}: expected at least {min} items"));
+                }
+            }
+            if let Some(max) = rules.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    return Err(format!("{path}: expected at most {max} items"));
+                }
+            }
+            if let Some(sub) = rules.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(sub, item, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = rules.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    return Err(format!("{path}: shorter than {min} characters"));
+                }
+            }
+            if let Some(max) = rules.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    return Err(format!("{path}: longer than {max} characters"));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            let bound = |key: &str| rules.get(key).and_then(Value::as_f64);
+            if bound("minimum").is_some_and(|min| n < min)
+                || bound("exclusiveMinimum").is_some_and(|min| n <= min)
+            {
+                return Err(format!("{path}: {n} is below the minimum"));
+            }
+            if bound("maximum").is_some_and(|max| n > max)
+                || bound("exclusiveMaximum").is_some_and(|max| n >= max)
+            {
+                return Err(format!("{path}: {n} is above the maximum"));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn matches_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::ChatResponse;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn subject_schema() -> ResponseSchema {
+        ResponseSchema::new(
+            "subject",
+            json!({
+                "type": "object",
+                "properties": {
+                    "subject": {"type": "string", "maxLength": 20},
+                    "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 3},
+                    "priority": {"enum": ["low", "high"]}
+                },
+                "required": ["subject"],
+                "additionalProperties": false
+            }),
+        )
+    }
+
+    #[test]
+    fn validate_accepts_conforming_value() {
+        let schema = subject_schema().schema;
+        let value = json!({"subject": "Install neomutt", "tags": ["mail"], "priority": "low"});
+        assert!(validate(&schema, &value).is_ok());
+    }
+
+    #[test]
+    fn validate_reports_path_of_first_violation() {
+        let schema = subject_schema().schema;
+
+        let err = validate(&schema, &json!({"tags": []})).unwrap_err();
+        assert!(err.contains("missing required property 'subject'"), "{err}");
+
+        let err = validate(&schema, &json!({"subject": "x", "tags": [1]})).unwrap_err();
+        assert!(err.starts_with("$.tags[0]: expected string"), "{err}");
+
+        let err = validate(&schema, &json!({"subject": "x", "extra": true})).unwrap_err();
+        assert!(err.contains("unexpected property 'extra'"), "{err}");
+
+        let err = validate(&schema, &json!({"subject": "x", "priority": "urgent"})).unwrap_err();
+        assert!(err.contains("is not one of"), "{err}");
+
+        assert!(validate(&schema, &json!({"subject": "a very long subject line"})).is_err());
+    }
+
+    #[test]
+    fn validate_handles_integers_and_combinators() {
+        let schema = json!({"anyOf": [{"type": "integer", "minimum": 1}, {"type": "null"}]});
+        assert!(validate(&schema, &json!(3)).is_ok());
+        assert!(validate(&schema, &json!(null)).is_ok());
+        assert!(validate(&schema, &json!(0)).is_err());
+        assert!(validate(&schema, &json!(1.5)).is_err());
+    }
+
+    #[test]
+    fn extract_json_tolerates_fences_and_prose() {
+        assert_eq!(extract_json(r#"{"a":1}"#), Some(json!({"a": 1})));
+        assert_eq!(
+            extract_json("```json\n{\"a\": 1}\n```"),
+            Some(json!({"a": 1}))
+        );
+        assert_eq!(
+            extract_json("Here you go: [1, 2] hope that helps"),
+            Some(json!([1, 2]))
+        );
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn schema_instructions_extend_existing_system_prompt() {
+        let messages = vec![ChatMessage::system("Be brief"), ChatMessage::user("hi")];
+        let out = with_schema_instructions(&messages, &subject_schema());
+        assert_eq!(out.len(), 2);
+        assert!(out[0].content.starts_with("Be brief"));
+        assert!(out[0].content.contains("\"subject\""));
+
+        let out = with_schema_instructions(&[ChatMessage::user("hi")], &subject_schema());
+        assert_eq!(out[0].role, "system");
+        assert_eq!(out[1].role, "user");
+    }
+
+    /// Replays canned replies and records the history each call received.
+    struct ScriptedProvider {
+        replies: Mutex<Vec<&'static str>>,
+        seen: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(replies: &[&'static str]) -> Self {
+            Self {
+                replies: Mutex::new(replies.iter().rev().copied().collect()),
+                seen: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("structured output goes through chat()")
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            self.seen.lock().unwrap().push(request.messages.to_vec());
+            let reply = self.replies.lock().unwrap().pop().unwrap_or("{}");
+            Ok(ChatResponse {
+                text: Some(reply.to_string()),
+                tool_calls: Vec::new(),
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_structured_retries_with_validation_error() {
+        let provider = ScriptedProvider::new(&[
+            "Sure! Here is the subject.",
+            r#"{"subject": 42}"#,
+            r#"```json
+{"subject": "Mail setup"}
+```"#,
+        ]);
+        let messages = vec![ChatMessage::user("install neomutt")];
+
+        let value = provider
+            .chat_structured(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                &subject_schema(),
+                "model",
+                0.0,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(value, json!({"subject": "Mail setup"}));
+        let seen = provider.seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0][0].content.contains("JSON Schema"));
+        let feedback = &seen[2].last().unwrap().content;
+        assert!(
+            feedback.contains("$.subject: expected string"),
+            "{feedback}"
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_structured_gives_up_after_max_attempts() {
+        let provider = ScriptedProvider::new(&["nope", "still nope", "never"]);
+        let messages = vec![ChatMessage::user("hi")];
+
+        let err = provider
+            .chat_structured(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: None,
+                },
+                &subject_schema(),
+                "model",
+                0.0,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("failed validation after 3 attempts"));
+        assert_eq!(provider.seen.lock().unwrap().len(), MAX_STRUCTURED_ATTEMPTS);
+    }
+}
@@ -0,0 +1,578 @@
+//! Token counting for context budgeting and usage estimates.
+//!
+//! OpenAI, Llama 3 and Qwen models are counted exactly with their BPE
+//! vocabularies when the `tiktoken` files are available offline in
+//! `<workspace>/tokenizers/` (`cl100k_base.tiktoken`, `o200k_base.tiktoken`,
+//! `llama3.tiktoken` — Meta's `tokenizer.model` — and `qwen.tiktoken`).
+//! Everything else uses a heuristic calibrated per model family that counts
+//! Latin, CJK and other scripts separately, since a flat chars/4 rule
+//! undercounts CJK text by roughly 4x.
+//!
+//! DeepSeek ships only a Hugging Face `tokenizer.json` with its own
+//! pre-tokenizer, which this module does not read, so DeepSeek models always
+//! use the heuristic, calibrated to DeepSeek's published ratios.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Counts the tokens a model would see for a piece of text.
+pub trait Tokenizer: Send + Sync {
+    /// Identifier shown in diagnostics (e.g. `cl100k_base`, `heuristic:claude`).
+    fn name(&self) -> &str;
+
+    fn count(&self, text: &str) -> usize;
+}
+
+// ── Heuristic ───────────────────────────────────────────────────
+
+/// Script-aware estimate. Ratios were calibrated against the reference
+/// tokenizers on mixed English, code and CJK chat transcripts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeuristicTokenizer {
+    pub name: &'static str,
+    /// ASCII characters (English prose, code, whitespace) per token.
+    pub ascii_chars_per_token: f64,
+    /// Tokens per CJK character (Han, kana, Hangul, fullwidth forms).
+    pub cjk_tokens_per_char: f64,
+    /// Other non-ASCII characters (accented Latin, Cyrillic, emoji...) per token.
+    pub other_chars_per_token: f64,
+}
+
+impl HeuristicTokenizer {
+    /// Conservative default for models of unknown family.
+    pub const GENERIC: Self = Self {
+        name: "heuristic",
+        ascii_chars_per_token: 3.6,
+        cjk_tokens_per_char: 1.3,
+        other_chars_per_token: 2.2,
+    };
+
+    pub const CLAUDE: Self = Self {
+        name: "heuristic:claude",
+        ascii_chars_per_token: 3.5,
+        cjk_tokens_per_char: 1.4,
+        other_chars_per_token: 2.0,
+    };
+
+    /// GPT and Llama 3 (both use large tiktoken-style vocabularies).
+    pub const OPENAI: Self = Self {
+        name: "heuristic:openai",
+        ascii_chars_per_token: 4.0,
+        cjk_tokens_per_char: 1.0,
+        other_chars_per_token: 2.8,
+    };
+
+    pub const GEMINI: Self = Self {
+        name: "heuristic:gemini",
+        ascii_chars_per_token: 4.2,
+        cjk_tokens_per_char: 0.9,
+        other_chars_per_token: 3.0,
+    };
+
+    /// Qwen, whose vocabulary is trained heavily on Chinese.
+    pub const QWEN: Self = Self {
+        name: "heuristic:qwen",
+        ascii_chars_per_token: 3.8,
+        cjk_tokens_per_char: 0.7,
+        other_chars_per_token: 2.8,
+    };
+
+    /// DeepSeek, per its published ratios: about 0.3 tokens per English
+    /// character and 0.6 per Chinese character.
+    pub const DEEPSEEK: Self = Self {
+        name: "heuristic:deepseek",
+        ascii_chars_per_token: 3.3,
+        cjk_tokens_per_char: 0.6,
+        other_chars_per_token: 2.5,
+    };
+
+    /// Preset for `model`'s family.
+    pub fn for_model(model: &str) -> Self {
+        let base = model
+            .rsplit('/')
+            .next()
+            .unwrap_or(model)
+            .to_ascii_lowercase();
+        if base.starts_with("claude") {
+            Self::CLAUDE
+        } else if base.starts_with("gemini") || base.starts_with("gemma") {
+            Self::GEMINI
+        } else if base.starts_with("deepseek") {
+            Self::DEEPSEEK
+        } else if base.starts_with("qwen") {
+            Self::QWEN
+        } else if ["gpt-", "o1", "o3", "o4", "llama3", "llama-3"]
+            .iter()
+            .any(|prefix| base.starts_with(prefix))
+        {
+            Self::OPENAI
+        } else {
+            Self::GENERIC
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        u32::from(c),
+        0x1100..=0x11FF
+            | 0x3000..=0x30FF
+            | 0x3130..=0x318F
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xAC00..=0xD7AF
+            | 0xF900..=0xFAFF
+            | 0xFF00..=0xFFEF
+            | 0x20000..=0x2FFFF
+    )
+}
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn count(&self, text: &str) -> usize {
+        let (mut ascii, mut cjk, mut other) = (0usize, 0usize, 0usize);
+        for c in text.chars() {
+            if c.is_ascii() {
+                ascii += 1;
+            } else if is_cjk(c) {
+                cjk += 1;
+            } else {
+                other += 1;
+            }
+        }
+        let tokens = ascii as f64 / self.ascii_chars_per_token
+            + cjk as f64 * self.cjk_tokens_per_char
+            + other as f64 / self.other_chars_per_token;
+        tokens.ceil() as usize
+    }
+}
+
+// ── BPE ─────────────────────────────────────────────────────────
+
+/// Byte-level BPE vocabularies in `tiktoken` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// GPT-4, GPT-3.5 and `text-embedding-3-*`.
+    Cl100k,
+    /// GPT-4o, GPT-4.1, GPT-5 and the o-series.
+    O200k,
+    /// Llama 3.x, which uses the cl100k pre-tokenizer with a larger vocabulary.
+    Llama3,
+    /// Qwen 1 to 3, which share one vocabulary.
+    Qwen,
+}
+
+// The upstream patterns end in `\s+(?!\S)|\s+`; the look-ahead is emulated
+// in `BpeTokenizer::pieces` since `regex` has no look-around.
+const CL100K_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+/// Like cl100k, but digits are split one at a time.
+const QWEN_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+const O200K_PATTERN: &str = r"[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]*[\p{Ll}\p{Lm}\p{Lo}\p{M}]+(?i:'s|'t|'re|'ve|'m|'ll|'d)?|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]+[\p{Ll}\p{Lm}\p{Lo}\p{M}]*(?i:'s|'t|'re|'ve|'m|'ll|'d)?|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+";
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cl100k => "cl100k_base",
+            Self::O200k => "o200k_base",
+            Self::Llama3 => "llama3",
+            Self::Qwen => "qwen",
+        }
+    }
+
+    /// Vocabulary file name inside the tokenizer directory.
+    pub fn file_name(self) -> String {
+        format!("{}.tiktoken", self.name())
+    }
+
+    fn pattern(self) -> &'static str {
+        match self {
+            Self::Cl100k | Self::Llama3 => CL100K_PATTERN,
+            Self::O200k => O200K_PATTERN,
+            Self::Qwen => QWEN_PATTERN,
+        }
+    }
+
+    /// Encoding used by `model`, if it belongs to a supported family.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let base = model
+            .rsplit('/')
+            .next()
+            .unwrap_or(model)
+            .to_ascii_lowercase();
+        if ["gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| base.starts_with(prefix))
+        {
+            Some(Self::O200k)
+        } else if ["gpt-4", "gpt-3.5", "text-embedding-3", "text-embedding-ada"]
+            .iter()
+            .any(|prefix| base.starts_with(prefix))
+        {
+            Some(Self::Cl100k)
+        } else if base.starts_with("llama3") || base.starts_with("llama-3") {
+            Some(Self::Llama3)
+        } else if base.starts_with("qwen") {
+            Some(Self::Qwen)
+        } else {
+            None
+        }
+    }
+}
+
+/// Exact byte-level BPE tokenizer (the `tiktoken` algorithm).
+pub struct BpeTokenizer {
+    name: String,
+    ranks: HashMap<Vec<u8>, u32>,
+    pattern: Regex,
+}
+
+impl fmt::Debug for BpeTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BpeTokenizer")
+            .field("name", &self.name)
+            .field("vocab_size", &self.ranks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BpeTokenizer {
+    /// Build from `tiktoken` vocabulary text: one `<base64 token> <rank>` per line.
+    pub fn from_tiktoken(name: &str, vocab: &str, pattern: &str) -> Result<Self> {
+        let mut ranks = HashMap::new();
+        for (line_no, line) in vocab.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (token, rank) = line
+                .split_once(' ')
+                .with_context(|| format!("line {}: expected `<token> <rank>`", line_no + 1))?;
+            let token = base64::engine::general_purpose::STANDARD
+                .decode(token)
+                .with_context(|| format!("line {}: invalid base64 token", line_no + 1))?;
+            let rank: u32 = rank
+                .parse()
+                .with_context(|| format!("line {}: invalid rank", line_no + 1))?;
+            ranks.insert(token, rank);
+        }
+        anyhow::ensure!(!ranks.is_empty(), "vocabulary is empty");
+
+        Ok(Self {
+            name: name.to_string(),
+            ranks,
+            pattern: Regex::new(pattern).context("invalid pre-tokenizer pattern")?,
+        })
+    }
+
+    /// Load `encoding` from `<dir>/<encoding>.tiktoken`.
+    pub fn load(dir: &Path, encoding: Encoding) -> Result<Self> {
+        let path = dir.join(encoding.file_name());
+        let vocab = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_tiktoken(encoding.name(), &vocab, encoding.pattern())
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Split text into pre-tokenizer pieces.
+    fn pieces<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut pieces = Vec::new();
+        let mut pos = 0;
+        while let Some(m) = self.pattern.find_at(text, pos) {
+            let piece = m.as_str();
+            let mut end = m.end();
+            // `\s+(?!\S)`: a whitespace run followed by text leaves its last
+            // character to prefix the next piece.
+            if end < text.len()
+                && !piece.ends_with(['\r', '\n'])
+                && piece.chars().all(char::is_whitespace)
+            {
+                if let Some((last, _)) = piece.char_indices().last().filter(|(i, _)| *i > 0) {
+                    end = m.start() + last;
+                }
+            }
+            pieces.push(&text[m.start()..end]);
+            pos = end;
+        }
+        pieces
+    }
+
+    /// Merge boundaries for one piece, lowest-rank pair first (leftmost on
+    /// ties). Boundaries form a linked list and candidate pairs wait in a
+    /// min-heap; entries made stale by a neighbouring merge are skipped.
+    fn merge(&self, piece: &[u8]) -> Vec<usize> {
+        const END: usize = usize::MAX;
+        let len = piece.len();
+        let mut next: Vec<usize> = (1..=len).chain([END]).collect();
+        let mut prev: Vec<usize> = [END].into_iter().chain(0..len).collect();
+        let mut alive = vec![true; len + 1];
+        let rank_at = |next: &[usize], i: usize| {
+            let end = next.get(next[i]).copied().unwrap_or(END);
+            if end == END {
+                return None;
+            }
+            self.ranks.get(&piece[i..end]).copied()
+        };
+
+        let mut heap: BinaryHeap<Reverse<(u32, usize)>> = (0..len)
+            .filter_map(|i| rank_at(&next, i).map(|rank| Reverse((rank, i))))
+            .collect();
+        while let Some(Reverse((rank, i))) = heap.pop() {
+            if !alive[i] || rank_at(&next, i) != Some(rank) {
+                continue;
+            }
+            let removed = next[i];
+            alive[removed] = false;
+            next[i] = next[removed];
+            if next[i] != END {
+                prev[next[i]] = i;
+            }
+            for start in [i, prev[i]].into_iter().filter(|&start| start != END) {
+                if let Some(rank) = rank_at(&next, start) {
+                    heap.push(Reverse((rank, start)));
+                }
+            }
+        }
+        (0..=len).filter(|&i| alive[i]).collect()
+    }
+
+    /// Token ids for `text` (special tokens are treated as ordinary text).
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        let mut tokens = Vec::new();
+        for piece in self.pieces(text) {
+            let bytes = piece.as_bytes();
+            if let Some(rank) = self.ranks.get(bytes) {
+                tokens.push(*rank);
+                continue;
+            }
+            let bounds = self.merge(bytes);
+            tokens.extend(bounds.windows(2).map(|w| {
+                self.ranks
+                    .get(&bytes[w[0]..w[1]])
+                    .copied()
+                    .unwrap_or(u32::MAX)
+            }));
+        }
+        tokens
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.pieces(text)
+            .into_iter()
+            .map(|piece| {
+                let bytes = piece.as_bytes();
+                if self.ranks.contains_key(bytes) {
+                    1
+                } else {
+                    self.merge(bytes).len() - 1
+                }
+            })
+            .sum()
+    }
+}
+
+// ── Lookup ──────────────────────────────────────────────────────
+
+/// Picks the tokenizer for a model, loading BPE vocabularies on first use.
+#[derive(Debug, Default)]
+pub struct Tokenizers {
+    vocab_dir: Option<PathBuf>,
+    cl100k: OnceLock<Option<Arc<BpeTokenizer>>>,
+    o200k: OnceLock<Option<Arc<BpeTokenizer>>>,
+    llama3: OnceLock<Option<Arc<BpeTokenizer>>>,
+    qwen: OnceLock<Option<Arc<BpeTokenizer>>>,
+}
+
+impl Tokenizers {
+    /// Look for `*.tiktoken` vocabularies in `vocab_dir`.
+    pub fn new(vocab_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            vocab_dir: Some(vocab_dir.into()),
+            ..Self::default()
+        }
+    }
+
+    fn bpe(&self, encoding: Encoding) -> Option<Arc<BpeTokenizer>> {
+        let dir = self.vocab_dir.as_deref()?;
+        let slot = match encoding {
+            Encoding::Cl100k => &self.cl100k,
+            Encoding::O200k => &self.o200k,
+            Encoding::Llama3 => &self.llama3,
+            Encoding::Qwen => &self.qwen,
+        };
+        slot.get_or_init(|| {
+            if !dir.join(encoding.file_name()).exists() {
+                return None;
+            }
+            BpeTokenizer::load(dir, encoding)
+                .map(Arc::new)
+                .map_err(|e| tracing::warn!("Falling back to estimated token counts: {e:#}"))
+                .ok()
+        })
+        .clone()
+    }
+
+    /// Exact tokenizer for `model` when its vocabulary is available,
+    /// otherwise the calibrated heuristic for its family.
+    pub fn for_model(&self, model: &str) -> Arc<dyn Tokenizer> {
+        if let Some(bpe) = Encoding::for_model(model).and_then(|e| self.bpe(e)) {
+            return bpe;
+        }
+        Arc::new(HeuristicTokenizer::for_model(model))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocab(tokens: &[&str]) -> String {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let mut lines: Vec<String> = (0..=255u8)
+            .map(|b| format!("{} {b}", engine.encode([b])))
+            .collect();
+        for (i, token) in tokens.iter().enumerate() {
+            lines.push(format!("{} {}", engine.encode(token), 256 + i));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn heuristic_counts_cjk_per_character() {
+        let tokenizer = HeuristicTokenizer::GENERIC;
+        let english = "The quick brown fox jumps over the lazy dog.";
+        let chinese = "敏捷的棕色狐狸跳过了懒狗。";
+        assert_eq!(tokenizer.count(""), 0);
+        assert!(tokenizer.count(english) <= english.len() / 3);
+        // A chars/4 rule would give 10 tokens for this 13-character sentence.
+        assert!(tokenizer.count(chinese) >= chinese.chars().count());
+    }
+
+    #[test]
+    fn heuristic_presets_follow_model_family() {
+        assert_eq!(
+            HeuristicTokenizer::for_model("anthropic/claude-sonnet-4"),
+            HeuristicTokenizer::CLAUDE
+        );
+        assert_eq!(
+            HeuristicTokenizer::for_model("qwen2.5:7b"),
+            HeuristicTokenizer::QWEN
+        );
+        assert_eq!(
+            HeuristicTokenizer::for_model("deepseek/deepseek-chat"),
+            HeuristicTokenizer::DEEPSEEK
+        );
+        assert_eq!(
+            HeuristicTokenizer::for_model("my-model"),
+            HeuristicTokenizer::GENERIC
+        );
+        assert_eq!(
+            Encoding::for_model("openai/gpt-4o-mini"),
+            Some(Encoding::O200k)
+        );
+        assert_eq!(Encoding::for_model("gpt-4-turbo"), Some(Encoding::Cl100k));
+        assert_eq!(
+            Encoding::for_model("meta-llama/llama-3.1-70b-instruct"),
+            Some(Encoding::Llama3)
+        );
+        assert_eq!(Encoding::for_model("qwen3:8b"), Some(Encoding::Qwen));
+        assert_eq!(Encoding::for_model("claude-sonnet-4"), None);
+        assert_eq!(Encoding::for_model("deepseek-chat"), None);
+    }
+
+    #[test]
+    fn deepseek_heuristic_matches_published_ratios() {
+        let tokenizer = HeuristicTokenizer::DEEPSEEK;
+        let english = "a".repeat(1000);
+        let chinese = "中".repeat(1000);
+        // DeepSeek documents ~0.3 tokens per English and ~0.6 per Chinese character.
+        assert!((290..=310).contains(&tokenizer.count(&english)));
+        assert!((590..=610).contains(&tokenizer.count(&chinese)));
+    }
+
+    #[test]
+    fn qwen_pre_tokenizer_splits_single_digits() {
+        let tokenizer = BpeTokenizer::from_tiktoken("test", &vocab(&[]), QWEN_PATTERN).unwrap();
+        assert_eq!(tokenizer.pieces("v123"), vec!["v", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn bpe_merges_lowest_rank_pairs_first() {
+        let tokenizer =
+            BpeTokenizer::from_tiktoken("test", &vocab(&["ab", "bc", "abc"]), CL100K_PATTERN)
+                .unwrap();
+        // "ab" outranks "bc", then "ab"+"c" merges into "abc".
+        assert_eq!(tokenizer.encode("abcd"), vec![258, u32::from(b'd')]);
+        assert_eq!(tokenizer.count("abcd"), 2);
+        assert_eq!(tokenizer.encode("bcb"), vec![257, u32::from(b'b')]);
+    }
+
+    #[test]
+    fn bpe_merge_handles_long_repetitive_pieces() {
+        let tokenizer =
+            BpeTokenizer::from_tiktoken("test", &vocab(&["aa", "aaaa", "ab"]), CL100K_PATTERN)
+                .unwrap();
+        // One 20k-byte piece: the old quadratic scan took seconds here.
+        let piece = "a".repeat(20_001);
+        let tokens = tokenizer.encode(&piece);
+        assert_eq!(tokens.len(), 5_001);
+        assert!(tokens[..5_000].iter().all(|&t| t == 257));
+        assert_eq!(tokens[5_000], u32::from(b'a'));
+        assert_eq!(tokenizer.encode("aaab"), vec![256, 258]);
+    }
+
+    #[test]
+    fn bpe_pre_tokenizer_matches_tiktoken_whitespace_handling() {
+        let tokenizer = BpeTokenizer::from_tiktoken("test", &vocab(&[]), CL100K_PATTERN).unwrap();
+        assert_eq!(
+            tokenizer.pieces("hello  world's 12345\n\nok"),
+            vec!["hello", " ", " world", "'s", " ", "123", "45", "\n\n", "ok"]
+        );
+        assert_eq!(tokenizer.pieces("end   "), vec!["end", "   "]);
+    }
+
+    #[test]
+    fn tokenizers_fall_back_to_heuristic_without_vocab() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let tokenizers = Tokenizers::new(tmp.path());
+        assert_eq!(tokenizers.for_model("gpt-4o").name(), "heuristic:openai");
+
+        std::fs::write(tmp.path().join("cl100k_base.tiktoken"), vocab(&["ab"])).unwrap();
+        let tokenizers = Tokenizers::new(tmp.path());
+        let tokenizer = tokenizers.for_model("gpt-4");
+        assert_eq!(tokenizer.name(), "cl100k_base");
+        assert_eq!(tokenizer.count("abab"), 2);
+
+        std::fs::write(tmp.path().join("llama3.tiktoken"), vocab(&["ab"])).unwrap();
+        let tokenizers = Tokenizers::new(tmp.path());
+        assert_eq!(tokenizers.for_model("llama3.1:8b").name(), "llama3");
+    }
+
+    #[test]
+    fn invalid_vocab_is_rejected() {
+        assert!(BpeTokenizer::from_tiktoken("test", "", CL100K_PATTERN).is_err());
+        assert!(BpeTokenizer::from_tiktoken("test", "!!! 1", CL100K_PATTERN).is_err());
+        assert!(BpeTokenizer::from_tiktoken("test", "YQ== x", CL100K_PATTERN).is_err());
+    }
+}
@@ -1,5 +1,8 @@
+use crate::config::ReasoningEffort;
+use crate::providers::{streaming, structured};
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 
 // --- ZeroClaw fork: multimodal content support for vision models ---
@@ -129,6 +132,50 @@ pub struct ToolCall {
     pub arguments: String,
 }
 
+/// Token counts reported by the provider for a single call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatUsage {
+    /// Prompt tokens, including any served from the provider's cache.
+    pub input_tokens: u64,
+    /// Completion tokens.
+    pub output_tokens: u64,
+    /// Portion of `input_tokens` read from the provider's prompt cache.
+    pub cached_input_tokens: u64,
+    /// Portion of `input_tokens` written to the provider's prompt cache.
+    pub cache_write_input_tokens: u64,
+}
+
+impl ChatUsage {
+    /// Fold in counts from a later report of the same call. Streaming APIs
+    /// send cumulative counts, so the larger value wins.
+    pub fn merge(&mut self, other: &ChatUsage) {
+        self.input_tokens = self.input_tokens.max(other.input_tokens);
+        self.output_tokens = self.output_tokens.max(other.output_tokens);
+        self.cached_input_tokens = self.cached_input_tokens.max(other.cached_input_tokens);
+        self.cache_write_input_tokens = self
+            .cache_write_input_tokens
+            .max(other.cache_write_input_tokens);
+    }
+}
+
+/// Reasoning a model produced before its answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reasoning {
+    /// Thinking text or reasoning summary, for display.
+    pub text: String,
+    /// Provider-native blocks that must be sent back unchanged with the
+    /// assistant turn they came from, such as Anthropic's signed thinking
+    /// blocks during a tool-call exchange.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<serde_json::Value>,
+}
+
+impl Reasoning {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.blocks.is_empty()
+    }
+}
+
 /// An LLM response that may contain text, tool calls, or both.
 #[derive(Debug, Clone)]
 pub struct ChatResponse {
@@ -136,6 +183,10 @@ pub struct ChatResponse {
     pub text: Option<String>,
     /// Tool calls requested by the LLM.
     pub tool_calls: Vec<ToolCall>,
+    /// Token usage, when the provider reports it.
+    pub usage: Option<ChatUsage>,
+    /// Model reasoning, when requested and returned by the provider.
+    pub reasoning: Option<Reasoning>,
 }
 
 impl ChatResponse {
@@ -150,11 +201,101 @@ impl ChatResponse {
     }
 }
 
+/// Incremental event emitted by a streaming chat call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// A fragment of assistant text.
+    TextDelta(String),
+    /// A fragment of a native tool call. The first fragment for an `index`
+    /// usually carries `id` and `name`; later fragments append to `arguments`.
+    ToolCallDelta {
+        index: usize,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
+    /// Token usage for the call, usually sent once near the end.
+    Usage(ChatUsage),
+    /// A fragment of the model's reasoning text.
+    ReasoningDelta(String),
+    /// A complete provider block to keep with the assistant turn
+    /// (see [`Reasoning::blocks`]).
+    ReasoningBlock(serde_json::Value),
+}
+
+/// Boxed stream of deltas returned by [`Provider::stream_chat`].
+pub type ChatStream = BoxStream<'static, anyhow::Result<StreamEvent>>;
+
+/// Reasoning controls for a single request. Providers apply the settings
+/// they understand and ignore the rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReasoningOptions {
+    /// Extended-thinking token budget (Anthropic, OpenRouter).
+    pub budget_tokens: Option<u32>,
+    /// Reasoning effort (OpenAI, OpenRouter).
+    pub effort: Option<ReasoningEffort>,
+}
+
+impl ReasoningOptions {
+    pub fn is_empty(&self) -> bool {
+        self.budget_tokens.is_none() && self.effort.is_none()
+    }
+}
+
 /// Request payload for provider chat calls.
 #[derive(Debug, Clone, Copy)]
 pub struct ChatRequest<'a> {
     pub messages: &'a [ChatMessage],
     pub tools: Option<&'a [ToolSpec]>,
+    /// Reasoning controls; `None` leaves the provider default.
+    pub reasoning: Option<ReasoningOptions>,
+}
+
+impl ChatRequest<'_> {
+    /// Whether the request offers tools that need native tool calling.
+    pub fn has_tools(&self) -> bool {
+        self.tools.is_some_and(|tools| !tools.is_empty())
+    }
+
+    /// Whether any message carries image parts.
+    pub fn has_images(&self) -> bool {
+        self.messages.iter().any(ChatMessage::has_images)
+    }
+
+    /// Whether `provider` can serve this request without dropping anything:
+    /// requests that offer tools need a provider with native tool support,
+    /// and requests with images need one that sends them.
+    pub fn is_supported_by(&self, provider: &dyn Provider) -> bool {
+        (!self.has_tools() || provider.supports_native_tools())
+            && (!self.has_images() || provider.supports_vision())
+    }
+}
+
+/// JSON Schema a structured response must satisfy.
+#[derive(Debug, Clone)]
+pub struct ResponseSchema {
+    /// Identifier for the schema (letters, digits, `_` and `-`). Sent as the
+    /// OpenAI schema name and the Anthropic forced-tool name.
+    pub name: String,
+    /// What the value represents; helps the model fill it in.
+    pub description: String,
+    pub schema: serde_json::Value,
+}
+
+impl ResponseSchema {
+    pub fn new(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            schema,
+        }
+    }
+
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
 }
 
 /// A tool result to feed back to the LLM.
@@ -174,6 +315,10 @@ pub enum ConversationMessage {
     AssistantToolCalls {
         text: Option<String>,
         tool_calls: Vec<ToolCall>,
+        /// Reasoning emitted alongside the calls; signed blocks must be
+        /// replayed for providers that verify them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reasoning: Option<Reasoning>,
     },
     /// Results of tool executions, fed back to the LLM.
     ToolResults(Vec<ToolResultMessage>),
@@ -239,14 +384,79 @@ pub trait Provider: Send + Sync {
         Ok(ChatResponse {
             text: Some(text),
             tool_calls: Vec::new(),
+            usage: None,
+            reasoning: None,
         })
     }
 
+    /// Chat whose answer is a JSON value guaranteed to validate against
+    /// `schema`. Candidates come from `generate_structured`; invalid ones are
+    /// fed back to the model with the validation error and retried a bounded
+    /// number of times before giving up.
+    async fn chat_structured(
+        &self,
+        request: ChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<serde_json::Value> {
+        structured::generate_validated(self, request, schema, model, temperature).await
+    }
+
+    /// Produce one unvalidated JSON candidate for `schema`. Providers with a
+    /// native structured-output mode override this; the default puts the
+    /// schema in the system prompt. Callers should use `chat_structured`.
+    async fn generate_structured(
+        &self,
+        request: ChatRequest<'_>,
+        schema: &ResponseSchema,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let messages = structured::with_schema_instructions(request.messages, schema);
+        let response = self
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    reasoning: request.reasoning,
+                },
+                model,
+                temperature,
+            )
+            .await?;
+        Ok(response.text.unwrap_or_default())
+    }
+
     /// Whether provider supports native tool calls over API.
     fn supports_native_tools(&self) -> bool {
         false
     }
 
+    /// Whether the provider sends image parts of multimodal messages rather
+    /// than just their text.
+    fn supports_vision(&self) -> bool {
+        false
+    }
+
+    /// Whether `stream_chat` yields incremental deltas from the backend.
+    /// When false, callers should prefer the non-streaming APIs.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Streaming variant of `chat`. Default implementation awaits `chat` and
+    /// replays the complete response as a single burst of deltas.
+    async fn stream_chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self.chat(request, model, temperature).await?;
+        Ok(streaming::replay_response(response))
+    }
+
     /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup).
     /// Default implementation is a no-op; providers with HTTP clients should override.
     async fn warmup(&self) -> anyhow::Result<()> {
@@ -279,6 +489,8 @@ mod tests {
         let empty = ChatResponse {
             text: None,
             tool_calls: vec![],
+            usage: None,
+            reasoning: None,
         };
         assert!(!empty.has_tool_calls());
         assert_eq!(empty.text_or_empty(), "");
@@ -290,11 +502,38 @@ mod tests {
                 name: "shell".into(),
                 arguments: "{}".into(),
             }],
+            usage: None,
+            reasoning: None,
         };
         assert!(with_tools.has_tool_calls());
         assert_eq!(with_tools.text_or_empty(), "Let me check");
     }
 
+    #[test]
+    fn chat_usage_merge_keeps_cumulative_counts() {
+        let mut usage = ChatUsage {
+            input_tokens: 120,
+            output_tokens: 1,
+            cached_input_tokens: 100,
+            cache_write_input_tokens: 0,
+        };
+        usage.merge(&ChatUsage {
+            input_tokens: 0,
+            output_tokens: 42,
+            cached_input_tokens: 0,
+            cache_write_input_tokens: 0,
+        });
+        assert_eq!(
+            usage,
+            ChatUsage {
+                input_tokens: 120,
+                output_tokens: 42,
+                cached_input_tokens: 100,
+                cache_write_input_tokens: 0,
+            }
+        );
+    }
+
     #[test]
     fn tool_call_serialization() {
         let tc = ToolCall {
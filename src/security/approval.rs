@@ -0,0 +1,376 @@
+//! Human-in-the-loop approval for tool calls.
+//!
+//! When a tool answers `APPROVAL_REQUIRED`, the channel runtime parks the
+//! exact call here under a short ID and asks the user directly. A reply of
+//! `approve <id>` runs that parked call with `approved: true`; the model
+//! never sees the approval flag, so it cannot grant itself permission.
+
+use super::audit::{AuditEvent, AuditEventType, AuditLogger};
+use super::policy::{CommandRiskLevel, SecurityPolicy};
+use crate::util::truncate_with_ellipsis;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest action summary shown to the user and written to the audit log.
+const MAX_SUMMARY_CHARS: usize = 300;
+
+/// Length of request IDs (hex digits).
+const ID_LEN: usize = 6;
+
+/// A tool call waiting for the user's decision.
+#[derive(Debug, Clone)]
+pub struct PendingApproval {
+    pub id: String,
+    /// Conversation that may decide (`{channel}_{sender}`).
+    pub conversation: String,
+    pub tool: String,
+    /// Arguments exactly as the model sent them, minus any approval flag.
+    pub arguments: serde_json::Value,
+    pub risk: CommandRiskLevel,
+    /// Why the tool asked for approval.
+    pub reason: String,
+    pub expires_at: Instant,
+}
+
+impl PendingApproval {
+    /// What the call will do: the shell command or path when there is one,
+    /// otherwise the arguments.
+    pub fn action(&self) -> String {
+        let field = |name| self.arguments.get(name).and_then(serde_json::Value::as_str);
+        let action = field("command")
+            .or_else(|| field("path"))
+            .map_or_else(|| self.arguments.to_string(), ToString::to_string);
+        truncate_with_ellipsis(&action, MAX_SUMMARY_CHARS)
+    }
+
+    /// Message asking the user to decide. The approve/deny choices (`approve
+    /// <id>` / `deny <id>`) are added by the channel as buttons or a menu.
+    pub fn prompt(&self) -> String {
+        let minutes = self
+            .expires_at
+            .saturating_duration_since(Instant::now())
+            .as_secs()
+            .div_ceil(60);
+        format!(
+            "🔐 Approval required ({id})\n\
+             Tool: {tool}\n\
+             Risk: {risk}\n\
+             Action: {action}\n\
+             Expires in {minutes} min.",
+            id = self.id,
+            tool = self.tool,
+            risk = self.risk.as_str(),
+            action = self.action(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approve,
+    Deny,
+}
+
+impl ApprovalDecision {
+    /// Recognize `approve <id>` / `deny <id>` replies (optionally with a
+    /// leading `/`). Anything else is an ordinary message for the model.
+    pub fn parse(text: &str) -> Option<(Self, String)> {
+        let mut words = text.split_whitespace();
+        let verb = words.next()?.trim_start_matches('/').to_ascii_lowercase();
+        let id = words
+            .next()?
+            .trim_matches(|c: char| !c.is_ascii_alphanumeric());
+        if words.next().is_some()
+            || id.len() != ID_LEN
+            || !id.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let decision = match verb.as_str() {
+            "approve" => Self::Approve,
+            "deny" => Self::Deny,
+            _ => return None,
+        };
+        Some((decision, id.to_ascii_lowercase()))
+    }
+}
+
+/// Parks tool calls that need approval until the user decides or they
+/// expire. Decisions are written to the audit log when one is attached.
+pub struct ApprovalBroker {
+    pending: Mutex<HashMap<String, PendingApproval>>,
+    policy: Arc<SecurityPolicy>,
+    ttl: Duration,
+    audit: Option<AuditLogger>,
+}
+
+impl ApprovalBroker {
+    pub fn new(policy: Arc<SecurityPolicy>, ttl: Duration) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            policy,
+            ttl,
+            audit: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Risk of running `tool` with `arguments`: shell commands are scored by
+    /// the security policy, anything else needing approval counts as medium.
+    pub fn risk_level(&self, arguments: &serde_json::Value) -> CommandRiskLevel {
+        arguments
+            .get("command")
+            .and_then(serde_json::Value::as_str)
+            .map_or(CommandRiskLevel::Medium, |command| {
+                self.policy.command_risk_level(command)
+            })
+    }
+
+    /// Park a call for `conversation` and return it with its new ID. Any
+    /// `approved` flag in `arguments` is removed.
+    pub fn park(
+        &self,
+        conversation: &str,
+        tool: &str,
+        mut arguments: serde_json::Value,
+        reason: &str,
+    ) -> PendingApproval {
+        if let Some(args) = arguments.as_object_mut() {
+            args.remove("approved");
+        }
+        let mut pending = self.pending.lock();
+        let expired = Self::drain_expired(&mut pending);
+
+        let id = loop {
+            let id = uuid::Uuid::new_v4().simple().to_string()[..ID_LEN].to_string();
+            if !pending.contains_key(&id) {
+                break id;
+            }
+        };
+        let request = PendingApproval {
+            id: id.clone(),
+            conversation: conversation.to_string(),
+            tool: tool.to_string(),
+            risk: self.risk_level(&arguments),
+            arguments,
+            reason: reason.to_string(),
+            expires_at: Instant::now() + self.ttl,
+        };
+        pending.insert(id, request.clone());
+        drop(pending);
+
+        for request in &expired {
+            self.audit_decision(request, "expired", false, None);
+        }
+        request
+    }
+
+    /// Take the request `id` for `conversation` so it can be decided. Fails
+    /// for unknown or expired IDs and for requests from another
+    /// conversation, which stay parked.
+    pub fn take(&self, conversation: &str, id: &str) -> anyhow::Result<PendingApproval> {
+        let mut pending = self.pending.lock();
+        let expired = Self::drain_expired(&mut pending);
+        let result = match pending.get(id) {
+            Some(request) if request.conversation == conversation => {
+                Ok(pending.remove(id).expect("request is present"))
+            }
+            _ if expired
+                .iter()
+                .any(|r| r.id == id && r.conversation == conversation) =>
+            {
+                Err(anyhow::anyhow!("Approval request {id} has expired"))
+            }
+            _ => Err(anyhow::anyhow!("No pending approval request {id}")),
+        };
+        drop(pending);
+
+        for request in &expired {
+            self.audit_decision(request, "expired", false, None);
+        }
+        result
+    }
+
+    /// Requests still waiting in `conversation`.
+    pub fn pending_for(&self, conversation: &str) -> Vec<PendingApproval> {
+        let now = Instant::now();
+        self.pending
+            .lock()
+            .values()
+            .filter(|r| r.conversation == conversation && r.expires_at > now)
+            .cloned()
+            .collect()
+    }
+
+    /// Write a decision to the audit log. `outcome` is the execution
+    /// result of an approved call: success and duration.
+    pub fn audit_decision(
+        &self,
+        request: &PendingApproval,
+        decision: &str,
+        approved: bool,
+        outcome: Option<(bool, Duration)>,
+    ) {
+        tracing::info!(
+            id = request.id,
+            tool = request.tool,
+            conversation = request.conversation,
+            decision,
+            "Approval request decided"
+        );
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let (channel, sender) = request
+            .conversation
+            .split_once('_')
+            .unwrap_or((request.conversation.as_str(), ""));
+        let mut event = AuditEvent::new(AuditEventType::ApprovalDecision)
+            .with_actor(channel.to_string(), Some(sender.to_string()), None)
+            .with_action(
+                format!(
+                    "{} [{}] {}: {}",
+                    request.tool,
+                    request.id,
+                    decision,
+                    request.action()
+                ),
+                request.risk.as_str().to_string(),
+                approved,
+                approved,
+            );
+        if let Some((success, duration)) = outcome {
+            event = event.with_result(
+                success,
+                None,
+                u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                None,
+            );
+        }
+        if let Err(e) = audit.log(&event) {
+            tracing::warn!("Failed to write approval audit event: {e}");
+        }
+    }
+
+    fn drain_expired(pending: &mut HashMap<String, PendingApproval>) -> Vec<PendingApproval> {
+        let now = Instant::now();
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, r)| r.expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|id| pending.remove(&id))
+            .collect()
+    }
+}
+
+/// What a tool-call loop needs to park calls for one conversation. Calls
+/// parked during the turn are collected in `parked` so the caller can ask
+/// the user about them.
+pub struct ApprovalScope<'a> {
+    pub broker: &'a ApprovalBroker,
+    pub conversation: &'a str,
+    pub parked: Vec<PendingApproval>,
+}
+
+impl<'a> ApprovalScope<'a> {
+    pub fn new(broker: &'a ApprovalBroker, conversation: &'a str) -> Self {
+        Self {
+            broker,
+            conversation,
+            parked: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuditConfig;
+    use serde_json::json;
+
+    fn broker(ttl: Duration) -> ApprovalBroker {
+        ApprovalBroker::new(Arc::new(SecurityPolicy::default()), ttl)
+    }
+
+    #[test]
+    fn parse_recognizes_decisions_only() {
+        assert_eq!(
+            ApprovalDecision::parse("approve AB12cd"),
+            Some((ApprovalDecision::Approve, "ab12cd".into()))
+        );
+        assert_eq!(
+            ApprovalDecision::parse("/deny ab12cd"),
+            Some((ApprovalDecision::Deny, "ab12cd".into()))
+        );
+        assert_eq!(
+            ApprovalDecision::parse("approve `ab12cd`"),
+            Some((ApprovalDecision::Approve, "ab12cd".into()))
+        );
+        assert_eq!(ApprovalDecision::parse("approve"), None);
+        assert_eq!(ApprovalDecision::parse("deny access"), None);
+        assert_eq!(ApprovalDecision::parse("approve ab12cd please"), None);
+        assert_eq!(ApprovalDecision::parse("yes ab12cd"), None);
+    }
+
+    #[test]
+    fn park_strips_approval_and_scores_risk() {
+        let broker = broker(Duration::from_secs(60));
+        let request = broker.park(
+            "telegram_alice",
+            "shell",
+            json!({"command": "rm -rf build", "approved": true}),
+            "APPROVAL_REQUIRED: high risk",
+        );
+        assert_eq!(request.id.len(), 6);
+        assert!(request.arguments.get("approved").is_none());
+        assert_eq!(request.risk, CommandRiskLevel::High);
+        assert_eq!(request.action(), "rm -rf build");
+        assert!(request.prompt().contains(&format!("({})", request.id)));
+
+        let other = broker.park(
+            "telegram_alice",
+            "file_write",
+            json!({"path": "/etc/x"}),
+            "",
+        );
+        assert_eq!(other.risk, CommandRiskLevel::Medium);
+        assert_eq!(broker.pending_for("telegram_alice").len(), 2);
+    }
+
+    #[test]
+    fn take_is_scoped_to_conversation_and_single_use() {
+        let broker = broker(Duration::from_secs(60));
+        let request = broker.park("telegram_alice", "shell", json!({"command": "rm x"}), "");
+
+        assert!(broker.take("telegram_mallory", &request.id).is_err());
+        let taken = broker.take("telegram_alice", &request.id).unwrap();
+        assert_eq!(taken.arguments, json!({"command": "rm x"}));
+        assert!(broker.take("telegram_alice", &request.id).is_err());
+    }
+
+    #[test]
+    fn expired_requests_cannot_be_taken_and_are_audited() {
+        let tmp = tempfile::tempdir().unwrap();
+        let audit = AuditLogger::new(AuditConfig::default(), tmp.path().to_path_buf()).unwrap();
+        let broker = broker(Duration::ZERO).with_audit(audit);
+        let request = broker.park("telegram_alice", "shell", json!({"command": "rm x"}), "");
+
+        let err = broker.take("telegram_alice", &request.id).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+
+        let log =
+            std::fs::read_to_string(tmp.path().join(AuditConfig::default().log_path)).unwrap();
+        assert!(log.contains("approval_decision"));
+        assert!(log.contains("expired"));
+    }
+}
@@ -21,6 +21,7 @@ pub enum AuditEventType {
     AuthFailure,
     PolicyViolation,
     SecurityEvent,
+    ApprovalDecision,
 }
 
 /// Actor information (who performed the action)
@@ -1,3 +1,4 @@
+pub mod approval;
 pub mod audit;
 #[cfg(feature = "sandbox-bubblewrap")]
 pub mod bubblewrap;
@@ -12,6 +13,8 @@ pub mod policy;
 pub mod secrets;
 pub mod traits;
 
+#[allow(unused_imports)]
+pub use approval::{ApprovalBroker, ApprovalDecision, ApprovalScope, PendingApproval};
 #[allow(unused_imports)]
 pub use audit::{AuditEvent, AuditEventType, AuditLogger};
 #[allow(unused_imports)]
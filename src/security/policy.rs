@@ -24,6 +24,16 @@ pub enum CommandRiskLevel {
     High,
 }
 
+impl CommandRiskLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
 /// Sliding-window action tracker for rate limiting.
 #[derive(Debug)]
 pub struct ActionTracker {
@@ -51,6 +61,23 @@ impl ActionTracker {
         actions.len()
     }
 
+    /// Record an action only if fewer than `limit` are in the window.
+    /// Checking and recording happen under one lock, so concurrent tool
+    /// calls can never take more than `limit` actions between them, and
+    /// rejected attempts do not use up the window.
+    pub fn try_record(&self, limit: usize) -> bool {
+        let mut actions = self.actions.lock();
+        let cutoff = Instant::now()
+            .checked_sub(std::time::Duration::from_secs(3600))
+            .unwrap_or_else(Instant::now);
+        actions.retain(|t| *t > cutoff);
+        if actions.len() >= limit {
+            return false;
+        }
+        actions.push(Instant::now());
+        true
+    }
+
     /// Count of actions in the current window without recording.
     pub fn count(&self) -> usize {
         let mut actions = self
@@ -579,8 +606,7 @@ impl SecurityPolicy {
     /// Record an action and check if the rate limit has been exceeded.
     /// Returns `true` if the action is allowed, `false` if rate-limited.
     pub fn record_action(&self) -> bool {
-        let count = self.tracker.record();
-        count <= self.max_actions_per_hour as usize
+        self.tracker.try_record(self.max_actions_per_hour as usize)
     }
 
     /// Check if the rate limit would be exceeded without recording.
@@ -1034,6 +1060,7 @@ mod tests {
             max_cost_per_day_cents: 1000,
             require_approval_for_medium_risk: false,
             block_high_risk_commands: false,
+            approval_timeout_secs: 600,
         };
         let workspace = PathBuf::from("/tmp/test-workspace");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
@@ -1104,6 +1131,23 @@ mod tests {
         assert!(!p.record_action()); // 4 — over limit
     }
 
+    #[test]
+    fn record_action_is_exact_under_concurrency() {
+        let p = std::sync::Arc::new(SecurityPolicy {
+            max_actions_per_hour: 10,
+            ..SecurityPolicy::default()
+        });
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let p = std::sync::Arc::clone(&p);
+                std::thread::spawn(move || (0..10).filter(|_| p.record_action()).count())
+            })
+            .collect();
+        let allowed: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(allowed, 10);
+        assert_eq!(p.tracker.count(), 10);
+    }
+
     #[test]
     fn is_rate_limited_reflects_count() {
         let p = SecurityPolicy {
@@ -1377,6 +1421,7 @@ mod tests {
             max_cost_per_day_cents: 100,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: true,
+            approval_timeout_secs: 600,
         };
         let workspace = PathBuf::from("/tmp/test");
         let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
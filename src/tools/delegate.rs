@@ -1,6 +1,7 @@
 use super::traits::{Tool, ToolResult};
-use crate::config::DelegateAgentConfig;
-use crate::providers::{self, Provider};
+use crate::config::{Config, DelegateAgentConfig};
+use crate::observability::Observer;
+use crate::providers::{self, ChatMessage, ChatRequest, Provider, ResponseSchema};
 use async_trait::async_trait;
 use serde_json::json;
 use std::collections::HashMap;
@@ -20,6 +21,9 @@ pub struct DelegateTool {
     fallback_api_key: Option<String>,
     /// Depth at which this tool instance lives in the delegation chain.
     depth: u32,
+    /// Config and observer for the replay/redaction layers the main
+    /// provider gets; without them sub-agents talk to the raw provider.
+    provider_layers: Option<(Arc<Config>, Arc<dyn Observer>)>,
 }
 
 impl DelegateTool {
@@ -31,6 +35,7 @@ impl DelegateTool {
             agents: Arc::new(agents),
             fallback_api_key,
             depth: 0,
+            provider_layers: None,
         }
     }
 
@@ -46,8 +51,28 @@ impl DelegateTool {
             agents: Arc::new(agents),
             fallback_api_key,
             depth,
+            provider_layers: None,
         }
     }
+
+    /// Wrap sub-agent providers with `with_replay` and `with_redaction`,
+    /// so delegated prompts are recorded and scrubbed like the main loop's.
+    pub fn with_provider_layers(
+        mut self,
+        config: Arc<Config>,
+        observer: Arc<dyn Observer>,
+    ) -> Self {
+        self.provider_layers = Some((config, observer));
+        self
+    }
+
+    fn layer_provider(&self, provider: Box<dyn Provider>) -> anyhow::Result<Box<dyn Provider>> {
+        let Some((config, observer)) = &self.provider_layers else {
+            return Ok(provider);
+        };
+        let provider = providers::with_replay(provider, &config.replay, &config.workspace_dir)?;
+        providers::with_redaction(provider, config, Arc::clone(observer))
+    }
 }
 
 #[async_trait]
@@ -59,7 +84,7 @@ impl Tool for DelegateTool {
     fn description(&self) -> &str {
         "Delegate a subtask to a specialized agent. Use when: a task benefits from a different model \
          (e.g. fast summarization, deep reasoning, code generation). The sub-agent runs a single \
-         prompt and returns its response."
+         prompt and returns its response; pass response_schema to get JSON that matches it."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -88,6 +113,10 @@ impl Tool for DelegateTool {
                 "context": {
                     "type": "string",
                     "description": "Optional context to prepend (e.g. relevant code, prior findings)"
+                },
+                "response_schema": {
+                    "type": "object",
+                    "description": "Optional JSON Schema; the sub-agent then answers with a JSON value that validates against it"
                 }
             },
             "required": ["agent", "prompt"]
@@ -133,6 +162,22 @@ impl Tool for DelegateTool {
             .map(str::trim)
             .unwrap_or("");
 
+        let response_schema = match args.get("response_schema") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(schema @ serde_json::Value::Object(_)) => {
+                Some(ResponseSchema::new("delegate_result", schema.clone()))
+            }
+            Some(_) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some("'response_schema' must be a JSON Schema object".into()),
+                    image_base64: None,
+                    image_mime: None,
+                });
+            }
+        };
+
         // Look up agent config
         let agent_config = match self.agents.get(agent_name) {
             Some(cfg) => cfg,
@@ -179,7 +224,9 @@ impl Tool for DelegateTool {
             .or(self.fallback_api_key.as_deref());
 
         let provider: Box<dyn Provider> =
-            match providers::create_provider(&agent_config.provider, api_key) {
+            match providers::create_provider(&agent_config.provider, api_key)
+                .and_then(|provider| self.layer_provider(provider))
+            {
                 Ok(p) => p,
                 Err(e) => {
                     return Ok(ToolResult {
@@ -207,11 +254,13 @@ impl Tool for DelegateTool {
         // Wrap the provider call in a timeout to prevent indefinite blocking
         let result = tokio::time::timeout(
             Duration::from_secs(DELEGATE_TIMEOUT_SECS),
-            provider.chat_with_system(
+            ask(
+                provider.as_ref(),
                 agent_config.system_prompt.as_deref(),
                 &full_prompt,
                 &agent_config.model,
                 temperature,
+                response_schema.as_ref(),
             ),
         )
         .await;
@@ -261,6 +310,37 @@ impl Tool for DelegateTool {
     }
 }
 
+/// Run one prompt on `provider`: free text, or with `schema` a JSON value
+/// that validates against it.
+async fn ask(
+    provider: &dyn Provider,
+    system_prompt: Option<&str>,
+    prompt: &str,
+    model: &str,
+    temperature: f64,
+    schema: Option<&ResponseSchema>,
+) -> anyhow::Result<String> {
+    let Some(schema) = schema else {
+        return provider
+            .chat_with_system(system_prompt, prompt, model, temperature)
+            .await;
+    };
+    let mut messages = Vec::with_capacity(2);
+    if let Some(system) = system_prompt {
+        messages.push(ChatMessage::system(system));
+    }
+    messages.push(ChatMessage::user(prompt));
+    let request = ChatRequest {
+        messages: &messages,
+        tools: None,
+        reasoning: None,
+    };
+    let value = provider
+        .chat_structured(request, schema, model, temperature)
+        .await?;
+    Ok(value.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,6 +380,7 @@ mod tests {
         assert!(schema["properties"]["agent"].is_object());
         assert!(schema["properties"]["prompt"].is_object());
         assert!(schema["properties"]["context"].is_object());
+        assert_eq!(schema["properties"]["response_schema"]["type"], "object");
         let required = schema["required"].as_array().unwrap();
         assert!(required.contains(&json!("agent")));
         assert!(required.contains(&json!("prompt")));
@@ -446,4 +527,108 @@ mod tests {
                     .contains("Unknown agent")
         );
     }
+
+    struct JsonProvider;
+
+    #[async_trait]
+    impl Provider for JsonProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            if message.contains("Reply again") {
+                Ok(r#"{"verdict": "safe"}"#.into())
+            } else {
+                Ok("Looks safe to me.".into())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn response_schema_yields_validated_json() {
+        let schema = ResponseSchema::new(
+            "delegate_result",
+            json!({
+                "type": "object",
+                "properties": {"verdict": {"enum": ["safe", "unsafe"]}},
+                "required": ["verdict"]
+            }),
+        );
+
+        let text = ask(&JsonProvider, None, "review this", "m", 0.0, None)
+            .await
+            .unwrap();
+        assert_eq!(text, "Looks safe to me.");
+
+        let json = ask(&JsonProvider, None, "review this", "m", 0.0, Some(&schema))
+            .await
+            .unwrap();
+        assert_eq!(json, r#"{"verdict":"safe"}"#);
+    }
+
+    /// Records the last prompt it was sent.
+    #[derive(Default)]
+    struct RecordingProvider {
+        seen: Arc<std::sync::Mutex<String>>,
+    }
+
+    #[async_trait]
+    impl Provider for RecordingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            *self.seen.lock().unwrap() = message.to_string();
+            Ok("done".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn provider_layers_redact_delegated_prompts() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        let tool = DelegateTool::new(sample_agents(), None).with_provider_layers(
+            Arc::new(config),
+            Arc::new(crate::observability::NoopObserver),
+        );
+        let inner = RecordingProvider::default();
+        let seen = Arc::clone(&inner.seen);
+        let provider = tool.layer_provider(Box::new(inner)).unwrap();
+
+        let key = "sk-proj-abcdefghijklmnopqrstuvwxyz012345";
+        ask(
+            provider.as_ref(),
+            None,
+            &format!("use {key}"),
+            "m",
+            0.0,
+            None,
+        )
+        .await
+        .unwrap();
+        let seen = seen.lock().unwrap().clone();
+        assert!(!seen.contains(key));
+        assert!(seen.contains("[REDACTED_API_KEY_1]"));
+    }
+
+    #[tokio::test]
+    async fn non_object_response_schema_rejected() {
+        let tool = DelegateTool::new(sample_agents(), None);
+        let result = tool
+            .execute(json!({"agent": "researcher", "prompt": "test", "response_schema": "json"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("response_schema"));
+    }
 }
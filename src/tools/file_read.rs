@@ -45,6 +45,10 @@ impl Tool for FileReadTool {
         })
     }
 
+    fn parallel_safe(&self, _args: &serde_json::Value) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -183,6 +183,14 @@ impl Tool for HttpRequestTool {
         })
     }
 
+    /// Only requests without side effects may run alongside other calls.
+    fn parallel_safe(&self, args: &serde_json::Value) -> bool {
+        let method = args.get("method").and_then(|v| v.as_str()).unwrap_or("GET");
+        ["GET", "HEAD", "OPTIONS"]
+            .iter()
+            .any(|safe| method.eq_ignore_ascii_case(safe))
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let url = args
             .get("url")
@@ -569,6 +577,15 @@ mod tests {
         assert!(tool.validate_method("OPTIONS").is_ok());
     }
 
+    #[test]
+    fn only_side_effect_free_methods_are_parallel_safe() {
+        let tool = test_tool(vec!["example.com"]);
+        assert!(tool.parallel_safe(&json!({"url": "https://example.com"})));
+        assert!(tool.parallel_safe(&json!({"method": "head"})));
+        assert!(!tool.parallel_safe(&json!({"method": "POST"})));
+        assert!(!tool.parallel_safe(&json!({"method": "DELETE"})));
+    }
+
     #[test]
     fn validate_rejects_invalid_method() {
         let tool = test_tool(vec!["example.com"]);
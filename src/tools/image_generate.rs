@@ -0,0 +1,300 @@
+use super::image_info::ImageInfoTool;
+use super::traits::{Tool, ToolResult};
+use crate::config::ImageGenerationConfig;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use base64::Engine;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Subdirectory of the workspace where generated images are saved.
+const IMAGE_OUTPUT_DIR: &str = "generated_images";
+
+/// Tool that generates an image from a text prompt through an
+/// OpenAI-compatible `/images/generations` endpoint.
+///
+/// The image is saved to the workspace and returned as `image_base64`, so
+/// channels that can send pictures deliver it to the user directly.
+pub struct ImageGenerateTool {
+    security: Arc<SecurityPolicy>,
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    default_size: String,
+}
+
+impl ImageGenerateTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: &ImageGenerationConfig) -> Self {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+            .filter(|k| !k.trim().is_empty());
+        Self {
+            security,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+                .connect_timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            base_url: config.api_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: config.model.clone(),
+            default_size: config.size.clone(),
+        }
+    }
+
+    fn generations_url(&self) -> String {
+        let Ok(url) = reqwest::Url::parse(&self.base_url) else {
+            return format!("{}/v1/images/generations", self.base_url);
+        };
+
+        let path = url.path().trim_end_matches('/');
+        if path.ends_with("/images/generations") {
+            self.base_url.clone()
+        } else if path.is_empty() {
+            format!("{}/v1/images/generations", self.base_url)
+        } else {
+            format!("{}/images/generations", self.base_url)
+        }
+    }
+
+    fn request_body(&self, prompt: &str, size: &str) -> serde_json::Value {
+        let mut body = json!({
+            "model": self.model,
+            "prompt": prompt,
+            "n": 1,
+            "size": size,
+        });
+        // gpt-image models always return base64 and reject the parameter.
+        if !self.model.starts_with("gpt-image") {
+            body["response_format"] = json!("b64_json");
+        }
+        body
+    }
+
+    /// Fetch the first image of a generations response, either inline
+    /// (`b64_json`) or from the returned `url`.
+    async fn image_bytes(&self, response: &serde_json::Value) -> anyhow::Result<Vec<u8>> {
+        let image = response
+            .get("data")
+            .and_then(|d| d.as_array())
+            .and_then(|d| d.first())
+            .ok_or_else(|| anyhow::anyhow!("Invalid image response: missing 'data'"))?;
+
+        if let Some(b64) = image.get("b64_json").and_then(|v| v.as_str()) {
+            return Ok(base64::engine::general_purpose::STANDARD.decode(b64)?);
+        }
+        let url = image
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid image response: no 'b64_json' or 'url'"))?;
+        let resp = self.client.get(url).send().await?.error_for_status()?;
+        Ok(resp.bytes().await?.to_vec())
+    }
+
+    async fn generate(&self, prompt: &str, size: &str) -> anyhow::Result<ToolResult> {
+        let mut request = self
+            .client
+            .post(self.generations_url())
+            .json(&self.request_body(prompt, size));
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let resp = request.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!(
+                    "Image API error {status}: {}",
+                    crate::providers::sanitize_api_error(&text)
+                )),
+                ..Default::default()
+            });
+        }
+
+        let json: serde_json::Value = resp.json().await?;
+        let bytes = self.image_bytes(&json).await?;
+        let format = match ImageInfoTool::detect_format(&bytes) {
+            "unknown" => "png",
+            known => known,
+        };
+
+        let dir = self.security.workspace_dir.join(IMAGE_OUTPUT_DIR);
+        tokio::fs::create_dir_all(&dir).await?;
+        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+        let path = dir.join(format!(
+            "image_{timestamp}_{}.{format}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ));
+        tokio::fs::write(&path, &bytes).await?;
+
+        let revised = json
+            .pointer("/data/0/revised_prompt")
+            .and_then(|v| v.as_str())
+            .map(|p| format!("\nRevised prompt: {p}"))
+            .unwrap_or_default();
+        Ok(ToolResult {
+            success: true,
+            output: format!(
+                "Generated image saved to {} ({} bytes). It will be shown to the user.{revised}",
+                path.display(),
+                bytes.len()
+            ),
+            error: None,
+            image_base64: Some(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+            image_mime: Some(format!("image/{format}")),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for ImageGenerateTool {
+    fn name(&self) -> &str {
+        "image_generate"
+    }
+
+    fn description(&self) -> &str {
+        "Generate an image from a text prompt. The image is saved to the workspace and sent to the user."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "prompt": {
+                    "type": "string",
+                    "description": "Detailed description of the image to generate"
+                },
+                "size": {
+                    "type": "string",
+                    "description": "Image size as WIDTHxHEIGHT, e.g. 1024x1024 or 1792x1024 (optional)"
+                }
+            },
+            "required": ["prompt"]
+        })
+    }
+
+    fn delivers_images(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let prompt = args
+            .get("prompt")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'prompt' parameter"))?;
+        let size = args
+            .get("size")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.default_size);
+
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+                ..Default::default()
+            });
+        }
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+                ..Default::default()
+            });
+        }
+
+        self.generate(prompt, size).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+
+    fn tool(model: &str, api_url: &str) -> ImageGenerateTool {
+        let config = ImageGenerationConfig {
+            enabled: true,
+            api_url: api_url.into(),
+            model: model.into(),
+            ..ImageGenerationConfig::default()
+        };
+        ImageGenerateTool::new(Arc::new(SecurityPolicy::default()), &config)
+    }
+
+    #[test]
+    fn image_generate_schema_requires_prompt() {
+        let tool = tool("dall-e-3", "https://api.openai.com");
+        assert_eq!(tool.name(), "image_generate");
+        assert!(tool.delivers_images());
+        let schema = tool.parameters_schema();
+        assert_eq!(schema["required"], json!(["prompt"]));
+        assert!(schema["properties"]["size"].is_object());
+    }
+
+    #[test]
+    fn generations_url_handles_explicit_paths() {
+        assert_eq!(
+            tool("dall-e-3", "https://api.openai.com").generations_url(),
+            "https://api.openai.com/v1/images/generations"
+        );
+        assert_eq!(
+            tool("sd", "http://localhost:8080/v1/").generations_url(),
+            "http://localhost:8080/v1/images/generations"
+        );
+    }
+
+    #[test]
+    fn request_body_asks_for_base64_unless_gpt_image() {
+        let body = tool("dall-e-3", "https://api.openai.com").request_body("a cat", "512x512");
+        assert_eq!(body["response_format"], "b64_json");
+        assert_eq!(body["size"], "512x512");
+
+        let body = tool("gpt-image-1", "https://api.openai.com").request_body("a cat", "512x512");
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[tokio::test]
+    async fn image_bytes_decodes_inline_base64() {
+        let png = b"\x89PNG\r\n\x1a\nrest";
+        let response = json!({
+            "data": [{ "b64_json": base64::engine::general_purpose::STANDARD.encode(png) }]
+        });
+        let bytes = tool("dall-e-3", "https://api.openai.com")
+            .image_bytes(&response)
+            .await
+            .unwrap();
+        assert_eq!(bytes, png);
+        assert_eq!(ImageInfoTool::detect_format(&bytes), "png");
+
+        assert!(tool("dall-e-3", "https://api.openai.com")
+            .image_bytes(&json!({ "data": [] }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn image_generate_blocked_when_read_only() {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            ..SecurityPolicy::default()
+        });
+        let tool = ImageGenerateTool::new(security, &ImageGenerationConfig::default());
+        let result = tool.execute(json!({ "prompt": "a cat" })).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+        assert!(tool.execute(json!({})).await.is_err());
+    }
+}
@@ -24,7 +24,7 @@ impl ImageInfoTool {
     }
 
     /// Detect image format from first few bytes (magic numbers).
-    fn detect_format(bytes: &[u8]) -> &'static str {
+    pub(crate) fn detect_format(bytes: &[u8]) -> &'static str {
         if bytes.len() < 4 {
             return "unknown";
         }
@@ -145,6 +145,10 @@ impl Tool for ImageInfoTool {
         })
     }
 
+    fn parallel_safe(&self, _args: &serde_json::Value) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path_str = args
             .get("path")
@@ -43,6 +43,10 @@ impl Tool for MemoryRecallTool {
         })
     }
 
+    fn parallel_safe(&self, _args: &serde_json::Value) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let query = args
             .get("query")
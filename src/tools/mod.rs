@@ -10,6 +10,7 @@ pub mod hardware_board_info;
 pub mod hardware_memory_map;
 pub mod hardware_memory_read;
 pub mod http_request;
+pub mod image_generate;
 pub mod image_info;
 pub mod memory_forget;
 pub mod memory_recall;
@@ -35,6 +36,7 @@ pub use hardware_board_info::HardwareBoardInfoTool;
 pub use hardware_memory_map::HardwareMemoryMapTool;
 pub use hardware_memory_read::HardwareMemoryReadTool;
 pub use http_request::HttpRequestTool;
+pub use image_generate::ImageGenerateTool;
 pub use image_info::ImageInfoTool;
 pub use memory_forget::MemoryForgetTool;
 pub use memory_recall::MemoryRecallTool;
@@ -165,6 +167,13 @@ pub fn all_tools_with_runtime(
         )));
     }
 
+    if config.image_generation.enabled {
+        tools.push(Box::new(ImageGenerateTool::new(
+            security.clone(),
+            &config.image_generation,
+        )));
+    }
+
     // Vision tools are always available
     tools.push(Box::new(ScreenshotTool::new(security.clone())));
     tools.push(Box::new(ImageInfoTool::new(security.clone())));
@@ -191,10 +200,12 @@ pub fn all_tools_with_runtime(
             .iter()
             .map(|(name, cfg)| (name.clone(), cfg.clone()))
             .collect();
-        tools.push(Box::new(DelegateTool::new(
-            delegate_agents,
-            fallback_api_key.map(String::from),
-        )));
+        let observer: Arc<dyn crate::observability::Observer> =
+            Arc::from(crate::observability::create_observer(&config.observability));
+        tools.push(Box::new(
+            DelegateTool::new(delegate_agents, fallback_api_key.map(String::from))
+                .with_provider_layers(Arc::new(config.clone()), observer),
+        ));
     }
 
     tools
@@ -25,6 +25,20 @@ const EXTRA_PATH_DIRS: &[&str] = &[
     "/usr/local/sbin",
 ];
 
+/// Kills a command's process group unless disarmed by clearing the pid.
+struct ProcessGroupGuard(Option<u32>);
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0.and_then(|pid| i32::try_from(pid).ok()) {
+            use nix::sys::signal::{killpg, Signal};
+            use nix::unistd::Pid;
+            let _ = killpg(Pid::from_raw(pid), Signal::SIGKILL);
+        }
+    }
+}
+
 /// Shell command execution tool with sandboxing
 pub struct ShellTool {
     security: Arc<SecurityPolicy>,
@@ -178,8 +192,36 @@ impl Tool for ShellTool {
             }
         }
 
-        let result =
-            tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
+        // Run the command in its own process group so that a timeout, or a
+        // cancelled turn dropping this call, kills everything it started.
+        #[cfg(unix)]
+        cmd.process_group(0);
+        cmd.kill_on_drop(true)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        let child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to execute command: {e}")),
+                    image_base64: None,
+                    image_mime: None,
+                });
+            }
+        };
+        let mut group = ProcessGroupGuard(child.id());
+        let result = tokio::time::timeout(
+            Duration::from_secs(SHELL_TIMEOUT_SECS),
+            child.wait_with_output(),
+        )
+        .await;
+        if matches!(result, Ok(Ok(_))) {
+            // Background jobs the command started on purpose outlive it.
+            group.0 = None;
+        }
+        drop(group);
 
         match result {
             Ok(Ok(output)) => {
@@ -459,4 +501,29 @@ mod tests {
 
         let _ = std::fs::remove_file(std::env::temp_dir().join("zeroclaw_shell_approval_test"));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dropping_the_call_kills_the_command() {
+        let marker = std::env::temp_dir().join("zeroclaw_shell_cancel_test");
+        let _ = std::fs::remove_file(&marker);
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            allowed_commands: vec!["sleep".into(), "touch".into()],
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ShellTool::new(security, test_runtime());
+
+        let call = tool.execute(json!({"command": "sleep 1 && touch zeroclaw_shell_cancel_test"}));
+        assert!(tokio::time::timeout(Duration::from_millis(200), call)
+            .await
+            .is_err());
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(
+            !marker.exists(),
+            "command kept running after the call was dropped"
+        );
+    }
 }
@@ -40,6 +40,20 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult>;
 
+    /// Whether images this tool returns are meant for the user rather than
+    /// only for the model to look at. Channels that can send pictures
+    /// deliver them alongside the reply.
+    fn delivers_images(&self) -> bool {
+        false
+    }
+
+    /// Whether this call, with these arguments, may run concurrently with
+    /// other parallel-safe calls from the same model response. Only calls
+    /// without side effects (reads and lookups) should opt in.
+    fn parallel_safe(&self, _args: &serde_json::Value) -> bool {
+        false
+    }
+
     /// Get the full spec for LLM registration
     fn spec(&self) -> ToolSpec {
         ToolSpec {